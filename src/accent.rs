@@ -0,0 +1,108 @@
+//! Accent color for progress bars, count badges and the notification window
+//!
+//! `Settings::accent_color` is either "system", which follows the Windows accent color, or
+//! an RGB hex string. It is parsed when settings are applied, so drawing code only reads
+//! the stored value.
+
+use log::{debug, warn};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Setting value that follows the Windows accent color
+pub const SYSTEM: &str = "system";
+
+/// Windows' default blue, used until settings are applied or if the system color is unknown
+const DEFAULT_ACCENT: u32 = 0x0078D7;
+
+/// Current accent as 0xRRGGBB
+static ACCENT: AtomicU32 = AtomicU32::new(DEFAULT_ACCENT);
+
+/// Parse the setting and make it the current accent
+pub fn apply(setting: &str) {
+    let accent = if setting.trim().eq_ignore_ascii_case(SYSTEM) {
+        system_accent().unwrap_or(DEFAULT_ACCENT)
+    } else {
+        parse_hex(setting).unwrap_or_else(|| {
+            warn!("Invalid accent color {:?}, using the default", setting);
+            DEFAULT_ACCENT
+        })
+    };
+    debug!("Accent color: #{:06X}", accent);
+    ACCENT.store(accent, Ordering::Relaxed);
+}
+
+/// Current accent as 0xRRGGBB, the form `gpui::rgb` takes
+pub fn rgb() -> u32 {
+    ACCENT.load(Ordering::Relaxed)
+}
+
+/// Current accent as opaque RGBA
+pub fn rgba() -> [u8; 4] {
+    let [_, r, g, b] = rgb().to_be_bytes();
+    [r, g, b, 255]
+}
+
+/// A 0xRRGGBB color as a GDI `COLORREF` value (0x00BBGGRR)
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn colorref(rgb: u32) -> u32 {
+    let [_, r, g, b] = rgb.to_be_bytes();
+    u32::from_le_bytes([r, g, b, 0])
+}
+
+/// "#RRGGBB" or "RRGGBB" as 0xRRGGBB
+pub fn parse_hex(value: &str) -> Option<u32> {
+    let hex = value.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Whether a setting value is "system" or a valid hex color
+pub fn is_valid_setting(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case(SYSTEM) || parse_hex(value).is_some()
+}
+
+/// The Windows accent (colorization) color, without its alpha
+#[cfg(windows)]
+fn system_accent() -> Option<u32> {
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::Graphics::Dwm::DwmGetColorizationColor;
+
+    let mut color: u32 = 0;
+    let mut opaque = BOOL::default();
+    // 0xAARRGGBB
+    unsafe { DwmGetColorizationColor(&mut color, &mut opaque) }
+        .map_err(|e| debug!("DwmGetColorizationColor failed: {}", e))
+        .ok()?;
+    Some(color & 0x00FF_FFFF)
+}
+
+#[cfg(not(windows))]
+fn system_accent() -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("#FF8800"), Some(0xFF8800));
+        assert_eq!(parse_hex(" 0a0B0c "), Some(0x0A0B0C));
+        assert_eq!(parse_hex("#FFF"), None);
+        assert_eq!(parse_hex("#GG0000"), None);
+        assert_eq!(parse_hex("+12345"), None);
+        assert!(is_valid_setting("System"));
+        assert!(!is_valid_setting("blue"));
+    }
+
+    #[test]
+    fn test_conversions() {
+        apply("#123456");
+        assert_eq!(rgb(), 0x123456);
+        assert_eq!(rgba(), [0x12, 0x34, 0x56, 255]);
+        assert_eq!(colorref(0x123456), 0x563412);
+    }
+}
@@ -0,0 +1,268 @@
+//! Draw rectangles, arrows and text onto a screenshot
+//!
+//! Like redaction, annotating writes a new file and leaves the original untouched.
+//! Note: There is no annotation UI yet; this is the API it will call.
+
+#![allow(dead_code)]
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use anyhow::Result;
+use image::{Pixel, Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+
+use crate::redact::{self, ImageRect};
+
+/// Fonts tried in order for `system_font`, relative to the Windows fonts folder
+const FONT_CANDIDATES: &[&str] = &["segoeui.ttf", "arial.ttf", "tahoma.ttf"];
+
+/// Arrowhead wings are this many times the line thickness, but at least `MIN_HEAD_LENGTH`
+const HEAD_LENGTH_FACTOR: f32 = 4.0;
+const MIN_HEAD_LENGTH: f32 = 10.0;
+
+/// Angle between the shaft and each arrowhead wing
+const HEAD_ANGLE: f32 = std::f32::consts::PI / 6.0;
+
+/// A shape to draw with `annotate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Annotation {
+    Rect {
+        rect: ImageRect,
+        color: Rgba<u8>,
+        thickness: u32,
+    },
+    Arrow {
+        from: (i32, i32),
+        to: (i32, i32),
+        color: Rgba<u8>,
+        thickness: u32,
+    },
+    Text {
+        text: String,
+        /// Top-left corner of the first line
+        origin: (i32, i32),
+        /// Line height in pixels
+        size: f32,
+        color: Rgba<u8>,
+    },
+}
+
+/// Draw the annotations onto `src` and save the result as a new file
+pub fn annotate(src: &Path, annotations: &[Annotation]) -> Result<PathBuf> {
+    // Only load a font when there is text to draw
+    let font = annotations
+        .iter()
+        .any(|a| matches!(a, Annotation::Text { .. }))
+        .then(system_font)
+        .transpose()?;
+
+    redact::edit(src, "annotated", |img| {
+        for annotation in annotations {
+            match annotation {
+                Annotation::Rect { rect, color, thickness } => {
+                    draw_rect(img, *rect, *color, *thickness)
+                }
+                Annotation::Arrow { from, to, color, thickness } => {
+                    draw_arrow(img, *from, *to, *color, *thickness)
+                }
+                Annotation::Text { text, origin, size, color } => {
+                    if let Some(font) = &font {
+                        draw_text(img, text, *origin, *size, *color, font);
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Load a UI font from the Windows fonts folder
+pub fn system_font() -> Result<FontVec> {
+    let windir = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+    let fonts = PathBuf::from(windir).join("Fonts");
+
+    for name in FONT_CANDIDATES {
+        if let Ok(bytes) = std::fs::read(fonts.join(name)) {
+            if let Ok(font) = FontVec::try_from_vec(bytes) {
+                return Ok(font);
+            }
+        }
+    }
+    anyhow::bail!("No usable font found in {:?}", fonts)
+}
+
+/// Outline a rectangle; the border is drawn inside `rect`
+pub fn draw_rect(img: &mut RgbaImage, rect: ImageRect, color: Rgba<u8>, thickness: u32) {
+    let Some(rect) = rect.clamped(img.width(), img.height()) else {
+        return;
+    };
+    let thickness = thickness.max(1);
+    for y in rect.y..rect.y + rect.height {
+        for x in rect.x..rect.x + rect.width {
+            let inset = (x - rect.x)
+                .min(y - rect.y)
+                .min(rect.x + rect.width - 1 - x)
+                .min(rect.y + rect.height - 1 - y);
+            if inset < thickness {
+                blend(img, x as i32, y as i32, color);
+            }
+        }
+    }
+}
+
+/// Draw an arrow pointing from `from` to `to`
+pub fn draw_arrow(
+    img: &mut RgbaImage,
+    from: (i32, i32),
+    to: (i32, i32),
+    color: Rgba<u8>,
+    thickness: u32,
+) {
+    let thickness = thickness.max(1) as f32;
+    let (dx, dy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+    let length = (dx * dx + dy * dy).sqrt();
+
+    let mut segments = vec![(from, to)];
+    if length > 0.0 {
+        let head = (thickness * HEAD_LENGTH_FACTOR).max(MIN_HEAD_LENGTH).min(length);
+        let back = dy.atan2(dx) + std::f32::consts::PI;
+        for angle in [back - HEAD_ANGLE, back + HEAD_ANGLE] {
+            let wing = (
+                to.0 + (head * angle.cos()).round() as i32,
+                to.1 + (head * angle.sin()).round() as i32,
+            );
+            segments.push((to, wing));
+        }
+    }
+
+    // Each segment is drawn as a capsule, so joins stay solid
+    let radius = thickness / 2.0;
+    let (min_x, max_x, min_y, max_y) = segments.iter().fold(
+        (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+        |(min_x, max_x, min_y, max_y), (a, b)| {
+            (
+                min_x.min(a.0).min(b.0),
+                max_x.max(a.0).max(b.0),
+                min_y.min(a.1).min(b.1),
+                max_y.max(a.1).max(b.1),
+            )
+        },
+    );
+    let pad = radius.ceil() as i32;
+    let x_range = (min_x - pad).max(0)..=(max_x + pad).min(img.width() as i32 - 1);
+    let y_range = (min_y - pad).max(0)..=(max_y + pad).min(img.height() as i32 - 1);
+
+    for y in y_range {
+        for x in x_range.clone() {
+            let p = (x as f32, y as f32);
+            if segments.iter().any(|(a, b)| distance_to_segment(p, *a, *b) <= radius) {
+                blend(img, x, y, color);
+            }
+        }
+    }
+}
+
+/// Draw text with its first line's top-left corner at `origin`
+pub fn draw_text(
+    img: &mut RgbaImage,
+    text: &str,
+    origin: (i32, i32),
+    size: f32,
+    color: Rgba<u8>,
+    font: &impl Font,
+) {
+    let scale = PxScale::from(size);
+    let scaled = font.as_scaled(scale);
+    let line_height = scaled.height() + scaled.line_gap();
+
+    let mut caret = point(origin.0 as f32, origin.1 as f32 + scaled.ascent());
+    let mut previous = None;
+    for ch in text.chars() {
+        if ch == '\n' {
+            caret = point(origin.0 as f32, caret.y + line_height);
+            previous = None;
+            continue;
+        }
+
+        let id = scaled.glyph_id(ch);
+        if let Some(previous) = previous {
+            caret.x += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(scale, caret);
+        caret.x += scaled.h_advance(id);
+        previous = Some(id);
+
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let alpha = (f32::from(color[3]) * coverage).round() as u8;
+                blend(
+                    img,
+                    bounds.min.x as i32 + gx as i32,
+                    bounds.min.y as i32 + gy as i32,
+                    Rgba([color[0], color[1], color[2], alpha]),
+                );
+            });
+        }
+    }
+}
+
+/// Alpha-blend `color` over the pixel, ignoring coordinates outside the image
+fn blend(img: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>) {
+    if x < 0 || y < 0 || x >= img.width() as i32 || y >= img.height() as i32 {
+        return;
+    }
+    img.get_pixel_mut(x as u32, y as u32).blend(&color);
+}
+
+fn distance_to_segment(p: (f32, f32), a: (i32, i32), b: (i32, i32)) -> f32 {
+    let (ax, ay) = (a.0 as f32, a.1 as f32);
+    let (bx, by) = (b.0 as f32, b.1 as f32);
+    let (dx, dy) = (bx - ax, by - ay);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((p.0 - ax) * dx + (p.1 - ay) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    let (cx, cy) = (ax + t * dx, ay + t * dy);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    #[test]
+    fn test_draw_rect_border() {
+        let mut img = RgbaImage::from_pixel(10, 10, WHITE);
+        draw_rect(&mut img, ImageRect { x: 2, y: 2, width: 6, height: 6 }, RED, 1);
+
+        assert_eq!(*img.get_pixel(2, 2), RED);
+        assert_eq!(*img.get_pixel(7, 5), RED);
+        assert_eq!(*img.get_pixel(4, 4), WHITE);
+        assert_eq!(*img.get_pixel(1, 1), WHITE);
+    }
+
+    #[test]
+    fn test_draw_arrow() {
+        let mut img = RgbaImage::from_pixel(40, 40, WHITE);
+        draw_arrow(&mut img, (5, 20), (35, 20), RED, 2);
+
+        // Shaft and tip are drawn, far corners are not
+        assert_eq!(*img.get_pixel(15, 20), RED);
+        assert_eq!(*img.get_pixel(35, 20), RED);
+        assert_eq!(*img.get_pixel(5, 5), WHITE);
+        // One wing goes up-left from the tip
+        assert_eq!(*img.get_pixel(30, 17), RED);
+    }
+
+    #[test]
+    fn test_distance_to_segment() {
+        assert_eq!(distance_to_segment((5.0, 3.0), (0, 0), (10, 0)), 3.0);
+        assert_eq!(distance_to_segment((13.0, 4.0), (0, 0), (10, 0)), 5.0);
+        assert_eq!(distance_to_segment((3.0, 4.0), (0, 0), (0, 0)), 5.0);
+    }
+}
@@ -3,11 +3,12 @@
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::WindowExt;
-use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
+use gpui_component::dialog::DialogButtonProps;
 use gpui_component::input::{Input, InputEvent, InputState};
 use gpui_component::notification::{Notification, NotificationType};
 use gpui_component::switch::Switch;
-use gpui_component::{ActiveTheme, Disableable, Sizable, h_flex, v_flex};
+use gpui_component::{ActiveTheme, Disableable, Root, Sizable, h_flex, v_flex};
 use log::{debug, error, info};
 use rust_i18n::t;
 use parking_lot::Mutex;
@@ -27,13 +28,58 @@ pub enum SettingsPage {
     About,
 }
 
+/// Part of the settings UI that can be opened directly, e.g. from a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsSection {
+    General,
+    Notifications,
+    Language,
+    Upload,
+    Conversion,
+    Indexing,
+    Hotkeys,
+    Updates,
+}
+
+impl SettingsSection {
+    /// Sections that start partway down their page, and so need a scroll anchor
+    const ANCHORED: [SettingsSection; 4] =
+        [Self::Notifications, Self::Language, Self::Upload, Self::Updates];
+
+    pub fn page(self) -> SettingsPage {
+        match self {
+            Self::General | Self::Notifications | Self::Language | Self::Upload => {
+                SettingsPage::General
+            }
+            Self::Conversion => SettingsPage::Conversion,
+            Self::Indexing => SettingsPage::Indexing,
+            Self::Hotkeys => SettingsPage::Hotkey,
+            Self::Updates => SettingsPage::About,
+        }
+    }
+}
+
+use crate::autostart;
+use crate::capture::{self, FullscreenTarget};
 use crate::clipboard;
+use crate::hotkey::HotkeyAction;
+use crate::identity::ContentHash;
 use crate::convert;
+use crate::dedup;
+use crate::delete;
+use crate::ocr;
 use crate::organizer;
-use crate::settings::ConversionFormat;
+use crate::pins;
+use crate::retention;
+use crate::settings::{
+    CaptureFormat, CaptureTarget, ConversionFormat, DragEscAction, LargeImageAction, OpenAction,
+    SortOrder, TrayClickAction, TrayDoubleClickAction, TrayIconStyle,
+};
 use crate::thumbnail::ThumbnailCache;
 use crate::ui::gallery;
-use crate::{AppMessage, AppState, set_latest_screenshot};
+use crate::upload;
+use crate::watcher::ScreenshotWatcher;
+use crate::{AppMessage, AppState};
 use fastembed;
 
 /// App version
@@ -52,18 +98,16 @@ static PREWARMED_VISION_MODEL: parking_lot::Mutex<Option<Arc<Mutex<fastembed::Im
 /// Start native window drag using Windows API
 #[cfg(windows)]
 fn start_window_drag(_window: &mut Window) {
-    use crate::tray::WINDOW_HWND;
-    use windows::Win32::Foundation::HWND;
     use windows::Win32::UI::Input::KeyboardAndMouse::ReleaseCapture;
     use windows::Win32::UI::WindowsAndMessaging::{HTCAPTION, PostMessageW, WM_NCLBUTTONDOWN};
 
-    if let Some(hwnd) = *WINDOW_HWND.lock() {
+    if let Some(handle) = crate::tray::window_handle() {
         unsafe {
             // Release mouse capture first
             let _ = ReleaseCapture();
             // Post message to start window drag (asynchronous to avoid RefCell conflicts)
             let _ = PostMessageW(
-                HWND(hwnd as *mut std::ffi::c_void),
+                handle.hwnd(),
                 WM_NCLBUTTONDOWN,
                 windows::Win32::Foundation::WPARAM(HTCAPTION as usize),
                 windows::Win32::Foundation::LPARAM(0),
@@ -82,18 +126,15 @@ fn start_window_drag(_window: &mut Window) {
 pub fn pick_folder() -> Option<PathBuf> {
     use std::ffi::OsString;
     use std::os::windows::ffi::OsStringExt;
-    use windows::Win32::System::Com::{
-        CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx,
-        CoUninitialize,
-    };
+    use windows::Win32::System::Com::{CLSCTX_INPROC_SERVER, CoCreateInstance};
     use windows::Win32::UI::Shell::{
         FOS_PICKFOLDERS, FileOpenDialog, IFileDialog, IShellItem, SIGDN_FILESYSPATH,
     };
     use windows::core::PWSTR;
 
+    // Declared first so the dialog is released before COM is uninitialized
+    let _com = crate::platform::ComGuard::apartment_threaded();
     unsafe {
-        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
-
         let dialog: IFileDialog =
             CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER).ok()?;
 
@@ -104,7 +145,6 @@ pub fn pick_folder() -> Option<PathBuf> {
 
         // Show dialog
         if dialog.Show(None).is_err() {
-            CoUninitialize();
             return None;
         }
 
@@ -118,7 +158,6 @@ pub fn pick_folder() -> Option<PathBuf> {
         let path = PathBuf::from(OsString::from_wide(slice));
 
         windows::Win32::System::Com::CoTaskMemFree(Some(path_ptr.0 as *const _));
-        CoUninitialize();
 
         Some(path)
     }
@@ -129,6 +168,59 @@ pub fn pick_folder() -> Option<PathBuf> {
     None
 }
 
+/// Open a Windows save/open dialog for a settings JSON file
+#[cfg(windows)]
+pub fn pick_settings_file(save: bool) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use windows::Win32::System::Com::{CLSCTX_INPROC_SERVER, CoCreateInstance};
+    use windows::Win32::UI::Shell::Common::COMDLG_FILTERSPEC;
+    use windows::Win32::UI::Shell::{
+        FileOpenDialog, FileSaveDialog, IFileDialog, IShellItem, SIGDN_FILESYSPATH,
+    };
+    use windows::core::{PWSTR, w};
+
+    // Declared first so the dialog is released before COM is uninitialized
+    let _com = crate::platform::ComGuard::apartment_threaded();
+    unsafe {
+        let clsid = if save { &FileSaveDialog } else { &FileOpenDialog };
+        let dialog: IFileDialog = CoCreateInstance(clsid, None, CLSCTX_INPROC_SERVER).ok()?;
+
+        let filters = [COMDLG_FILTERSPEC {
+            pszName: w!("JSON"),
+            pszSpec: w!("*.json"),
+        }];
+        dialog.SetFileTypes(&filters).ok()?;
+        dialog.SetDefaultExtension(w!("json")).ok()?;
+        if save {
+            dialog.SetFileName(w!("sukusho-settings.json")).ok()?;
+        }
+
+        // Show dialog
+        if dialog.Show(None).is_err() {
+            return None;
+        }
+
+        // Get result
+        let result: IShellItem = dialog.GetResult().ok()?;
+        let path_ptr: PWSTR = result.GetDisplayName(SIGDN_FILESYSPATH).ok()?;
+
+        // Convert to PathBuf
+        let len = (0..).take_while(|&i| *path_ptr.0.add(i) != 0).count();
+        let slice = std::slice::from_raw_parts(path_ptr.0, len);
+        let path = PathBuf::from(OsString::from_wide(slice));
+
+        windows::Win32::System::Com::CoTaskMemFree(Some(path_ptr.0 as *const _));
+
+        Some(path)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn pick_settings_file(_save: bool) -> Option<PathBuf> {
+    None
+}
+
 /// Detect Windows system theme (Dark or Light)
 #[cfg(windows)]
 fn detect_system_theme() -> gpui_component::theme::ThemeMode {
@@ -206,98 +298,68 @@ fn apply_theme(
     gpui_component::theme::Theme::change(theme_mode, Some(window), cx);
 }
 
-/// Register app to run on Windows startup
-#[cfg(windows)]
-fn register_startup() {
-    use windows::Win32::System::Registry::{
-        RegSetValueExW, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, KEY_WRITE, REG_SZ,
-    };
-    use windows::core::PCWSTR;
-    use std::os::windows::ffi::OsStrExt;
-
-    unsafe {
-        let subkey = "Software\\Microsoft\\Windows\\CurrentVersion\\Run\0"
-            .encode_utf16()
-            .collect::<Vec<u16>>();
-        let value_name = "Sukusho\0".encode_utf16().collect::<Vec<u16>>();
-
-        let mut hkey: HKEY = HKEY::default();
-        if RegOpenKeyExW(
-            HKEY_CURRENT_USER,
-            PCWSTR(subkey.as_ptr()),
-            0,
-            KEY_WRITE,
-            &mut hkey,
-        )
-        .is_ok()
-        {
-            // Get executable path
-            if let Ok(exe_path) = std::env::current_exe() {
-                let exe_str = exe_path.as_os_str()
-                    .encode_wide()
-                    .chain(std::iter::once(0))
-                    .collect::<Vec<u16>>();
-
-                // Convert to byte slice
-                let data_bytes = std::slice::from_raw_parts(
-                    exe_str.as_ptr() as *const u8,
-                    exe_str.len() * 2,
-                );
-
-                let _ = RegSetValueExW(
-                    hkey,
-                    PCWSTR(value_name.as_ptr()),
-                    0,
-                    REG_SZ,
-                    Some(data_bytes),
-                );
-
-                info!("Registered Sukusho to run on Windows startup");
-            }
-        }
+/// The hotkey set for an action ("" when unbound)
+fn action_hotkey(settings: &crate::settings::Settings, action: HotkeyAction) -> &str {
+    match action {
+        HotkeyAction::CaptureRegion => &settings.capture_hotkey,
+        HotkeyAction::CaptureFullscreen => &settings.capture_fullscreen_hotkey,
+        HotkeyAction::CaptureWindow => &settings.capture_window_hotkey,
+        HotkeyAction::CaptureWindowToClipboard => &settings.capture_window_clipboard_hotkey,
     }
 }
 
-#[cfg(not(windows))]
-fn register_startup() {
-    // Not implemented for non-Windows
+/// Settings field holding the hotkey for an action
+fn action_hotkey_mut(settings: &mut crate::settings::Settings, action: HotkeyAction) -> &mut String {
+    match action {
+        HotkeyAction::CaptureRegion => &mut settings.capture_hotkey,
+        HotkeyAction::CaptureFullscreen => &mut settings.capture_fullscreen_hotkey,
+        HotkeyAction::CaptureWindow => &mut settings.capture_window_hotkey,
+        HotkeyAction::CaptureWindowToClipboard => &mut settings.capture_window_clipboard_hotkey,
+    }
 }
 
-/// Unregister app from Windows startup
-#[cfg(windows)]
-fn unregister_startup() {
-    use windows::Win32::System::Registry::{
-        RegDeleteValueW, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, KEY_WRITE,
+/// Copy the foreground window to the clipboard, saving it too if enabled
+fn capture_window_to_clipboard(settings: &Mutex<crate::settings::Settings>) {
+    let (target, directory) = {
+        let settings = settings.lock();
+        let target = if settings.save_clipboard_captures {
+            CaptureTarget::Both
+        } else {
+            CaptureTarget::Clipboard
+        };
+        (target, settings.screenshot_directory.clone())
     };
-    use windows::core::PCWSTR;
-
-    unsafe {
-        let subkey = "Software\\Microsoft\\Windows\\CurrentVersion\\Run\0"
-            .encode_utf16()
-            .collect::<Vec<u16>>();
-        let value_name = "Sukusho\0".encode_utf16().collect::<Vec<u16>>();
-
-        let mut hkey: HKEY = HKEY::default();
-        if RegOpenKeyExW(
-            HKEY_CURRENT_USER,
-            PCWSTR(subkey.as_ptr()),
-            0,
-            KEY_WRITE,
-            &mut hkey,
-        )
-        .is_ok()
-        {
-            let _ = RegDeleteValueW(hkey, PCWSTR(value_name.as_ptr()));
-            info!("Unregistered Sukusho from Windows startup");
+    let result = capture::capture_active_window(&directory, target).and_then(|captured| {
+        if !captured.copied {
+            anyhow::bail!("Failed to copy the capture to the clipboard");
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => {
+            info!("Active window copied to clipboard");
+            crate::tray::notify(
+                &settings.lock(),
+                crate::tray::NotificationKind::Success,
+                "notifications.title",
+                "notifications.capture.copied",
+                &[],
+            );
+        }
+        Err(e) => {
+            error!("Clipboard capture failed: {}", e);
+            crate::tray::notify(
+                &settings.lock(),
+                crate::tray::NotificationKind::Error,
+                "notifications.title",
+                "notifications.capture.failed",
+                &[("error", &e.to_string())],
+            );
         }
     }
 }
 
-#[cfg(not(windows))]
-fn unregister_startup() {
-    // Not implemented for non-Windows
-}
-
 /// Hide window from taskbar
 #[cfg(windows)]
 fn hide_from_taskbar(window: &mut Window) {
@@ -373,12 +435,15 @@ const PAGE_SIZE: usize = 50;
 #[derive(Debug, Clone)]
 pub struct ScreenshotInfo {
     pub path: PathBuf,
-    #[allow(dead_code)]
     pub filename: String,
     pub modified: SystemTime,
     pub file_size: u64,
     /// File extension (uppercase, e.g., "PNG", "WEBP", "JPEG")
     pub extension: String,
+    /// Application that was active when the screenshot arrived (new screenshots only)
+    pub source_app: Option<String>,
+    /// Content hash, if it was already computed for this version of the file
+    pub content_hash: Option<ContentHash>,
 }
 
 impl ScreenshotInfo {
@@ -393,14 +458,36 @@ impl ScreenshotInfo {
             .map(|e| e.to_uppercase())
             .unwrap_or_default();
 
+        let source_app = crate::foreground::source_of(&path);
+        let content_hash = crate::identity::cached_hash(&path);
+
         Some(Self {
             path,
             filename,
             modified,
             file_size,
             extension,
+            source_app,
+            content_hash,
         })
     }
+
+    /// Ordering against another screenshot under `order`
+    pub fn cmp_by(&self, other: &Self, order: SortOrder) -> std::cmp::Ordering {
+        order.compare((&self.filename, self.modified), (&other.filename, other.modified))
+    }
+}
+
+/// Paths of `screenshots` in `order`
+fn sorted_paths(screenshots: &[ScreenshotInfo], order: SortOrder) -> Vec<PathBuf> {
+    let mut sorted: Vec<&ScreenshotInfo> = screenshots.iter().collect();
+    sorted.sort_by(|a, b| a.cmp_by(b, order));
+    sorted.into_iter().map(|s| s.path.clone()).collect()
+}
+
+/// Text for an optional numeric setting (empty = unset)
+fn optional_to_string<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
 }
 
 /// Format file size in human readable format (using IEC binary units)
@@ -443,12 +530,18 @@ pub struct Sukusho {
     /// All screenshot paths (sorted by modification time, newest first)
     all_screenshots: Vec<ScreenshotInfo>,
 
+    /// Whether the watcher has finished announcing existing files
+    initial_scan_complete: bool,
+
     /// Currently visible screenshots (paginated)
     visible_count: usize,
 
     /// Selected screenshot paths
     selected: HashSet<PathBuf>,
 
+    /// Screenshots dropped into another app since launch, marked in the gallery
+    shared: HashSet<PathBuf>,
+
     /// Last selected item for shift-click range selection
     last_selected: Option<PathBuf>,
 
@@ -461,6 +554,15 @@ pub struct Sukusho {
     /// Current settings page
     settings_page: SettingsPage,
 
+    /// Scroll position of the settings content
+    settings_scroll: ScrollHandle,
+
+    /// Anchors of the sections in `SettingsSection::ANCHORED`
+    section_anchors: Vec<(SettingsSection, ScrollAnchor)>,
+
+    /// Section to scroll to once its page has been laid out
+    pending_section: Option<SettingsSection>,
+
     /// Current grid columns
     grid_columns: u32,
 
@@ -476,9 +578,35 @@ pub struct Sukusho {
     /// Whether search input has focus
     search_input_focused: bool,
 
+    /// Filename template input (rename settings)
+    filename_template_input: Entity<InputState>,
+
+    /// Parse error for the filename template being edited
+    filename_template_error: Option<String>,
+
+    /// Retention settings inputs (empty = unlimited)
+    max_screenshots_input: Entity<InputState>,
+    max_age_days_input: Entity<InputState>,
+
+    /// Capture encoder settings inputs
+    png_compression_input: Entity<InputState>,
+    jpeg_quality_input: Entity<InputState>,
+
+    /// Set while a retention cleanup is running
+    cleanup_running: Arc<std::sync::atomic::AtomicBool>,
+
+    /// Upload settings inputs
+    imgur_client_id_input: Entity<InputState>,
+    upload_endpoint_input: Entity<InputState>,
+    upload_field_name_input: Entity<InputState>,
+    accent_color_input: Entity<InputState>,
+
     /// Whether we're recording a new hotkey
     recording_hotkey: bool,
 
+    /// Action whose hotkey is being recorded, if any
+    recording_action_hotkey: Option<HotkeyAction>,
+
     /// Whether we're currently organizing files
     organizing: bool,
 
@@ -535,12 +663,147 @@ pub struct Sukusho {
     first_render: bool,
     /// Track if we've already hidden the window on start (to do it only once)
     hidden_on_start: bool,
+
+    /// Screenshot shown in the preview overlay
+    preview_path: Option<PathBuf>,
 }
 
 impl Sukusho {
+    /// Text input bound to a string setting; changes are saved when the settings stay valid
+    fn setting_input(
+        value: String,
+        apply: fn(&mut crate::settings::Settings, String),
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<InputState> {
+        let input = cx.new(|cx| InputState::new(window, cx).default_value(value));
+        cx.subscribe_in(&input, window, move |_this, state, event, _window, cx| {
+            if let InputEvent::Change = event {
+                let value = state.read(cx).value().trim().to_string();
+                let app_state = cx.global::<AppState>();
+                let mut settings = app_state.settings.lock();
+                let mut updated = settings.clone();
+                apply(&mut updated, value);
+                if updated.validate().is_ok() && updated != *settings {
+                    *settings = updated;
+                    let _ = settings.save();
+                    // e.g. entering an upload destination enables the tray's upload action
+                    if let Some(tray) = app_state.tray_manager.lock().as_mut() {
+                        tray.update_action_availability(&settings);
+                    }
+                }
+            }
+        })
+        .detach();
+        input
+    }
+
+    /// Input for a retention limit, applied on Enter or when it loses focus rather than on
+    /// every keystroke, so typing "100" never briefly keeps just 1 screenshot
+    fn retention_input(
+        value_of: fn(&crate::settings::Settings) -> String,
+        apply: fn(&mut crate::settings::Settings, String),
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<InputState> {
+        let value = value_of(&cx.global::<AppState>().settings.lock());
+        let input = cx.new(|cx| InputState::new(window, cx).default_value(value));
+        cx.subscribe_in(&input, window, move |this, state, event, window, cx| {
+            if matches!(event, InputEvent::Blur | InputEvent::PressEnter { .. }) {
+                this.commit_retention(state.clone(), value_of, apply, window, cx);
+            }
+        })
+        .detach();
+        input
+    }
+
+    /// Save a retention limit typed into `input`, asking first if it deletes screenshots now
+    fn commit_retention(
+        &mut self,
+        input: Entity<InputState>,
+        value_of: fn(&crate::settings::Settings) -> String,
+        apply: fn(&mut crate::settings::Settings, String),
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Opening the dialog blurs the input; that blur is not a second commit
+        if window.has_active_dialog(cx) {
+            return;
+        }
+        let value = input.read(cx).value().trim().to_string();
+        let updated = {
+            let settings = cx.global::<AppState>().settings.lock();
+            let mut updated = settings.clone();
+            apply(&mut updated, value);
+            if updated == *settings || updated.validate().is_err() {
+                return;
+            }
+            updated
+        };
+
+        let pinned = pins::pinned().into_iter().collect();
+        let files = self.retention_files(cx);
+        let expired = retention::select_expired(
+            &files,
+            &updated.retention_policy(),
+            &pinned,
+            SystemTime::now(),
+        )
+        .len();
+        if expired == 0 {
+            self.save_retention(updated, cx);
+            return;
+        }
+
+        let view = cx.entity().downgrade();
+        let message =
+            crate::i18n_helpers::t_count("settings.general.cleanup.confirm_message", expired);
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view.clone();
+            let updated = updated.clone();
+            let input = input.clone();
+            dialog
+                .confirm()
+                .title(t!("settings.general.cleanup.confirm_title").to_string())
+                .child(message.clone())
+                .button_props(
+                    DialogButtonProps::default()
+                        .ok_text(t!("settings.general.cleanup.confirm_button").to_string())
+                        .ok_variant(ButtonVariant::Danger)
+                        .cancel_text(t!("common.button.cancel").to_string()),
+                )
+                .on_ok(move |_, _, cx| {
+                    let updated = updated.clone();
+                    let _ = view.update(cx, |this, cx| this.save_retention(updated, cx));
+                    true
+                })
+                .on_cancel(move |_, window, cx| {
+                    // Put back the limit that is still in effect
+                    let value = value_of(&cx.global::<AppState>().settings.lock());
+                    input.update(cx, |input, cx| input.set_value(value, window, cx));
+                    true
+                })
+        });
+    }
+
+    /// Save changed retention limits and enforce them
+    fn save_retention(&mut self, updated: crate::settings::Settings, cx: &mut Context<Self>) {
+        {
+            let mut settings = cx.global::<AppState>().settings.lock();
+            *settings = updated;
+            let _ = settings.save();
+        }
+        self.run_cleanup(cx);
+    }
+
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let app_state = cx.global::<AppState>();
         let settings = app_state.settings.lock().clone();
+        crate::recent::set_capacity(settings.recent_history_size);
+        crate::thumbnail::set_decode_limits(
+            settings.max_decode_pixels,
+            std::time::Duration::from_millis(settings.thumbnail_open_timeout_ms),
+        );
 
         // Create search input state
         let search_input = cx.new(|cx| {
@@ -562,16 +825,20 @@ impl Sukusho {
                     let text = state.read(cx).value().to_string();
                     this.search_query = text.clone();
 
-                    // Clear search results if query is empty
-                    if text.is_empty() {
-                        this.search_results = None;
-                    }
+                    // Filter by filename as you type; Enter searches image contents
+                    this.search_results = if text.trim().is_empty() {
+                        None
+                    } else {
+                        let matches =
+                            crate::search::search_screenshots(&this.all_screenshots, &text);
+                        Some(matches.into_iter().map(|s| s.path).collect())
+                    };
                     cx.notify();
                 }
                 InputEvent::PressEnter { .. } => {
                     // Use the state parameter directly (no RefCell borrow of this.search_input)
                     let query = state.read(cx).value().to_string();
-                    if !query.is_empty() {
+                    if !query.is_empty() && this.models_downloaded {
                         info!("Starting search for: {}", query);
 
                         // Get message channel and config
@@ -646,20 +913,115 @@ impl Sukusho {
         })
         .detach();
 
+        // Filename template input; only valid templates are saved
+        let filename_template_input = cx.new(|cx| {
+            InputState::new(window, cx).default_value(settings.filename_template.clone())
+        });
+        cx.subscribe_in(&filename_template_input, window, |this, state, event, _window, cx| {
+            if let InputEvent::Change = event {
+                let template = state.read(cx).value().to_string();
+                match crate::rename::FilenameTemplate::parse(&template) {
+                    Ok(_) => {
+                        this.filename_template_error = None;
+                        let app_state = cx.global::<AppState>();
+                        let mut settings = app_state.settings.lock();
+                        settings.filename_template = template;
+                        let _ = settings.save();
+                    }
+                    Err(e) => {
+                        this.filename_template_error = Some(e.to_string());
+                    }
+                }
+                cx.notify();
+            }
+        })
+        .detach();
+
+        let max_screenshots_input = Self::retention_input(
+            |s| optional_to_string(s.max_screenshots),
+            |s, v| s.max_screenshots = v.parse().ok(),
+            window,
+            cx,
+        );
+        let max_age_days_input = Self::retention_input(
+            |s| optional_to_string(s.max_age_days),
+            |s, v| s.max_age_days = v.parse().ok(),
+            window,
+            cx,
+        );
+        let png_compression_input = Self::setting_input(
+            settings.png_compression.to_string(),
+            |s, v| s.png_compression = v.parse().unwrap_or(u8::MAX),
+            window,
+            cx,
+        );
+        let jpeg_quality_input = Self::setting_input(
+            settings.jpeg_quality.to_string(),
+            |s, v| s.jpeg_quality = v.parse().unwrap_or(0),
+            window,
+            cx,
+        );
+        let imgur_client_id_input = Self::setting_input(
+            settings.imgur_client_id.clone(),
+            |s, v| s.imgur_client_id = v,
+            window,
+            cx,
+        );
+        let upload_endpoint_input = Self::setting_input(
+            settings.upload_endpoint.clone(),
+            |s, v| s.upload_endpoint = v,
+            window,
+            cx,
+        );
+        let upload_field_name_input = Self::setting_input(
+            settings.upload_field_name.clone(),
+            |s, v| s.upload_field_name = v,
+            window,
+            cx,
+        );
+        let accent_color_input = Self::setting_input(
+            settings.accent_color.clone(),
+            |s, v| s.accent_color = v,
+            window,
+            cx,
+        );
+
+        let settings_scroll = ScrollHandle::new();
+
         let app = Self {
             all_screenshots: Vec::new(),
+            initial_scan_complete: false,
             visible_count: PAGE_SIZE,
             selected: HashSet::new(),
+            shared: HashSet::new(),
             last_selected: None,
             thumbnail_cache: Arc::new(ThumbnailCache::new(500)),
             settings_open: false,
             settings_page: SettingsPage::default(),
+            settings_scroll: settings_scroll.clone(),
+            section_anchors: SettingsSection::ANCHORED
+                .into_iter()
+                .map(|section| (section, ScrollAnchor::for_handle(settings_scroll.clone())))
+                .collect(),
+            pending_section: None,
             grid_columns: settings.grid_columns,
             thumbnail_size: settings.thumbnail_size,
             focus_handle: cx.focus_handle(),
             search_input,
             search_input_focused: false,
+            filename_template_input,
+            filename_template_error: None,
+            max_screenshots_input,
+            max_age_days_input,
+            png_compression_input,
+            jpeg_quality_input,
+            cleanup_running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            imgur_client_id_input,
+            upload_endpoint_input,
+            upload_field_name_input,
+            accent_color_input,
             recording_hotkey: false,
+            recording_action_hotkey: None,
             organizing: false,
             organize_progress: (0, 0),
             organize_current_file: String::new(),
@@ -675,10 +1037,13 @@ impl Sukusho {
             search_query: String::new(),
             search_results: None,
             index_stats: crate::indexer::IndexStats::default(),
-            toast_manager: crate::ui::ToastManager::new(),
+            toast_manager: crate::ui::ToastManager::new(std::time::Duration::from_millis(
+                settings.notification_duration_ms,
+            )),
             window_opacity: settings.window_opacity,
             first_render: true,
             hidden_on_start: false,
+            preview_path: None,
         };
 
         // Prewarm models if indexing is enabled (creates SINGLE shared model instances)
@@ -834,6 +1199,14 @@ impl Sukusho {
         // }
         // self.was_focused = is_focused;
 
+        // The page opened by `open_settings` has been laid out by now
+        if let Some(section) = self.pending_section.take() {
+            if let Some(anchor) = self.section_anchor(section) {
+                anchor.scroll_to(window, cx);
+                window.request_animation_frame();
+            }
+        }
+
         // Update toast manager to remove expired toasts
         self.toast_manager.update();
 
@@ -857,13 +1230,36 @@ impl Sukusho {
         };
 
         // Now process collected messages
+        let mut screenshots_added = false;
+        let mut screenshots_changed = false;
         for msg in messages {
             match msg {
                 AppMessage::NewScreenshot(path, should_auto_index) => {
                     self.add_screenshot(path, should_auto_index, cx);
+                    screenshots_added = true;
+                    screenshots_changed = true;
                 }
                 AppMessage::ScreenshotRemoved(path) => {
                     self.remove_screenshot(&path, cx);
+                    screenshots_changed = true;
+                }
+                AppMessage::DragCompleted { files, effect } => {
+                    info!("Dropped {} files ({:?})", files.len(), effect);
+                    self.shared.extend(files);
+                    cx.notify();
+                }
+                AppMessage::InitialScanComplete { count } => {
+                    info!("Initial scan complete: {} screenshots", count);
+                    self.initial_scan_complete = true;
+                    cx.notify();
+                }
+                AppMessage::DeleteFailed(error) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.delete.failed", error = error).to_string())
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
                 }
                 AppMessage::ToggleWindow => {
                     info!("Toggle window requested - activating window");
@@ -875,58 +1271,197 @@ impl Sukusho {
                     self.settings_open = false;
                     cx.notify();
                 }
-                AppMessage::OpenSettings => {
-                    self.settings_open = true;
+                AppMessage::ShowPreview(path) => {
+                    self.settings_open = false;
+                    self.preview_path = Some(path);
                     cx.notify();
                 }
-                AppMessage::ChangeDirectory(new_dir) => {
-                    info!("Changing screenshot directory to: {:?}", new_dir);
-                    // Update settings
-                    {
+                AppMessage::Capture(mode) => {
+                    let (directory, fullscreen, target, tx) = {
                         let app_state = cx.global::<AppState>();
-                        let mut settings = app_state.settings.lock();
-                        settings.screenshot_directory = new_dir;
-                        let _ = settings.save();
-                    }
-                    // Clear current screenshots and reload
-                    self.all_screenshots.clear();
-                    self.selected.clear();
-                    self.visible_count = PAGE_SIZE;
-                    // Note: Would need to restart watcher for new directory
-                    // For now, user needs to restart app
-                    cx.notify();
+                        let settings = app_state.settings.lock();
+                        (
+                            settings.screenshot_directory.clone(),
+                            settings.fullscreen_target(),
+                            settings.default_capture_target,
+                            app_state.message_tx.clone(),
+                        )
+                    };
+                    // Region selection runs its own message loop; the watcher picks up the saved file
+                    std::thread::spawn(move || match capture::capture(mode, &directory, fullscreen, target) {
+                        Ok(Some(captured)) => match captured.path {
+                            Some(path) => {
+                                info!("{:?} capture saved: {:?}", mode, path);
+                                let _ = tx.send(AppMessage::CaptureSaved(path));
+                            }
+                            None => {
+                                info!("{:?} capture copied to clipboard", mode);
+                                let _ = tx.send(AppMessage::CaptureCopied);
+                            }
+                        },
+                        Ok(None) => {}
+                        Err(e) => {
+                            error!("{:?} capture failed: {}", mode, e);
+                            let _ = tx.send(AppMessage::CaptureFailed(e.to_string()));
+                        }
+                    });
                 }
-                AppMessage::Quit => {
-                    info!("Quit requested");
-                    cx.quit();
+                AppMessage::CaptureWindowToClipboard => {
+                    let settings = Arc::clone(&cx.global::<AppState>().settings);
+                    // The window is usually hidden here, so report through the tray
+                    std::thread::spawn(move || capture_window_to_clipboard(&settings));
+                }
+                AppMessage::CaptureSaved(path) => {
+                    let app_state = cx.global::<AppState>();
+                    let tx = app_state.message_tx.clone();
+                    let open_action = app_state.settings.lock().open_action;
+                    let open_path = path.clone();
+                    let open_tx = tx.clone();
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.capture.saved").to_string())
+                            .with_type(NotificationType::Success)
+                            .on_click(move |_, _, _| {
+                                crate::open_screenshot(&open_path, open_action, &open_tx);
+                            })
+                            .action(move |_, _, _| {
+                                let tx = tx.clone();
+                                let path = path.clone();
+                                Button::new("capture-copy-text")
+                                    .small()
+                                    .label(t!("notifications.capture.copy_text").to_string())
+                                    .on_click(move |_, _, _| {
+                                        let _ = tx.send(AppMessage::ExtractText(path.clone()));
+                                    })
+                            }),
+                        cx,
+                    );
+                }
+                AppMessage::CaptureCopied => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.capture.clipboard").to_string())
+                            .with_type(NotificationType::Success),
+                        cx,
+                    );
                 }
-                AppMessage::RequestLatestScreenshot => {
-                    // Update the latest screenshot from current state
-                    if let Some(latest) = self.all_screenshots.first() {
-                        set_latest_screenshot(Some(latest.path.clone()));
+                AppMessage::CaptureFailed(error) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.capture.failed", error = error).to_string())
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
+                }
+                AppMessage::ExtractText(path) => {
+                    self.extract_text(path, cx);
+                }
+                AppMessage::TextExtracted(chars) => {
+                    if chars == 0 {
+                        window.push_notification(
+                            Notification::new()
+                                .message(&t!("notifications.ocr.no_text").to_string())
+                                .with_type(NotificationType::Warning),
+                            cx,
+                        );
+                    } else {
+                        self.toast_manager.show(t!("notifications.ocr.copied").to_string());
+                        cx.notify();
                     }
                 }
-                AppMessage::OrganizeStarted(total) => {
-                    info!("Organization started: {} files", total);
-                    self.organizing = true;
-                    self.organize_progress = (0, total);
-                    self.organize_current_file = String::new();
-                    cx.notify();
+                AppMessage::TextExtractionFailed(message) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&message)
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
                 }
-                AppMessage::OrganizeProgress(current, total, file) => {
-                    self.organize_progress = (current, total);
-                    self.organize_current_file = file;
-                    cx.notify();
+                AppMessage::Rescan => {
+                    self.rescan(true, cx);
                 }
-                AppMessage::OrganizeCompleted => {
-                    info!("Organization completed");
-                    self.organizing = false;
-                    self.organize_progress = (0, 0);
-                    self.organize_current_file = String::new();
+                AppMessage::RescanCompleted { directory, found, report } => {
+                    self.apply_rescan(directory, found, report, cx);
+                    screenshots_changed = true;
+                }
+                AppMessage::FindDuplicates => {
+                    self.find_duplicates(window, cx);
+                }
+                AppMessage::DuplicatesFound(groups) => {
+                    self.show_duplicates(groups, window, cx);
+                }
+                AppMessage::ThumbnailReady => {
+                    cx.notify();
+                }
+                AppMessage::TogglePin(path) => {
+                    self.toggle_pin(&[path], cx);
+                }
+                AppMessage::Upload(path) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.upload.started").to_string())
+                            .with_type(NotificationType::Info),
+                        cx,
+                    );
+                    self.upload(path, cx);
+                }
+                AppMessage::UploadCompleted(url) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.upload.completed", url = url).to_string())
+                            .with_type(NotificationType::Success),
+                        cx,
+                    );
+                }
+                AppMessage::UploadFailed(message) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&message)
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
+                }
+                AppMessage::ToggleAutostart => {
+                    let enabled = !cx.global::<AppState>().settings.lock().autostart;
+                    self.set_autostart(enabled, window, cx);
+                }
+                AppMessage::ToggleAlwaysOnTop => {
+                    let enabled = !cx.global::<AppState>().settings.lock().always_on_top;
+                    self.set_always_on_top(enabled, cx);
+                }
+                AppMessage::OpenSettings(section) => self.open_settings(section, window, cx),
+                AppMessage::ChangeDirectory(new_dir) => {
+                    self.change_directory(new_dir, window, cx);
+                    screenshots_changed = true;
+                }
+                AppMessage::Quit => {
+                    info!("Quit requested");
+                    self.shutdown(cx);
+                    cx.quit();
+                }
+                AppMessage::OrganizeStarted(total) => {
+                    info!("Organization started: {} files", total);
+                    self.organizing = true;
+                    self.organize_progress = (0, total);
+                    self.organize_current_file = String::new();
+                    cx.notify();
+                }
+                AppMessage::OrganizeProgress(current, total, file) => {
+                    self.organize_progress = (current, total);
+                    self.organize_current_file = file;
+                    cx.notify();
+                }
+                AppMessage::OrganizeCompleted => {
+                    info!("Organization completed");
+                    self.organizing = false;
+                    self.organize_progress = (0, 0);
+                    self.organize_current_file = String::new();
+                    self.resume_watcher(cx);
                     cx.notify();
                 }
                 AppMessage::ConvertStarted(total) => {
                     info!("Conversion started: {} files", total);
+                    self.pause_watcher(cx);
                     self.converting = true;
                     self.convert_progress = (0, total);
                     self.convert_current_file = String::new();
@@ -942,6 +1477,7 @@ impl Sukusho {
                     self.converting = false;
                     self.convert_progress = (0, 0);
                     self.convert_current_file = String::new();
+                    self.resume_watcher(cx);
                     cx.notify();
                 }
                 AppMessage::ModelDownloadProgress(current, total, model) => {
@@ -1113,59 +1649,827 @@ impl Sukusho {
                     self.search_results = if paths.is_empty() { None } else { Some(paths) };
                     cx.notify();
                 }
+                AppMessage::SettingsReloaded(settings) => {
+                    info!("Applying reloaded settings");
+                    self.apply_settings(&settings, window, cx);
+                }
+                AppMessage::SettingsExported(path) => {
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.settings.exported", path = path.display()).to_string())
+                            .with_type(NotificationType::Success),
+                        cx,
+                    );
+                }
+                AppMessage::SettingsImported(settings) => {
+                    info!("Applying imported settings");
+                    self.apply_settings(&settings, window, cx);
+                    crate::tray::notify(
+                        &settings,
+                        crate::tray::NotificationKind::Success,
+                        "notifications.title",
+                        "notifications.settings.imported",
+                        &[],
+                    );
+                }
+                AppMessage::SettingsTransferFailed(error) => {
+                    error!("Settings import/export failed: {}", error);
+                    window.push_notification(
+                        Notification::new()
+                            .message(&t!("notifications.settings.transfer_failed", error = error).to_string())
+                            .with_type(NotificationType::Error),
+                        cx,
+                    );
+                }
+                AppMessage::CreateTray(attempt) => self.create_tray(attempt, cx),
+                AppMessage::TaskbarCreated => {
+                    let app_state = cx.global::<AppState>();
+                    let icon_style = app_state.settings.lock().tray_icon_style;
+                    // An existing icon is re-added by tray-icon itself
+                    let created = match app_state.tray_manager.lock().as_mut() {
+                        Some(tray) => {
+                            tray.set_icon_style(icon_style);
+                            true
+                        }
+                        None => false,
+                    };
+                    if !created {
+                        self.create_tray(0, cx);
+                    }
+                }
+                AppMessage::SystemThemeChanged => {
+                    let app_state = cx.global::<AppState>();
+                    let (icon_style, theme, accent) = {
+                        let settings = app_state.settings.lock();
+                        (settings.tray_icon_style, settings.theme, settings.accent_color.clone())
+                    };
+                    // Accent changes arrive as theme changes too
+                    crate::accent::apply(&accent);
+                    if let Some(tray) = app_state.tray_manager.lock().as_mut() {
+                        tray.set_icon_style(icon_style);
+                    }
+                    if theme == crate::settings::ThemeMode::System {
+                        apply_theme(theme, window, cx);
+                    }
+                    cx.notify();
+                }
+                AppMessage::DisplayScaleChanged => {
+                    let app_state = cx.global::<AppState>();
+                    let icon_style = app_state.settings.lock().tray_icon_style;
+                    if let Some(tray) = app_state.tray_manager.lock().as_mut() {
+                        tray.set_icon_style(icon_style);
+                    }
+                }
+                AppMessage::LanguageChanged(lang) => {
+                    info!("Language changed to {} - refreshing tray labels", lang);
+                    let app_state = cx.global::<AppState>();
+                    if let Some(tray) = app_state.tray_manager.lock().as_mut() {
+                        tray.refresh_labels();
+                    }
+                    let language = crate::i18n_helpers::SUPPORTED_LANGUAGES
+                        .iter()
+                        .find(|(code, _)| *code == lang)
+                        .map_or(lang.as_str(), |&(_, name)| name);
+                    crate::tray::notify(
+                        &app_state.settings.lock(),
+                        crate::tray::NotificationKind::Info,
+                        "notifications.title",
+                        "notifications.settings.language_changed",
+                        &[("language", language)],
+                    );
+                    cx.notify();
+                }
+                AppMessage::UpdateCheckFailed => {
+                    self.notify_settings_problem(
+                        "notifications.update.check_failed",
+                        &[],
+                        SettingsSection::Updates,
+                        window,
+                        cx,
+                    );
+                }
+                AppMessage::HotkeyConflict(hotkey) => {
+                    self.notify_settings_problem(
+                        "notifications.hotkey.conflict",
+                        &[("hotkey", &hotkey)],
+                        SettingsSection::Hotkeys,
+                        window,
+                        cx,
+                    );
+                }
                 AppMessage::CopiedToClipboard(count) => {
                     info!("Showing clipboard toast for {} items", count);
                     // Show toast notification
-                    let message = if count == 1 {
-                        t!("notifications.copied_to_clipboard.one").to_string()
-                    } else {
-                        t!("notifications.copied_to_clipboard.other", count = count).to_string()
-                    };
+                    let message = crate::i18n_helpers::t_count("notifications.copied_to_clipboard", count);
                     self.toast_manager.show(message);
                     cx.notify();
                 }
             }
         }
 
+        // Covers both the startup scan and each new screenshot
+        if screenshots_added {
+            self.run_cleanup(cx);
+        }
+
+        if screenshots_changed {
+            if let Some(tray) = cx.global::<AppState>().tray_manager.lock().as_mut() {
+                tray.set_latest_screenshot(crate::recent::latest().as_ref());
+            }
+        }
+
         // If there are more messages, schedule another render to process them
         if has_more {
             cx.notify();
         }
     }
 
+    /// Show settings, at the given section if any
+    fn open_settings(
+        &mut self,
+        section: Option<SettingsSection>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.settings_open = true;
+        if let Some(section) = section {
+            info!("Opening settings at {:?}", section);
+            self.settings_page = section.page();
+            // Start at the top; anchored sections are scrolled to after the next layout
+            self.settings_scroll.set_offset(point(px(0.0), px(0.0)));
+            self.pending_section = Some(section);
+            window.request_animation_frame();
+        }
+        cx.notify();
+    }
+
+    /// Report a problem that's fixed in settings; clicking the notification opens that section
+    fn notify_settings_problem(
+        &mut self,
+        message_key: &str,
+        args: &[(&str, &str)],
+        section: SettingsSection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let app_state = cx.global::<AppState>();
+        if !crate::tray::is_window_visible() {
+            crate::tray::notify(
+                &app_state.settings.lock(),
+                crate::tray::NotificationKind::Warning,
+                "notifications.title",
+                message_key,
+                args,
+            );
+            return;
+        }
+
+        let tx = app_state.message_tx.clone();
+        window.push_notification(
+            Notification::new()
+                .message(&crate::tray::interpolate(&t!(message_key), args))
+                .with_type(NotificationType::Warning)
+                .on_click(move |_, _, _| {
+                    let _ = tx.send(AppMessage::OpenSettings(Some(section)));
+                }),
+            cx,
+        );
+    }
+
+    fn section_anchor(&self, section: SettingsSection) -> Option<ScrollAnchor> {
+        self.section_anchors
+            .iter()
+            .find(|(anchored, _)| *anchored == section)
+            .map(|(_, anchor)| anchor.clone())
+    }
+
+    /// Create the tray icon if it doesn't exist yet; `attempt` counts the retries so far
+    fn create_tray(&mut self, attempt: u32, cx: &mut Context<Self>) {
+        if crate::session::is_shutting_down() {
+            return;
+        }
+        let app_state = cx.global::<AppState>();
+        let mut tray_manager = app_state.tray_manager.lock();
+        if tray_manager.is_some() {
+            return;
+        }
+
+        match crate::tray::TrayManager::new(
+            app_state.message_tx.clone(),
+            Arc::clone(&app_state.settings),
+        ) {
+            Ok(mut tray) => {
+                info!("Tray icon created after {} retries", attempt);
+                tray.set_latest_screenshot(crate::recent::latest().as_ref());
+                *tray_manager = Some(tray);
+            }
+            Err(e) => {
+                log::warn!("Failed to create tray icon: {:#}", e);
+                if !crate::tray::schedule_tray_retry(app_state.message_tx.clone(), attempt + 1) {
+                    log::warn!("Giving up on the tray icon until Explorer restarts");
+                }
+            }
+        }
+    }
+
+    /// Save settings, stop the watcher and remove the tray icon before exiting
+    fn shutdown(&self, cx: &mut Context<Self>) {
+        crate::session::begin_shutdown();
+
+        // Also settles a save `settings_store::update` still has pending
+        crate::settings_store::flush();
+        let app_state = cx.global::<AppState>();
+        // Dropping the tray manager removes the icon from the notification area
+        app_state.tray_manager.lock().take();
+        // Joins the watcher thread (outside the lock); the session window exits for us if
+        // this hangs
+        let watcher = app_state.watcher.lock().take();
+        if let Some(watcher) = watcher {
+            watcher.stop();
+        }
+        crate::session::mark_shutdown_complete();
+    }
+
+    /// Run OCR on a screenshot in the background and copy the text to the clipboard
+    fn extract_text(&self, path: PathBuf, cx: &mut Context<Self>) {
+        let tx = cx.global::<AppState>().message_tx.clone();
+        std::thread::spawn(move || {
+            let message = match ocr::extract_text(&path) {
+                Ok(text) if text.trim().is_empty() => AppMessage::TextExtracted(0),
+                Ok(text) => {
+                    if clipboard::copy_text_to_clipboard(&text) {
+                        AppMessage::TextExtracted(text.chars().count())
+                    } else {
+                        AppMessage::TextExtractionFailed(
+                            t!("notifications.ocr.failed", error = "clipboard unavailable").to_string(),
+                        )
+                    }
+                }
+                Err(e) => {
+                    error!("OCR failed for {:?}: {:#}", path, e);
+                    let message = match e.downcast_ref::<ocr::LanguageNotInstalled>() {
+                        Some(missing) => {
+                            t!("notifications.ocr.language_missing", language = missing.language)
+                        }
+                        None => t!("notifications.ocr.failed", error = format!("{:#}", e)),
+                    };
+                    AppMessage::TextExtractionFailed(message.to_string())
+                }
+            };
+            let _ = tx.send(message);
+        });
+    }
+
+    /// Copy a screenshot as a base64 data URI
+    fn copy_data_uri(&mut self, path: &std::path::Path, cx: &mut Context<Self>) {
+        let max_bytes = cx.global::<AppState>().settings.lock().data_uri_max_bytes;
+        let message = match clipboard::copy_as_data_uri(path, max_bytes) {
+            Ok(()) => t!("notifications.data_uri.copied").to_string(),
+            Err(e) => {
+                error!("Failed to copy {:?} as a data URI: {}", path, e);
+                t!("notifications.data_uri.failed", error = e.to_string()).to_string()
+            }
+        };
+        self.toast_manager.show(message);
+        cx.notify();
+    }
+
+    /// Pin the files, or unpin them if they are all pinned already
+    fn toggle_pin(&mut self, paths: &[PathBuf], cx: &mut Context<Self>) {
+        if let Err(e) = pins::toggle(paths) {
+            error!("Failed to save pins: {}", e);
+        }
+        cx.notify();
+    }
+
+    /// Every known screenshot with its modification time, for the retention policy
+    fn retention_files(&self, cx: &App) -> Vec<(PathBuf, SystemTime)> {
+        // Files not paged in yet still count toward the policy
+        let unscanned = cx
+            .global::<AppState>()
+            .watcher
+            .lock()
+            .as_ref()
+            .map(|w| w.unscanned())
+            .unwrap_or_default();
+        self.all_screenshots
+            .iter()
+            .map(|s| (s.path.clone(), s.modified))
+            .chain(unscanned)
+            .collect()
+    }
+
+    /// Apply the retention policy to all known screenshots in the background
+    fn run_cleanup(&self, cx: &mut Context<Self>) {
+        let (policy, pinned, permanent, tx) = {
+            let app_state = cx.global::<AppState>();
+            let settings = app_state.settings.lock();
+            (
+                settings.retention_policy(),
+                pins::pinned().into_iter().collect(),
+                !settings.delete_to_recycle_bin,
+                app_state.message_tx.clone(),
+            )
+        };
+        if !policy.is_enabled() {
+            return;
+        }
+        let files = self.retention_files(cx);
+        retention::spawn_cleanup(
+            files,
+            policy,
+            pinned,
+            permanent,
+            Arc::clone(&self.cleanup_running),
+            tx,
+        );
+    }
+
+    /// Scan the screenshot folder in the background, for changes the watcher missed
+    fn rescan(&mut self, report: bool, cx: &mut Context<Self>) {
+        let app_state = cx.global::<AppState>();
+        let settings = app_state.settings.lock().clone();
+        let tx = app_state.message_tx.clone();
+        std::thread::spawn(move || {
+            let directory = settings.screenshot_directory.clone();
+            let found = crate::watcher::existing_files(&directory, &settings)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            let _ = tx.send(AppMessage::RescanCompleted { directory, found, report });
+        });
+    }
+
+    /// Stop reacting to file events while a batch job rewrites the folder
+    fn pause_watcher(&mut self, cx: &mut Context<Self>) {
+        if let Some(watcher) = cx.global::<AppState>().watcher.lock().as_ref() {
+            watcher.pause();
+        }
+    }
+
+    /// Resume the watcher once no batch job is running, and rescan for what it missed
+    fn resume_watcher(&mut self, cx: &mut Context<Self>) {
+        if self.organizing || self.converting {
+            return;
+        }
+        let was_paused = cx
+            .global::<AppState>()
+            .watcher
+            .lock()
+            .as_ref()
+            .is_some_and(|watcher| watcher.resume());
+        if was_paused {
+            self.rescan(false, cx);
+        }
+    }
+
+    /// Add screenshots a rescan found and drop the ones it didn't
+    fn apply_rescan(
+        &mut self,
+        directory: PathBuf,
+        found: Vec<PathBuf>,
+        report: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let app_state = cx.global::<AppState>();
+        if app_state.settings.lock().screenshot_directory != directory {
+            debug!("Ignoring rescan of previous directory: {:?}", directory);
+            return;
+        }
+        let settings = Arc::clone(&app_state.settings);
+        let deferred: Vec<PathBuf> = app_state
+            .watcher
+            .lock()
+            .as_ref()
+            .map(|w| w.unscanned().into_iter().map(|(path, _)| path).collect())
+            .unwrap_or_default();
+        let known: Vec<PathBuf> = self.all_screenshots.iter().map(|s| s.path.clone()).collect();
+
+        let reconciled = crate::watcher::reconcile(&known, &deferred, &found);
+        info!(
+            "Rescan found {} new and {} vanished screenshots",
+            reconciled.added.len(),
+            reconciled.removed.len()
+        );
+        for path in &reconciled.removed {
+            self.remove_screenshot(path, cx);
+        }
+        for path in &reconciled.added {
+            self.add_screenshot(path.clone(), false, cx);
+        }
+
+        // Usually started from the tray, so report there; catch-up rescans stay quiet
+        if report {
+            crate::tray::notify(
+                &settings.lock(),
+                crate::tray::NotificationKind::Info,
+                "notifications.title",
+                "notifications.rescan.completed",
+                &[
+                    ("added", &reconciled.added.len().to_string()),
+                    ("removed", &reconciled.removed.len().to_string()),
+                ],
+            );
+        }
+        cx.notify();
+    }
+
+    /// Hash all screenshots in the background and report near-duplicate groups
+    fn find_duplicates(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        // Newest first whatever the gallery order, so each group starts with its newest copy
+        let paths: Vec<PathBuf> = sorted_paths(&self.all_screenshots, SortOrder::NewestFirst);
+        let cache = Arc::clone(&self.thumbnail_cache);
+        let tx = cx.global::<AppState>().message_tx.clone();
+
+        self.settings_open = false;
+        window.push_notification(
+            Notification::new()
+                .message(
+                    &t!(
+                        "notifications.duplicates.scanning",
+                        screenshots = crate::i18n_helpers::t_count("counts.screenshots", paths.len())
+                    )
+                    .to_string(),
+                )
+                .with_type(NotificationType::Info),
+            cx,
+        );
+        std::thread::spawn(move || {
+            let groups = dedup::find_duplicates(&paths, dedup::DEFAULT_MAX_DISTANCE, &cache);
+            let _ = tx.send(AppMessage::DuplicatesFound(groups));
+        });
+    }
+
+    /// Select every duplicate except the newest of each group so they can be reviewed and deleted
+    fn show_duplicates(&mut self, groups: Vec<Vec<PathBuf>>, window: &mut Window, cx: &mut Context<Self>) {
+        if groups.is_empty() {
+            window.push_notification(
+                Notification::new()
+                    .message(&t!("notifications.duplicates.none").to_string())
+                    .with_type(NotificationType::Success),
+                cx,
+            );
+            return;
+        }
+
+        for (i, group) in groups.iter().enumerate() {
+            info!("Duplicate group {}: {:?}", i + 1, group);
+        }
+
+        // Groups follow the scan order (newest first), so keep the first entry
+        self.selected = groups
+            .iter()
+            .flat_map(|group| group.iter().skip(1).cloned())
+            .collect();
+        self.last_selected = None;
+        let extra = self.selected.len();
+
+        window.push_notification(
+            Notification::new()
+                .message(
+                    &t!("notifications.duplicates.found", groups = groups.len(), count = extra)
+                        .to_string(),
+                )
+                .with_type(NotificationType::Warning)
+                .autohide(false),
+            cx,
+        );
+        cx.notify();
+    }
+
+    /// Delete the files, asking first when they would be deleted permanently
+    fn confirm_delete(&self, paths: Vec<PathBuf>, window: &mut Window, cx: &mut Context<Self>) {
+        let permanent = !cx.global::<AppState>().settings.lock().delete_to_recycle_bin;
+        if !permanent {
+            self.delete_screenshots(paths, cx);
+            return;
+        }
+        let view = cx.entity().downgrade();
+        let message = crate::i18n_helpers::t_count("app.delete_confirm.message", paths.len());
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view.clone();
+            let paths = paths.clone();
+            dialog
+                .confirm()
+                .title(t!("app.delete_confirm.title").to_string())
+                .child(message.clone())
+                .button_props(
+                    DialogButtonProps::default()
+                        .ok_text(t!("app.delete_confirm.button").to_string())
+                        .ok_variant(ButtonVariant::Danger)
+                        .cancel_text(t!("common.button.cancel").to_string()),
+                )
+                .on_ok(move |_, _, cx| {
+                    let paths = paths.clone();
+                    let _ = view.update(cx, |this, cx| this.delete_screenshots(paths, cx));
+                    true
+                })
+        });
+    }
+
+    /// Delete screenshots in the background, honoring the Recycle Bin setting
+    fn delete_screenshots(&self, paths: Vec<PathBuf>, cx: &mut Context<Self>) {
+        let (tx, permanent) = {
+            let app_state = cx.global::<AppState>();
+            let permanent = !app_state.settings.lock().delete_to_recycle_bin;
+            (app_state.message_tx.clone(), permanent)
+        };
+        info!("Deleting {} files (permanent: {})", paths.len(), permanent);
+        std::thread::spawn(move || {
+            for path in paths {
+                match delete::delete_screenshot(&path, permanent) {
+                    // Remove right away; the watcher's later event is a no-op
+                    Ok(()) => {
+                        let _ = tx.send(AppMessage::ScreenshotRemoved(path));
+                    }
+                    Err(e) => {
+                        error!("Failed to delete {:?}: {:#}", path, e);
+                        let _ = tx.send(AppMessage::DeleteFailed(format!("{:#}", e)));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Upload a screenshot in the background and copy the resulting URL to the clipboard
+    fn upload(&self, path: PathBuf, cx: &mut Context<Self>) {
+        let (tx, settings) = {
+            let app_state = cx.global::<AppState>();
+            (app_state.message_tx.clone(), app_state.settings.lock().clone())
+        };
+        std::thread::spawn(move || {
+            let message = match upload::upload(&path, &settings) {
+                Ok(url) => {
+                    if !clipboard::copy_text_to_clipboard(&url) {
+                        error!("Failed to copy upload URL to clipboard");
+                    }
+                    AppMessage::UploadCompleted(url)
+                }
+                Err(e) => {
+                    error!("Upload failed for {:?}: {:#}", path, e);
+                    let message = match e.downcast_ref::<upload::RateLimited>() {
+                        Some(upload::RateLimited { retry_after: Some(secs) }) => {
+                            t!("notifications.upload.rate_limited_retry", seconds = secs)
+                        }
+                        Some(upload::RateLimited { retry_after: None }) => {
+                            t!("notifications.upload.rate_limited")
+                        }
+                        None => t!("notifications.upload.failed", error = format!("{:#}", e)),
+                    };
+                    AppMessage::UploadFailed(message.to_string())
+                }
+            };
+            let _ = tx.send(message);
+        });
+    }
+
+    /// Register/unregister launch at login and keep the setting and tray in sync
+    fn set_autostart(&mut self, enabled: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let settings = Arc::clone(&cx.global::<AppState>().settings);
+        let applied = match autostart::set_autostart(enabled) {
+            Ok(()) => {
+                let mut settings = settings.lock();
+                settings.autostart = enabled;
+                if let Err(e) = settings.save() {
+                    error!("Failed to save settings: {}", e);
+                }
+                enabled
+            }
+            Err(e) => {
+                error!("Failed to update startup registration: {}", e);
+                window.push_notification(
+                    Notification::new()
+                        .message(&t!("notifications.error.generic", message = e.to_string()).to_string())
+                        .with_type(NotificationType::Error),
+                    cx,
+                );
+                settings.lock().autostart
+            }
+        };
+
+        if let Some(tray) = cx.global::<AppState>().tray_manager.lock().as_mut() {
+            tray.set_autostart_checked(applied);
+        }
+        cx.notify();
+    }
+
+    /// Pin the window above other apps; the window and tray follow once the change
+    /// comes back as `SettingsReloaded`
+    fn set_always_on_top(&mut self, enabled: bool, cx: &mut Context<Self>) {
+        if let Err(e) = crate::settings_store::update(|s| s.always_on_top = enabled) {
+            error!("Failed to change always on top: {}", e);
+        }
+        cx.notify();
+    }
+
+    /// Switch the watched screenshot directory, restarting the watcher and rescanning
+    fn change_directory(&mut self, new_dir: PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        info!("Changing screenshot directory to: {:?}", new_dir);
+
+        if let Err(e) = std::fs::create_dir_all(&new_dir) {
+            error!("Failed to create screenshot directory {:?}: {}", new_dir, e);
+            window.push_notification(
+                Notification::new()
+                    .message(&t!("notifications.error.generic", message = e.to_string()).to_string())
+                    .with_type(NotificationType::Error),
+                cx,
+            );
+            return;
+        }
+
+        let (settings, message_tx, watcher) = {
+            let app_state = cx.global::<AppState>();
+            (
+                Arc::clone(&app_state.settings),
+                app_state.message_tx.clone(),
+                Arc::clone(&app_state.watcher),
+            )
+        };
+
+        // Stop the old watcher before the new directory becomes current; events it still
+        // sends are for the old folder and get ignored by `add_screenshot`
+        let old = watcher.lock().take();
+        if let Some(old) = old {
+            old.stop_detached();
+        }
+
+        {
+            let mut settings = settings.lock();
+            settings.screenshot_directory = new_dir.clone();
+            if let Err(e) = settings.save() {
+                error!("Failed to save settings: {}", e);
+            }
+        }
+
+        // Clear current screenshots; the new watcher rescans on start
+        self.all_screenshots.clear();
+        self.initial_scan_complete = false;
+        crate::recent::clear();
+        self.selected.clear();
+        self.last_selected = None;
+        self.visible_count = PAGE_SIZE;
+        self.thumbnail_cache.clear();
+        crate::ui::clear_thumbnails();
+
+        *watcher.lock() = Some(ScreenshotWatcher::new(new_dir, message_tx, settings).spawn());
+        cx.notify();
+    }
+
+    /// Put the gallery in `order`, e.g. after the setting changed
+    fn sort_screenshots(&mut self, order: SortOrder) {
+        if self.all_screenshots.is_sorted_by(|a, b| a.cmp_by(b, order).is_le()) {
+            return;
+        }
+        info!("Sorting screenshots: {:?}", order);
+        self.all_screenshots.sort_by(|a, b| a.cmp_by(b, order));
+    }
+
+    /// Apply settings that can change without a restart to the running UI
+    fn apply_settings(
+        &mut self,
+        settings: &crate::settings::Settings,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.thumbnail_size = settings.thumbnail_size;
+        self.grid_columns = settings.grid_columns;
+        self.toast_manager.set_duration(std::time::Duration::from_millis(
+            settings.notification_duration_ms,
+        ));
+        crate::tray::set_tray_drag_enabled(settings.enable_tray_drag);
+        crate::tray::set_always_on_top(settings.always_on_top);
+        crate::tray::set_exclude_own_windows(settings.exclude_own_windows);
+        crate::recent::set_capacity(settings.recent_history_size);
+        crate::accent::apply(&settings.accent_color);
+        self.sort_screenshots(settings.sort_order);
+        if let Some(tray) = cx.global::<AppState>().tray_manager.lock().as_mut() {
+            tray.set_always_on_top_checked(settings.always_on_top);
+            tray.set_icon_style(settings.tray_icon_style);
+            tray.update_action_availability(settings);
+            tray.set_autostart_checked(settings.autostart);
+        }
+
+        // Imported or hand-edited settings skip the settings page, so register what it would;
+        // unchanged hotkeys are left alone
+        if crate::hotkey::is_initialized() {
+            let message_tx = cx.global::<AppState>().message_tx.clone();
+            if settings.hotkey_enabled && !crate::hotkey::update_hotkey(&settings.hotkey) {
+                let _ = message_tx.send(AppMessage::HotkeyConflict(settings.hotkey.clone()));
+            }
+            for action in HotkeyAction::ALL {
+                let hotkey = action_hotkey(settings, action);
+                if !crate::hotkey::update_action_hotkey(action, hotkey) {
+                    let _ = message_tx.send(AppMessage::HotkeyConflict(hotkey.to_string()));
+                }
+            }
+        }
+        if settings.autostart != autostart::is_autostart_enabled() {
+            if let Err(e) = autostart::set_autostart(settings.autostart) {
+                error!("Failed to update startup registration: {}", e);
+                window.push_notification(
+                    Notification::new()
+                        .message(&t!("notifications.error.generic", message = e.to_string()).to_string())
+                        .with_type(NotificationType::Error),
+                    cx,
+                );
+            }
+        }
+
+        if (self.window_opacity - settings.window_opacity).abs() > f32::EPSILON {
+            self.window_opacity = settings.window_opacity;
+            set_window_opacity(window, settings.window_opacity);
+        }
+
+        apply_theme(settings.theme, window, cx);
+
+        for (input, value) in [
+            (&self.filename_template_input, &settings.filename_template),
+            (&self.max_screenshots_input, &optional_to_string(settings.max_screenshots)),
+            (&self.max_age_days_input, &optional_to_string(settings.max_age_days)),
+            (&self.png_compression_input, &settings.png_compression.to_string()),
+            (&self.jpeg_quality_input, &settings.jpeg_quality.to_string()),
+            (&self.imgur_client_id_input, &settings.imgur_client_id),
+            (&self.upload_endpoint_input, &settings.upload_endpoint),
+            (&self.upload_field_name_input, &settings.upload_field_name),
+            (&self.accent_color_input, &settings.accent_color),
+        ] {
+            let value = value.clone();
+            input.update(cx, |input, cx| {
+                if input.value().as_ref() != value.as_str() {
+                    input.set_value(value, window, cx);
+                }
+            });
+        }
+
+        if let Some(lang) = settings.language.as_deref() {
+            if lang != crate::i18n_helpers::current_language() {
+                crate::i18n_helpers::change_language(lang);
+                let app_state = cx.global::<AppState>();
+                if let Some(tray) = app_state.tray_manager.lock().as_mut() {
+                    tray.refresh_labels();
+                }
+            }
+        }
+
+        cx.notify();
+    }
+
     /// Add a new screenshot
     fn add_screenshot(&mut self, path: PathBuf, should_auto_index: bool, cx: &mut Context<Self>) {
         if self.all_screenshots.iter().any(|s| s.path == path) {
             return;
         }
 
+        // Drop events still queued from a watcher replaced by a directory change
+        let (current_dir, recursive) = {
+            let settings = cx.global::<AppState>().settings.lock();
+            (settings.screenshot_directory.clone(), settings.watches_subdirectories())
+        };
+        let in_dir = if recursive {
+            path.starts_with(&current_dir)
+        } else {
+            path.parent() == Some(current_dir.as_path())
+        };
+        if !in_dir {
+            debug!("Ignoring screenshot outside current directory: {:?}", path);
+            return;
+        }
+
         // Check if we should auto-convert
-        let (auto_convert, format, quality, message_tx) = {
+        let (convert_to, quality, keep_original, message_tx, settings) = {
             let app_state = cx.global::<AppState>();
             let settings = app_state.settings.lock();
             (
-                settings.auto_convert_webp,
-                settings.conversion_format,
-                settings.webp_quality,
+                settings.convert_to(),
+                settings.quality_for(settings.conversion_format),
+                settings.keep_original_after_convert,
                 app_state.message_tx.clone(),
+                Arc::clone(&app_state.settings),
             )
         };
 
-        // If auto-convert is enabled and this is a PNG, convert it
-        if auto_convert && convert::is_convertible(&path) {
+        // If auto-convert is enabled and this is a PNG/BMP, convert it
+        if let Some(format) = convert_to.filter(|f| convert::needs_conversion(&path, *f)) {
             info!("Auto-converting new screenshot to {:?}: {:?}", format, path);
             let path_clone = path.clone();
             std::thread::spawn(move || {
                 // Small delay to ensure the file is fully written
                 std::thread::sleep(std::time::Duration::from_millis(500));
 
-                match convert::convert_image(&path_clone, format, quality) {
+                match convert::convert_image(&path_clone, format, quality, keep_original) {
                     Ok(output_path) => {
                         info!("{:?} conversion successful: {:?}", format, output_path);
+                        convert::notify_completed(&settings.lock(), format, &output_path);
                         // Notify about the new file (the remove is handled in convert)
                         // The watcher will pick up the new file automatically
                         // We send a remove for the old path since convert deleted it
-                        let _ = message_tx.send(AppMessage::ScreenshotRemoved(path_clone));
+                        // A kept original now has a sibling, so it is added without converting
+                        let original = if keep_original {
+                            AppMessage::NewScreenshot(path_clone, should_auto_index)
+                        } else {
+                            AppMessage::ScreenshotRemoved(path_clone)
+                        };
+                        let _ = message_tx.send(original);
                         let _ = message_tx
                             .send(AppMessage::NewScreenshot(output_path, should_auto_index));
                     }
@@ -1182,20 +2486,23 @@ impl Sukusho {
         }
 
         if let Some(info) = ScreenshotInfo::from_path(path.clone()) {
+            let sort_order = cx.global::<AppState>().settings.lock().sort_order;
             let insert_pos = self
                 .all_screenshots
                 .iter()
-                .position(|s| s.modified < info.modified)
+                .position(|s| s.cmp_by(&info, sort_order).is_gt())
                 .unwrap_or(self.all_screenshots.len());
 
-            // If inserted at position 0, this is the newest screenshot
-            if insert_pos == 0 {
-                set_latest_screenshot(Some(info.path.clone()));
-            }
-
+            crate::recent::push(info.clone());
+            pins::follow_rename(&info);
             self.all_screenshots.insert(insert_pos, info);
             cx.notify();
 
+            // The gallery shows arrivals while it is open
+            if should_auto_index && !crate::tray::is_window_visible() {
+                crate::tray::notify_new_screenshot(&cx.global::<AppState>().settings, path.clone());
+            }
+
             // Auto-index the new screenshot if indexing is enabled and this is a truly new screenshot
             if should_auto_index {
                 let (
@@ -1248,9 +2555,15 @@ impl Sukusho {
 
     /// Remove a screenshot
     fn remove_screenshot(&mut self, path: &PathBuf, cx: &mut Context<Self>) {
+        // Already removed (e.g. deleted in-app, then reported again by the watcher)
+        if !self.all_screenshots.iter().any(|s| s.path == *path) {
+            return;
+        }
         self.all_screenshots.retain(|s| s.path != *path);
+        crate::recent::remove(path);
         self.selected.remove(path);
         self.thumbnail_cache.invalidate(path);
+        crate::ui::forget_thumbnails(path);
 
         // Cleanup vector DB if indexing is enabled
         let (indexing_enabled, screenshot_dir, indexing_cpu_mode) = {
@@ -1292,7 +2605,9 @@ impl Sukusho {
                 self.handle_select(path, modifiers, cx);
             }
             GalleryAction::Open(path) => {
-                self.open_file(&path);
+                let app_state = cx.global::<AppState>();
+                let action = app_state.settings.lock().open_action;
+                crate::open_screenshot(&path, action, &app_state.message_tx);
             }
             GalleryAction::ContextMenu { paths, position } => {
                 self.show_context_menu(&paths, position, cx);
@@ -1355,37 +2670,32 @@ impl Sukusho {
         cx.notify();
     }
 
-    /// Open file with default application
-    fn open_file(&self, path: &PathBuf) {
-        info!("Opening file: {:?}", path);
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            let _ = std::process::Command::new("cmd")
-                .args(["/C", "start", "", &path.to_string_lossy()])
-                .creation_flags(CREATE_NO_WINDOW)
-                .spawn();
-        }
-        #[cfg(not(windows))]
-        {
-            let _ = open::that(path);
-        }
-    }
-
     /// Show Windows context menu for files
+    #[cfg_attr(not(windows), allow(unused_variables))]
     fn show_context_menu(
-        &self,
+        &mut self,
         paths: &[PathBuf],
         _position: Point<Pixels>,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
         info!("Context menu for {} files", paths.len());
         #[cfg(windows)]
         {
             // Context menu MUST run on UI thread (same thread that owns the window)
             // This will block the UI while the menu is open, but that's expected behavior
-            crate::ui::show_shell_context_menu(paths);
+            use crate::ui::ShellMenuCommand;
+            let command = crate::ui::show_shell_context_menu(paths);
+            if let (Some(command), Some(path)) = (command, paths.first()) {
+                match command {
+                    ShellMenuCommand::CopyText => self.extract_text(path.clone(), cx),
+                    ShellMenuCommand::CopyDataUri => self.copy_data_uri(path, cx),
+                    ShellMenuCommand::TogglePin => self.toggle_pin(paths, cx),
+                    ShellMenuCommand::Upload => {
+                        let tx = cx.global::<AppState>().message_tx.clone();
+                        let _ = tx.send(AppMessage::Upload(path.clone()));
+                    }
+                }
+            }
         }
     }
 
@@ -1401,6 +2711,13 @@ impl Sukusho {
 
     /// Load more items for infinite scroll
     fn load_more(&mut self, cx: &mut Context<Self>) {
+        // Page in older files from disk once everything loaded is about to be shown
+        if self.visible_count + PAGE_SIZE > self.all_screenshots.len() {
+            if let Some(watcher) = cx.global::<AppState>().watcher.lock().as_ref() {
+                watcher.load_more(PAGE_SIZE);
+            }
+        }
+
         let new_count = (self.visible_count + PAGE_SIZE).min(self.all_screenshots.len());
         if new_count > self.visible_count {
             self.visible_count = new_count;
@@ -1415,8 +2732,14 @@ impl Sukusho {
     }
 
     /// Check if there are more items to load
-    fn has_more(&self) -> bool {
+    fn has_more(&self, cx: &App) -> bool {
         self.visible_count < self.all_screenshots.len()
+            || cx
+                .global::<AppState>()
+                .watcher
+                .lock()
+                .as_ref()
+                .is_some_and(|w| w.has_unscanned())
     }
 
     /// Get selected paths for context menu
@@ -1489,7 +2812,7 @@ impl Render for Sukusho {
         let visible_count = self.visible_screenshots().len();
         let selected_count = self.selected.len();
         let settings_open = self.settings_open;
-        let has_more = self.has_more();
+        let has_more = self.has_more(cx);
 
         v_flex()
             .id("main-container")
@@ -1531,12 +2854,37 @@ impl Render for Sukusho {
                     return;
                 }
 
+                // Handle action hotkey recording
+                if let Some(action) = this.recording_action_hotkey {
+                    if event.keystroke.key.as_str() == "escape" {
+                        this.recording_action_hotkey = None;
+                        cx.notify();
+                        return;
+                    }
+
+                    if let Some(hotkey_str) = Self::keystroke_to_hotkey_string(&event.keystroke) {
+                        info!("Recorded {:?} hotkey: {}", action, hotkey_str);
+                        if crate::hotkey::update_action_hotkey(action, &hotkey_str) {
+                            let app_state = cx.global::<AppState>();
+                            let mut settings = app_state.settings.lock();
+                            *action_hotkey_mut(&mut settings, action) = hotkey_str;
+                            let _ = settings.save();
+                        }
+                        this.recording_action_hotkey = None;
+                        cx.notify();
+                    }
+                    return;
+                }
+
                 match event.keystroke.key.as_str() {
                     // ESC - clear selection, close settings, or minimize window
                     "escape" => {
                         if this.recording_hotkey {
                             this.recording_hotkey = false;
                             cx.notify();
+                        } else if this.preview_path.is_some() {
+                            this.preview_path = None;
+                            cx.notify();
                         } else if !this.selected.is_empty() {
                             // Clear selection if items are selected
                             this.selected.clear();
@@ -1557,18 +2905,30 @@ impl Render for Sukusho {
                             let files: Vec<_> = this.selected.iter().cloned().collect();
                             let count = files.len();
                             info!("Attempting to copy {} files to clipboard", count);
-                            if clipboard::copy_files_to_clipboard(&files) {
-                                info!("Successfully copied {} files to clipboard", count);
-                                // Send message to show notification (will be handled in process_messages)
-                                let app_state = cx.global::<AppState>();
-                                let _ = app_state.message_tx.send(AppMessage::CopiedToClipboard(count));
-                            } else {
-                                error!("Failed to copy files to clipboard");
-                            }
+                            let app_state = cx.global::<AppState>();
+                            let as_image = app_state.settings.lock().copy_as_image;
+                            let message_tx = app_state.message_tx.clone();
+                            // Copying as an image decodes the screenshot, so keep it off the UI thread
+                            std::thread::spawn(move || {
+                                if clipboard::copy_screenshots(&files, as_image) {
+                                    info!("Successfully copied {} files to clipboard", count);
+                                    // Send message to show notification (will be handled in process_messages)
+                                    let _ = message_tx.send(AppMessage::CopiedToClipboard(count));
+                                } else {
+                                    error!("Failed to copy files to clipboard");
+                                }
+                            });
                         } else {
                             info!("No files selected for clipboard copy");
                         }
                     }
+                    // Delete - delete selected files (to the Recycle Bin unless disabled)
+                    "delete" if !this.settings_open && !window.has_active_dialog(cx) => {
+                        if !this.selected.is_empty() {
+                            let files: Vec<_> = this.selected.iter().cloned().collect();
+                            this.confirm_delete(files, window, cx);
+                        }
+                    }
                     // Ctrl+A - select all visible
                     "a" if event.keystroke.modifiers.control => {
                         let paths: Vec<_> = this
@@ -1652,10 +3012,10 @@ impl Render for Sukusho {
                                                 .px_2()
                                                 .py_1()
                                                 .rounded(px(12.0))
-                                                .bg(cx.theme().primary)
+                                                .bg(gpui::rgb(crate::accent::rgb()))
                                                 .text_xs()
                                                 .font_weight(FontWeight::MEDIUM)
-                                                .text_color(cx.theme().primary_foreground)
+                                                .text_color(gpui::rgb(0xFFFFFF))
                                                 .child(t!("app.header.selected", count = selected_count).to_string()),
                                         )
                                     }),
@@ -1720,66 +3080,106 @@ impl Render for Sukusho {
                     .child(if settings_open {
                         self.render_settings(cx).into_any_element()
                     } else {
-                        self.render_gallery(has_more, cx).into_any_element()
+                        self.render_gallery(has_more, window, cx).into_any_element()
                     }),
             )
-            // Render toast overlay at bottom center
-            .child(self.toast_manager.render())
-    }
-}
-
-impl Sukusho {
-    fn render_gallery(&self, has_more: bool, cx: &mut Context<Self>) -> impl IntoElement {
-        let search_enabled = self.models_downloaded;
-        let has_search_results = self.search_results.is_some();
-
-        v_flex()
-            .size_full()
-            // Search bar (only show if models are downloaded)
-            .when(search_enabled, |el| {
-                el.child(
-                    h_flex()
-                        .w_full()
-                        .px_4()
-                        .py_3()
-                        .bg(cx.theme().background)
-                        .border_b_1()
-                        .border_color(cx.theme().border)
-                        .child(
-                            h_flex()
-                                .w_full()
-                                .px_4()
-                                .gap_2()
-                                .items_center()
-                                .child(Input::new(&self.search_input).flex_1())
-                                .when(has_search_results, |el| {
-                                    el.child(
-                                        Button::new("clear-search")
-                                            .small()
-                                            .ghost()
-                                            .label(&t!("app.search.clear_button").to_string())
-                                            .on_click(cx.listener(|this, _, window, cx| {
-                                                this.search_input.update(cx, |input, cx| {
-                                                    input.set_value("", window, cx);
-                                                });
-                                                this.search_query.clear();
-                                                this.search_results = None;
-                                                cx.notify();
-                                            })),
-                                    )
-                                }),
-                        ),
-                )
-            })
+            // Screenshot preview above everything but the toasts
+            .when_some(self.preview_path.clone(), |el, path| {
+                el.child(self.render_preview(path, cx))
+            })
+            // Render toast overlay at bottom center
+            .child(self.toast_manager.render())
+            // Delete confirmation
+            .children(Root::render_dialog_layer(window, cx))
+    }
+}
+
+impl Sukusho {
+    /// Full-window preview of one screenshot; a click or Escape closes it
+    fn render_preview(&self, path: PathBuf, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .id("preview-overlay")
+            .absolute()
+            .top_0()
+            .left_0()
+            .size_full()
+            .p_4()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(gpui::rgba(0x000000d9))
+            .cursor_pointer()
+            .on_click(cx.listener(|this, _, _, cx| {
+                this.preview_path = None;
+                cx.notify();
+            }))
+            .child(img(path.as_path()).size_full().object_fit(ObjectFit::Contain))
+    }
+
+    fn render_gallery(
+        &self,
+        has_more: bool,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let has_search_results = self.search_results.is_some();
+        // Search results may be anywhere in the list, not just the loaded pages
+        let screenshots = if has_search_results {
+            self.all_screenshots.clone()
+        } else {
+            self.visible_screenshots().to_vec()
+        };
+        let pinned: HashSet<PathBuf> = pins::pinned().into_iter().collect();
+
+        v_flex()
+            .size_full()
+            // Search bar
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_4()
+                    .py_3()
+                    .bg(cx.theme().background)
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .px_4()
+                            .gap_2()
+                            .items_center()
+                            .child(Input::new(&self.search_input).flex_1())
+                            .when(has_search_results, |el| {
+                                el.child(
+                                    Button::new("clear-search")
+                                        .small()
+                                        .ghost()
+                                        .label(&t!("app.search.clear_button").to_string())
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.search_input.update(cx, |input, cx| {
+                                                input.set_value("", window, cx);
+                                            });
+                                            this.search_query.clear();
+                                            this.search_results = None;
+                                            cx.notify();
+                                        })),
+                                )
+                            }),
+                    ),
+            )
             // Gallery
             .child(gallery(
-                self.visible_screenshots().to_vec(),
+                screenshots,
                 self.search_results.clone(),
                 self.selected.clone(),
+                pinned,
+                self.shared.clone(),
                 Arc::clone(&self.thumbnail_cache),
                 self.grid_columns,
                 self.thumbnail_size,
+                window.scale_factor(),
                 has_more,
+                !self.initial_scan_complete,
                 cx,
             ))
     }
@@ -1849,6 +3249,7 @@ impl Sukusho {
                     .flex_1()
                     .h_full()
                     .overflow_scroll()
+                    .track_scroll(&self.settings_scroll)
                     .p_4()
                     .child(match current_page {
                         SettingsPage::General => self
@@ -1937,6 +3338,40 @@ impl Sukusho {
             })
     }
 
+    /// Render a labeled text input with help text underneath
+    fn render_text_setting(
+        &self,
+        label: &str,
+        help: &str,
+        input: &Entity<InputState>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .w_full()
+            .gap_1()
+            .mb_2()
+            .child(div().text_sm().child(label.to_string()))
+            .child(Input::new(input).w_full())
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(help.to_string()),
+            )
+    }
+
+    /// Section header that `open_settings` can scroll to
+    fn render_anchored_section_header(
+        &self,
+        title: &str,
+        section: SettingsSection,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        div()
+            .anchor_scroll(self.section_anchor(section))
+            .child(self.render_section_header(title, cx))
+    }
+
     fn render_section_header(&self, title: &str, cx: &Context<Self>) -> impl IntoElement {
         div()
             .text_base()
@@ -1956,7 +3391,11 @@ impl Sukusho {
         let thumbnail_size = self.thumbnail_size;
         let organizer_enabled = settings.organizer_enabled;
         let organizer_format = settings.organizer_format.clone();
+        let delete_to_recycle_bin = settings.delete_to_recycle_bin;
         let format_preview = organizer::format_preview(&organizer_format);
+        let rename_preview = crate::rename::FilenameTemplate::parse(&settings.filename_template)
+            .map(|template| template.render(chrono::Local::now(), 1, "app"))
+            .unwrap_or_default();
         let organizing = self.organizing;
         let organize_progress = self.organize_progress;
         let organize_current_file = self.organize_current_file.clone();
@@ -1984,38 +3423,51 @@ impl Sukusho {
                     &t!("settings.general.startup.run_on_startup_label").to_string(),
                     Some(&t!("settings.general.startup.run_on_startup_desc").to_string()),
                     Switch::new("run-on-startup")
-                        .checked(settings.run_on_startup)
+                        .checked(settings.autostart)
+                        .on_click(cx.listener(move |this, checked, window, cx| {
+                            this.set_autostart(*checked, window, cx);
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.startup.hide_window_on_start_label").to_string(),
+                    Some(&t!("settings.general.startup.hide_window_on_start_desc").to_string()),
+                    Switch::new("hide-on-start")
+                        .checked(settings.hide_window_on_start)
                         .on_click(cx.listener(move |_this, checked, _, cx| {
                             let checked = *checked;
                             {
                                 let app_state = cx.global::<AppState>();
                                 let mut settings = app_state.settings.lock();
-                                settings.run_on_startup = checked;
+                                settings.hide_window_on_start = checked;
                                 let _ = settings.save();
                             }
-                            // Register/unregister from Windows startup
-                            if checked {
-                                register_startup();
-                            } else {
-                                unregister_startup();
-                            }
                             cx.notify();
                         })),
                     cx,
                 ),
             )
+            // Tray icon
+            .child(self.render_anchored_section_header(
+                &t!("settings.general.tray.title").to_string(),
+                SettingsSection::Notifications,
+                cx,
+            ))
             .child(
                 self.render_setting_row(
-                    &t!("settings.general.startup.hide_window_on_start_label").to_string(),
-                    Some(&t!("settings.general.startup.hide_window_on_start_desc").to_string()),
-                    Switch::new("hide-on-start")
-                        .checked(settings.hide_window_on_start)
+                    &t!("settings.general.tray.drag_label").to_string(),
+                    Some(&t!("settings.general.tray.drag_desc").to_string()),
+                    Switch::new("tray-drag")
+                        .checked(settings.enable_tray_drag)
                         .on_click(cx.listener(move |_this, checked, _, cx| {
                             let checked = *checked;
+                            crate::tray::set_tray_drag_enabled(checked);
                             {
                                 let app_state = cx.global::<AppState>();
                                 let mut settings = app_state.settings.lock();
-                                settings.hide_window_on_start = checked;
+                                settings.enable_tray_drag = checked;
                                 let _ = settings.save();
                             }
                             cx.notify();
@@ -2023,65 +3475,237 @@ impl Sukusho {
                     cx,
                 ),
             )
-            // Language
-            .child(self.render_section_header(&language_title, cx))
             .child(
                 self.render_setting_row(
-                    &language_label,
-                    Some(&language_desc),
+                    &t!("settings.general.tray.drag_esc_label").to_string(),
+                    Some(&t!("settings.general.tray.drag_esc_desc").to_string()),
                     h_flex()
                         .gap_1()
-                        .child(
-                            Button::new("lang-en")
+                        .children([
+                            (DragEscAction::Cancel, "drag-esc-cancel", "settings.general.tray.drag_esc_cancel"),
+                            (DragEscAction::CopyToClipboard, "drag-esc-copy", "settings.general.tray.drag_esc_copy"),
+                        ]
+                        .into_iter()
+                        .map(|(action, id, label)| {
+                            let selected = settings.drag_esc_action == action;
+                            Button::new(id)
                                 .small()
-                                .when(current_lang == "en", |b| b.primary())
-                                .when(current_lang != "en", |b| b.outline())
-                                .label("English")
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    crate::i18n_helpers::change_language("en");
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.language = Some("en".to_string());
+                                        settings.drag_esc_action = action;
                                         let _ = settings.save();
                                     }
                                     cx.notify();
                                 }))
-                        )
-                        .child(
-                            Button::new("lang-ko")
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.tray.left_click_label").to_string(),
+                    Some(&t!("settings.general.tray.left_click_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (TrayClickAction::ToggleWindow, "tray-click-toggle", "settings.general.tray.left_click_toggle_window"),
+                            (TrayClickAction::OpenFolder, "tray-click-folder", "settings.general.tray.left_click_open_folder"),
+                            (TrayClickAction::CopyLatest, "tray-click-copy", "settings.general.tray.left_click_copy_latest"),
+                            (TrayClickAction::Nothing, "tray-click-nothing", "settings.general.tray.left_click_nothing"),
+                        ]
+                        .into_iter()
+                        .map(|(action, id, label)| {
+                            let selected = settings.tray_left_click_action == action;
+                            Button::new(id)
                                 .small()
-                                .when(current_lang == "ko", |b| b.primary())
-                                .when(current_lang != "ko", |b| b.outline())
-                                .label("한국어")
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    crate::i18n_helpers::change_language("ko");
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.language = Some("ko".to_string());
+                                        settings.tray_left_click_action = action;
                                         let _ = settings.save();
                                     }
                                     cx.notify();
                                 }))
-                        )
-                        .child(
-                            Button::new("lang-ja")
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.tray.double_click_label").to_string(),
+                    Some(&t!("settings.general.tray.double_click_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (TrayDoubleClickAction::ShowWindow, "tray-dblclick-show", "settings.general.tray.double_click_show_window"),
+                            (TrayDoubleClickAction::OpenLatest, "tray-dblclick-latest", "settings.general.tray.double_click_open_latest"),
+                            (TrayDoubleClickAction::OpenFolder, "tray-dblclick-folder", "settings.general.tray.double_click_open_folder"),
+                        ]
+                        .into_iter()
+                        .map(|(action, id, label)| {
+                            let selected = settings.tray_double_click_action == action;
+                            Button::new(id)
                                 .small()
-                                .when(current_lang == "ja", |b| b.primary())
-                                .when(current_lang != "ja", |b| b.outline())
-                                .label("日本語")
-                                .on_click(cx.listener(|_this, _, _, cx| {
-                                    crate::i18n_helpers::change_language("ja");
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.language = Some("ja".to_string());
+                                        settings.tray_double_click_action = action;
                                         let _ = settings.save();
                                     }
                                     cx.notify();
-                                })),
-                        ),
+                                }))
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.tray.icon_style_label").to_string(),
+                    Some(&t!("settings.general.tray.icon_style_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (TrayIconStyle::Color, "tray-icon-color", "settings.general.tray.icon_style_color"),
+                            (TrayIconStyle::Mono, "tray-icon-mono", "settings.general.tray.icon_style_mono"),
+                        ]
+                        .into_iter()
+                        .map(|(style, id, label)| {
+                            let selected = settings.tray_icon_style == style;
+                            Button::new(id)
+                                .small()
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    let app_state = cx.global::<AppState>();
+                                    {
+                                        let mut settings = app_state.settings.lock();
+                                        settings.tray_icon_style = style;
+                                        let _ = settings.save();
+                                    }
+                                    if let Some(tray) = app_state.tray_manager.lock().as_mut() {
+                                        tray.set_icon_style(style);
+                                    }
+                                    cx.notify();
+                                }))
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.tray.open_action_label").to_string(),
+                    Some(&t!("settings.general.tray.open_action_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (OpenAction::Internal, "open-action-internal", "settings.general.tray.open_action_internal"),
+                            (OpenAction::DefaultApp, "open-action-default-app", "settings.general.tray.open_action_default_app"),
+                            (OpenAction::Reveal, "open-action-reveal", "settings.general.tray.open_action_reveal"),
+                        ]
+                        .into_iter()
+                        .map(|(action, id, label)| {
+                            let selected = settings.open_action == action;
+                            Button::new(id)
+                                .small()
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.open_action = action;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                }))
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.tray.notifications_label").to_string(),
+                    Some(&t!("settings.general.tray.notifications_desc").to_string()),
+                    Switch::new("notifications-enabled")
+                        .checked(settings.notifications_enabled)
+                        .on_click(cx.listener(move |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.notifications_enabled = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.tray.notification_batching_label").to_string(),
+                    Some(&t!("settings.general.tray.notification_batching_desc").to_string()),
+                    Switch::new("notification-batching")
+                        .checked(settings.notification_batching)
+                        .disabled(!settings.notifications_enabled)
+                        .on_click(cx.listener(move |_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.notification_batching = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+            // Language
+            .child(self.render_anchored_section_header(
+                &language_title,
+                SettingsSection::Language,
+                cx,
+            ))
+            .child(
+                self.render_setting_row(
+                    &language_label,
+                    Some(&language_desc),
+                    h_flex()
+                        .gap_1()
+                        .children(crate::i18n_helpers::SUPPORTED_LANGUAGES.iter().map(|&(code, name)| {
+                            Button::new(SharedString::from(format!("lang-{}", code)))
+                                .small()
+                                .when(current_lang == code, |b| b.primary())
+                                .when(current_lang != code, |b| b.outline())
+                                .label(name)
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    crate::i18n_helpers::change_language(code);
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.language = Some(code.to_string());
+                                        let _ = settings.save();
+                                        let _ = app_state
+                                            .message_tx
+                                            .send(AppMessage::LanguageChanged(code.to_string()));
+                                    }
+                                    cx.notify();
+                                }))
+                        })),
                     cx,
                 )
             )
@@ -2147,6 +3771,12 @@ impl Sukusho {
                     cx,
                 )
             )
+            .child(self.render_text_setting(
+                &t!("settings.general.appearance.accent_color_label").to_string(),
+                &t!("settings.general.appearance.accent_color_help").to_string(),
+                &self.accent_color_input,
+                cx,
+            ))
             // Screenshot Directory
             .child(self.render_section_header(&screenshot_dir_title, cx))
             .child(
@@ -2185,6 +3815,38 @@ impl Sukusho {
                             }),
                     ),
             )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.screenshot_dir.recycle_bin_label").to_string(),
+                    Some(&t!("settings.general.screenshot_dir.recycle_bin_desc").to_string()),
+                    Switch::new("delete-to-recycle-bin")
+                        .checked(delete_to_recycle_bin)
+                        .on_click(cx.listener(|_this, checked: &bool, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.delete_to_recycle_bin = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+            // Automatic cleanup
+            .child(self.render_section_header(&t!("settings.general.cleanup.title").to_string(), cx))
+            .child(self.render_text_setting(
+                &t!("settings.general.cleanup.max_screenshots_label").to_string(),
+                &t!("settings.general.cleanup.max_screenshots_help").to_string(),
+                &self.max_screenshots_input,
+                cx,
+            ))
+            .child(self.render_text_setting(
+                &t!("settings.general.cleanup.max_age_days_label").to_string(),
+                &t!("settings.general.cleanup.max_age_days_help").to_string(),
+                &self.max_age_days_input,
+                cx,
+            ))
             // Screenshot Organizer
             .child(self.render_section_header(&organizer_title, cx))
             .child(
@@ -2210,6 +3872,8 @@ impl Sukusho {
                                 }
                                 // If enabling, organize existing files
                                 if *checked && !this.organizing {
+                                    // Organizing moves every file; catch up with one rescan after
+                                    this.pause_watcher(cx);
                                     let tx = {
                                         let app_state = cx.global::<AppState>();
                                         app_state.message_tx.clone()
@@ -2251,7 +3915,7 @@ impl Sukusho {
                                     div()
                                         .h_full()
                                         .w(relative(progress_pct / 100.0))
-                                        .bg(cx.theme().primary)
+                                        .bg(gpui::rgb(crate::accent::rgb()))
                                         .rounded(px(4.0)),
                                 ),
                         )
@@ -2360,6 +4024,111 @@ impl Sukusho {
                             .child(t!("settings.general.organizer.format_preview", preview = format_preview).to_string()),
                     ),
             )
+            // Rename new screenshots
+            .child(self.render_section_header(&t!("settings.general.rename.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.rename.enable_label").to_string(),
+                    Some(&t!("settings.general.rename.enable_desc").to_string()),
+                    Switch::new("rename-enable")
+                        .checked(settings.rename_enabled)
+                        .on_click(cx.listener(|_this, checked: &bool, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.rename_enabled = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                v_flex()
+                    .w_full()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_sm()
+                            .child(t!("settings.general.rename.template_label").to_string()),
+                    )
+                    .child(Input::new(&self.filename_template_input).w_full())
+                    .child(
+                        div()
+                            .text_xs()
+                            .map(|d| match &self.filename_template_error {
+                                Some(error) => d.text_color(cx.theme().danger).child(error.clone()),
+                                None => d.text_color(cx.theme().muted_foreground).child(
+                                    t!("settings.general.rename.template_help", preview = rename_preview)
+                                        .to_string(),
+                                ),
+                            }),
+                    ),
+            )
+            // Upload
+            .child(self.render_anchored_section_header(
+                &t!("settings.general.upload.title").to_string(),
+                SettingsSection::Upload,
+                cx,
+            ))
+            .child(self.render_text_setting(
+                &t!("settings.general.upload.imgur_client_id_label").to_string(),
+                &t!("settings.general.upload.imgur_client_id_help").to_string(),
+                &self.imgur_client_id_input,
+                cx,
+            ))
+            .child(self.render_text_setting(
+                &t!("settings.general.upload.endpoint_label").to_string(),
+                &t!("settings.general.upload.endpoint_help").to_string(),
+                &self.upload_endpoint_input,
+                cx,
+            ))
+            .child(self.render_text_setting(
+                &t!("settings.general.upload.field_name_label").to_string(),
+                &t!("settings.general.upload.field_name_help").to_string(),
+                &self.upload_field_name_input,
+                cx,
+            ))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.upload.large_image_label").to_string(),
+                    Some(
+                        &t!(
+                            "settings.general.upload.large_image_desc",
+                            pixels = settings.large_image_threshold,
+                            size = format_file_size(settings.large_image_max_bytes)
+                        )
+                        .to_string(),
+                    ),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (LargeImageAction::Warn, "large-image-warn", "settings.general.upload.large_image_warn"),
+                            (LargeImageAction::Downscale, "large-image-downscale", "settings.general.upload.large_image_downscale"),
+                            (LargeImageAction::Proceed, "large-image-proceed", "settings.general.upload.large_image_proceed"),
+                        ]
+                        .into_iter()
+                        .map(|(action, id, label)| {
+                            let selected = settings.large_image_action == action;
+                            Button::new(id)
+                                .small()
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.large_image_action = action;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                }))
+                        })),
+                    cx,
+                ),
+            )
             // Display Settings
             .child(self.render_section_header(&t!("settings.general.appearance.title").to_string(), cx))
             .child(
@@ -2465,12 +4234,127 @@ impl Sukusho {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.window_opacity = new_opacity;
+                                        settings.window_opacity = new_opacity;
+                                        let _ = settings.save();
+                                    }
+                                    set_window_opacity(window, new_opacity);
+                                    cx.notify();
+                                })),
+                        ),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.sort_order_label").to_string(),
+                    Some(&t!("settings.general.appearance.sort_order_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (SortOrder::NewestFirst, "sort-newest-first", "settings.general.appearance.sort_newest_first"),
+                            (SortOrder::OldestFirst, "sort-oldest-first", "settings.general.appearance.sort_oldest_first"),
+                            (SortOrder::NameAsc, "sort-name-asc", "settings.general.appearance.sort_name_asc"),
+                            (SortOrder::NameDesc, "sort-name-desc", "settings.general.appearance.sort_name_desc"),
+                        ]
+                        .into_iter()
+                        .map(|(order, id, label)| {
+                            let selected = settings.sort_order == order;
+                            Button::new(id)
+                                .small()
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.sort_order = order;
                                         let _ = settings.save();
                                     }
-                                    set_window_opacity(window, new_opacity);
+                                    this.sort_screenshots(order);
                                     cx.notify();
-                                })),
+                                }))
+                        })),
+                    cx,
+                ),
+            )
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.appearance.always_on_top_label").to_string(),
+                    Some(&t!("settings.general.appearance.always_on_top_desc").to_string()),
+                    Switch::new("always-on-top")
+                        .checked(settings.always_on_top)
+                        .on_click(cx.listener(move |this, checked, _, cx| {
+                            this.set_always_on_top(*checked, cx);
+                        })),
+                    cx,
+                ),
+            )
+            // Backup (export/import settings)
+            .child(self.render_section_header(&t!("settings.general.backup.title").to_string(), cx))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.general.backup.label").to_string(),
+                    Some(&t!("settings.general.backup.desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Button::new("settings-export")
+                                .small()
+                                .outline()
+                                .label(&t!("settings.general.backup.export_button").to_string())
+                                .on_click(|_, _, cx| {
+                                    let (tx, settings) = {
+                                        let app_state = cx.global::<AppState>();
+                                        (app_state.message_tx.clone(), Arc::clone(&app_state.settings))
+                                    };
+                                    std::thread::spawn(move || {
+                                        if let Some(path) = pick_settings_file(true) {
+                                            let snapshot = settings.lock().clone();
+                                            let _ = match snapshot.export_to(&path) {
+                                                Ok(()) => tx.send(AppMessage::SettingsExported(path)),
+                                                Err(e) => tx.send(AppMessage::SettingsTransferFailed(format!("{:#}", e))),
+                                            };
+                                        }
+                                    });
+                                }),
+                        )
+                        .child(
+                            Button::new("settings-import")
+                                .small()
+                                .outline()
+                                .label(&t!("settings.general.backup.import_button").to_string())
+                                .on_click(|_, _, cx| {
+                                    let (tx, settings) = {
+                                        let app_state = cx.global::<AppState>();
+                                        (app_state.message_tx.clone(), Arc::clone(&app_state.settings))
+                                    };
+                                    std::thread::spawn(move || {
+                                        if let Some(path) = pick_settings_file(false) {
+                                            let current = settings.lock().clone();
+                                            let mut imported = current.clone();
+                                            match imported.import_from(&path) {
+                                                Ok(()) => {
+                                                    // The directory switch restarts the watcher on the UI side
+                                                    let new_dir = imported.screenshot_directory.clone();
+                                                    imported.screenshot_directory = current.screenshot_directory;
+                                                    *settings.lock() = imported.clone();
+                                                    if let Err(e) = imported.save() {
+                                                        log::warn!("Failed to save imported settings: {}", e);
+                                                    }
+                                                    let changed = new_dir != imported.screenshot_directory;
+                                                    let _ = tx.send(AppMessage::SettingsImported(imported));
+                                                    if changed {
+                                                        let _ = tx.send(AppMessage::ChangeDirectory(new_dir));
+                                                    }
+                                                }
+                                                Err(e) => {
+                                                    let _ = tx.send(AppMessage::SettingsTransferFailed(format!("{:#}", e)));
+                                                }
+                                            }
+                                        }
+                                    });
+                                }),
                         ),
                     cx,
                 ),
@@ -2484,7 +4368,9 @@ impl Sukusho {
     ) -> impl IntoElement {
         let auto_convert = settings.auto_convert_webp;
         let format = settings.conversion_format;
-        let quality = settings.webp_quality;
+        let quality = settings.quality_for(format);
+        let lossless = !matches!(format, ConversionFormat::Jpeg | ConversionFormat::Avif);
+        let keep_original = settings.keep_original_after_convert;
         let converting = self.converting;
         let convert_progress = self.convert_progress;
         let convert_current_file = self.convert_current_file.clone();
@@ -2550,11 +4436,62 @@ impl Sukusho {
                                     }
                                     cx.notify();
                                 })),
+                        )
+                        .child(
+                            Button::new("fmt-png")
+                                .small()
+                                .when(format == ConversionFormat::Png, |s| s.primary())
+                                .when(format != ConversionFormat::Png, |s| s.outline())
+                                .label(&t!("settings.conversion.format.png").to_string())
+                                .on_click(cx.listener(|_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.conversion_format = ConversionFormat::Png;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                })),
+                        )
+                        .child(
+                            Button::new("fmt-avif")
+                                .small()
+                                .when(format == ConversionFormat::Avif, |s| s.primary())
+                                .when(format != ConversionFormat::Avif, |s| s.outline())
+                                .label(&t!("settings.conversion.format.avif").to_string())
+                                .on_click(cx.listener(|_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.conversion_format = ConversionFormat::Avif;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                })),
                         ),
                     cx,
                 ),
             )
-            // Quality (only for JPEG, WebP doesn't support lossy quality in image crate)
+            // Keep original
+            .child(
+                self.render_setting_row(
+                    &t!("settings.conversion.keep_original.label").to_string(),
+                    Some(&t!("settings.conversion.keep_original.desc").to_string()),
+                    Switch::new("keep-original")
+                        .checked(keep_original)
+                        .on_click(cx.listener(|_this, checked: &bool, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.keep_original_after_convert = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+            // Quality (only for JPEG and AVIF, WebP and PNG output are lossless)
             .child(
                 self.render_setting_row(
                     &t!("settings.conversion.quality.label").to_string(),
@@ -2567,13 +4504,17 @@ impl Sukusho {
                                 .ghost()
                                 .compact()
                                 .label("-")
-                                .when(format == ConversionFormat::WebP, |s| s.disabled(true))
+                                .when(lossless, |s| s.disabled(true))
                                 .on_click(cx.listener(|_this, _, _, cx| {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.webp_quality =
-                                            (settings.webp_quality as i32 - 5).max(1) as u32;
+                                        let quality = if settings.conversion_format == ConversionFormat::Avif {
+                                            &mut settings.avif_quality
+                                        } else {
+                                            &mut settings.webp_quality
+                                        };
+                                        *quality = quality.saturating_sub(5).max(1);
                                         let _ = settings.save();
                                     }
                                     cx.notify();
@@ -2588,7 +4529,7 @@ impl Sukusho {
                                 .rounded(px(4.0))
                                 .bg(cx.theme().muted)
                                 .text_sm()
-                                .when(format == ConversionFormat::WebP, |s| s.opacity(0.5))
+                                .when(lossless, |s| s.opacity(0.5))
                                 .child(format!("{}", quality)),
                         )
                         .child(
@@ -2596,13 +4537,17 @@ impl Sukusho {
                                 .ghost()
                                 .compact()
                                 .label("+")
-                                .when(format == ConversionFormat::WebP, |s| s.disabled(true))
+                                .when(lossless, |s| s.disabled(true))
                                 .on_click(cx.listener(|_this, _, _, cx| {
                                     {
                                         let app_state = cx.global::<AppState>();
                                         let mut settings = app_state.settings.lock();
-                                        settings.webp_quality =
-                                            (settings.webp_quality + 5).min(100);
+                                        let quality = if settings.conversion_format == ConversionFormat::Avif {
+                                            &mut settings.avif_quality
+                                        } else {
+                                            &mut settings.webp_quality
+                                        };
+                                        *quality = (*quality + 5).min(100);
                                         let _ = settings.save();
                                     }
                                     cx.notify();
@@ -2636,7 +4581,7 @@ impl Sukusho {
                                     div()
                                         .h_full()
                                         .w(relative(progress_pct / 100.0))
-                                        .bg(cx.theme().primary)
+                                        .bg(gpui::rgb(crate::accent::rgb()))
                                         .rounded(px(4.0)),
                                 ),
                         )
@@ -2777,7 +4722,7 @@ impl Sukusho {
                                         div()
                                             .h_full()
                                             .w(relative(progress_pct / 100.0))
-                                            .bg(cx.theme().primary)
+                                            .bg(gpui::rgb(crate::accent::rgb()))
                                             .rounded(px(3.0)),
                                     ),
                             )
@@ -2884,7 +4829,7 @@ impl Sukusho {
                                         div()
                                             .h_full()
                                             .w(relative(progress_pct / 100.0))
-                                            .bg(cx.theme().primary)
+                                            .bg(gpui::rgb(crate::accent::rgb()))
                                             .rounded(px(4.0)),
                                     ),
                             )
@@ -2976,6 +4921,8 @@ impl Sukusho {
         let hotkey_enabled = settings.hotkey_enabled;
         let hotkey_str = settings.hotkey.clone();
         let recording = self.recording_hotkey;
+        let fullscreen_target = settings.fullscreen_target();
+        let monitor_count = capture::monitor_count();
 
         v_flex()
             .w_full()
@@ -3053,6 +5000,7 @@ impl Sukusho {
                                             .label(&if recording { t!("settings.hotkey.cancel_button").to_string() } else { t!("settings.hotkey.record_button").to_string() })
                                             .on_click(cx.listener(|this, _, _, cx| {
                                                 this.recording_hotkey = !this.recording_hotkey;
+                                                this.recording_action_hotkey = None;
                                                 cx.notify();
                                             })),
                                     ),
@@ -3065,6 +5013,324 @@ impl Sukusho {
                             .child(t!("settings.hotkey.examples").to_string()),
                     ),
             )
+            // Capture hotkeys
+            .child(self.render_section_header(&t!("settings.hotkey.capture_title").to_string(), cx))
+            .child(self.render_action_hotkey_row(
+                "record-capture-region",
+                HotkeyAction::CaptureRegion,
+                t!("settings.hotkey.capture_region_label").to_string(),
+                settings.capture_hotkey.clone(),
+                cx,
+            ))
+            .child(self.render_action_hotkey_row(
+                "record-capture-fullscreen",
+                HotkeyAction::CaptureFullscreen,
+                t!("settings.hotkey.capture_fullscreen_label").to_string(),
+                settings.capture_fullscreen_hotkey.clone(),
+                cx,
+            ))
+            .child(self.render_action_hotkey_row(
+                "record-capture-window",
+                HotkeyAction::CaptureWindow,
+                t!("settings.hotkey.capture_window_label").to_string(),
+                settings.capture_window_hotkey.clone(),
+                cx,
+            ))
+            .child(self.render_action_hotkey_row(
+                "record-capture-window-clipboard",
+                HotkeyAction::CaptureWindowToClipboard,
+                t!("settings.hotkey.capture_window_clipboard_label").to_string(),
+                settings.capture_window_clipboard_hotkey.clone(),
+                cx,
+            ))
+            .child(
+                self.render_setting_row(
+                    &t!("settings.hotkey.save_clipboard_captures_label").to_string(),
+                    Some(&t!("settings.hotkey.save_clipboard_captures_desc").to_string()),
+                    Switch::new("save-clipboard-captures")
+                        .checked(settings.save_clipboard_captures)
+                        .on_click(cx.listener(|_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.save_clipboard_captures = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+            // Where region, full-screen and window captures go
+            .child(
+                self.render_setting_row(
+                    &t!("settings.hotkey.capture_target_label").to_string(),
+                    Some(&t!("settings.hotkey.capture_target_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (CaptureTarget::File, "capture-target-file", "settings.hotkey.capture_target_file"),
+                            (CaptureTarget::Clipboard, "capture-target-clipboard", "settings.hotkey.capture_target_clipboard"),
+                            (CaptureTarget::Both, "capture-target-both", "settings.hotkey.capture_target_both"),
+                        ]
+                        .into_iter()
+                        .map(|(target, id, label)| {
+                            let selected = settings.default_capture_target == target;
+                            Button::new(id)
+                                .small()
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(&t!(label).to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.default_capture_target = target;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                }))
+                        })),
+                    cx,
+                ),
+            )
+            // File format of saved captures
+            .child(
+                self.render_setting_row(
+                    &t!("settings.hotkey.capture_format_label").to_string(),
+                    Some(&t!("settings.hotkey.capture_format_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .children([
+                            (CaptureFormat::Png, "capture-format-png", "PNG"),
+                            (CaptureFormat::Jpeg, "capture-format-jpeg", "JPEG"),
+                        ]
+                        .into_iter()
+                        .map(|(format, id, label)| {
+                            let selected = settings.capture_format == format;
+                            Button::new(id)
+                                .small()
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(label)
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.capture_format = format;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                }))
+                        })),
+                    cx,
+                ),
+            )
+            .child(match settings.capture_format {
+                CaptureFormat::Png => self.render_text_setting(
+                    &t!("settings.hotkey.png_compression_label").to_string(),
+                    &t!("settings.hotkey.png_compression_help").to_string(),
+                    &self.png_compression_input,
+                    cx,
+                ),
+                CaptureFormat::Jpeg => self.render_text_setting(
+                    &t!("settings.hotkey.jpeg_quality_label").to_string(),
+                    &t!("settings.hotkey.jpeg_quality_help").to_string(),
+                    &self.jpeg_quality_input,
+                    cx,
+                ),
+            })
+            // Monitor used by full-screen capture
+            .child(
+                self.render_setting_row(
+                    &t!("settings.hotkey.capture_monitor_label").to_string(),
+                    Some(&t!("settings.hotkey.capture_monitor_desc").to_string()),
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Button::new("capture-monitor-all")
+                                .small()
+                                .when(fullscreen_target == FullscreenTarget::AllMonitors, |s| {
+                                    s.primary()
+                                })
+                                .when(fullscreen_target != FullscreenTarget::AllMonitors, |s| {
+                                    s.outline()
+                                })
+                                .label(&t!("settings.hotkey.capture_monitor_all").to_string())
+                                .on_click(cx.listener(|_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.capture_monitor = None;
+                                        settings.capture_monitor_under_cursor = false;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                })),
+                        )
+                        .child(
+                            Button::new("capture-monitor-cursor")
+                                .small()
+                                .when(fullscreen_target == FullscreenTarget::UnderCursor, |s| {
+                                    s.primary()
+                                })
+                                .when(fullscreen_target != FullscreenTarget::UnderCursor, |s| {
+                                    s.outline()
+                                })
+                                .label(&t!("settings.hotkey.capture_monitor_cursor").to_string())
+                                .on_click(cx.listener(|_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.capture_monitor_under_cursor = true;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                })),
+                        )
+                        .children((0..monitor_count).map(|index| {
+                            let selected = fullscreen_target == FullscreenTarget::Monitor(index);
+                            Button::new(SharedString::from(format!("capture-monitor-{}", index)))
+                                .small()
+                                .when(selected, |s| s.primary())
+                                .when(!selected, |s| s.outline())
+                                .label(format!("{}", index + 1))
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        settings.capture_monitor = Some(index);
+                                        settings.capture_monitor_under_cursor = false;
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                }))
+                        })),
+                    cx,
+                ),
+            )
+            // Save images copied to the clipboard
+            .child(
+                self.render_setting_row(
+                    &t!("settings.hotkey.clipboard_images_label").to_string(),
+                    Some(&t!("settings.hotkey.clipboard_images_desc").to_string()),
+                    Switch::new("capture-clipboard-images")
+                        .checked(settings.capture_clipboard_images)
+                        .on_click(cx.listener(|_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.capture_clipboard_images = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+            // Copy a single screenshot as an image as well as a file
+            .child(
+                self.render_setting_row(
+                    &t!("settings.hotkey.copy_as_image_label").to_string(),
+                    Some(&t!("settings.hotkey.copy_as_image_desc").to_string()),
+                    Switch::new("copy-as-image")
+                        .checked(settings.copy_as_image)
+                        .on_click(cx.listener(|_this, checked, _, cx| {
+                            {
+                                let app_state = cx.global::<AppState>();
+                                let mut settings = app_state.settings.lock();
+                                settings.copy_as_image = *checked;
+                                let _ = settings.save();
+                            }
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+    }
+
+    /// Row showing an action hotkey with a record button
+    fn render_action_hotkey_row(
+        &self,
+        id: &'static str,
+        action: HotkeyAction,
+        label: String,
+        hotkey: String,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let recording = self.recording_action_hotkey == Some(action);
+        let bound = !hotkey.is_empty();
+
+        h_flex()
+            .w_full()
+            .justify_between()
+            .items_center()
+            .child(
+                div()
+                    .text_sm()
+                    .font_weight(FontWeight::MEDIUM)
+                    .text_color(cx.theme().foreground)
+                    .child(label),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div()
+                            .px_3()
+                            .py_1()
+                            .rounded(px(6.0))
+                            .bg(if recording {
+                                cx.theme().primary
+                            } else {
+                                cx.theme().muted
+                            })
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(if recording {
+                                cx.theme().primary_foreground
+                            } else {
+                                cx.theme().foreground
+                            })
+                            .child(if recording {
+                                t!("settings.hotkey.recording").to_string()
+                            } else if hotkey.is_empty() {
+                                t!("settings.hotkey.unbound").to_string()
+                            } else {
+                                hotkey
+                            }),
+                    )
+                    .child(
+                        Button::new(id)
+                            .small()
+                            .when(recording, |s| s.danger())
+                            .when(!recording, |s| s.outline())
+                            .label(&if recording { t!("settings.hotkey.cancel_button").to_string() } else { t!("settings.hotkey.record_button").to_string() })
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.recording_action_hotkey = if recording { None } else { Some(action) };
+                                this.recording_hotkey = false;
+                                cx.notify();
+                            })),
+                    )
+                    .when(bound && !recording, |row| {
+                        row.child(
+                            Button::new(SharedString::from(format!("{}-clear", id)))
+                                .small()
+                                .ghost()
+                                .label(&t!("common.button.clear").to_string())
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    if crate::hotkey::update_action_hotkey(action, "") {
+                                        let app_state = cx.global::<AppState>();
+                                        let mut settings = app_state.settings.lock();
+                                        action_hotkey_mut(&mut settings, action).clear();
+                                        let _ = settings.save();
+                                    }
+                                    cx.notify();
+                                })),
+                        )
+                    }),
+            )
     }
 
     fn render_about_settings(&self, cx: &Context<Self>) -> impl IntoElement {
@@ -3112,6 +5378,7 @@ impl Sukusho {
             // Links
             .child(
                 h_flex()
+                    .anchor_scroll(self.section_anchor(SettingsSection::Updates))
                     .gap_2()
                     .mt_4()
                     .child(
@@ -3128,27 +5395,10 @@ impl Sukusho {
                             .outline()
                             .small()
                             .label(&t!("settings.about.check_updates_button").to_string())
-                            .on_click(cx.listener(|_this, _, _, _cx| {
+                            .on_click(cx.listener(|_this, _, _, cx| {
                                 info!("Check for updates requested from About settings");
-                                std::thread::spawn(|| {
-                                    use crate::update_checker;
-                                    info!("{}", rust_i18n::t!("notifications.update.checking"));
-
-                                    match update_checker::check_for_updates() {
-                                        Ok(has_update) => {
-                                            if has_update {
-                                                info!("{}", rust_i18n::t!("notifications.update.available"));
-                                                update_checker::open_releases_page();
-                                            } else {
-                                                info!("{}", rust_i18n::t!("notifications.update.up_to_date"));
-                                            }
-                                        }
-                                        Err(e) => {
-                                            log::warn!("Failed to check for updates: {}", e);
-                                            log::warn!("{}", rust_i18n::t!("notifications.update.check_failed"));
-                                        }
-                                    }
-                                });
+                                let tx = cx.global::<AppState>().message_tx.clone();
+                                crate::update_checker::check_in_background(tx);
                             })),
                     ),
             )
@@ -0,0 +1,121 @@
+//! Launch at login via the per-user Run registry key
+
+use anyhow::Result;
+use std::path::Path;
+
+/// Registry key Windows reads at login for per-user startup programs
+#[cfg_attr(not(windows), allow(dead_code))]
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+/// Value name under the Run key
+#[cfg_attr(not(windows), allow(dead_code))]
+const VALUE_NAME: &str = "Sukusho";
+
+/// Encode a string as a null-terminated UTF-16 buffer
+#[cfg(windows)]
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Command line stored in the Run key, quoted so paths with spaces work
+#[cfg_attr(not(windows), allow(dead_code))]
+fn startup_command(exe: &Path) -> String {
+    format!("\"{}\"", exe.display())
+}
+
+/// Register or unregister the current executable to run at login
+#[cfg(windows)]
+pub fn set_autostart(enabled: bool) -> Result<()> {
+    use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+    use windows::Win32::System::Registry::{
+        RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegSetValueExW, HKEY, HKEY_CURRENT_USER,
+        KEY_SET_VALUE, REG_SZ,
+    };
+    use windows::core::PCWSTR;
+
+    let subkey = to_wide(RUN_KEY);
+    let value_name = to_wide(VALUE_NAME);
+
+    unsafe {
+        let mut hkey = HKEY::default();
+        RegOpenKeyExW(
+            HKEY_CURRENT_USER,
+            PCWSTR(subkey.as_ptr()),
+            0,
+            KEY_SET_VALUE,
+            &mut hkey,
+        )
+        .ok()?;
+
+        let result = if enabled {
+            let command = to_wide(&startup_command(&std::env::current_exe()?));
+            let data = std::slice::from_raw_parts(command.as_ptr() as *const u8, command.len() * 2);
+            RegSetValueExW(hkey, PCWSTR(value_name.as_ptr()), 0, REG_SZ, Some(data)).ok()
+        } else {
+            // Already absent counts as success
+            let status = RegDeleteValueW(hkey, PCWSTR(value_name.as_ptr()));
+            if status == ERROR_FILE_NOT_FOUND {
+                Ok(())
+            } else {
+                status.ok()
+            }
+        };
+        let _ = RegCloseKey(hkey);
+        result?;
+    }
+
+    log::info!(
+        "{} Sukusho to run at login",
+        if enabled { "Registered" } else { "Unregistered" }
+    );
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn set_autostart(_enabled: bool) -> Result<()> {
+    // Not implemented for non-Windows
+    Ok(())
+}
+
+/// Check whether a Run entry for Sukusho exists
+#[cfg(windows)]
+pub fn is_autostart_enabled() -> bool {
+    use windows::Win32::System::Registry::{HKEY_CURRENT_USER, RRF_RT_REG_SZ, RegGetValueW};
+    use windows::core::PCWSTR;
+
+    let subkey = to_wide(RUN_KEY);
+    let value_name = to_wide(VALUE_NAME);
+    let mut size = 0u32;
+
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(subkey.as_ptr()),
+            PCWSTR(value_name.as_ptr()),
+            RRF_RT_REG_SZ,
+            None,
+            None,
+            Some(&mut size),
+        )
+        .is_ok()
+    }
+}
+
+#[cfg(not(windows))]
+pub fn is_autostart_enabled() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_command_is_quoted() {
+        let exe = Path::new("C:\\Program Files\\Sukusho\\sukusho.exe");
+        assert_eq!(
+            startup_command(exe),
+            "\"C:\\Program Files\\Sukusho\\sukusho.exe\""
+        );
+    }
+}
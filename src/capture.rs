@@ -0,0 +1,861 @@
+//! Screen capture - region, full-screen and active-window captures saved as PNG or JPEG into
+//! the watched folder, copied to the clipboard, or both
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use image::buffer::ConvertBuffer;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{ColorType, ImageEncoder, RgbImage};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::settings::{CaptureFormat, CaptureTarget};
+
+/// What to capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureMode {
+    /// Drag-select a region
+    Region,
+    /// All monitors, or a single chosen monitor
+    Fullscreen,
+    /// The foreground window including its frame
+    ActiveWindow,
+}
+
+/// Which part of the desktop full-screen capture grabs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenTarget {
+    /// The whole virtual desktop
+    AllMonitors,
+    /// One monitor, by index in enumeration order
+    Monitor(usize),
+    /// The monitor the mouse cursor is on
+    UnderCursor,
+}
+
+/// A rectangle in virtual-desktop pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl CaptureRect {
+    /// Build a rectangle from two drag corners, in any order
+    pub fn from_corners(a: (i32, i32), b: (i32, i32)) -> Self {
+        Self {
+            x: a.0.min(b.0),
+            y: a.1.min(b.1),
+            width: (a.0 - b.0).abs(),
+            height: (a.1 - b.1).abs(),
+        }
+    }
+
+    /// Clamp to a `width` x `height` image at the origin
+    fn clamp_to(&self, width: i32, height: i32) -> Self {
+        let x = self.x.clamp(0, width);
+        let y = self.y.clamp(0, height);
+        Self {
+            x,
+            y,
+            width: (self.x + self.width).clamp(0, width) - x,
+            height: (self.y + self.height).clamp(0, height) - y,
+        }
+    }
+}
+
+/// Minimum selection size; smaller drags are treated as a cancelled click
+const MIN_SELECTION: i32 = 3;
+
+/// Crop a top-down BGRA buffer into an opaque RGBA image
+fn crop_bgra(
+    pixels: &[u8],
+    width: i32,
+    height: i32,
+    rect: CaptureRect,
+) -> Option<image::RgbaImage> {
+    let rect = rect.clamp_to(width, height);
+    if rect.width <= 0 || rect.height <= 0 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity((rect.width * rect.height * 4) as usize);
+    for row in rect.y..rect.y + rect.height {
+        let start = ((row * width + rect.x) * 4) as usize;
+        let end = start + (rect.width * 4) as usize;
+        for bgra in pixels[start..end].chunks_exact(4) {
+            // GDI leaves alpha undefined for screen bitmaps
+            out.extend_from_slice(&[bgra[2], bgra[1], bgra[0], 255]);
+        }
+    }
+    image::RgbaImage::from_raw(rect.width as u32, rect.height as u32, out)
+}
+
+/// Pick a free timestamped file name with `extension` in `directory`
+fn capture_path(directory: &Path, extension: &str) -> PathBuf {
+    let stem = format!("Screenshot {}", Local::now().format("%Y-%m-%d %H-%M-%S"));
+    let mut path = directory.join(format!("{}.{}", stem, extension));
+    let mut counter = 1;
+    while path.exists() {
+        path = directory.join(format!("{}_{}.{}", stem, counter, extension));
+        counter += 1;
+    }
+    path
+}
+
+/// The `image` crate's PNG encoder has three levels; `level` 0-9 picks the nearest
+fn png_compression_type(level: u8) -> CompressionType {
+    match level {
+        0..=2 => CompressionType::Fast,
+        3..=6 => CompressionType::Default,
+        _ => CompressionType::Best,
+    }
+}
+
+/// Save a captured image into `directory` in the configured format
+/// (the watcher picks it up from there)
+pub fn save_capture(image: &image::RgbaImage, directory: &Path) -> Result<PathBuf> {
+    let (format, png_compression, jpeg_quality) = crate::settings_store::read(|s| {
+        (s.capture_format, s.png_compression, s.jpeg_quality)
+    });
+    std::fs::create_dir_all(directory)?;
+    let path = capture_path(directory, format.extension());
+    let file = std::fs::File::create(&path)
+        .with_context(|| format!("Failed to create {:?}", path))?;
+    let mut writer = BufWriter::new(file);
+
+    let (width, height) = image.dimensions();
+    match format {
+        CaptureFormat::Png => PngEncoder::new_with_quality(
+            &mut writer,
+            png_compression_type(png_compression),
+            FilterType::Adaptive,
+        )
+        .write_image(image.as_raw(), width, height, ColorType::Rgba8)
+        .context("Failed to encode PNG capture")?,
+        CaptureFormat::Jpeg => {
+            // Captures are opaque, so dropping alpha loses nothing
+            let rgb: RgbImage = image.convert();
+            JpegEncoder::new_with_quality(&mut writer, jpeg_quality.clamp(1, 100))
+                .write_image(rgb.as_raw(), width, height, ColorType::Rgb8)
+                .context("Failed to encode JPEG capture")?
+        }
+    }
+    writer.flush().context("Failed to flush capture")?;
+
+    log::info!("Saved capture: {:?}", path);
+    Ok(path)
+}
+
+/// What a capture produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captured {
+    /// The saved file, unless the capture went to the clipboard only
+    pub path: Option<PathBuf>,
+    /// Whether the image is on the clipboard
+    pub copied: bool,
+}
+
+/// Save and/or copy a captured image, depending on `target`.
+/// Only a clipboard-only capture fails when copying fails.
+pub fn deliver(image: &image::RgbaImage, directory: &Path, target: CaptureTarget) -> Result<Captured> {
+    let path = match target {
+        CaptureTarget::File | CaptureTarget::Both => Some(save_capture(image, directory)?),
+        CaptureTarget::Clipboard => None,
+    };
+    let copied = match target {
+        CaptureTarget::File => false,
+        CaptureTarget::Clipboard | CaptureTarget::Both => {
+            crate::clipboard::copy_image_to_clipboard(image)
+        }
+    };
+    match (target, copied) {
+        (CaptureTarget::Clipboard, false) => {
+            anyhow::bail!("Failed to copy the capture to the clipboard")
+        }
+        (CaptureTarget::Both, false) => log::warn!("Capture saved but not copied to the clipboard"),
+        _ => {}
+    }
+    Ok(Captured { path, copied })
+}
+
+/// Let the user drag a region over a frozen image of all monitors, then save and/or copy it.
+/// Returns `Ok(None)` if the selection was cancelled (Esc, right click or a plain click).
+pub fn capture_region(directory: &Path, target: CaptureTarget) -> Result<Option<Captured>> {
+    match grab_region()? {
+        Some(image) => deliver(&image, directory, target).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Let the user drag a region over a frozen image of all monitors; `None` if cancelled
+#[cfg(windows)]
+pub fn grab_region() -> Result<Option<image::RgbaImage>> {
+    let screen = win::ScreenGrab::virtual_screen()?;
+    let Some(rect) = win::select_region(&screen)? else {
+        log::info!("Region capture cancelled");
+        return Ok(None);
+    };
+
+    crop_bgra(&screen.pixels, screen.width, screen.height, rect)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("Selected region is empty"))
+}
+
+#[cfg(not(windows))]
+pub fn grab_region() -> Result<Option<image::RgbaImage>> {
+    anyhow::bail!("Screen capture is only supported on Windows")
+}
+
+/// Capture all monitors or a single one, then save and/or copy it
+pub fn capture_fullscreen(
+    directory: &Path,
+    fullscreen: FullscreenTarget,
+    target: CaptureTarget,
+) -> Result<Captured> {
+    deliver(&grab_fullscreen(fullscreen)?, directory, target)
+}
+
+/// Grab all monitors or a single one
+pub fn grab_fullscreen(fullscreen: FullscreenTarget) -> Result<image::RgbaImage> {
+    match fullscreen {
+        FullscreenTarget::AllMonitors => grab_screen_rect(None),
+        FullscreenTarget::Monitor(index) => grab_monitor(index),
+        FullscreenTarget::UnderCursor => grab_monitor_under_cursor(),
+    }
+}
+
+/// Grab the monitor at `index` (enumeration order)
+#[cfg(windows)]
+pub fn grab_monitor(index: usize) -> Result<image::RgbaImage> {
+    let rect = *win::monitor_rects()
+        .get(index)
+        .ok_or_else(|| anyhow::anyhow!("Monitor {} not found", index + 1))?;
+    grab_screen_rect(Some(rect))
+}
+
+#[cfg(not(windows))]
+pub fn grab_monitor(_index: usize) -> Result<image::RgbaImage> {
+    anyhow::bail!("Screen capture is only supported on Windows")
+}
+
+/// Grab the monitor the mouse cursor is on
+#[cfg(windows)]
+pub fn grab_monitor_under_cursor() -> Result<image::RgbaImage> {
+    let rect = win::cursor_monitor_rect()?;
+    grab_screen_rect(Some(rect))
+}
+
+#[cfg(not(windows))]
+pub fn grab_monitor_under_cursor() -> Result<image::RgbaImage> {
+    anyhow::bail!("Screen capture is only supported on Windows")
+}
+
+/// Grab `rect` in virtual-desktop pixels (the whole desktop if `None`)
+#[cfg(windows)]
+fn grab_screen_rect(rect: Option<CaptureRect>) -> Result<image::RgbaImage> {
+    let rect = match rect {
+        Some(rect) => rect,
+        None => win::virtual_screen_rect()?,
+    };
+
+    let pixels = win::grab_screen_rect(rect)?;
+    let full = CaptureRect { x: 0, y: 0, ..rect };
+    crop_bgra(&pixels, rect.width, rect.height, full).ok_or_else(|| anyhow::anyhow!("Screen is empty"))
+}
+
+#[cfg(not(windows))]
+fn grab_screen_rect(_rect: Option<CaptureRect>) -> Result<image::RgbaImage> {
+    anyhow::bail!("Screen capture is only supported on Windows")
+}
+
+/// Capture the foreground window (never Sukusho itself) including its frame,
+/// then save and/or copy it
+pub fn capture_active_window(directory: &Path, target: CaptureTarget) -> Result<Captured> {
+    deliver(&grab_active_window()?, directory, target)
+}
+
+/// Grab the foreground window (never Sukusho itself) including its frame
+#[cfg(windows)]
+pub fn grab_active_window() -> Result<image::RgbaImage> {
+    let (width, height, pixels) = win::grab_foreground_window()?;
+    let full = CaptureRect {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    };
+    crop_bgra(&pixels, width, height, full).ok_or_else(|| anyhow::anyhow!("Window is empty"))
+}
+
+#[cfg(not(windows))]
+pub fn grab_active_window() -> Result<image::RgbaImage> {
+    anyhow::bail!("Screen capture is only supported on Windows")
+}
+
+/// Number of connected monitors
+#[cfg(windows)]
+pub fn monitor_count() -> usize {
+    win::monitor_rects().len()
+}
+
+#[cfg(not(windows))]
+pub fn monitor_count() -> usize {
+    1
+}
+
+/// Run a capture of the given kind, saving into `directory` and/or copying it per `target`.
+/// Returns `Ok(None)` if the user cancelled a region selection.
+pub fn capture(
+    mode: CaptureMode,
+    directory: &Path,
+    fullscreen: FullscreenTarget,
+    target: CaptureTarget,
+) -> Result<Option<Captured>> {
+    match mode {
+        CaptureMode::Region => capture_region(directory, target),
+        CaptureMode::Fullscreen => capture_fullscreen(directory, fullscreen, target).map(Some),
+        CaptureMode::ActiveWindow => capture_active_window(directory, target).map(Some),
+    }
+}
+
+#[cfg(windows)]
+mod win {
+    use super::{CaptureRect, MIN_SELECTION};
+    use anyhow::Result;
+    use windows::Win32::Foundation::{BOOL, COLORREF, HWND, LPARAM, LRESULT, RECT, TRUE, WPARAM};
+    use windows::Win32::Graphics::Gdi::*;
+    use windows::Win32::Storage::Xps::{PRINT_WINDOW_FLAGS, PrintWindow};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::HiDpi::{
+        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, SetThreadDpiAwarenessContext,
+    };
+    use windows::Win32::UI::Input::KeyboardAndMouse::{ReleaseCapture, SetCapture, VK_ESCAPE};
+    use windows::Win32::UI::WindowsAndMessaging::*;
+    use windows::core::w;
+
+    /// Registers the overlay window class on the first region capture
+    static OVERLAY_CLASS: std::sync::Once = std::sync::Once::new();
+
+    /// A full-resolution copy of the virtual desktop (all monitors)
+    pub struct ScreenGrab {
+        /// Virtual desktop origin; negative when a monitor sits left of/above the primary
+        pub left: i32,
+        pub top: i32,
+        pub width: i32,
+        pub height: i32,
+        /// Top-down BGRA pixels
+        pub pixels: Vec<u8>,
+    }
+
+    impl ScreenGrab {
+        /// Grab every monitor in physical pixels
+        pub fn virtual_screen() -> Result<Self> {
+            let rect = virtual_screen_rect()?;
+            let pixels = grab_screen_rect(rect)?;
+            Ok(Self {
+                left: rect.x,
+                top: rect.y,
+                width: rect.width,
+                height: rect.height,
+                pixels,
+            })
+        }
+    }
+
+    /// Use physical pixels on this thread regardless of per-monitor scaling
+    fn set_dpi_aware() {
+        unsafe {
+            let _ = SetThreadDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+        }
+    }
+
+    /// Bounds of the virtual desktop spanning all monitors
+    pub fn virtual_screen_rect() -> Result<CaptureRect> {
+        set_dpi_aware();
+        let rect = unsafe {
+            CaptureRect {
+                x: GetSystemMetrics(SM_XVIRTUALSCREEN),
+                y: GetSystemMetrics(SM_YVIRTUALSCREEN),
+                width: GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                height: GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            }
+        };
+        if rect.width <= 0 || rect.height <= 0 {
+            anyhow::bail!("Could not determine virtual screen size");
+        }
+        Ok(rect)
+    }
+
+    /// Bounds of each monitor in virtual-desktop coordinates, in enumeration order
+    pub fn monitor_rects() -> Vec<CaptureRect> {
+        unsafe extern "system" fn collect(
+            _monitor: HMONITOR,
+            _hdc: HDC,
+            rect: *mut RECT,
+            data: LPARAM,
+        ) -> BOOL {
+            unsafe {
+                let rects = &mut *(data.0 as *mut Vec<CaptureRect>);
+                let r = &*rect;
+                rects.push(CaptureRect {
+                    x: r.left,
+                    y: r.top,
+                    width: r.right - r.left,
+                    height: r.bottom - r.top,
+                });
+            }
+            TRUE
+        }
+
+        set_dpi_aware();
+        let mut rects: Vec<CaptureRect> = Vec::new();
+        unsafe {
+            let _ = EnumDisplayMonitors(
+                None,
+                None,
+                Some(collect),
+                LPARAM(&mut rects as *mut _ as isize),
+            );
+        }
+        rects
+    }
+
+    /// Bounds of the monitor under the cursor, in physical virtual-desktop pixels
+    pub fn cursor_monitor_rect() -> Result<CaptureRect> {
+        // The cursor position and monitor bounds must use the same (physical) coordinates
+        set_dpi_aware();
+        let (_, info) = crate::tray::cursor_monitor()
+            .ok_or_else(|| anyhow::anyhow!("Could not find the monitor under the cursor"))?;
+        let r = info.rcMonitor;
+        Ok(CaptureRect {
+            x: r.left,
+            y: r.top,
+            width: r.right - r.left,
+            height: r.bottom - r.top,
+        })
+    }
+
+    /// BitBlt a screen rectangle into memory and read it back as top-down BGRA
+    pub fn grab_screen_rect(rect: CaptureRect) -> Result<Vec<u8>> {
+        set_dpi_aware();
+        unsafe {
+            let screen_dc = GetDC(HWND::default());
+            let mem_dc = CreateCompatibleDC(screen_dc);
+            let bitmap = CreateCompatibleBitmap(screen_dc, rect.width, rect.height);
+            let old = SelectObject(mem_dc, bitmap);
+
+            // CAPTUREBLT includes layered (transparent) windows
+            let blit = BitBlt(
+                mem_dc,
+                0,
+                0,
+                rect.width,
+                rect.height,
+                screen_dc,
+                rect.x,
+                rect.y,
+                ROP_CODE(SRCCOPY.0 | CAPTUREBLT.0),
+            );
+            SelectObject(mem_dc, old);
+            let pixels = read_bitmap(mem_dc, bitmap, rect.width, rect.height);
+
+            let _ = DeleteObject(bitmap);
+            let _ = DeleteDC(mem_dc);
+            ReleaseDC(HWND::default(), screen_dc);
+
+            blit?;
+            pixels
+        }
+    }
+
+    /// Render a window, including its non-client frame, into top-down BGRA.
+    /// Our own windows (unless `Settings::exclude_own_windows` is off) and hidden or
+    /// minimized windows are skipped in Z-order.
+    pub fn grab_foreground_window() -> Result<(i32, i32, Vec<u8>)> {
+        set_dpi_aware();
+        unsafe {
+            let is_capturable = |hwnd: HWND| {
+                !crate::tray::skip_own_window(hwnd)
+                    && IsWindowVisible(hwnd).as_bool()
+                    && !IsIconic(hwnd).as_bool()
+            };
+
+            let mut hwnd = GetForegroundWindow();
+            while !hwnd.is_invalid() && !is_capturable(hwnd) {
+                hwnd = GetWindow(hwnd, GW_HWNDNEXT).unwrap_or_default();
+            }
+            if hwnd.is_invalid() {
+                anyhow::bail!("No window to capture");
+            }
+
+            let mut rect = RECT::default();
+            GetWindowRect(hwnd, &mut rect)?;
+            let width = rect.right - rect.left;
+            let height = rect.bottom - rect.top;
+            if width <= 0 || height <= 0 {
+                anyhow::bail!("Window has no visible area");
+            }
+
+            let screen_dc = GetDC(HWND::default());
+            let mem_dc = CreateCompatibleDC(screen_dc);
+            let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+            let old = SelectObject(mem_dc, bitmap);
+
+            // PW_RENDERFULLCONTENT captures DirectComposition/GPU-rendered content too
+            let printed =
+                PrintWindow(hwnd, mem_dc, PRINT_WINDOW_FLAGS(PW_RENDERFULLCONTENT)).as_bool();
+            SelectObject(mem_dc, old);
+            let pixels = read_bitmap(mem_dc, bitmap, width, height);
+
+            let _ = DeleteObject(bitmap);
+            let _ = DeleteDC(mem_dc);
+            ReleaseDC(HWND::default(), screen_dc);
+
+            if !printed {
+                anyhow::bail!("PrintWindow failed");
+            }
+            Ok((width, height, pixels?))
+        }
+    }
+
+    /// Read a (deselected) bitmap as top-down BGRA
+    unsafe fn read_bitmap(dc: HDC, bitmap: HBITMAP, width: i32, height: i32) -> Result<Vec<u8>> {
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        let mut info = bitmap_info(width, height);
+        let lines = unsafe {
+            GetDIBits(
+                dc,
+                bitmap,
+                0,
+                height as u32,
+                Some(pixels.as_mut_ptr() as *mut _),
+                &mut info,
+                DIB_RGB_COLORS,
+            )
+        };
+        if lines != height {
+            anyhow::bail!("GetDIBits failed");
+        }
+        Ok(pixels)
+    }
+
+    /// 32bpp top-down DIB header
+    pub fn bitmap_info(width: i32, height: i32) -> BITMAPINFO {
+        BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negative height = top-down rows
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Overlay state stored in GWLP_USERDATA
+    struct Overlay {
+        bright: HBITMAP,
+        dim: HBITMAP,
+        width: i32,
+        height: i32,
+        anchor: Option<(i32, i32)>,
+        cursor: (i32, i32),
+        result: Option<CaptureRect>,
+    }
+
+    /// Create a device bitmap from BGRA pixels
+    fn bitmap_from_pixels(pixels: &[u8], width: i32, height: i32) -> HBITMAP {
+        unsafe {
+            let screen_dc = GetDC(HWND::default());
+            let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+            let info = bitmap_info(width, height);
+            SetDIBits(
+                screen_dc,
+                bitmap,
+                0,
+                height as u32,
+                pixels.as_ptr() as *const _,
+                &info,
+                DIB_RGB_COLORS,
+            );
+            ReleaseDC(HWND::default(), screen_dc);
+            bitmap
+        }
+    }
+
+    /// Show the selection overlay and block until the user finishes or cancels
+    pub fn select_region(screen: &ScreenGrab) -> Result<Option<CaptureRect>> {
+        // Darkened copy shown outside the selection
+        let dimmed: Vec<u8> = screen
+            .pixels
+            .chunks_exact(4)
+            .flat_map(|p| [p[0] / 2, p[1] / 2, p[2] / 2, 255])
+            .collect();
+
+        unsafe {
+            let instance = GetModuleHandleW(None)?;
+            let class_name = w!("SukushoCaptureOverlay");
+            let cursor = LoadCursorW(None, IDC_CROSS)?;
+            OVERLAY_CLASS.call_once(|| {
+                let wc = WNDCLASSW {
+                    lpfnWndProc: Some(overlay_proc),
+                    hInstance: instance.into(),
+                    lpszClassName: class_name,
+                    hCursor: cursor,
+                    ..Default::default()
+                };
+                if RegisterClassW(&wc) == 0 {
+                    log::warn!(
+                        "Failed to register capture overlay window class: {}",
+                        windows::core::Error::from_win32()
+                    );
+                }
+            });
+
+            let overlay = Box::new(Overlay {
+                bright: bitmap_from_pixels(&screen.pixels, screen.width, screen.height),
+                dim: bitmap_from_pixels(&dimmed, screen.width, screen.height),
+                width: screen.width,
+                height: screen.height,
+                anchor: None,
+                cursor: (0, 0),
+                result: None,
+            });
+            let overlay_ptr = Box::into_raw(overlay);
+
+            let hwnd = match CreateWindowExW(
+                WS_EX_TOPMOST | WS_EX_TOOLWINDOW,
+                class_name,
+                w!("Sukusho Capture"),
+                WS_POPUP,
+                screen.left,
+                screen.top,
+                screen.width,
+                screen.height,
+                None,
+                None,
+                instance,
+                None,
+            ) {
+                Ok(hwnd) => hwnd,
+                Err(e) => {
+                    free_overlay(overlay_ptr);
+                    return Err(e.into());
+                }
+            };
+
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, overlay_ptr as isize);
+            let _ = ShowWindow(hwnd, SW_SHOW);
+            let _ = SetForegroundWindow(hwnd);
+
+            // Modal loop until the overlay destroys itself
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            let result = (*overlay_ptr).result;
+            free_overlay(overlay_ptr);
+            Ok(result)
+        }
+    }
+
+    unsafe fn free_overlay(ptr: *mut Overlay) {
+        unsafe {
+            let overlay = Box::from_raw(ptr);
+            let _ = DeleteObject(overlay.bright);
+            let _ = DeleteObject(overlay.dim);
+        }
+    }
+
+    /// Signed client coordinates from a mouse message
+    fn mouse_pos(lparam: LPARAM) -> (i32, i32) {
+        let x = (lparam.0 & 0xFFFF) as i16 as i32;
+        let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
+        (x, y)
+    }
+
+    unsafe extern "system" fn overlay_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        unsafe {
+            let overlay = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut Overlay;
+            if overlay.is_null() {
+                return DefWindowProcW(hwnd, msg, wparam, lparam);
+            }
+            let overlay = &mut *overlay;
+
+            match msg {
+                WM_LBUTTONDOWN => {
+                    overlay.anchor = Some(mouse_pos(lparam));
+                    overlay.cursor = mouse_pos(lparam);
+                    SetCapture(hwnd);
+                    LRESULT(0)
+                }
+                WM_MOUSEMOVE => {
+                    if overlay.anchor.is_some() {
+                        overlay.cursor = mouse_pos(lparam);
+                        let _ = InvalidateRect(hwnd, None, false);
+                    }
+                    LRESULT(0)
+                }
+                WM_LBUTTONUP => {
+                    let _ = ReleaseCapture();
+                    if let Some(anchor) = overlay.anchor.take() {
+                        let rect = CaptureRect::from_corners(anchor, mouse_pos(lparam));
+                        if rect.width >= MIN_SELECTION && rect.height >= MIN_SELECTION {
+                            overlay.result = Some(rect);
+                        }
+                    }
+                    let _ = DestroyWindow(hwnd);
+                    LRESULT(0)
+                }
+                WM_RBUTTONUP => {
+                    let _ = DestroyWindow(hwnd);
+                    LRESULT(0)
+                }
+                WM_KEYDOWN if wparam.0 == VK_ESCAPE.0 as usize => {
+                    let _ = DestroyWindow(hwnd);
+                    LRESULT(0)
+                }
+                WM_ERASEBKGND => LRESULT(1),
+                WM_PAINT => {
+                    let mut ps = PAINTSTRUCT::default();
+                    let hdc = BeginPaint(hwnd, &mut ps);
+
+                    // Compose off-screen to avoid flicker while dragging
+                    let buffer_dc = CreateCompatibleDC(hdc);
+                    let buffer = CreateCompatibleBitmap(hdc, overlay.width, overlay.height);
+                    let old_buffer = SelectObject(buffer_dc, buffer);
+                    let source_dc = CreateCompatibleDC(hdc);
+
+                    let old_source = SelectObject(source_dc, overlay.dim);
+                    let _ = BitBlt(
+                        buffer_dc,
+                        0,
+                        0,
+                        overlay.width,
+                        overlay.height,
+                        source_dc,
+                        0,
+                        0,
+                        SRCCOPY,
+                    );
+
+                    if let Some(anchor) = overlay.anchor {
+                        let rect = CaptureRect::from_corners(anchor, overlay.cursor);
+                        SelectObject(source_dc, overlay.bright);
+                        let _ = BitBlt(
+                            buffer_dc,
+                            rect.x,
+                            rect.y,
+                            rect.width,
+                            rect.height,
+                            source_dc,
+                            rect.x,
+                            rect.y,
+                            SRCCOPY,
+                        );
+
+                        let pen = CreatePen(PS_SOLID, 1, COLORREF(0x00FFFFFF));
+                        let old_pen = SelectObject(buffer_dc, pen);
+                        let old_brush = SelectObject(buffer_dc, GetStockObject(NULL_BRUSH));
+                        let _ = Rectangle(
+                            buffer_dc,
+                            rect.x,
+                            rect.y,
+                            rect.x + rect.width,
+                            rect.y + rect.height,
+                        );
+                        SelectObject(buffer_dc, old_brush);
+                        SelectObject(buffer_dc, old_pen);
+                        let _ = DeleteObject(pen);
+                    }
+                    SelectObject(source_dc, old_source);
+                    let _ = DeleteDC(source_dc);
+
+                    let _ = BitBlt(
+                        hdc,
+                        0,
+                        0,
+                        overlay.width,
+                        overlay.height,
+                        buffer_dc,
+                        0,
+                        0,
+                        SRCCOPY,
+                    );
+                    SelectObject(buffer_dc, old_buffer);
+                    let _ = DeleteObject(buffer);
+                    let _ = DeleteDC(buffer_dc);
+
+                    let _ = EndPaint(hwnd, &ps);
+                    LRESULT(0)
+                }
+                WM_DESTROY => {
+                    PostQuitMessage(0);
+                    LRESULT(0)
+                }
+                _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png_compression_type() {
+        assert_eq!(png_compression_type(0), CompressionType::Fast);
+        assert_eq!(png_compression_type(6), CompressionType::Default);
+        assert_eq!(png_compression_type(9), CompressionType::Best);
+    }
+
+    #[test]
+    fn test_rect_from_corners() {
+        let rect = CaptureRect::from_corners((50, 80), (10, 20));
+        assert_eq!(
+            rect,
+            CaptureRect {
+                x: 10,
+                y: 20,
+                width: 40,
+                height: 60
+            }
+        );
+    }
+
+    #[test]
+    fn test_crop_bgra() {
+        // 2x2 image: blue, green / red, white (BGRA)
+        let pixels = [
+            255, 0, 0, 0, 0, 255, 0, 0, //
+            0, 0, 255, 0, 255, 255, 255, 0,
+        ];
+        let rect = CaptureRect::from_corners((0, 1), (2, 2));
+        let image = crop_bgra(&pixels, 2, 2, rect).unwrap();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 255, 255, 255]);
+
+        // Out-of-bounds selections are clamped, empty ones rejected
+        let rect = CaptureRect::from_corners((1, 1), (10, 10));
+        assert_eq!(crop_bgra(&pixels, 2, 2, rect).unwrap().dimensions(), (1, 1));
+        let rect = CaptureRect::from_corners((5, 5), (10, 10));
+        assert!(crop_bgra(&pixels, 2, 2, rect).is_none());
+    }
+}
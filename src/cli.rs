@@ -0,0 +1,407 @@
+//! One-shot actions from the command line
+//!
+//! `sukusho --capture region`, `--copy-latest`, `--upload <path>` and `--check-updates` run
+//! the action and exit without a tray icon. If Sukusho is already running, the command is
+//! sent to it over a named pipe instead, so it runs in the resident instance. A plain second
+//! launch sends `--show`, bringing up the existing window. `--doctor` always runs here, so
+//! its report prints in the terminal.
+
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::Sender;
+use log::{error, info};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::capture::{self, CaptureMode};
+use crate::settings::Settings;
+use crate::AppMessage;
+
+/// Pipe the resident instance listens on for forwarded commands
+#[cfg(windows)]
+const PIPE_NAME: windows::core::PCWSTR = windows::core::w!(r"\\.\pipe\sukusho-command");
+
+/// An action requested on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Capture(CaptureMode),
+    CopyLatest,
+    Upload(PathBuf),
+    CheckUpdates,
+    /// Print a self-test report
+    Doctor,
+    /// Show the main window; a normal launch when nothing is running yet
+    Show,
+}
+
+impl Command {
+    /// The arguments that produce this command, as sent over the pipe
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn to_args(&self) -> Vec<String> {
+        match self {
+            Command::Capture(mode) => vec!["--capture".to_string(), mode_name(*mode).to_string()],
+            Command::CopyLatest => vec!["--copy-latest".to_string()],
+            Command::Upload(path) => vec!["--upload".to_string(), path.display().to_string()],
+            Command::CheckUpdates => vec!["--check-updates".to_string()],
+            Command::Doctor => vec!["--doctor".to_string()],
+            Command::Show => vec!["--show".to_string()],
+        }
+    }
+}
+
+fn mode_name(mode: CaptureMode) -> &'static str {
+    match mode {
+        CaptureMode::Region => "region",
+        CaptureMode::Fullscreen => "fullscreen",
+        CaptureMode::ActiveWindow => "window",
+    }
+}
+
+/// Find a command in the arguments (without the program name).
+/// Returns `Ok(None)` for a plain launch; options like `--console` are left alone.
+pub fn parse(args: &[String]) -> Result<Option<Command>> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let command = match arg.as_str() {
+            "--capture" => {
+                let mode = match args.next().map(String::as_str) {
+                    Some("region") => CaptureMode::Region,
+                    Some("fullscreen") => CaptureMode::Fullscreen,
+                    Some("window") => CaptureMode::ActiveWindow,
+                    Some(other) => bail!("Unknown capture mode '{}' (region, fullscreen or window)", other),
+                    None => bail!("--capture needs a mode (region, fullscreen or window)"),
+                };
+                Command::Capture(mode)
+            }
+            "--copy-latest" => Command::CopyLatest,
+            "--upload" => {
+                let path = args.next().context("--upload needs a file path")?;
+                Command::Upload(PathBuf::from(path))
+            }
+            "--check-updates" => Command::CheckUpdates,
+            "--doctor" => Command::Doctor,
+            "--show" => Command::Show,
+            _ => continue,
+        };
+        return Ok(Some(command));
+    }
+    Ok(None)
+}
+
+/// Run a command in this process, printing the result.
+/// `other_instance` is whether Sukusho is already running (only `--doctor` runs anyway).
+pub fn run(command: &Command, settings: &Settings, other_instance: bool) -> Result<()> {
+    // Only takes effect in a one-shot process; the app has its own live settings
+    crate::settings_store::init(Arc::new(Mutex::new(settings.clone())), None);
+    crate::thumbnail::set_decode_limits(
+        settings.max_decode_pixels,
+        std::time::Duration::from_millis(settings.thumbnail_open_timeout_ms),
+    );
+    match command {
+        Command::Capture(mode) => {
+            let captured = capture::capture(
+                *mode,
+                &settings.screenshot_directory,
+                settings.fullscreen_target(),
+                settings.default_capture_target,
+            )?;
+            match captured {
+                Some(captured) => match captured.path {
+                    Some(path) => println!("{}", path.display()),
+                    None => println!("{}", t!("notifications.capture.clipboard")),
+                },
+                None => println!("{}", t!("notifications.capture.cancelled")),
+            }
+        }
+        Command::CopyLatest => {
+            let path = newest_screenshot(&settings.screenshot_directory, &settings.watched_extensions)
+                .context("No screenshots found")?;
+            if !crate::clipboard::copy_screenshots(&[path.clone()], settings.copy_as_image) {
+                bail!("Failed to copy {} to the clipboard", path.display());
+            }
+            println!("{}", path.display());
+        }
+        Command::Upload(path) => {
+            let url = crate::upload::upload(path, settings)?;
+            if !crate::clipboard::copy_text_to_clipboard(&url) {
+                error!("Failed to copy upload URL to clipboard");
+            }
+            println!("{}", url);
+        }
+        Command::CheckUpdates => {
+            if crate::update_checker::check_for_updates_with_retry()? {
+                println!("{}", t!("notifications.update.available"));
+                crate::update_checker::open_releases_page();
+            } else {
+                println!("{}", t!("notifications.update.up_to_date"));
+            }
+        }
+        Command::Doctor => {
+            let origin = crate::doctor::Origin::CommandLine { other_instance };
+            let checks = crate::doctor::run_checks(settings, origin);
+            print!("{}", crate::doctor::format_report(&checks));
+            let failures = crate::doctor::failures(&checks);
+            if failures > 0 {
+                bail!("{} checks failed", failures);
+            }
+        }
+        // Starting the app normally is how this one runs locally
+        Command::Show => {}
+    }
+    Ok(())
+}
+
+/// Run a command forwarded to the resident instance
+#[cfg_attr(not(windows), allow(dead_code))]
+fn handle_forwarded(
+    command: Command,
+    settings: &Arc<Mutex<Settings>>,
+    message_tx: &Sender<AppMessage>,
+) {
+    info!("Running forwarded command: {:?}", command);
+    match command {
+        Command::Capture(mode) => {
+            let _ = message_tx.send(AppMessage::Capture(mode));
+        }
+        Command::CopyLatest => crate::tray::copy_latest(settings),
+        Command::Upload(path) => {
+            let _ = message_tx.send(AppMessage::Upload(path));
+        }
+        Command::CheckUpdates => crate::update_checker::check_in_background(message_tx.clone()),
+        // Not forwarded by `sukusho --doctor`, but a pipe client may still ask
+        Command::Doctor => {
+            let settings = settings.lock().clone();
+            std::thread::spawn(move || crate::doctor::run_from_tray(&settings));
+        }
+        Command::Show => {
+            crate::platform::current().show_window();
+            let _ = message_tx.send(AppMessage::ShowMainWindow);
+        }
+    }
+}
+
+/// Newest file with a watched extension in `directory` or its subfolders
+fn newest_screenshot(directory: &Path, extensions: &[String]) -> Option<PathBuf> {
+    fn walk(dir: &Path, extensions: &[String], newest: &mut Option<(std::time::SystemTime, PathBuf)>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(&path, extensions, newest);
+                continue;
+            }
+            let watched = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)));
+            let Some(modified) = watched
+                .then(|| entry.metadata().and_then(|m| m.modified()).ok())
+                .flatten()
+            else {
+                continue;
+            };
+            if newest.as_ref().is_none_or(|(at, _)| modified > *at) {
+                *newest = Some((modified, path));
+            }
+        }
+    }
+
+    let mut newest = None;
+    walk(directory, extensions, &mut newest);
+    newest.map(|(_, path)| path)
+}
+
+/// Show `println!` output in the terminal the command was run from
+#[cfg(windows)]
+pub fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // Not started from a terminal (e.g. a shortcut): there is nowhere to print
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(windows))]
+pub fn attach_parent_console() {}
+
+/// Send a command to the resident instance
+#[cfg(windows)]
+pub fn forward(command: &Command) -> Result<()> {
+    use windows::Win32::Foundation::{CloseHandle, GENERIC_WRITE, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, WriteFile, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_NONE, OPEN_EXISTING,
+    };
+
+    let message = command.to_args().join("\n");
+    unsafe {
+        let pipe = CreateFileW(
+            PIPE_NAME,
+            GENERIC_WRITE.0,
+            FILE_SHARE_NONE,
+            None,
+            OPEN_EXISTING,
+            FILE_ATTRIBUTE_NORMAL,
+            HANDLE::default(),
+        )
+        .context("Sukusho is running but not accepting commands")?;
+        let mut written = 0u32;
+        let result = WriteFile(pipe, Some(message.as_bytes()), Some(&mut written), None);
+        let _ = CloseHandle(pipe);
+        result.context("Failed to send the command to Sukusho")?;
+    }
+    info!("Forwarded {:?} to the running instance", command);
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn forward(_command: &Command) -> Result<()> {
+    bail!("Forwarding commands is only supported on Windows")
+}
+
+/// Accept commands from later `sukusho --...` invocations on a background thread
+#[cfg(windows)]
+pub fn serve(settings: Arc<Mutex<Settings>>, message_tx: Sender<AppMessage>) {
+    use windows::Win32::Foundation::{CloseHandle, ERROR_PIPE_CONNECTED};
+    use windows::Win32::Storage::FileSystem::{
+        ReadFile, FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_INBOUND,
+    };
+    use windows::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_READMODE_BYTE,
+        PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    std::thread::spawn(move || {
+        let mut first = true;
+        loop {
+            // Fail instead of joining a pipe another process created under our name
+            let open_mode = if first {
+                PIPE_ACCESS_INBOUND | FILE_FLAG_FIRST_PIPE_INSTANCE
+            } else {
+                PIPE_ACCESS_INBOUND
+            };
+            first = false;
+            let message = unsafe {
+                let pipe = CreateNamedPipeW(
+                    PIPE_NAME,
+                    open_mode,
+                    PIPE_TYPE_BYTE
+                        | PIPE_READMODE_BYTE
+                        | PIPE_WAIT
+                        | PIPE_REJECT_REMOTE_CLIENTS,
+                    PIPE_UNLIMITED_INSTANCES,
+                    0,
+                    4096,
+                    0,
+                    None,
+                );
+                if pipe.is_invalid() {
+                    error!(
+                        "Failed to create command pipe: {}",
+                        windows::core::Error::from_win32()
+                    );
+                    return;
+                }
+
+                // A client that connected before we started waiting is already connected
+                let connected = match ConnectNamedPipe(pipe, None) {
+                    Ok(()) => true,
+                    Err(e) => e.code() == ERROR_PIPE_CONNECTED.to_hresult(),
+                };
+                let mut message = Vec::new();
+                if connected {
+                    let mut buffer = [0u8; 1024];
+                    loop {
+                        let mut read = 0u32;
+                        if ReadFile(pipe, Some(&mut buffer), Some(&mut read), None).is_err()
+                            || read == 0
+                        {
+                            break;
+                        }
+                        message.extend_from_slice(&buffer[..read as usize]);
+                    }
+                }
+                let _ = DisconnectNamedPipe(pipe);
+                let _ = CloseHandle(pipe);
+                message
+            };
+
+            let args: Vec<String> = String::from_utf8_lossy(&message)
+                .lines()
+                .map(str::to_string)
+                .collect();
+            match parse(&args) {
+                Ok(Some(command)) => handle_forwarded(command, &settings, &message_tx),
+                Ok(None) => log::warn!("Ignoring empty forwarded command"),
+                Err(e) => log::warn!("Ignoring forwarded command: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn serve(_settings: Arc<Mutex<Settings>>, _message_tx: Sender<AppMessage>) {
+    // Not implemented for non-Windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(&args(&[])).unwrap(), None);
+        assert_eq!(parse(&args(&["--console"])).unwrap(), None);
+        assert_eq!(
+            parse(&args(&["--console", "--capture", "window"])).unwrap(),
+            Some(Command::Capture(CaptureMode::ActiveWindow))
+        );
+        assert_eq!(parse(&args(&["--copy-latest"])).unwrap(), Some(Command::CopyLatest));
+        assert_eq!(parse(&args(&["--doctor"])).unwrap(), Some(Command::Doctor));
+        assert_eq!(
+            parse(&args(&["--upload", r"C:\shots\a b.png"])).unwrap(),
+            Some(Command::Upload(PathBuf::from(r"C:\shots\a b.png")))
+        );
+        assert!(parse(&args(&["--capture"])).is_err());
+        assert!(parse(&args(&["--capture", "everything"])).is_err());
+        assert!(parse(&args(&["--upload"])).is_err());
+    }
+
+    #[test]
+    fn test_args_round_trip() {
+        for command in [
+            Command::Capture(CaptureMode::Region),
+            Command::Capture(CaptureMode::Fullscreen),
+            Command::CopyLatest,
+            Command::Upload(PathBuf::from("shot.png")),
+            Command::CheckUpdates,
+            Command::Doctor,
+            Command::Show,
+        ] {
+            assert_eq!(parse(&command.to_args()).unwrap(), Some(command));
+        }
+    }
+
+    #[test]
+    fn test_newest_screenshot() {
+        let dir = std::env::temp_dir().join(format!("sukusho-cli-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("2024-05")).unwrap();
+        let extensions = vec!["png".to_string()];
+        assert_eq!(newest_screenshot(&dir, &extensions), None);
+
+        std::fs::write(dir.join("old.png"), b"old").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(dir.join("2024-05").join("new.PNG"), b"new").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"newer").unwrap();
+        assert_eq!(
+            newest_screenshot(&dir, &extensions),
+            Some(dir.join("2024-05").join("new.PNG"))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
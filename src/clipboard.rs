@@ -1,21 +1,46 @@
 //! Clipboard operations for copying files
 //!
-//! Implements CF_HDROP format for copying file paths to clipboard.
+//! Implements CF_HDROP format for copying file paths to clipboard,
+//! CF_DIBV5 and "PNG" for copying image data, and CF_UNICODETEXT for copying text.
+//! Images can also be read back from CF_DIBV5, CF_DIB or "PNG", e.g. for the clipboard monitor.
 
 use log::{debug, error, info};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+#[cfg(windows)]
+use std::sync::atomic::{AtomicU32, Ordering};
 
 #[cfg(windows)]
 use windows::Win32::{
     Foundation::{BOOL, HANDLE, POINT},
     System::{
         DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
-        Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GHND},
-        Ole::CF_HDROP,
+        Memory::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GHND},
+        Ole::{CF_DIBV5, CF_HDROP, CF_UNICODETEXT},
     },
     UI::Shell::DROPFILES,
 };
 
+/// Clipboard sequence number after our own last write, so the monitor can skip it
+#[cfg(windows)]
+static OWN_SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// Remember the clipboard state we just wrote; call after `CloseClipboard`
+#[cfg(windows)]
+fn remember_own_change() {
+    use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
+
+    OWN_SEQUENCE.store(unsafe { GetClipboardSequenceNumber() }, Ordering::SeqCst);
+}
+
+/// Whether the clipboard still holds what we last put there ourselves
+#[cfg(windows)]
+pub fn is_own_change() -> bool {
+    use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
+
+    let current = unsafe { GetClipboardSequenceNumber() };
+    current == OWN_SEQUENCE.load(Ordering::SeqCst)
+}
+
 /// Copy files to clipboard using CF_HDROP format
 /// This allows pasting files in Explorer and other applications
 #[cfg(windows)]
@@ -64,6 +89,7 @@ pub fn copy_files_to_clipboard(files: &[PathBuf]) -> bool {
         }
 
         let _ = CloseClipboard();
+        remember_own_change();
         success
     }
 }
@@ -134,7 +160,518 @@ unsafe fn create_hdrop(files: &[PathBuf]) -> Option<HANDLE> {
     Some(HANDLE(hglobal.0))
 }
 
+/// Size of a BITMAPV5HEADER
+const DIBV5_HEADER_SIZE: usize = 124;
+
+/// Encode an image as a packed CF_DIBV5 (32-bit BGRA, bottom-up, with an alpha mask)
+fn dibv5_bytes(img: &image::RgbaImage) -> Vec<u8> {
+    const BI_BITFIELDS: u32 = 3;
+    const LCS_SRGB: u32 = 0x7352_4742;
+    const LCS_GM_IMAGES: u32 = 4;
+
+    let (width, height) = img.dimensions();
+    let pixel_bytes = width as usize * height as usize * 4;
+    let mut data = Vec::with_capacity(DIBV5_HEADER_SIZE + pixel_bytes);
+
+    data.extend_from_slice(&(DIBV5_HEADER_SIZE as u32).to_le_bytes());
+    data.extend_from_slice(&(width as i32).to_le_bytes());
+    data.extend_from_slice(&(height as i32).to_le_bytes()); // positive = bottom-up
+    data.extend_from_slice(&1u16.to_le_bytes()); // planes
+    data.extend_from_slice(&32u16.to_le_bytes()); // bit count
+    data.extend_from_slice(&BI_BITFIELDS.to_le_bytes());
+    data.extend_from_slice(&(pixel_bytes as u32).to_le_bytes());
+    data.extend_from_slice(&[0; 16]); // pels per meter, colors used/important
+    for mask in [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+        data.extend_from_slice(&mask.to_le_bytes());
+    }
+    data.extend_from_slice(&LCS_SRGB.to_le_bytes());
+    data.extend_from_slice(&[0; 36 + 12]); // endpoints, gamma
+    data.extend_from_slice(&LCS_GM_IMAGES.to_le_bytes());
+    data.extend_from_slice(&[0; 12]); // profile data/size, reserved
+    debug_assert_eq!(data.len(), DIBV5_HEADER_SIZE);
+
+    for row in img.rows().rev() {
+        for pixel in row {
+            let [r, g, b, a] = pixel.0;
+            data.extend_from_slice(&[b, g, r, a]);
+        }
+    }
+    data
+}
+
+/// Copy global memory holding `bytes` for use as clipboard data
+#[cfg(windows)]
+unsafe fn global_from_bytes(bytes: &[u8]) -> Option<HANDLE> {
+    unsafe {
+        let hglobal = GlobalAlloc(GHND, bytes.len()).ok()?;
+        let ptr = GlobalLock(hglobal) as *mut u8;
+        if ptr.is_null() {
+            let _ = GlobalFree(hglobal);
+            return None;
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+        let _ = GlobalUnlock(hglobal);
+        Some(HANDLE(hglobal.0))
+    }
+}
+
+/// Copy a screenshot as a file (CF_HDROP) and as an image (CF_DIBV5 and "PNG")
+/// in one clipboard transaction, so each target app can paste the form it prefers
+///
+/// A large image may be downscaled (see `large_image`); the file stays as it is. Decoding can
+/// take a while, so call this off the UI thread.
+#[cfg(windows)]
+pub fn copy_screenshot_everything(path: &Path) -> bool {
+    let img = match crate::thumbnail::open_limited(path) {
+        Ok(img) => img.to_rgba8(),
+        Err(e) => {
+            error!("Failed to decode {:?} for clipboard: {}", path, e);
+            return false;
+        }
+    };
+    let bytes = std::fs::metadata(path).ok().map(|m| m.len());
+    let img = crate::large_image::check_image(&img, bytes).unwrap_or(img);
+
+    let formats = set_image_formats(&img, Some(path));
+    if formats == 0 {
+        error!("Failed to set any clipboard format for {:?}", path);
+        return false;
+    }
+    info!("Copied {:?} to clipboard in {} formats", path, formats);
+    true
+}
+
+/// Copy an image that isn't saved anywhere (CF_DIBV5 and "PNG")
+#[cfg(windows)]
+pub fn copy_image_to_clipboard(img: &image::RgbaImage) -> bool {
+    let resized = crate::large_image::check_image(img, None);
+    let img = resized.as_ref().unwrap_or(img);
+    let formats = set_image_formats(img, None);
+    if formats == 0 {
+        error!("Failed to set any clipboard format for {}x{} image", img.width(), img.height());
+        return false;
+    }
+    info!("Copied {}x{} image to clipboard in {} formats", img.width(), img.height(), formats);
+    true
+}
+
+#[cfg(not(windows))]
+pub fn copy_image_to_clipboard(_img: &image::RgbaImage) -> bool {
+    false
+}
+
+/// Replace the clipboard with `img` as CF_DIBV5 and "PNG", plus `file` as CF_HDROP if given.
+/// Returns the number of formats set.
+#[cfg(windows)]
+fn set_image_formats(img: &image::RgbaImage, file: Option<&Path>) -> usize {
+    use image::ImageEncoder;
+    use windows::core::w;
+    use windows::Win32::System::DataExchange::RegisterClipboardFormatW;
+
+    let dib = dibv5_bytes(img);
+    let mut png = Vec::new();
+    if let Err(e) = image::codecs::png::PngEncoder::new(&mut png).write_image(
+        img.as_raw(),
+        img.width(),
+        img.height(),
+        image::ColorType::Rgba8,
+    ) {
+        error!("Failed to encode PNG for clipboard: {}", e);
+        return 0;
+    }
+
+    unsafe {
+        let png_format = RegisterClipboardFormatW(w!("PNG"));
+
+        if OpenClipboard(None).is_err() {
+            error!("Failed to open clipboard");
+            return 0;
+        }
+
+        if EmptyClipboard().is_err() {
+            error!("Failed to empty clipboard");
+            let _ = CloseClipboard();
+            return 0;
+        }
+
+        // The clipboard owns each handle once SetClipboardData succeeds
+        let mut formats = 0;
+        if let Some(hdrop) = file.and_then(|file| create_hdrop(&[file.to_path_buf()])) {
+            if SetClipboardData(CF_HDROP.0 as u32, hdrop).is_ok() {
+                formats += 1;
+            }
+        }
+        if let Some(handle) = global_from_bytes(&dib) {
+            if SetClipboardData(CF_DIBV5.0 as u32, handle).is_ok() {
+                formats += 1;
+            }
+        }
+        if png_format != 0 {
+            if let Some(handle) = global_from_bytes(&png) {
+                if SetClipboardData(png_format, handle).is_ok() {
+                    formats += 1;
+                }
+            }
+        }
+
+        let _ = CloseClipboard();
+        remember_own_change();
+        formats
+    }
+}
+
+#[cfg(not(windows))]
+pub fn copy_screenshot_everything(_path: &Path) -> bool {
+    false
+}
+
+/// Copy screenshots as files; with `as_image`, a single screenshot goes on the clipboard as an
+/// image too (see `copy_screenshot_everything`)
+pub fn copy_screenshots(files: &[PathBuf], as_image: bool) -> bool {
+    (as_image && files.len() == 1 && copy_screenshot_everything(&files[0]))
+        || crate::platform::current().copy_files(files)
+}
+
+/// Copy text to clipboard using CF_UNICODETEXT format
+#[cfg(windows)]
+pub fn copy_text_to_clipboard(text: &str) -> bool {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        if OpenClipboard(None).is_err() {
+            error!("Failed to open clipboard");
+            return false;
+        }
+
+        if EmptyClipboard().is_err() {
+            error!("Failed to empty clipboard");
+            let _ = CloseClipboard();
+            return false;
+        }
+
+        let Ok(hglobal) = GlobalAlloc(GHND, wide.len() * 2) else {
+            error!("Failed to allocate clipboard text");
+            let _ = CloseClipboard();
+            return false;
+        };
+        let ptr = GlobalLock(hglobal) as *mut u16;
+        if ptr.is_null() {
+            error!("Failed to lock clipboard text");
+            let _ = CloseClipboard();
+            return false;
+        }
+        std::ptr::copy_nonoverlapping(wide.as_ptr(), ptr, wide.len());
+        let _ = GlobalUnlock(hglobal);
+
+        let result = SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(hglobal.0));
+        let success = result.is_ok();
+
+        if success {
+            info!("Copied {} characters to clipboard", text.chars().count());
+        } else {
+            error!("Failed to set clipboard text: {:?}", result);
+        }
+
+        let _ = CloseClipboard();
+        remember_own_change();
+        success
+    }
+}
+
 #[cfg(not(windows))]
-pub fn copy_files_to_clipboard(_files: &[PathBuf]) -> bool {
+pub fn copy_text_to_clipboard(_text: &str) -> bool {
     false
 }
+
+/// Copy a screenshot as a `data:<mime>;base64,...` URI (CF_UNICODETEXT) for HTML or Markdown.
+/// Files over `max_bytes` are refused, since the URI is a third larger than the file.
+pub fn copy_as_data_uri(path: &Path, max_bytes: u64) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let mime = image_mime(path)
+        .ok_or_else(|| anyhow::anyhow!("{:?} is not an image type browsers can show", path))?;
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read {:?}", path))?
+        .len();
+    if size > max_bytes {
+        log::warn!(
+            "Refusing to copy {:?} as a data URI: {} bytes is over the {} byte limit",
+            path,
+            size,
+            max_bytes
+        );
+        anyhow::bail!("File is too large for a data URI ({} KB)", size / 1024);
+    }
+
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let uri = data_uri(mime, &bytes);
+    if !copy_text_to_clipboard(&uri) {
+        anyhow::bail!("Failed to copy the data URI to the clipboard");
+    }
+    info!("Copied {:?} as a {} character data URI", path, uri.len());
+    Ok(())
+}
+
+/// MIME type of an image file, from its extension
+fn image_mime(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        _ => return None,
+    })
+}
+
+/// `data:<mime>;base64,<bytes>`
+fn data_uri(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", mime, base64_encode(bytes))
+}
+
+/// Standard base64 with padding
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (u32::from(chunk[0]) << 16)
+            | (u32::from(chunk.get(1).copied().unwrap_or(0)) << 8)
+            | u32::from(chunk.get(2).copied().unwrap_or(0));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Image currently on the clipboard, or `None` if there isn't one (or it can't be decoded).
+///
+/// Formats are tried in the order CF_DIBV5, CF_DIB, "PNG"; Windows converts between the two
+/// DIB formats itself, so "PNG" is only used when an app put nothing else on the clipboard.
+#[cfg(windows)]
+pub fn paste_image_from_clipboard() -> Option<image::DynamicImage> {
+    use windows::core::w;
+    use windows::Win32::System::DataExchange::{
+        GetClipboardData, IsClipboardFormatAvailable, RegisterClipboardFormatW,
+    };
+    use windows::Win32::System::Ole::CF_DIB;
+
+    unsafe {
+        let png_format = RegisterClipboardFormatW(w!("PNG"));
+
+        // The app that just wrote the clipboard may still hold it open
+        let mut opened = false;
+        for _ in 0..5 {
+            if OpenClipboard(None).is_ok() {
+                opened = true;
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        if !opened {
+            error!("Failed to open clipboard");
+            return None;
+        }
+
+        let read = |format: u32| {
+            if IsClipboardFormatAvailable(format).is_err() {
+                return None;
+            }
+            GetClipboardData(format).ok().and_then(|handle| global_to_bytes(handle))
+        };
+        let data = match read(CF_DIBV5.0 as u32).or_else(|| read(CF_DIB.0 as u32)) {
+            Some(dib) => Some(ClipboardImage::Dib(dib)),
+            None if png_format != 0 => read(png_format).map(ClipboardImage::Png),
+            None => None,
+        };
+        let _ = CloseClipboard();
+
+        let Some(data) = data else {
+            debug!("No image on the clipboard");
+            return None;
+        };
+        match data.decode() {
+            Ok(img) => Some(img),
+            Err(e) => {
+                error!("Failed to decode clipboard image: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn paste_image_from_clipboard() -> Option<image::DynamicImage> {
+    None
+}
+
+/// Raw image data read from the clipboard
+#[cfg_attr(not(windows), allow(dead_code))]
+enum ClipboardImage {
+    /// Packed DIB (CF_DIB or CF_DIBV5)
+    Dib(Vec<u8>),
+    /// "PNG" registered format
+    Png(Vec<u8>),
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl ClipboardImage {
+    fn decode(&self) -> anyhow::Result<image::DynamicImage> {
+        Ok(match self {
+            ClipboardImage::Dib(dib) => {
+                let bmp = dib_to_bmp(dib).ok_or_else(|| anyhow::anyhow!("Malformed DIB header"))?;
+                image::load_from_memory_with_format(&bmp, image::ImageFormat::Bmp)?
+            }
+            ClipboardImage::Png(png) => image::load_from_memory_with_format(png, image::ImageFormat::Png)?,
+        })
+    }
+}
+
+/// Copy the contents of clipboard global memory
+#[cfg(windows)]
+unsafe fn global_to_bytes(handle: HANDLE) -> Option<Vec<u8>> {
+    use windows::Win32::Foundation::HGLOBAL;
+    use windows::Win32::System::Memory::GlobalSize;
+
+    unsafe {
+        let hglobal = HGLOBAL(handle.0);
+        let size = GlobalSize(hglobal);
+        let ptr = GlobalLock(hglobal) as *const u8;
+        if ptr.is_null() || size == 0 {
+            return None;
+        }
+        let bytes = std::slice::from_raw_parts(ptr, size).to_vec();
+        let _ = GlobalUnlock(hglobal);
+        Some(bytes)
+    }
+}
+
+/// Prefix a packed DIB with a BITMAPFILEHEADER so it can be decoded as a .bmp
+#[cfg_attr(not(windows), allow(dead_code))]
+fn dib_to_bmp(dib: &[u8]) -> Option<Vec<u8>> {
+    const FILE_HEADER_SIZE: usize = 14;
+    const BITMAPINFOHEADER_SIZE: usize = 40;
+    const BI_BITFIELDS: u32 = 3;
+    const BI_ALPHABITFIELDS: u32 = 6;
+
+    let u16_at = |at: usize| Some(u16::from_le_bytes(dib.get(at..at + 2)?.try_into().ok()?));
+    let u32_at = |at: usize| Some(u32::from_le_bytes(dib.get(at..at + 4)?.try_into().ok()?));
+
+    let header_size = u32_at(0)? as usize;
+    let bit_count = u16_at(14)?;
+    let compression = u32_at(16)?;
+    let colors_used = u32_at(32)? as usize;
+
+    // A plain BITMAPINFOHEADER is followed by its channel masks; larger headers hold them
+    let masks = match compression {
+        BI_BITFIELDS if header_size == BITMAPINFOHEADER_SIZE => 12,
+        BI_ALPHABITFIELDS if header_size == BITMAPINFOHEADER_SIZE => 16,
+        _ => 0,
+    };
+    let palette = match colors_used {
+        0 if bit_count <= 8 => 1usize << bit_count,
+        n => n,
+    } * 4;
+    let offset = FILE_HEADER_SIZE + header_size + masks + palette;
+    if offset > FILE_HEADER_SIZE + dib.len() {
+        return None;
+    }
+
+    let mut bmp = Vec::with_capacity(FILE_HEADER_SIZE + dib.len());
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&((FILE_HEADER_SIZE + dib.len()) as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]); // reserved
+    bmp.extend_from_slice(&(offset as u32).to_le_bytes());
+    bmp.extend_from_slice(dib);
+    Some(bmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dibv5_bytes() {
+        // 1x2: red on top, blue at the bottom
+        let img = image::RgbaImage::from_raw(1, 2, vec![255, 0, 0, 255, 0, 0, 255, 128]).unwrap();
+        let data = dibv5_bytes(&img);
+
+        assert_eq!(data.len(), DIBV5_HEADER_SIZE + 8);
+        assert_eq!(&data[0..4], &124u32.to_le_bytes());
+        assert_eq!(&data[8..12], &2i32.to_le_bytes());
+        // Bottom-up BGRA: the blue pixel comes first
+        assert_eq!(&data[DIBV5_HEADER_SIZE..], &[255, 0, 0, 128, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xff, 0xfe, 0xfd]), "//79");
+    }
+
+    #[test]
+    fn test_data_uri() {
+        assert_eq!(image_mime(Path::new("shot.PNG")), Some("image/png"));
+        assert_eq!(image_mime(Path::new("shot.jpeg")), Some("image/jpeg"));
+        assert_eq!(image_mime(Path::new("shot.heic")), None);
+        assert_eq!(image_mime(Path::new("shot")), None);
+
+        assert_eq!(data_uri("image/png", b"foo"), "data:image/png;base64,Zm9v");
+    }
+
+    #[test]
+    fn test_copy_as_data_uri_size_limit() {
+        let dir = std::env::temp_dir().join(format!("sukusho-data-uri-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("big.png");
+        std::fs::write(&path, vec![0u8; 2048]).unwrap();
+
+        let error = copy_as_data_uri(&path, 1024).unwrap_err();
+        assert!(error.to_string().contains("too large"));
+        assert!(copy_as_data_uri(&dir.join("shot.txt"), 1024).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dib_to_bmp_roundtrip() {
+        let img = image::RgbaImage::from_raw(2, 1, vec![255, 0, 0, 255, 0, 128, 255, 255]).unwrap();
+        let bmp = dib_to_bmp(&dibv5_bytes(&img)).unwrap();
+
+        assert_eq!(&bmp[0..2], b"BM");
+        assert_eq!(&bmp[10..14], &(14u32 + DIBV5_HEADER_SIZE as u32).to_le_bytes());
+        let decoded = image::load_from_memory_with_format(&bmp, image::ImageFormat::Bmp)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded, img);
+
+        assert!(dib_to_bmp(&[0; 8]).is_none());
+    }
+
+    #[test]
+    fn test_clipboard_image_decode() {
+        let img = image::RgbaImage::from_raw(1, 1, vec![10, 20, 30, 255]).unwrap();
+        let decoded = ClipboardImage::Dib(dibv5_bytes(&img)).decode().unwrap();
+        assert_eq!(decoded.to_rgba8(), img);
+
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(img.clone())
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        assert_eq!(ClipboardImage::Png(png).decode().unwrap().to_rgba8(), img);
+
+        assert!(ClipboardImage::Dib(vec![0; 8]).decode().is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Save images copied to the clipboard as screenshots
+//!
+//! The session window listens for `WM_CLIPBOARDUPDATE`; new images are saved as PNG
+//! into the screenshot folder, where the watcher picks them up like any other capture.
+
+#[cfg(windows)]
+use log::{debug, error, info};
+use parking_lot::Mutex;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+#[cfg(windows)]
+use std::sync::Arc;
+
+#[cfg(windows)]
+use crate::settings::Settings;
+
+/// Number of clipboard image hashes kept
+const MAX_SEEN: usize = 256;
+
+/// Recently saved clipboard images, oldest first
+static SEEN: Mutex<VecDeque<u64>> = Mutex::new(VecDeque::new());
+
+/// Start receiving `WM_CLIPBOARDUPDATE` on `hwnd`
+#[cfg(windows)]
+pub fn register(hwnd: windows::Win32::Foundation::HWND) {
+    use windows::Win32::System::DataExchange::AddClipboardFormatListener;
+
+    match unsafe { AddClipboardFormatListener(hwnd) } {
+        Ok(()) => info!("Listening for clipboard changes"),
+        Err(e) => log::warn!("Failed to listen for clipboard changes: {}", e),
+    }
+}
+
+/// Handle `WM_CLIPBOARDUPDATE`; decoding and saving happen on a background thread
+#[cfg(windows)]
+pub fn on_clipboard_update(settings: &Arc<Mutex<Settings>>) {
+    let directory = {
+        let settings = settings.lock();
+        if !settings.capture_clipboard_images {
+            return;
+        }
+        settings.screenshot_directory.clone()
+    };
+    if crate::clipboard::is_own_change() {
+        debug!("Ignoring our own clipboard change");
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let Some(image) = crate::clipboard::paste_image_from_clipboard().map(|img| img.to_rgba8()) else {
+            return;
+        };
+        if !remember(image_hash(&image)) {
+            debug!("Clipboard image was already saved");
+            return;
+        }
+        match crate::capture::save_capture(&image, &directory) {
+            Ok(path) => info!("Saved clipboard image: {:?}", path),
+            Err(e) => error!("Failed to save clipboard image: {}", e),
+        }
+    });
+}
+
+/// Hash of an image's size and pixels
+#[cfg_attr(not(windows), allow(dead_code))]
+fn image_hash(image: &image::RgbaImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    image.as_raw().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Record `hash`, returning false if it was already seen
+#[cfg_attr(not(windows), allow(dead_code))]
+fn remember(hash: u64) -> bool {
+    let mut seen = SEEN.lock();
+    if seen.contains(&hash) {
+        return false;
+    }
+    if seen.len() == MAX_SEEN {
+        seen.pop_front();
+    }
+    seen.push_back(hash);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_image_hash() {
+        let a = RgbaImage::from_pixel(4, 2, Rgba([1, 2, 3, 255]));
+        let b = RgbaImage::from_pixel(2, 4, Rgba([1, 2, 3, 255]));
+        let mut c = a.clone();
+        c.put_pixel(3, 1, Rgba([1, 2, 4, 255]));
+
+        assert_eq!(image_hash(&a), image_hash(&a.clone()));
+        assert_ne!(image_hash(&a), image_hash(&b));
+        assert_ne!(image_hash(&a), image_hash(&c));
+    }
+
+    #[test]
+    fn test_remember() {
+        let hash = 0x5eed_c11b;
+        assert!(remember(hash));
+        assert!(!remember(hash));
+    }
+}
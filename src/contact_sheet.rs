@@ -0,0 +1,139 @@
+//! Contact sheets: several screenshots laid out as a grid in one image, for sharing a batch
+//!
+//! Cells are square; each thumbnail is fitted and centered in its cell, with thin separator
+//! lines between cells. The last row is left partly empty when the count doesn't divide evenly.
+
+use anyhow::Result;
+use chrono::Local;
+use image::{Rgba, RgbaImage};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+
+use crate::thumbnail::ThumbnailCache;
+
+/// Width of the lines between and around cells
+const SEPARATOR: u32 = 1;
+
+/// Space between a thumbnail and its cell's edges
+const PADDING: u32 = 8;
+
+const BACKGROUND: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const SEPARATOR_COLOR: Rgba<u8> = Rgba([208, 208, 208, 255]);
+
+/// Grid size for `count` images: (columns, rows). Fewer images than columns use fewer columns.
+fn grid(count: usize, columns: usize) -> (u32, u32) {
+    let columns = columns.clamp(1, count.max(1));
+    (columns as u32, count.div_ceil(columns) as u32)
+}
+
+/// Top-left corner of the inside of cell `index`
+fn cell_origin(index: usize, columns: u32, cell_size: u32) -> (u32, u32) {
+    let (column, row) = (index as u32 % columns, index as u32 / columns);
+    (
+        SEPARATOR + column * (cell_size + SEPARATOR),
+        SEPARATOR + row * (cell_size + SEPARATOR),
+    )
+}
+
+/// Lay the thumbnails out in a grid of `cell_size` squares
+pub fn compose(thumbnails: &[RgbaImage], columns: usize, cell_size: u32) -> RgbaImage {
+    let (columns, rows) = grid(thumbnails.len(), columns);
+    let width = columns * (cell_size + SEPARATOR) + SEPARATOR;
+    let height = rows * (cell_size + SEPARATOR) + SEPARATOR;
+
+    let mut sheet = RgbaImage::from_pixel(width, height, SEPARATOR_COLOR);
+    for index in 0..(columns * rows) as usize {
+        let (x, y) = cell_origin(index, columns, cell_size);
+        for dy in 0..cell_size {
+            for dx in 0..cell_size {
+                sheet.put_pixel(x + dx, y + dy, BACKGROUND);
+            }
+        }
+    }
+
+    for (index, thumbnail) in thumbnails.iter().enumerate() {
+        let (x, y) = cell_origin(index, columns, cell_size);
+        // Thumbnails fit the padded cell; anything larger is clipped to the cell
+        let left = x + cell_size.saturating_sub(thumbnail.width()) / 2;
+        let top = y + cell_size.saturating_sub(thumbnail.height()) / 2;
+        for (tx, ty, pixel) in thumbnail.enumerate_pixels() {
+            if tx < cell_size && ty < cell_size {
+                let mut background = *sheet.get_pixel(left + tx, top + ty);
+                image::Pixel::blend(&mut background, pixel);
+                sheet.put_pixel(left + tx, top + ty, background);
+            }
+        }
+    }
+    sheet
+}
+
+/// Save a contact sheet of `paths` as a PNG in `directory`; unreadable images are left out
+pub fn export_contact_sheet(
+    paths: &[PathBuf],
+    directory: &Path,
+    columns: usize,
+    cell_size: u32,
+) -> Result<PathBuf> {
+    let cache = ThumbnailCache::new(paths.len().max(1));
+    let fit = cell_size.saturating_sub(2 * PADDING).max(1);
+    let thumbnails: Vec<RgbaImage> = paths
+        .iter()
+        .filter_map(|path| match cache.get_or_create(path, fit) {
+            Ok(thumbnail) => Some((*thumbnail).clone()),
+            Err(e) => {
+                warn!("Leaving {:?} out of the contact sheet: {}", path, e);
+                None
+            }
+        })
+        .collect();
+    if thumbnails.is_empty() {
+        anyhow::bail!("None of the screenshots could be read");
+    }
+
+    let sheet = compose(&thumbnails, columns, cell_size);
+    std::fs::create_dir_all(directory)?;
+    let path = sheet_path(directory);
+    sheet.save_with_format(&path, image::ImageFormat::Png)?;
+    info!("Saved contact sheet of {} screenshots: {:?}", thumbnails.len(), path);
+    Ok(path)
+}
+
+/// `Contact Sheet <date time>.png` in `directory`, numbered if taken
+fn sheet_path(directory: &Path) -> PathBuf {
+    let stem = format!("Contact Sheet {}", Local::now().format("%Y-%m-%d %H-%M-%S"));
+    let mut path = directory.join(format!("{}.png", stem));
+    let mut counter = 1;
+    while path.exists() {
+        path = directory.join(format!("{}_{}.png", stem, counter));
+        counter += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        assert_eq!(grid(7, 3), (3, 3));
+        assert_eq!(grid(6, 3), (3, 2));
+        assert_eq!(grid(2, 4), (2, 1));
+        assert_eq!(grid(5, 0), (1, 5));
+        assert_eq!(cell_origin(4, 3, 10), (12, 12));
+    }
+
+    #[test]
+    fn test_compose_partial_row() {
+        let red = RgbaImage::from_pixel(10, 10, Rgba([255, 0, 0, 255]));
+        let sheet = compose(&[red.clone(), red.clone(), red], 2, 10);
+        assert_eq!(sheet.dimensions(), (23, 23));
+
+        // Separators around cells, thumbnails inside, and the missing fourth cell is blank
+        assert_eq!(*sheet.get_pixel(0, 0), SEPARATOR_COLOR);
+        assert_eq!(*sheet.get_pixel(11, 5), SEPARATOR_COLOR);
+        assert_eq!(*sheet.get_pixel(5, 5), Rgba([255, 0, 0, 255]));
+        assert_eq!(*sheet.get_pixel(5, 17), Rgba([255, 0, 0, 255]));
+        assert_eq!(*sheet.get_pixel(17, 17), BACKGROUND);
+    }
+}
@@ -2,7 +2,9 @@
 
 use anyhow::{Context, Result};
 use filetime::{set_file_mtime, FileTime};
+use image::codecs::avif::AvifEncoder;
 use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
 use image::codecs::webp::WebPEncoder;
 use image::io::Reader as ImageReader;
 use log::{error, info};
@@ -10,31 +12,33 @@ use std::fs;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 
-use crate::settings::ConversionFormat;
+use crate::settings::{ConversionFormat, Settings};
+
+/// Source formats we re-encode (lossless captures that tend to be large)
+const CONVERTIBLE_EXTENSIONS: &[&str] = &["png", "bmp"];
+
+/// AVIF encoder speed (1-10); faster settings cost little size on screenshots
+const AVIF_SPEED: u8 = 8;
 
 /// Convert an image to the specified format
 ///
 /// Returns the path to the new file if successful.
-/// The original file is deleted after successful conversion.
+/// The original file is deleted after successful conversion unless `keep_original` is set.
 /// Preserves the original file's modification timestamp.
+/// AVIF encoding can take several seconds, so never call this on the UI thread.
 pub fn convert_image(
     source_path: &Path,
     format: ConversionFormat,
     quality: u32,
+    keep_original: bool,
 ) -> Result<PathBuf> {
     info!(
         "Converting to {:?}: {:?} (quality: {})",
         format, source_path, quality
     );
 
-    // Only convert PNG files
-    let ext = source_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
-
-    if !ext.eq_ignore_ascii_case("png") {
-        anyhow::bail!("Only PNG files can be converted");
+    if !is_convertible(source_path, format) {
+        anyhow::bail!("Only PNG and BMP files can be converted to another format");
     }
 
     // Wait a bit to ensure the source file is fully written
@@ -74,6 +78,21 @@ pub fn convert_image(
             img.write_with_encoder(encoder)
                 .context("Failed to encode JPEG image")?;
         }
+        ConversionFormat::Png => {
+            let encoder =
+                PngEncoder::new_with_quality(&mut writer, CompressionType::Best, FilterType::Adaptive);
+            img.write_with_encoder(encoder)
+                .context("Failed to encode PNG image")?;
+        }
+        ConversionFormat::Avif => {
+            let encoder = AvifEncoder::new_with_speed_quality(
+                &mut writer,
+                AVIF_SPEED,
+                quality.clamp(1, 100) as u8,
+            );
+            img.write_with_encoder(encoder)
+                .context("Failed to encode AVIF image")?;
+        }
     }
 
     // Ensure buffer is flushed to disk
@@ -109,6 +128,10 @@ pub fn convert_image(
         (output_size as f64 / original_size as f64) * 100.0
     );
 
+    if keep_original {
+        return Ok(output_path);
+    }
+
     // Delete the original file after successful conversion
     if let Err(e) = fs::remove_file(source_path) {
         error!(
@@ -122,11 +145,39 @@ pub fn convert_image(
     Ok(output_path)
 }
 
-/// Check if a file is a PNG that can be converted
-pub fn is_convertible(path: &Path) -> bool {
+/// Let the user know a slow conversion finished; the other formats are done before anyone waits
+pub fn notify_completed(settings: &Settings, format: ConversionFormat, output_path: &Path) {
+    if format != ConversionFormat::Avif {
+        return;
+    }
+    let file = output_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    crate::tray::notify(
+        settings,
+        crate::tray::NotificationKind::Success,
+        "notifications.title",
+        "notifications.convert.avif_completed",
+        &[("file", &file)],
+    );
+}
+
+/// Check if a file is a PNG or BMP that can be converted to `format`
+pub fn is_convertible(path: &Path, format: ConversionFormat) -> bool {
     path.extension()
         .and_then(|e| e.to_str())
-        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+        .is_some_and(|ext| {
+            CONVERTIBLE_EXTENSIONS.iter().any(|c| ext.eq_ignore_ascii_case(c))
+                && !ext.eq_ignore_ascii_case(format.extension())
+        })
+}
+
+/// Check if a file should be converted on arrival: convertible and not converted already
+///
+/// A kept original has its converted sibling next to it, so it is left alone.
+pub fn needs_conversion(path: &Path, format: ConversionFormat) -> bool {
+    is_convertible(path, format) && !path.with_extension(format.extension()).exists()
 }
 
 #[cfg(test)]
@@ -135,35 +186,55 @@ mod tests {
 
     #[test]
     fn test_is_convertible() {
-        assert!(is_convertible(Path::new("test.png")));
-        assert!(is_convertible(Path::new("test.PNG")));
-        assert!(!is_convertible(Path::new("test.jpg")));
-        assert!(!is_convertible(Path::new("test.webp")));
+        let webp = ConversionFormat::WebP;
+        assert!(is_convertible(Path::new("test.png"), webp));
+        assert!(is_convertible(Path::new("test.PNG"), webp));
+        assert!(is_convertible(Path::new("test.bmp"), webp));
+        assert!(!is_convertible(Path::new("test.jpg"), webp));
+        assert!(!is_convertible(Path::new("test.webp"), webp));
     }
 
     #[test]
     fn test_is_convertible_edge_cases() {
+        let webp = ConversionFormat::WebP;
+
         // Test mixed case
-        assert!(is_convertible(Path::new("test.PnG")));
-        assert!(is_convertible(Path::new("test.pNg")));
+        assert!(is_convertible(Path::new("test.PnG"), webp));
+        assert!(is_convertible(Path::new("test.pNg"), webp));
 
         // Test files with multiple dots
-        assert!(is_convertible(Path::new("test.backup.png")));
-        assert!(!is_convertible(Path::new("test.backup.jpg")));
+        assert!(is_convertible(Path::new("test.backup.png"), webp));
+        assert!(!is_convertible(Path::new("test.backup.jpg"), webp));
 
         // Test files without extensions
-        assert!(!is_convertible(Path::new("test")));
+        assert!(!is_convertible(Path::new("test"), webp));
 
         // Test other image formats (should not be convertible)
-        assert!(!is_convertible(Path::new("test.gif")));
-        assert!(!is_convertible(Path::new("test.bmp")));
-        assert!(!is_convertible(Path::new("test.avif")));
+        assert!(!is_convertible(Path::new("test.gif"), webp));
+        assert!(!is_convertible(Path::new("test.avif"), webp));
+    }
+
+    #[test]
+    fn test_is_convertible_to_png() {
+        // BMP -> PNG, but a PNG is never re-encoded to itself
+        assert!(is_convertible(Path::new("test.bmp"), ConversionFormat::Png));
+        assert!(is_convertible(Path::new("test.BMP"), ConversionFormat::Png));
+        assert!(!is_convertible(Path::new("test.png"), ConversionFormat::Png));
+    }
+
+    #[test]
+    fn test_is_convertible_to_avif() {
+        assert!(is_convertible(Path::new("test.png"), ConversionFormat::Avif));
+        assert!(is_convertible(Path::new("test.bmp"), ConversionFormat::Avif));
+        assert!(!is_convertible(Path::new("test.avif"), ConversionFormat::Avif));
     }
 
     #[test]
     fn test_conversion_format_extension() {
         assert_eq!(ConversionFormat::WebP.extension(), "webp");
         assert_eq!(ConversionFormat::Jpeg.extension(), "jpg");
+        assert_eq!(ConversionFormat::Png.extension(), "png");
+        assert_eq!(ConversionFormat::Avif.extension(), "avif");
     }
 
     #[test]
@@ -0,0 +1,60 @@
+//! Crop a screenshot into a new file
+//!
+//! The copy is saved next to the original, so the watcher picks it up as a new screenshot.
+
+#![allow(dead_code)]
+
+use anyhow::{bail, Result};
+use image::imageops;
+use image::RgbaImage;
+use std::path::{Path, PathBuf};
+
+use crate::redact::{self, ImageRect};
+
+/// Crop `path` to `rect` and save the result as `name_crop.ext`
+///
+/// A rectangle reaching past the edges is clamped to the image.
+pub fn crop(path: &Path, rect: ImageRect) -> Result<PathBuf> {
+    redact::edit(path, "crop", |img| {
+        *img = crop_image(img, rect)?;
+        Ok(())
+    })
+}
+
+fn crop_image(img: &RgbaImage, rect: ImageRect) -> Result<RgbaImage> {
+    if rect.width == 0 || rect.height == 0 {
+        bail!("Crop area is empty ({}x{})", rect.width, rect.height);
+    }
+    let Some(clamped) = rect.clamped(img.width(), img.height()) else {
+        bail!(
+            "Crop area at ({}, {}) is outside the {}x{} image",
+            rect.x,
+            rect.y,
+            img.width(),
+            img.height()
+        );
+    };
+    Ok(imageops::crop_imm(img, clamped.x, clamped.y, clamped.width, clamped.height).to_image())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_crop_image() {
+        let img = RgbaImage::from_fn(10, 6, |x, y| Rgba([x as u8, y as u8, 0, 255]));
+
+        let cropped = crop_image(&img, ImageRect { x: 2, y: 1, width: 3, height: 2 }).unwrap();
+        assert_eq!(cropped.dimensions(), (3, 2));
+        assert_eq!(*cropped.get_pixel(0, 0), Rgba([2, 1, 0, 255]));
+
+        // Clamped at the edges
+        let cropped = crop_image(&img, ImageRect { x: 8, y: 4, width: 10, height: 10 }).unwrap();
+        assert_eq!(cropped.dimensions(), (2, 2));
+
+        assert!(crop_image(&img, ImageRect { x: 0, y: 0, width: 0, height: 3 }).is_err());
+        assert!(crop_image(&img, ImageRect { x: 10, y: 0, width: 3, height: 3 }).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! Near-duplicate screenshot detection using a perceptual difference hash (dHash)
+
+use image::imageops::{self, FilterType};
+use image::{GrayImage, RgbaImage};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::identity::{self, ContentHash};
+use crate::thumbnail::{ThumbnailCache, ThumbnailError, THUMBNAIL_SIZE};
+
+/// Default Hamming distance at which two screenshots count as duplicates
+pub const DEFAULT_MAX_DISTANCE: u32 = 5;
+
+/// 64-bit dHash of an image file, computed from its cached thumbnail
+pub fn perceptual_hash(path: &Path, cache: &ThumbnailCache) -> Result<u64, ThumbnailError> {
+    let thumbnail = cache.get_or_create(path, THUMBNAIL_SIZE)?;
+    Ok(dhash(&thumbnail))
+}
+
+/// Compare each pixel with its right neighbour on a 9x8 grayscale version
+fn dhash(img: &RgbaImage) -> u64 {
+    let gray: GrayImage = imageops::grayscale(img);
+    let small = imageops::resize(&gray, 9, 8, FilterType::Triangle);
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+/// Number of differing bits between two hashes
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Group screenshots whose hashes are within `max_distance` of each other.
+/// Only groups with two or more files are returned; paths keep their input order.
+pub fn find_duplicates(paths: &[PathBuf], max_distance: u32, cache: &ThumbnailCache) -> Vec<Vec<PathBuf>> {
+    // Exact copies share a content hash, so each distinct file is decoded only once
+    let mut by_content: HashMap<ContentHash, u64> = HashMap::new();
+    let hashed: Vec<(PathBuf, u64)> = paths
+        .iter()
+        .filter_map(|path| {
+            let content = identity::screenshot_hash(path).ok();
+            if let Some(&hash) = content.and_then(|c| by_content.get(&c)) {
+                return Some((path.clone(), hash));
+            }
+            match perceptual_hash(path, cache) {
+                Ok(hash) => {
+                    if let Some(content) = content {
+                        by_content.insert(content, hash);
+                    }
+                    Some((path.clone(), hash))
+                }
+                Err(e) => {
+                    debug!("Skipping unhashable file: {}", e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let groups = group_hashes(&hashed, max_distance);
+    info!(
+        "Duplicate scan: {} files hashed, {} groups found (thumbnail cache: {:?})",
+        hashed.len(),
+        groups.len(),
+        cache.stats()
+    );
+    groups
+}
+
+/// Cluster hashes transitively (union-find) by Hamming distance
+fn group_hashes(hashed: &[(PathBuf, u64)], max_distance: u32) -> Vec<Vec<PathBuf>> {
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        // Path compression
+        let mut node = i;
+        while parent[node] != root {
+            let next = parent[node];
+            parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    let mut parent: Vec<usize> = (0..hashed.len()).collect();
+    for i in 0..hashed.len() {
+        for j in (i + 1)..hashed.len() {
+            if hamming_distance(hashed[i].1, hashed[j].1) <= max_distance {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                if a != b {
+                    // Keep the earliest index as root so groups follow input order
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<PathBuf>> = Vec::new();
+    let mut group_of_root: Vec<Option<usize>> = vec![None; hashed.len()];
+    for (i, (path, _)) in hashed.iter().enumerate() {
+        let root = find(&mut parent, i);
+        match group_of_root[root] {
+            Some(g) => groups[g].push(path.clone()),
+            None => {
+                group_of_root[root] = Some(groups.len());
+                groups.push(vec![path.clone()]);
+            }
+        }
+    }
+    groups.retain(|g| g.len() > 1);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn gradient(width: u32, height: u32, invert: bool) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, _| {
+            let v = (x * 255 / width) as u8;
+            let v = if invert { 255 - v } else { v };
+            Rgba([v, v, v, 255])
+        })
+    }
+
+    #[test]
+    fn test_dhash_similarity() {
+        let a = dhash(&gradient(150, 100, false));
+        let b = dhash(&gradient(140, 90, false));
+        let c = dhash(&gradient(150, 100, true));
+        assert!(hamming_distance(a, b) <= DEFAULT_MAX_DISTANCE);
+        assert!(hamming_distance(a, c) > DEFAULT_MAX_DISTANCE);
+    }
+
+    #[test]
+    fn test_group_hashes() {
+        let hashed = vec![
+            (PathBuf::from("a.png"), 0b0000),
+            (PathBuf::from("b.png"), u64::MAX),
+            (PathBuf::from("c.png"), 0b0011),
+            (PathBuf::from("d.png"), 0b1111),
+        ];
+        // a-c and c-d are within 2 bits, so all three chain together
+        let groups = group_hashes(&hashed, 2);
+        assert_eq!(
+            groups,
+            vec![vec![
+                PathBuf::from("a.png"),
+                PathBuf::from("c.png"),
+                PathBuf::from("d.png")
+            ]]
+        );
+        assert!(group_hashes(&hashed, 0).is_empty());
+    }
+}
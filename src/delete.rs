@@ -0,0 +1,78 @@
+//! Screenshot deletion, to the Recycle Bin by default
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use std::fs;
+use std::path::Path;
+
+/// Delete a screenshot. Unless `permanent` is set the file goes to the Recycle Bin
+/// so the deletion can be undone from Explorer.
+pub fn delete_screenshot(path: &Path, permanent: bool) -> Result<()> {
+    if !path.exists() {
+        bail!("File not found: {:?}", path);
+    }
+
+    if permanent {
+        fs::remove_file(path).with_context(|| format!("Failed to delete {:?}", path))?;
+        info!("Permanently deleted {:?}", path);
+    } else {
+        move_to_recycle_bin(path)?;
+        info!("Moved {:?} to the Recycle Bin", path);
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn move_to_recycle_bin(path: &Path) -> Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::Win32::UI::Shell::{
+        SHFileOperationW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT, FO_DELETE,
+        SHFILEOPSTRUCTW,
+    };
+    use windows::core::PCWSTR;
+
+    // pFrom is a list of paths terminated by an extra null
+    let from: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain([0, 0])
+        .collect();
+
+    let mut op = SHFILEOPSTRUCTW {
+        wFunc: FO_DELETE,
+        pFrom: PCWSTR(from.as_ptr()),
+        fFlags: (FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT).0 as u16,
+        ..Default::default()
+    };
+
+    let result = unsafe { SHFileOperationW(&mut op) };
+    if result != 0 {
+        bail!("Failed to move {:?} to the Recycle Bin (error {:#x})", path, result);
+    }
+    // Copy out first: the struct is packed on 32-bit targets
+    let aborted = op.fAnyOperationsAborted;
+    if aborted.as_bool() {
+        bail!("Deleting {:?} was cancelled", path);
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn move_to_recycle_bin(_path: &Path) -> Result<()> {
+    bail!("Recycle Bin is only supported on Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete_permanent() {
+        let path = std::env::temp_dir().join(format!("sukusho-delete-{}.png", std::process::id()));
+        fs::write(&path, b"png").unwrap();
+
+        delete_screenshot(&path, true).unwrap();
+        assert!(!path.exists());
+        assert!(delete_screenshot(&path, true).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! Self-test for debugging user reports
+//!
+//! `sukusho --doctor` prints the report and exits non-zero if a check failed; the tray's
+//! Diagnostics entry runs the same checks in the resident instance and opens the report as a
+//! text file. The clipboard check replaces the clipboard contents with a sample file.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::settings::Settings;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail,
+    /// Not applicable here, e.g. Windows-only checks on other systems
+    Skip,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Pass => "PASS",
+            Status::Fail => "FAIL",
+            Status::Skip => "SKIP",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    /// Pass with `Ok`'s detail, fail with the error
+    fn from_result(name: &'static str, result: Result<String>) -> Self {
+        match result {
+            Ok(detail) => Self::new(name, Status::Pass, detail),
+            Err(e) => Self::new(name, Status::Fail, format!("{:#}", e)),
+        }
+    }
+}
+
+/// Where the checks run, which decides how the tray icon is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// `--doctor`; `other_instance` if Sukusho is already running
+    CommandLine { other_instance: bool },
+    /// The tray menu of the running app
+    Tray,
+}
+
+/// Run every check, in report order
+pub fn run_checks(settings: &Settings, origin: Origin) -> Vec<Check> {
+    let mut checks = vec![Check::from_result(
+        "Screenshot folder",
+        check_folder(&settings.screenshot_directory),
+    )];
+    match write_sample() {
+        Ok(sample) => {
+            checks.push(check_thumbnail(&sample));
+            checks.push(check_clipboard(&sample));
+            let _ = std::fs::remove_file(sample);
+        }
+        Err(e) => checks.push(Check::new("Sample image", Status::Fail, format!("{:#}", e))),
+    }
+    checks.push(check_tray(origin));
+    checks.push(Check::new("Locale", Status::Pass, locale_detail(settings)));
+    checks.push(Check::from_result("Version", check_version()));
+    checks
+}
+
+/// The report as printed: one line per check, then a summary
+pub fn format_report(checks: &[Check]) -> String {
+    let mut report = format!("Sukusho {} diagnostics\n\n", env!("CARGO_PKG_VERSION"));
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in checks {
+        report.push_str(&format!(
+            "[{}] {:width$}  {}\n",
+            check.status,
+            check.name,
+            check.detail,
+            width = width
+        ));
+    }
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    report.push_str(&format!(
+        "\n{} passed, {} failed, {} skipped\n",
+        count(Status::Pass),
+        count(Status::Fail),
+        count(Status::Skip)
+    ));
+    report
+}
+
+/// Number of failed checks
+pub fn failures(checks: &[Check]) -> usize {
+    checks.iter().filter(|c| c.status == Status::Fail).count()
+}
+
+/// Run the checks from the tray and open the report in the default text editor
+pub fn run_from_tray(settings: &Settings) {
+    let report = format_report(&run_checks(settings, Origin::Tray));
+    log::info!("Diagnostics:\n{}", report);
+    let path = std::env::temp_dir().join("sukusho-diagnostics.txt");
+    match std::fs::write(&path, report) {
+        Ok(()) => {
+            if let Err(e) = open::that(&path) {
+                log::warn!("Failed to open diagnostics report {:?}: {}", path, e);
+            }
+        }
+        Err(e) => log::warn!("Failed to write diagnostics report {:?}: {}", path, e),
+    }
+}
+
+/// The folder exists and a file can be created in it
+fn check_folder(directory: &Path) -> Result<String> {
+    if !directory.is_dir() {
+        anyhow::bail!("{} does not exist", directory.display());
+    }
+    let probe = directory.join(format!(".sukusho-doctor-{}", std::process::id()));
+    std::fs::write(&probe, b"sukusho")
+        .with_context(|| format!("{} is not writable", directory.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(format!("{} is writable", directory.display()))
+}
+
+/// A small PNG in the temp folder for the thumbnail and clipboard checks
+fn write_sample() -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("sukusho-doctor-{}.png", std::process::id()));
+    let sample = image::RgbaImage::from_fn(64, 64, |x, y| {
+        image::Rgba([(x * 4) as u8, (y * 4) as u8, 128, 255])
+    });
+    sample.save_with_format(&path, image::ImageFormat::Png)?;
+    Ok(path)
+}
+
+#[cfg(windows)]
+fn check_thumbnail(sample: &Path) -> Check {
+    Check::from_result(
+        "Shell thumbnail",
+        crate::thumbnail::shell_thumbnail(sample, 64)
+            .map(|thumbnail| {
+                format!("Got a {}x{} thumbnail", thumbnail.width(), thumbnail.height())
+            })
+            .map_err(anyhow::Error::from),
+    )
+}
+
+#[cfg(not(windows))]
+fn check_thumbnail(_sample: &Path) -> Check {
+    Check::new("Shell thumbnail", Status::Skip, "Windows only")
+}
+
+#[cfg(windows)]
+fn check_clipboard(sample: &Path) -> Check {
+    if crate::clipboard::copy_files_to_clipboard(&[sample.to_path_buf()]) {
+        Check::new("Clipboard", Status::Pass, "Copied a sample file")
+    } else {
+        Check::new("Clipboard", Status::Fail, "Could not open the clipboard")
+    }
+}
+
+#[cfg(not(windows))]
+fn check_clipboard(_sample: &Path) -> Check {
+    Check::new("Clipboard", Status::Skip, "Windows only")
+}
+
+fn check_tray(origin: Origin) -> Check {
+    const NAME: &str = "Tray icon";
+    match origin {
+        Origin::Tray => Check::new(NAME, Status::Pass, "Created"),
+        Origin::CommandLine {
+            other_instance: true,
+        } => Check::new(
+            NAME,
+            Status::Skip,
+            "Sukusho is running; use Diagnostics in its tray menu",
+        ),
+        Origin::CommandLine {
+            other_instance: false,
+        } => {
+            // Created and removed again right away; its menu threads end with the process
+            let (tx, _rx) = crossbeam_channel::unbounded();
+            let settings = std::sync::Arc::new(parking_lot::Mutex::new(Settings::default()));
+            Check::from_result(
+                NAME,
+                crate::tray::TrayManager::new(tx, settings).map(|_| "Created".to_string()),
+            )
+        }
+    }
+}
+
+fn locale_detail(settings: &Settings) -> String {
+    let requested = match &settings.language {
+        Some(language) => format!("set to {}", language),
+        None => format!("detected {}", crate::i18n_helpers::detect_system_language()),
+    };
+    format!("{} ({})", crate::i18n_helpers::current_language(), requested)
+}
+
+fn check_version() -> Result<String> {
+    let latest = crate::update_checker::latest_release()?;
+    let current = env!("CARGO_PKG_VERSION");
+    Ok(if latest.has_update {
+        format!("{} installed, {} available", current, latest.latest_version)
+    } else {
+        format!("{} installed, latest is {}", current, latest.latest_version)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_folder() {
+        let dir = std::env::temp_dir().join(format!("sukusho-doctor-{}", std::process::id()));
+        assert!(check_folder(&dir).is_err());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(check_folder(&dir).is_ok());
+        // The probe file is cleaned up
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_report() {
+        let checks = [
+            Check::new("Folder", Status::Pass, "ok"),
+            Check::new("Clipboard", Status::Fail, "busy"),
+        ];
+        let report = format_report(&checks);
+        assert!(report.contains("[PASS] Folder     ok\n"));
+        assert!(report.contains("[FAIL] Clipboard  busy\n"));
+        assert!(report.ends_with("1 passed, 1 failed, 0 skipped\n"));
+        assert_eq!(failures(&checks), 1);
+    }
+}
@@ -1,19 +1,83 @@
 //! Native Windows drag-and-drop implementation using Windows COM APIs directly
 //!
 //! Implements IDataObject and IDropSource for OLE drag-drop operations.
-
-use log::{debug, error, info};
+//! Files are offered as CF_HDROP, and as `file://` URLs for targets that prefer those.
+//! The drag image shows the dragged files as a stack of thumbnails, like Explorer does.
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use image::{Rgba, RgbaImage};
+use log::{debug, error, info, warn};
+use parking_lot::Mutex;
+use std::path::Path;
+#[cfg(windows)]
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use crate::platform::DropEffect;
+#[cfg(windows)]
+use crate::settings::DragEscAction;
+
+/// Thumbnails shown in the drag image; further files are counted in the badge
+const MAX_STACKED: usize = 3;
+
+/// Longest side of a thumbnail card in the drag image
+const CARD_SIZE: u32 = 96;
+
+/// Space between a card's border and its thumbnail
+const CARD_PADDING: u32 = 3;
+
+/// How far each card behind the front one is shifted right and down
+const STACK_OFFSET: u32 = 10;
+
+/// Diameter of the "+N" badge
+const BADGE_SIZE: u32 = 28;
+
+const CARD_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const CARD_BORDER: Rgba<u8> = Rgba([160, 160, 160, 255]);
+const BADGE_TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
 
-/// Flag to prevent multiple concurrent drag operations
-static DRAG_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+/// A drag held for this long is assumed to be orphaned and can be taken over
+const STALE_DRAG_AFTER: Duration = Duration::from_secs(30);
+
+/// The drag in progress, to prevent concurrent drag operations
+#[cfg_attr(not(windows), allow(dead_code))]
+static DRAG_OWNER: Mutex<Option<DragOwner>> = Mutex::new(None);
+
+/// Who started a drag, and when
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DragOwner {
+    thread: ThreadId,
+    started: Instant,
+}
+
+/// Take `slot` for `owner`, unless a drag younger than `STALE_DRAG_AFTER` holds it
+#[cfg_attr(not(windows), allow(dead_code))]
+fn claim_drag(slot: &mut Option<DragOwner>, owner: DragOwner) -> bool {
+    if let Some(current) = *slot {
+        let age = owner.started.saturating_duration_since(current.started);
+        if age < STALE_DRAG_AFTER {
+            return false;
+        }
+        warn!("Taking over a drag started {:?} ago on {:?}", age, current.thread);
+    }
+    *slot = Some(owner);
+    true
+}
+
+/// Free `slot` if `owner` still holds it, so a drag that was taken over can't free its successor
+#[cfg_attr(not(windows), allow(dead_code))]
+fn release_drag(slot: &mut Option<DragOwner>, owner: DragOwner) {
+    if *slot == Some(owner) {
+        *slot = None;
+    }
+}
 
 /// Simple drag threshold check (for use with separate start_drag call)
 #[cfg(windows)]
 pub fn check_drag_threshold() -> bool {
-    use crate::tray::WINDOW_HWND;
-    use windows::Win32::Foundation::{HWND, POINT};
+    use windows::Win32::Foundation::POINT;
     use windows::Win32::UI::Input::KeyboardAndMouse::DragDetect;
     use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
 
@@ -23,12 +87,11 @@ pub fn check_drag_threshold() -> bool {
             return false;
         }
 
-        let hwnd = match *WINDOW_HWND.lock() {
-            Some(h) => HWND(h as *mut std::ffi::c_void),
-            None => return false,
+        let Some(handle) = crate::tray::window_handle() else {
+            return false;
         };
 
-        DragDetect(hwnd, pt).as_bool()
+        DragDetect(handle.hwnd(), pt).as_bool()
     }
 }
 
@@ -56,52 +119,57 @@ const DV_E_FORMATETC: i32 = -2147221404i32; // 0x80040064
 const DATA_S_SAMEFORMATETC: i32 = 0x00040130;
 
 /// Start a drag operation with the given files
-/// Returns true if drag actually happened, false if user just clicked (or error)
+/// Returns the drop effect if the files were dropped, `None` if the user just clicked,
+/// canceled, or the drag failed
 #[cfg(windows)]
-pub fn start_drag(files: &[PathBuf]) -> bool {
+pub fn start_drag(files: &[PathBuf]) -> Option<DropEffect> {
     use std::ffi::OsStr;
     use std::os::windows::ffi::OsStrExt;
-    use windows::core::{implement, HRESULT};
-    use windows::Win32::Foundation::{BOOL, E_NOTIMPL, S_OK};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use windows::core::{implement, IUnknown, HRESULT};
+    use windows::Win32::Foundation::{BOOL, E_NOTIMPL, HGLOBAL, S_OK};
     use windows::Win32::System::Com::{
         IAdviseSink, IDataObject, IDataObject_Impl, IEnumFORMATETC, IEnumSTATDATA, DATADIR_GET,
         DVASPECT_CONTENT, FORMATETC, STGMEDIUM, TYMED_HGLOBAL,
     };
     use windows::Win32::System::Memory::{
-        GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE, GMEM_ZEROINIT,
+        GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+        GMEM_ZEROINIT,
     };
     use windows::Win32::System::Ole::{
-        DoDragDrop, IDropSource, IDropSource_Impl, CF_HDROP, DROPEFFECT, DROPEFFECT_COPY,
-        DROPEFFECT_NONE,
+        DoDragDrop, IDropSource, IDropSource_Impl, ReleaseStgMedium, CF_HDROP, DROPEFFECT,
+        DROPEFFECT_COPY, DROPEFFECT_NONE,
     };
+    use windows::core::w;
+    use windows::Win32::System::DataExchange::RegisterClipboardFormatW;
     use windows::Win32::System::SystemServices::MODIFIERKEYS_FLAGS;
     use windows::Win32::UI::Shell::{SHCreateStdEnumFmtEtc, DROPFILES};
 
     if files.is_empty() {
         info!("start_drag called with empty files list");
-        return false;
+        return None;
     }
 
     // Check if drag is already in progress
-    if DRAG_IN_PROGRESS
-        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-        .is_err()
-    {
-        info!("Drag already in progress, skipping - resetting state");
-        // Reset the flag in case it got stuck
-        DRAG_IN_PROGRESS.store(false, Ordering::SeqCst);
-        return false;
-    }
-
-    // Use a guard to ensure DRAG_IN_PROGRESS is reset even if we panic
-    struct DragGuard;
+    let owner = DragOwner {
+        thread: std::thread::current().id(),
+        started: Instant::now(),
+    };
+    if !claim_drag(&mut DRAG_OWNER.lock(), owner) {
+        info!("Drag already in progress, skipping");
+        return None;
+    }
+
+    // Use a guard to ensure the drag is released even if we panic
+    struct DragGuard(DragOwner);
     impl Drop for DragGuard {
         fn drop(&mut self) {
-            DRAG_IN_PROGRESS.store(false, Ordering::SeqCst);
+            release_drag(&mut DRAG_OWNER.lock(), self.0);
             info!("Drag guard dropped, state reset");
         }
     }
-    let _guard = DragGuard;
+    let guard = DragGuard(owner);
 
     info!(
         "=== Starting native drag operation with {} files ===",
@@ -132,8 +200,7 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
 
     if normalized_paths.is_empty() {
         error!("No valid paths for drag operation");
-        DRAG_IN_PROGRESS.store(false, Ordering::SeqCst);
-        return false;
+        return None;
     }
 
     for path in &normalized_paths {
@@ -141,9 +208,136 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
     }
 
     // Implement IDataObject - Explorer requires proper EnumFormatEtc
+    //
+    // Each format's data block is built once and owned by the data object. Every STGMEDIUM
+    // we hand out references it with pUnkForRelease set to the data object itself, so
+    // ReleaseStgMedium drops a reference instead of freeing the block, and the blocks are
+    // freed when the last reference goes away - even if no target ever asked for them.
     #[implement(IDataObject)]
     struct FileDataObject {
         paths: Vec<PathBuf>,
+        /// Registered "UniformResourceLocatorW": the first file's URL (browsers)
+        url_format: u16,
+        /// Registered "text/uri-list": every file's URL, one per line (0 if unavailable)
+        uri_list_format: u16,
+        /// Data blocks built so far, by clipboard format
+        blocks: RefCell<Vec<(u16, HGLOBAL)>>,
+    }
+
+    impl FileDataObject {
+        /// CF_HDROP stays first, so Explorer keeps treating the drag as files
+        ///
+        /// Formats stored by `SetData` (the shell's drag image data) follow our own.
+        fn formats(&self) -> Vec<u16> {
+            let mut formats: Vec<u16> = [CF_HDROP.0, self.url_format, self.uri_list_format]
+                .into_iter()
+                .filter(|&format| format != 0)
+                .collect();
+            for (format, _) in self.blocks.borrow().iter() {
+                if !formats.contains(format) {
+                    formats.push(*format);
+                }
+            }
+            formats
+        }
+
+        /// Build the data block for a format
+        fn build(&self, format: u16) -> windows::core::Result<HGLOBAL> {
+            if format == CF_HDROP.0 {
+                build_hdrop(&self.paths)
+            } else if format == self.url_format {
+                let wide: Vec<u16> = file_uri(&self.paths[0])
+                    .encode_utf16()
+                    .chain(std::iter::once(0))
+                    .collect();
+                let bytes: Vec<u8> = wide.iter().flat_map(|c| c.to_le_bytes()).collect();
+                global_from_bytes(&bytes)
+            } else {
+                let mut list = uri_list(&self.paths).into_bytes();
+                list.push(0);
+                global_from_bytes(&list)
+            }
+        }
+    }
+
+    impl Drop for FileDataObject {
+        fn drop(&mut self) {
+            for (_, hglobal) in self.blocks.get_mut().drain(..) {
+                debug!("Freeing drag data block");
+                let _ = unsafe { GlobalFree(hglobal) };
+            }
+        }
+    }
+
+    /// Copy bytes into a movable global memory block
+    fn global_from_bytes(bytes: &[u8]) -> windows::core::Result<HGLOBAL> {
+        unsafe {
+            let hglobal = GlobalAlloc(GMEM_MOVEABLE | GMEM_ZEROINIT, bytes.len())?;
+            let ptr = GlobalLock(hglobal);
+            if ptr.is_null() {
+                error!("GetData: GlobalLock failed");
+                let _ = GlobalFree(hglobal);
+                return Err(windows::core::Error::from_hresult(HRESULT(E_NOTIMPL.0)));
+            }
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr as *mut u8, bytes.len());
+            let _ = GlobalUnlock(hglobal);
+            Ok(hglobal)
+        }
+    }
+
+    /// Allocate a DROPFILES block listing the paths
+    fn build_hdrop(paths: &[PathBuf]) -> windows::core::Result<HGLOBAL> {
+        // Build the file list as wide strings (UTF-16)
+        let mut wide_buffer: Vec<u16> = Vec::new();
+        for path in paths {
+            let path_str = path.to_string_lossy();
+            debug!("GetData: encoding path: {}", path_str);
+            let wide: Vec<u16> = OsStr::new(path)
+                .encode_wide()
+                .chain(std::iter::once(0)) // null terminator for each path
+                .collect();
+            debug!("GetData: path encoded to {} u16 chars", wide.len());
+            wide_buffer.extend_from_slice(&wide);
+        }
+        wide_buffer.push(0); // Double null terminator at end
+
+        let header_size = std::mem::size_of::<DROPFILES>();
+        let data_size = wide_buffer.len() * 2; // 2 bytes per u16
+        let total_size = header_size + data_size;
+
+        debug!("GetData: DROPFILES header size: {} bytes", header_size);
+        debug!(
+            "GetData: Total wide_buffer length: {} u16 chars ({} bytes)",
+            wide_buffer.len(),
+            data_size
+        );
+        info!(
+            "GetData: allocating {} bytes (header={}, data={})",
+            total_size, header_size, data_size
+        );
+
+        unsafe {
+            // Allocate global memory
+            let hglobal = GlobalAlloc(GMEM_MOVEABLE | GMEM_ZEROINIT, total_size)?;
+            let ptr = GlobalLock(hglobal);
+            if ptr.is_null() {
+                error!("GetData: GlobalLock failed");
+                let _ = GlobalFree(hglobal);
+                return Err(windows::core::Error::from_hresult(HRESULT(E_NOTIMPL.0)));
+            }
+
+            // Fill DROPFILES header
+            let dropfiles = ptr as *mut DROPFILES;
+            (*dropfiles).pFiles = header_size as u32;
+            (*dropfiles).fWide = BOOL(1); // UTF-16
+
+            // Copy file paths after header
+            let data_ptr = (ptr as *mut u8).add(header_size) as *mut u16;
+            std::ptr::copy_nonoverlapping(wide_buffer.as_ptr(), data_ptr, wide_buffer.len());
+
+            let _ = GlobalUnlock(hglobal);
+            Ok(hglobal)
+        }
     }
 
     impl IDataObject_Impl for FileDataObject_Impl {
@@ -156,12 +350,9 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
                     fmt.cfFormat, fmt.tymed
                 );
 
-                // Only support CF_HDROP format with HGLOBAL
-                if fmt.cfFormat != CF_HDROP.0 {
-                    info!(
-                        "GetData: wrong format {}, expected {}",
-                        fmt.cfFormat, CF_HDROP.0
-                    );
+                // Only our own formats, with HGLOBAL
+                if !self.formats().contains(&fmt.cfFormat) {
+                    info!("GetData: unsupported format {}", fmt.cfFormat);
                     return Err(windows::core::Error::from_hresult(HRESULT(DV_E_FORMATETC)));
                 }
 
@@ -170,60 +361,32 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
                     return Err(windows::core::Error::from_hresult(HRESULT(DV_E_FORMATETC)));
                 }
 
-                // Build the file list as wide strings (UTF-16)
-                let mut wide_buffer: Vec<u16> = Vec::new();
-                for path in &self.paths {
-                    let path_str = path.to_string_lossy();
-                    debug!("GetData: encoding path: {}", path_str);
-                    let wide: Vec<u16> = OsStr::new(path)
-                        .encode_wide()
-                        .chain(std::iter::once(0)) // null terminator for each path
-                        .collect();
-                    debug!("GetData: path encoded to {} u16 chars", wide.len());
-                    wide_buffer.extend_from_slice(&wide);
-                }
-                wide_buffer.push(0); // Double null terminator at end
-
-                let header_size = std::mem::size_of::<DROPFILES>();
-                let data_size = wide_buffer.len() * 2; // 2 bytes per u16
-                let total_size = header_size + data_size;
-
-                debug!("GetData: DROPFILES header size: {} bytes", header_size);
-                debug!(
-                    "GetData: Total wide_buffer length: {} u16 chars ({} bytes)",
-                    wide_buffer.len(),
-                    data_size
-                );
-                info!(
-                    "GetData: allocating {} bytes (header={}, data={})",
-                    total_size, header_size, data_size
-                );
-
-                // Allocate global memory
-                let hglobal = GlobalAlloc(GMEM_MOVEABLE | GMEM_ZEROINIT, total_size)?;
-                let ptr = GlobalLock(hglobal);
-                if ptr.is_null() {
-                    error!("GetData: GlobalLock failed");
-                    return Err(windows::core::Error::from_hresult(HRESULT(E_NOTIMPL.0)));
-                }
-
-                // Fill DROPFILES header
-                let dropfiles = ptr as *mut DROPFILES;
-                (*dropfiles).pFiles = header_size as u32;
-                (*dropfiles).fWide = BOOL(1); // UTF-16
-
-                // Copy file paths after header
-                let data_ptr = (ptr as *mut u8).add(header_size) as *mut u16;
-                std::ptr::copy_nonoverlapping(wide_buffer.as_ptr(), data_ptr, wide_buffer.len());
-
-                let _ = GlobalUnlock(hglobal);
+                // The newest block wins, so `SetData` can replace earlier data
+                let existing = self
+                    .blocks
+                    .borrow()
+                    .iter()
+                    .rev()
+                    .find(|(format, _)| *format == fmt.cfFormat)
+                    .map(|(_, hglobal)| *hglobal);
+                let hglobal = match existing {
+                    Some(hglobal) => hglobal,
+                    None => {
+                        let hglobal = self.build(fmt.cfFormat)?;
+                        self.blocks.borrow_mut().push((fmt.cfFormat, hglobal));
+                        hglobal
+                    }
+                };
+
+                // The receiver releases the medium through us instead of calling GlobalFree
+                let owner: IUnknown = self.to_object().to_interface();
 
                 info!("GetData: success, returning STGMEDIUM");
 
                 Ok(STGMEDIUM {
                     tymed: TYMED_HGLOBAL.0 as u32,
                     u: std::mem::transmute(hglobal),
-                    pUnkForRelease: std::mem::ManuallyDrop::new(None),
+                    pUnkForRelease: std::mem::ManuallyDrop::new(Some(owner)),
                 })
             }
         }
@@ -244,7 +407,9 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
                     fmt.cfFormat, fmt.tymed
                 );
 
-                if fmt.cfFormat == CF_HDROP.0 && (fmt.tymed & TYMED_HGLOBAL.0 as u32) != 0 {
+                if self.formats().contains(&fmt.cfFormat)
+                    && (fmt.tymed & TYMED_HGLOBAL.0 as u32) != 0
+                {
                     info!("QueryGetData: S_OK");
                     S_OK
                 } else {
@@ -262,13 +427,40 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
             HRESULT(DATA_S_SAMEFORMATETC)
         }
 
+        /// The drag image helper stores its data here for drop targets to read back
         fn SetData(
             &self,
-            _pformatetc: *const FORMATETC,
-            _pmedium: *const STGMEDIUM,
-            _frelease: BOOL,
+            pformatetc: *const FORMATETC,
+            pmedium: *const STGMEDIUM,
+            frelease: BOOL,
         ) -> windows::core::Result<()> {
-            Err(windows::core::Error::from_hresult(HRESULT(E_NOTIMPL.0)))
+            unsafe {
+                let fmt = &*pformatetc;
+                let medium = &*pmedium;
+                debug!("SetData: cfFormat={}, tymed={}", fmt.cfFormat, medium.tymed);
+
+                if medium.tymed != TYMED_HGLOBAL.0 as u32 {
+                    return Err(windows::core::Error::from_hresult(HRESULT(DV_E_FORMATETC)));
+                }
+
+                // Keep a copy, so the caller's medium is released the usual way
+                let source = medium.u.hGlobal;
+                let ptr = GlobalLock(source);
+                if ptr.is_null() {
+                    return Err(windows::core::Error::from_hresult(HRESULT(E_NOTIMPL.0)));
+                }
+                let size = GlobalSize(source);
+                let bytes = std::slice::from_raw_parts(ptr as *const u8, size).to_vec();
+                let _ = GlobalUnlock(source);
+                if frelease.as_bool() {
+                    ReleaseStgMedium(pmedium as *mut STGMEDIUM);
+                }
+
+                // Replaced blocks may still be in use by a reader; they are freed with the object
+                let hglobal = global_from_bytes(&bytes)?;
+                self.blocks.borrow_mut().push((fmt.cfFormat, hglobal));
+                Ok(())
+            }
         }
 
         fn EnumFormatEtc(&self, dwdirection: u32) -> windows::core::Result<IEnumFORMATETC> {
@@ -276,13 +468,17 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
 
             if dwdirection == DATADIR_GET.0 as u32 {
                 // Create standard format enumerator using Shell helper function
-                let formats = [FORMATETC {
-                    cfFormat: CF_HDROP.0,
-                    ptd: std::ptr::null_mut(),
-                    dwAspect: DVASPECT_CONTENT.0 as u32,
-                    lindex: -1,
-                    tymed: TYMED_HGLOBAL.0 as u32,
-                }];
+                let formats: Vec<FORMATETC> = self
+                    .formats()
+                    .into_iter()
+                    .map(|format| FORMATETC {
+                        cfFormat: format,
+                        ptd: std::ptr::null_mut(),
+                        dwAspect: DVASPECT_CONTENT.0 as u32,
+                        lindex: -1,
+                        tymed: TYMED_HGLOBAL.0 as u32,
+                    })
+                    .collect();
 
                 unsafe {
                     let result = SHCreateStdEnumFmtEtc(&formats);
@@ -317,7 +513,10 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
 
     // Implement IDropSource
     #[implement(IDropSource)]
-    struct FileDropSource;
+    struct FileDropSource {
+        /// Set when ESC canceled the drag, as opposed to a click that never became one
+        escaped: Rc<Cell<bool>>,
+    }
 
     impl IDropSource_Impl for FileDropSource_Impl {
         fn QueryContinueDrag(
@@ -327,6 +526,7 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
         ) -> HRESULT {
             if fescapepressed.as_bool() {
                 info!("QueryContinueDrag: ESC pressed, canceling");
+                self.escaped.set(true);
                 return HRESULT(DRAGDROP_S_CANCEL);
             }
 
@@ -345,12 +545,38 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
         }
     }
 
+    // Render the preview while the paths are still ours
+    let preview = drag_preview(&normalized_paths);
+    let esc_action = crate::settings_store::read(|s| s.drag_esc_action);
+    let esc_paths =
+        (esc_action == DragEscAction::CopyToClipboard).then(|| normalized_paths.clone());
+
     // Create COM objects
+    let (url_format, uri_list_format) = unsafe {
+        (
+            RegisterClipboardFormatW(w!("UniformResourceLocatorW")) as u16,
+            RegisterClipboardFormatW(w!("text/uri-list")) as u16,
+        )
+    };
     let data_object: IDataObject = FileDataObject {
         paths: normalized_paths,
+        url_format,
+        uri_list_format,
+        blocks: RefCell::new(Vec::new()),
     }
     .into();
-    let drop_source: IDropSource = FileDropSource.into();
+    let escaped = Rc::new(Cell::new(false));
+    let drop_source: IDropSource = FileDropSource {
+        escaped: Rc::clone(&escaped),
+    }
+    .into();
+
+    if let Some(preview) = preview {
+        // Without it the shell shows its generic drag image
+        if let Err(e) = set_drag_image(&data_object, &preview) {
+            debug!("Failed to set drag image: {}", e);
+        }
+    }
 
     info!("Calling DoDragDrop...");
 
@@ -366,7 +592,7 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
         )
     };
 
-    DRAG_IN_PROGRESS.store(false, Ordering::SeqCst);
+    drop(guard);
 
     info!(
         "=== DoDragDrop returned: result={:?}, effect={:?} ===",
@@ -378,24 +604,356 @@ pub fn start_drag(files: &[PathBuf]) -> bool {
     // - DRAGDROP_S_CANCEL (0x00040101): User cancelled (ESC or just clicked without dragging)
     // - S_OK: Also indicates success
     //
-    // Return true only if an actual drag happened (not cancelled)
+    // Return an effect only if an actual drop happened (not cancelled)
     // This allows the caller to handle clicks separately
     if result.0 == DRAGDROP_S_DROP || (result.is_ok() && drop_effect != DROPEFFECT_NONE) {
         info!("Drag was completed successfully");
-        true
+        Some(effect_from_dropeffect(drop_effect))
     } else if result.0 == DRAGDROP_S_CANCEL {
         info!("Drag was cancelled (user clicked without dragging or pressed ESC)");
-        false
+        if let Some(paths) = esc_paths.filter(|_| escaped.get()) {
+            copy_after_escape(&paths);
+        }
+        None
     } else if result.is_err() {
         error!("DoDragDrop failed: {:?}", result);
-        false
+        None
     } else {
         info!("Drag ended with no effect");
-        false
+        None
     }
 }
 
-#[cfg(not(windows))]
-pub fn start_drag(_files: &[PathBuf]) -> bool {
-    false
+/// The effect a target reported; only copying is offered, so a target that reports nothing
+/// still copied
+#[cfg(windows)]
+fn effect_from_dropeffect(effect: windows::Win32::System::Ole::DROPEFFECT) -> DropEffect {
+    use windows::Win32::System::Ole::{DROPEFFECT_LINK, DROPEFFECT_MOVE};
+
+    if effect.0 & DROPEFFECT_MOVE.0 != 0 {
+        DropEffect::Move
+    } else if effect.0 & DROPEFFECT_LINK.0 != 0 {
+        DropEffect::Link
+    } else {
+        DropEffect::Copy
+    }
+}
+
+/// `DragEscAction::CopyToClipboard`: put the files of a drag canceled with ESC on the clipboard
+#[cfg(windows)]
+fn copy_after_escape(paths: &[PathBuf]) {
+    use crate::tray::{notify, NotificationKind};
+
+    if !crate::clipboard::copy_files_to_clipboard(paths) {
+        warn!("Failed to copy {} dragged files after ESC", paths.len());
+        return;
+    }
+    info!("Copied {} dragged files after ESC", paths.len());
+    notify(
+        &crate::settings_store::get(),
+        NotificationKind::Success,
+        "notifications.title",
+        &crate::i18n_helpers::count_key("notifications.copied_to_clipboard", paths.len()),
+        &[("count", &paths.len().to_string())],
+    );
+}
+
+/// Drag image for `paths`, from the shell's thumbnails of the first few files
+#[cfg(windows)]
+fn drag_preview(paths: &[PathBuf]) -> Option<RgbaImage> {
+    let thumbnails: Vec<RgbaImage> = paths
+        .iter()
+        .take(MAX_STACKED)
+        .filter_map(|path| match crate::thumbnail::shell_thumbnail(path, CARD_SIZE) {
+            Ok(thumbnail) => Some(thumbnail),
+            Err(e) => {
+                debug!("No drag thumbnail for {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect();
+    if thumbnails.is_empty() {
+        return None;
+    }
+
+    // Only load a font when there is a badge to draw
+    let font = overflow_badge(paths.len()).and_then(|_| crate::annotate::system_font().ok());
+    Some(stack_thumbnails(&thumbnails, paths.len(), font.as_ref()))
+}
+
+/// Hand the preview to the shell's drag image helper, which draws it under the cursor
+#[cfg(windows)]
+fn set_drag_image(
+    data_object: &windows::Win32::System::Com::IDataObject,
+    preview: &RgbaImage,
+) -> windows::core::Result<()> {
+    use windows::Win32::Foundation::{COLORREF, POINT, SIZE};
+    use windows::Win32::Graphics::Gdi::{
+        CreateDIBSection, DeleteObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+    use windows::Win32::UI::Shell::{CLSID_DragDropHelper, IDragSourceHelper, SHDRAGIMAGE};
+
+    let (width, height) = preview.dimensions();
+    let bgra = premultiplied_bgra(preview);
+
+    unsafe {
+        let helper: IDragSourceHelper =
+            CoCreateInstance(&CLSID_DragDropHelper, None, CLSCTX_INPROC_SERVER)?;
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                biHeight: -(height as i32), // Negative for top-down
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bits = std::ptr::null_mut();
+        let bitmap = CreateDIBSection(None, &info, DIB_RGB_COLORS, &mut bits, None, 0)?;
+        std::ptr::copy_nonoverlapping(bgra.as_ptr(), bits as *mut u8, bgra.len());
+
+        let drag_image = SHDRAGIMAGE {
+            sizeDragImage: SIZE {
+                cx: width as i32,
+                cy: height as i32,
+            },
+            // Cursor over the middle of the front card
+            ptOffset: POINT {
+                x: (CARD_SIZE / 2) as i32,
+                y: (BADGE_SIZE / 2 + CARD_SIZE / 2) as i32,
+            },
+            hbmpDragImage: bitmap,
+            // No color key: the alpha channel is used
+            crColorKey: COLORREF(0xFFFF_FFFF),
+        };
+        // On success the helper owns the bitmap
+        helper.InitializeFromBitmap(&drag_image, data_object).inspect_err(|_| {
+            let _ = DeleteObject(bitmap);
+        })
+    }
+}
+
+/// "+N" for the files not shown as thumbnails
+#[cfg_attr(not(windows), allow(dead_code))]
+fn overflow_badge(total: usize) -> Option<String> {
+    (total > MAX_STACKED).then(|| format!("+{}", total - MAX_STACKED))
+}
+
+/// Up to `MAX_STACKED` thumbnails on cards fanned out diagonally, the first one in front,
+/// with a "+N" badge in the top-right corner when `total` is larger
+#[cfg_attr(not(windows), allow(dead_code))]
+fn stack_thumbnails(thumbnails: &[RgbaImage], total: usize, font: Option<&FontVec>) -> RgbaImage {
+    let stacked = thumbnails.len().min(MAX_STACKED) as u32;
+    let spread = STACK_OFFSET * stacked.saturating_sub(1);
+    // The badge overhangs the stack by half its size at the top and right
+    let top = BADGE_SIZE / 2;
+    let side = CARD_SIZE + spread + BADGE_SIZE / 2;
+    let mut canvas = RgbaImage::new(side, side);
+
+    // Back to front
+    for (i, thumbnail) in thumbnails.iter().take(MAX_STACKED).enumerate().rev() {
+        let offset = STACK_OFFSET * i as u32;
+        draw_card(&mut canvas, thumbnail, offset, top + offset);
+    }
+
+    if let (Some(text), Some(font)) = (overflow_badge(total), font) {
+        let center = (canvas.width() - BADGE_SIZE / 2, BADGE_SIZE / 2);
+        draw_badge(&mut canvas, &text, center, font);
+    }
+    canvas
+}
+
+/// A bordered card at (`x`, `y`) with the thumbnail centered on it
+fn draw_card(canvas: &mut RgbaImage, thumbnail: &RgbaImage, x: u32, y: u32) {
+    for dy in 0..CARD_SIZE {
+        for dx in 0..CARD_SIZE {
+            let border = dx == 0 || dy == 0 || dx == CARD_SIZE - 1 || dy == CARD_SIZE - 1;
+            canvas.put_pixel(x + dx, y + dy, if border { CARD_BORDER } else { CARD_COLOR });
+        }
+    }
+
+    let inner = CARD_SIZE - 2 * CARD_PADDING;
+    let (width, height) = thumbnail.dimensions();
+    let scale = (inner as f32 / width.max(height).max(1) as f32).min(1.0);
+    let fitted = (
+        ((width as f32 * scale) as u32).max(1),
+        ((height as f32 * scale) as u32).max(1),
+    );
+    let resized = crate::thumbnail::resize_rgba(thumbnail.clone(), fitted.0, fitted.1);
+    image::imageops::overlay(
+        canvas,
+        &resized,
+        i64::from(x + (CARD_SIZE - fitted.0) / 2),
+        i64::from(y + (CARD_SIZE - fitted.1) / 2),
+    );
+}
+
+/// A filled circle around `center` with the text centered on it
+fn draw_badge(canvas: &mut RgbaImage, text: &str, center: (u32, u32), font: &FontVec) {
+    let [r, g, b, _] = crate::accent::rgba();
+    let radius = BADGE_SIZE as f32 / 2.0;
+    let (cx, cy) = (center.0 as f32, center.1 as f32);
+    let half = BADGE_SIZE / 2;
+    let xs = center.0.saturating_sub(half)..(center.0 + half).min(canvas.width());
+    let ys = center.1.saturating_sub(half)..(center.1 + half).min(canvas.height());
+    for y in ys {
+        for x in xs.clone() {
+            let distance = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+            // One pixel of anti-aliasing at the edge
+            let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                let alpha = (coverage * 255.0).round() as u8;
+                image::Pixel::blend(
+                    canvas.get_pixel_mut(x, y),
+                    &Rgba([r, g, b, alpha]),
+                );
+            }
+        }
+    }
+
+    // Shrink longer counts to fit inside the circle
+    let size = if text.len() > 3 { 11.0 } else { 14.0 };
+    let scaled = font.as_scaled(PxScale::from(size));
+    let width: f32 = text.chars().map(|ch| scaled.h_advance(scaled.glyph_id(ch))).sum();
+    let origin = (
+        (cx - width / 2.0).round() as i32,
+        (cy - scaled.height() / 2.0).round() as i32,
+    );
+    crate::annotate::draw_text(canvas, text, origin, size, BADGE_TEXT, font);
+}
+
+/// Rows of premultiplied BGRA, the layout `SHDRAGIMAGE` bitmaps use
+#[cfg_attr(not(windows), allow(dead_code))]
+fn premultiplied_bgra(image: &RgbaImage) -> Vec<u8> {
+    image
+        .pixels()
+        .flat_map(|&Rgba([r, g, b, a])| {
+            let premultiply = |c: u8| ((u16::from(c) * u16::from(a) + 127) / 255) as u8;
+            [premultiply(b), premultiply(g), premultiply(r), a]
+        })
+        .collect()
+}
+
+/// `file://` URL for an absolute path, percent-encoded
+/// (`C:\shots\a b.png` becomes `file:///C:/shots/a%20b.png`, UNC paths keep their host)
+#[cfg_attr(not(windows), allow(dead_code))]
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let (prefix, rest) = match path.strip_prefix("//") {
+        Some(unc) => ("file://", unc),
+        None => ("file:///", path.trim_start_matches('/')),
+    };
+
+    let mut uri = String::from(prefix);
+    for byte in rest.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// `text/uri-list` body: one URL per line, CRLF-terminated
+#[cfg_attr(not(windows), allow(dead_code))]
+fn uri_list<P: AsRef<Path>>(paths: &[P]) -> String {
+    paths
+        .iter()
+        .map(|path| format!("{}\r\n", file_uri(path.as_ref())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_drag() {
+        let start = Instant::now();
+        let owner = |secs| DragOwner {
+            thread: std::thread::current().id(),
+            started: start + Duration::from_secs(secs),
+        };
+        let mut slot = None;
+
+        assert!(claim_drag(&mut slot, owner(0)));
+        // A concurrent drag is turned away
+        assert!(!claim_drag(&mut slot, owner(5)));
+        assert_eq!(slot, Some(owner(0)));
+
+        // An orphaned one is taken over, and can't release its successor
+        assert!(claim_drag(&mut slot, owner(30)));
+        release_drag(&mut slot, owner(0));
+        assert_eq!(slot, Some(owner(30)));
+        release_drag(&mut slot, owner(30));
+        assert_eq!(slot, None);
+    }
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(
+            file_uri(Path::new(r"C:\Users\me\Screenshots\Shot 1.png")),
+            "file:///C:/Users/me/Screenshots/Shot%201.png"
+        );
+        assert_eq!(
+            file_uri(Path::new(r"\\server\share\50%#.png")),
+            "file://server/share/50%25%23.png"
+        );
+        assert_eq!(
+            file_uri(Path::new("/home/me/스크린샷.png")),
+            "file:///home/me/%EC%8A%A4%ED%81%AC%EB%A6%B0%EC%83%B7.png"
+        );
+    }
+
+    #[test]
+    fn test_overflow_badge() {
+        assert_eq!(overflow_badge(1), None);
+        assert_eq!(overflow_badge(MAX_STACKED), None);
+        assert_eq!(overflow_badge(MAX_STACKED + 5).as_deref(), Some("+5"));
+    }
+
+    #[test]
+    fn test_stack_thumbnails() {
+        let thumbnail = RgbaImage::from_pixel(150, 100, Rgba([255, 0, 0, 255]));
+
+        let single = stack_thumbnails(&[thumbnail.clone()], 1, None);
+        let side = CARD_SIZE + BADGE_SIZE / 2;
+        assert_eq!(single.dimensions(), (side, side));
+
+        // Three cards are fanned out; a fourth file only counts toward the badge
+        let four: Vec<RgbaImage> = std::iter::repeat_n(thumbnail, 4).collect();
+        let stacked = stack_thumbnails(&four, 4, None);
+        let spread = 2 * STACK_OFFSET;
+        let side = CARD_SIZE + spread + BADGE_SIZE / 2;
+        assert_eq!(stacked.dimensions(), (side, side));
+        // Front card in the top-left, back card's corner in the bottom-right
+        let top = BADGE_SIZE / 2;
+        assert_eq!(*stacked.get_pixel(0, top), CARD_BORDER);
+        let middle = CARD_SIZE / 2;
+        assert_eq!(*stacked.get_pixel(middle, top + middle), Rgba([255, 0, 0, 255]));
+        let back = spread + CARD_SIZE - 1;
+        assert_eq!(*stacked.get_pixel(back, top + back), CARD_BORDER);
+        assert_eq!(stacked.get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn test_premultiplied_bgra() {
+        let image = RgbaImage::from_vec(2, 1, vec![255, 128, 0, 255, 200, 100, 50, 0]).unwrap();
+        assert_eq!(premultiplied_bgra(&image), [0, 128, 255, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_uri_list() {
+        assert_eq!(
+            uri_list(&[r"C:\a.png", r"C:\b c.png"]),
+            "file:///C:/a.png\r\nfile:///C:/b%20c.png\r\n"
+        );
+    }
 }
@@ -0,0 +1,224 @@
+//! Which application a screenshot came from
+//!
+//! When a screenshot lands, the capture tool itself is often the foreground window.
+//! We follow foreground changes so the app that was active before it can be used instead.
+
+#[cfg(windows)]
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Number of recent foreground apps kept
+const HISTORY_LEN: usize = 8;
+
+/// Source apps remembered per screenshot
+const MAX_SOURCES: usize = 1000;
+
+/// Capture tools (and ourselves) that are skipped when picking a source app
+const CAPTURE_TOOLS: &[&str] = &[
+    "sukusho",
+    "SnippingTool",
+    "ScreenClippingHost",
+    "ScreenSketch",
+    "ShareX",
+    "Greenshot",
+    "Lightshot",
+    "Snagit32",
+    "SnagitEditor",
+];
+
+/// Recent foreground apps, most recent last
+static HISTORY: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Source app of each screenshot seen since startup, with insertion order for trimming
+static SOURCES: Mutex<Option<(HashMap<PathBuf, String>, VecDeque<PathBuf>)>> = Mutex::new(None);
+
+/// Record a foreground change
+fn push_history(app: String) {
+    let mut history = HISTORY.lock();
+    if history.back() == Some(&app) {
+        return;
+    }
+    if history.len() == HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(app);
+}
+
+/// The most recent app that isn't a capture tool
+fn pick_source<'a>(history: impl DoubleEndedIterator<Item = &'a String>) -> Option<String> {
+    history
+        .rev()
+        .find(|app| !CAPTURE_TOOLS.iter().any(|tool| tool.eq_ignore_ascii_case(app)))
+        .cloned()
+}
+
+/// Best guess at the app a screenshot arriving now was taken from
+pub fn source_app() -> Option<String> {
+    // The current foreground window may not have been reported by the hook yet
+    if let Some(current) = foreground_app_name() {
+        push_history(current);
+    }
+    pick_source(HISTORY.lock().iter())
+}
+
+/// Remember the source app of a screenshot
+pub fn record_source(path: &Path, app: &str) {
+    let mut sources = SOURCES.lock();
+    let (by_path, order) = sources.get_or_insert_with(Default::default);
+    if by_path.insert(path.to_path_buf(), app.to_string()).is_none() {
+        order.push_back(path.to_path_buf());
+        if order.len() > MAX_SOURCES {
+            if let Some(oldest) = order.pop_front() {
+                by_path.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Source app recorded for a screenshot, if it arrived while we were running
+pub fn source_of(path: &Path) -> Option<String> {
+    SOURCES
+        .lock()
+        .as_ref()
+        .and_then(|(by_path, _)| by_path.get(path).cloned())
+}
+
+/// Follow foreground window changes on a background thread
+#[cfg(windows)]
+pub fn start_tracking() {
+    std::thread::spawn(|| {
+        use windows::Win32::UI::Accessibility::SetWinEventHook;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            DispatchMessageW, GetMessageW, TranslateMessage, EVENT_SYSTEM_FOREGROUND, MSG,
+            WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS,
+        };
+
+        unsafe {
+            // Out-of-context hooks are delivered through this thread's message loop
+            let hook = SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                None,
+                Some(on_foreground_changed),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+            );
+            if hook.is_invalid() {
+                warn!("Failed to hook foreground changes; source apps may name the capture tool");
+                return;
+            }
+            info!("Tracking foreground window changes");
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn start_tracking() {
+    // Not implemented for non-Windows
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn on_foreground_changed(
+    _hook: windows::Win32::UI::Accessibility::HWINEVENTHOOK,
+    _event: u32,
+    hwnd: windows::Win32::Foundation::HWND,
+    _id_object: i32,
+    _id_child: i32,
+    _thread: u32,
+    _time: u32,
+) {
+    if crate::tray::skip_own_window(hwnd) {
+        return;
+    }
+    if let Some(app) = process_name(hwnd) {
+        debug!("Foreground app: {}", app);
+        push_history(app);
+    }
+}
+
+/// Name of the application owning the foreground window; `None` while it's our own,
+/// so the previously active app recorded in the history is used instead
+#[cfg(windows)]
+fn foreground_app_name() -> Option<String> {
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if crate::tray::skip_own_window(hwnd) {
+        return None;
+    }
+    process_name(hwnd)
+}
+
+#[cfg(not(windows))]
+fn foreground_app_name() -> Option<String> {
+    None
+}
+
+/// Executable name (without extension) of the process owning a window
+#[cfg(windows)]
+fn process_name(hwnd: windows::Win32::Foundation::HWND) -> Option<String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+    use windows::core::PWSTR;
+
+    unsafe {
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+
+        let path = PathBuf::from(String::from_utf16_lossy(&buffer[..len as usize]));
+        path.file_stem().map(|s| s.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_source_skips_capture_tools() {
+        let history: Vec<String> = ["explorer", "chrome", "snippingtool", "Sukusho"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(pick_source(history.iter()).as_deref(), Some("chrome"));
+
+        let only_tools = vec!["ShareX".to_string()];
+        assert_eq!(pick_source(only_tools.iter()), None);
+    }
+
+    #[test]
+    fn test_record_source() {
+        let path = Path::new("C:/shots/test_record_source.png");
+        assert_eq!(source_of(path), None);
+        record_source(path, "chrome");
+        assert_eq!(source_of(path).as_deref(), Some("chrome"));
+    }
+}
@@ -1,4 +1,4 @@
-//! Global hotkey management for toggling the screenshot window
+//! Global hotkey management for toggling the screenshot window and capture actions
 
 use crossbeam_channel::Sender;
 use global_hotkey::{
@@ -10,7 +10,7 @@ use parking_lot::Mutex;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::OnceLock;
 
-use crate::tray::toggle_window;
+use crate::capture::CaptureMode;
 use crate::AppMessage;
 
 /// Global flag to track if hotkey is enabled at runtime
@@ -22,6 +22,41 @@ static CURRENT_HOTKEY_ID: AtomicU32 = AtomicU32::new(0);
 /// Current registered hotkey (for unregistering)
 static CURRENT_HOTKEY: Mutex<Option<HotKey>> = Mutex::new(None);
 
+/// Actions that can be bound to their own global hotkey (besides toggling the window)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Select a screen region and save it as a screenshot
+    CaptureRegion,
+    /// Capture all monitors (or the configured one)
+    CaptureFullscreen,
+    /// Capture the foreground window
+    CaptureWindow,
+    /// Capture the foreground window straight to the clipboard
+    CaptureWindowToClipboard,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 4] = [
+        HotkeyAction::CaptureRegion,
+        HotkeyAction::CaptureFullscreen,
+        HotkeyAction::CaptureWindow,
+        HotkeyAction::CaptureWindowToClipboard,
+    ];
+
+    /// Message sent to the UI when the action's hotkey is pressed
+    fn message(self) -> AppMessage {
+        match self {
+            HotkeyAction::CaptureRegion => AppMessage::Capture(CaptureMode::Region),
+            HotkeyAction::CaptureFullscreen => AppMessage::Capture(CaptureMode::Fullscreen),
+            HotkeyAction::CaptureWindow => AppMessage::Capture(CaptureMode::ActiveWindow),
+            HotkeyAction::CaptureWindowToClipboard => AppMessage::CaptureWindowToClipboard,
+        }
+    }
+}
+
+/// Registered action hotkeys
+static ACTION_HOTKEYS: Mutex<Vec<(HotkeyAction, HotKey)>> = Mutex::new(Vec::new());
+
 /// Message sender for sending UI messages
 static MESSAGE_SENDER: OnceLock<Sender<AppMessage>> = OnceLock::new();
 
@@ -39,7 +74,7 @@ static HOTKEY_MANAGER: OnceLock<Mutex<HotKeyManagerWrapper>> = OnceLock::new();
 /// Initialize global hotkey manager with custom hotkey string
 /// IMPORTANT: Must be called from main thread before GPUI app starts
 /// The manager is stored globally for runtime hotkey updates
-pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> bool {
+pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: Option<&str>) -> bool {
     let manager = match GlobalHotKeyManager::new() {
         Ok(m) => m,
         Err(e) => {
@@ -48,32 +83,6 @@ pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> b
         }
     };
 
-    // Parse the hotkey string
-    let (modifiers, code) = match parse_hotkey_string(hotkey_str) {
-        Some((m, c)) => (m, c),
-        None => {
-            warn!(
-                "Invalid hotkey string '{}', using default Ctrl+Shift+S",
-                hotkey_str
-            );
-            (Modifiers::CONTROL | Modifiers::SHIFT, Code::KeyS)
-        }
-    };
-
-    let hotkey = HotKey::new(Some(modifiers), code);
-
-    if let Err(e) = manager.register(hotkey) {
-        error!("Failed to register hotkey {}: {:?}", hotkey_str, e);
-        return false;
-    }
-
-    info!("Registered global hotkey: {}", hotkey_str);
-
-    // Store the hotkey ID and hotkey for later updates
-    let hotkey_id = hotkey.id();
-    CURRENT_HOTKEY_ID.store(hotkey_id, Ordering::SeqCst);
-    *CURRENT_HOTKEY.lock() = Some(hotkey);
-
     // Store manager globally for runtime updates
     let _ = HOTKEY_MANAGER.set(Mutex::new(HotKeyManagerWrapper(manager)));
 
@@ -86,11 +95,14 @@ pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> b
         let receiver = GlobalHotKeyEvent::receiver();
         loop {
             if let Ok(event) = receiver.recv() {
+                if event.state != HotKeyState::Pressed {
+                    continue;
+                }
                 let current_id = CURRENT_HOTKEY_ID.load(Ordering::SeqCst);
-                if event.id == current_id && event.state == HotKeyState::Pressed {
+                if event.id == current_id {
                     if HOTKEY_ENABLED.load(Ordering::SeqCst) {
                         info!("Global hotkey pressed - toggling window");
-                        let was_shown = toggle_window();
+                        let was_shown = crate::platform::current().toggle_window();
                         // If window was shown, send message to reset to main view
                         if was_shown {
                             if let Some(sender) = MESSAGE_SENDER.get() {
@@ -100,12 +112,44 @@ pub fn init_global_hotkey(message_tx: Sender<AppMessage>, hotkey_str: &str) -> b
                     } else {
                         warn!("Global hotkey pressed but disabled");
                     }
+                } else if let Some(action) = ACTION_HOTKEYS
+                    .lock()
+                    .iter()
+                    .find(|(_, hotkey)| hotkey.id() == event.id)
+                    .map(|(action, _)| *action)
+                {
+                    info!("Action hotkey pressed: {:?}", action);
+                    if let Some(sender) = MESSAGE_SENDER.get() {
+                        let _ = sender.send(action.message());
+                    }
                 }
             }
         }
     });
 
-    true
+    // The window toggle hotkey is optional; action hotkeys are registered separately
+    let Some(hotkey_str) = hotkey_str else {
+        info!("Global hotkey disabled in settings");
+        return true;
+    };
+
+    // Fall back to the default if the stored string can't be parsed
+    let hotkey_str = if parse_hotkey_string(hotkey_str).is_some() {
+        hotkey_str
+    } else {
+        warn!(
+            "Invalid hotkey string '{}', using default Ctrl+Shift+S",
+            hotkey_str
+        );
+        "Ctrl+Shift+S"
+    };
+
+    update_hotkey(hotkey_str)
+}
+
+/// Whether the hotkey manager exists, even if the show/hide hotkey failed to register
+pub fn is_initialized() -> bool {
+    HOTKEY_MANAGER.get().is_some()
 }
 
 /// Update the global hotkey to a new key combination
@@ -123,6 +167,9 @@ pub fn update_hotkey(new_hotkey_str: &str) -> bool {
     };
 
     let new_hotkey = HotKey::new(Some(modifiers), code);
+    if *CURRENT_HOTKEY.lock() == Some(new_hotkey) {
+        return true;
+    }
 
     // Get the manager
     let manager_cell = match HOTKEY_MANAGER.get() {
@@ -164,6 +211,50 @@ pub fn update_hotkey(new_hotkey_str: &str) -> bool {
     true
 }
 
+/// Register (or re-register) the hotkey for an action; an empty string unbinds it
+pub fn update_action_hotkey(action: HotkeyAction, hotkey_str: &str) -> bool {
+    let new_hotkey = if hotkey_str.trim().is_empty() {
+        None
+    } else {
+        match parse_hotkey_string(hotkey_str) {
+            Some((modifiers, code)) => Some(HotKey::new(Some(modifiers), code)),
+            None => {
+                error!("Invalid hotkey string for {:?}: {}", action, hotkey_str);
+                return false;
+            }
+        }
+    };
+
+    let Some(manager_cell) = HOTKEY_MANAGER.get() else {
+        error!("Hotkey manager not initialized");
+        return false;
+    };
+    let manager_guard = manager_cell.lock();
+    let manager = &manager_guard.0;
+
+    let mut actions = ACTION_HOTKEYS.lock();
+    let current = actions.iter().find(|(a, _)| *a == action).map(|(_, hotkey)| *hotkey);
+    if current == new_hotkey {
+        return true;
+    }
+    if let Some(index) = actions.iter().position(|(a, _)| *a == action) {
+        let (_, old_hotkey) = actions.remove(index);
+        if let Err(e) = manager.unregister(old_hotkey) {
+            warn!("Failed to unregister old {:?} hotkey: {:?}", action, e);
+        }
+    }
+
+    if let Some(hotkey) = new_hotkey {
+        if let Err(e) = manager.register(hotkey) {
+            error!("Failed to register {:?} hotkey {}: {:?}", action, hotkey_str, e);
+            return false;
+        }
+        actions.push((action, hotkey));
+        info!("Registered {:?} hotkey: {}", action, hotkey_str);
+    }
+    true
+}
+
 /// Enable or disable the hotkey
 #[allow(dead_code)]
 pub fn set_hotkey_enabled(enabled: bool) {
@@ -1,3 +1,6 @@
+use chrono::{DateTime, Local, NaiveDate};
+use std::time::SystemTime;
+
 use crate::settings::Settings;
 
 /// Supported languages: (code, display name)
@@ -5,11 +8,15 @@ pub const SUPPORTED_LANGUAGES: &[(&str, &str)] = &[
     ("en", "English"),
     ("ko", "한국어"),
     ("ja", "日本語"),
+    ("zh-Hans", "简体中文"),
 ];
 
+/// Language used when nothing in the fallback chain is translated
+const FALLBACK_LANGUAGE: &str = "en";
+
 /// Initialize language from settings or system locale
 pub fn init_language(settings: &Settings) {
-    let locale = if let Some(ref lang) = settings.language {
+    let requested = if let Some(ref lang) = settings.language {
         // User preference
         lang.clone()
     } else {
@@ -17,31 +24,153 @@ pub fn init_language(settings: &Settings) {
         detect_system_language()
     };
 
-    rust_i18n::set_locale(&locale);
-    log::info!("Language set to: {}", locale);
+    // Walk the fallback chain (e.g. "ko-KR" -> "ko" -> "en") to a translated locale
+    let locale = resolve_language(&requested);
+
+    rust_i18n::set_locale(locale);
+    log::info!("Language set to: {} (requested: {})", locale, requested);
 }
 
-/// Detect system language and map to supported language
-fn detect_system_language() -> String {
+/// Detect system language, keeping the full locale tag (e.g. "ko-KR")
+pub fn detect_system_language() -> String {
+    // Prefer the display language the user reads the OS in over the regional format
+    if let Some(locale) = detect_ui_language() {
+        log::info!("System UI language detected: {}", locale);
+        return locale;
+    }
+
     if let Some(locale) = sys_locale::get_locale() {
         log::info!("System locale detected: {}", locale);
+        return locale;
+    }
 
-        // locale might be "en-US", "ko-KR", "ja-JP", etc.
-        // Extract the language code (first part before '-')
-        let lang = locale.split('-').next().unwrap_or("en");
+    // Fallback to English
+    log::info!("Falling back to English");
+    FALLBACK_LANGUAGE.to_string()
+}
 
-        // Check if supported
-        if SUPPORTED_LANGUAGES
-            .iter()
-            .any(|(code, _)| *code == lang)
+/// Detect the Windows display (UI) language as a BCP-47 tag
+#[cfg(windows)]
+fn detect_ui_language() -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Globalization::{
+        GetUserDefaultUILanguage, GetUserPreferredUILanguages, LCIDToLocaleName,
+        MUI_LANGUAGE_NAME,
+    };
+
+    /// Maximum locale name length including the terminator (LOCALE_NAME_MAX_LENGTH)
+    const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+    unsafe {
+        // Preferred UI languages come back as a double-null-terminated list
+        let mut count = 0u32;
+        let mut len = 0u32;
+        if GetUserPreferredUILanguages(MUI_LANGUAGE_NAME, &mut count, PWSTR::null(), &mut len)
+            .is_ok()
+            && len > 0
         {
-            return lang.to_string();
+            let mut buffer = vec![0u16; len as usize];
+            if GetUserPreferredUILanguages(
+                MUI_LANGUAGE_NAME,
+                &mut count,
+                PWSTR(buffer.as_mut_ptr()),
+                &mut len,
+            )
+            .is_ok()
+            {
+                if let Some(locale) = first_multi_sz_entry(&buffer) {
+                    return Some(locale);
+                }
+            }
+        }
+
+        // Fall back to the default UI LANGID, converted to a locale name
+        let langid = GetUserDefaultUILanguage();
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+        let written = LCIDToLocaleName(langid as u32, Some(&mut name), 0);
+        if written > 1 {
+            // Length includes the null terminator
+            return Some(String::from_utf16_lossy(&name[..(written - 1) as usize]));
         }
     }
 
-    // Fallback to English
-    log::info!("Falling back to English");
-    "en".to_string()
+    None
+}
+
+#[cfg(not(windows))]
+fn detect_ui_language() -> Option<String> {
+    None
+}
+
+/// Read the first entry of a double-null-terminated UTF-16 string list
+#[cfg_attr(not(windows), allow(dead_code))]
+fn first_multi_sz_entry(buffer: &[u16]) -> Option<String> {
+    let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    if end == 0 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..end]))
+}
+
+/// Build the lookup chain for a BCP-47 locale tag, most specific first
+///
+/// "ko-KR" -> ["ko-KR", "ko", "en"]. The Chinese script is inferred from the
+/// region when it is omitted ("zh-CN" -> "zh-Hans", "zh-TW" -> "zh-Hant").
+fn fallback_chain(locale: &str) -> Vec<String> {
+    // Some platforms report "zh_CN" or "en_US.UTF-8"
+    let locale = locale.split('.').next().unwrap_or(locale).replace('_', "-");
+    let mut subtags = locale.split('-').filter(|s| !s.is_empty());
+    let mut chain = Vec::new();
+
+    if let Some(lang) = subtags.next() {
+        let lang = lang.to_lowercase();
+        let rest: Vec<&str> = subtags.collect();
+
+        // Script subtags are 4 letters, region subtags are 2 letters or 3 digits
+        let script = rest
+            .iter()
+            .find(|s| s.len() == 4 && s.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(|s| {
+                let mut chars = s.chars();
+                let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+                format!("{}{}", first, chars.as_str().to_lowercase())
+            })
+            .or_else(|| {
+                if lang != "zh" {
+                    return None;
+                }
+                let region = rest.first().map(|r| r.to_uppercase());
+                match region.as_deref() {
+                    Some("TW") | Some("HK") | Some("MO") => Some("Hant".to_string()),
+                    _ => Some("Hans".to_string()),
+                }
+            });
+
+        if !rest.is_empty() {
+            chain.push(format!("{}-{}", lang, rest.join("-")));
+        }
+        if let Some(script) = script {
+            chain.push(format!("{}-{}", lang, script));
+        }
+        chain.push(lang);
+    }
+
+    chain.push(FALLBACK_LANGUAGE.to_string());
+    chain.dedup();
+    chain
+}
+
+/// Map a locale tag to the first supported language in its fallback chain
+fn resolve_language(locale: &str) -> &'static str {
+    fallback_chain(locale)
+        .iter()
+        .find_map(|candidate| {
+            SUPPORTED_LANGUAGES
+                .iter()
+                .find(|(code, _)| code.eq_ignore_ascii_case(candidate))
+                .map(|(code, _)| *code)
+        })
+        .unwrap_or(FALLBACK_LANGUAGE)
 }
 
 /// Get current language display name
@@ -70,16 +199,181 @@ pub fn change_language(lang: &str) {
     }
 }
 
+/// How long ago something happened, in the largest unit that fits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelativeTime {
+    JustNow,
+    Minutes(i64),
+    Hours(i64),
+    Yesterday,
+    Days(i64),
+    /// More than a week ago (or in the future)
+    Date(NaiveDate),
+}
+
+/// Describe `then` relative to `now`; days are counted by calendar date
+fn relative_time(then: DateTime<Local>, now: DateTime<Local>) -> RelativeTime {
+    let age = now.signed_duration_since(then);
+    let days = (now.date_naive() - then.date_naive()).num_days();
+    if age.num_seconds() < 0 {
+        RelativeTime::Date(then.date_naive())
+    } else if age.num_minutes() < 1 {
+        RelativeTime::JustNow
+    } else if age.num_hours() < 1 {
+        RelativeTime::Minutes(age.num_minutes())
+    } else if age.num_hours() < 24 {
+        RelativeTime::Hours(age.num_hours())
+    } else if days <= 1 {
+        RelativeTime::Yesterday
+    } else if days <= 7 {
+        RelativeTime::Days(days)
+    } else {
+        RelativeTime::Date(then.date_naive())
+    }
+}
+
+/// Plural category of `count` in a language: "one" or "other".
+/// Korean, Japanese and Chinese don't inflect for number, so they only have "other".
+fn plural_category(locale: &str, count: i64) -> &'static str {
+    let lang = locale.split(['-', '_']).next().unwrap_or(locale);
+    match lang {
+        "ko" | "ja" | "zh" => "other",
+        _ if count == 1 => "one",
+        _ => "other",
+    }
+}
+
+/// The `.one` or `.other` variant of `key` for `count` in the current language,
+/// for APIs that take a key rather than a translated string
+pub fn count_key(key: &str, count: usize) -> String {
+    format!("{}.{}", key, plural_category(&rust_i18n::locale(), count as i64))
+}
+
+/// Translate `key` in its plural form for `count`, filling in `%{count}`.
+/// Languages without plural nouns only need the `.other` variant.
+pub fn t_count(key: &str, count: usize) -> String {
+    t_count_in(key, count, &rust_i18n::locale())
+}
+
+fn t_count_in(key: &str, count: usize, locale: &str) -> String {
+    let variant = format!("{}.{}", key, plural_category(locale, count as i64));
+    t!(&variant, locale = locale, count = count).to_string()
+}
+
+fn format_relative(relative: RelativeTime, locale: &str) -> String {
+    match relative {
+        RelativeTime::JustNow => t!("time.just_now", locale = locale).to_string(),
+        RelativeTime::Minutes(n) => t_count_in("time.minutes", n as usize, locale),
+        RelativeTime::Hours(n) => t_count_in("time.hours", n as usize, locale),
+        RelativeTime::Yesterday => t!("time.yesterday", locale = locale).to_string(),
+        RelativeTime::Days(n) => t_count_in("time.days", n as usize, locale),
+        RelativeTime::Date(date) => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// "just now", "5 minutes ago", "yesterday"... in the current language;
+/// anything older than a week is shown as a date
+pub fn format_relative_time(modified: SystemTime) -> String {
+    let relative = relative_time(DateTime::<Local>::from(modified), Local::now());
+    format_relative(relative, &rust_i18n::locale())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_supported_languages() {
-        assert_eq!(SUPPORTED_LANGUAGES.len(), 3);
+        assert_eq!(SUPPORTED_LANGUAGES.len(), 4);
         assert!(SUPPORTED_LANGUAGES.iter().any(|(code, _)| *code == "en"));
         assert!(SUPPORTED_LANGUAGES.iter().any(|(code, _)| *code == "ko"));
         assert!(SUPPORTED_LANGUAGES.iter().any(|(code, _)| *code == "ja"));
+        assert!(SUPPORTED_LANGUAGES.iter().any(|(code, _)| *code == "zh-Hans"));
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        assert_eq!(fallback_chain("ko-KR"), vec!["ko-KR", "ko", "en"]);
+        assert_eq!(fallback_chain("ja"), vec!["ja", "en"]);
+        assert_eq!(fallback_chain("en-US"), vec!["en-US", "en"]);
+        assert_eq!(fallback_chain("zh-CN"), vec!["zh-CN", "zh-Hans", "zh", "en"]);
+        assert_eq!(fallback_chain(""), vec!["en"]);
+    }
+
+    #[test]
+    fn test_first_multi_sz_entry() {
+        let buffer: Vec<u16> = "ko-KR\0en-US\0\0".encode_utf16().collect();
+        assert_eq!(first_multi_sz_entry(&buffer), Some("ko-KR".to_string()));
+        assert_eq!(first_multi_sz_entry(&[0, 0]), None);
+        assert_eq!(first_multi_sz_entry(&[]), None);
+    }
+
+    #[test]
+    fn test_resolve_language() {
+        assert_eq!(resolve_language("en-US"), "en");
+        assert_eq!(resolve_language("ko-KR"), "ko");
+        assert_eq!(resolve_language("ja"), "ja");
+        // Untranslated languages fall back to English
+        assert_eq!(resolve_language("fr-FR"), "en");
+    }
+
+    #[test]
+    fn test_resolve_language_script_subtags() {
+        assert_eq!(resolve_language("zh-Hans"), "zh-Hans");
+        assert_eq!(resolve_language("zh-Hans-CN"), "zh-Hans");
+        assert_eq!(resolve_language("zh-CN"), "zh-Hans");
+        assert_eq!(resolve_language("zh_CN.UTF-8"), "zh-Hans");
+        assert_eq!(resolve_language("zh-hans-sg"), "zh-Hans");
+        // Traditional Chinese is not translated yet
+        assert_eq!(resolve_language("zh-TW"), "en");
+        assert_eq!(resolve_language("zh-Hant-HK"), "en");
+    }
+
+    #[test]
+    fn test_relative_time() {
+        use chrono::TimeZone;
+
+        let now = Local.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let at = |d, h, m| Local.with_ymd_and_hms(2024, 5, d, h, m, 0).unwrap();
+
+        assert_eq!(relative_time(at(10, 11, 59), now), RelativeTime::Minutes(1));
+        assert_eq!(relative_time(now, now), RelativeTime::JustNow);
+        assert_eq!(relative_time(at(10, 9, 30), now), RelativeTime::Hours(2));
+        // Within a day counts in hours even across midnight
+        assert_eq!(relative_time(at(9, 13, 0), now), RelativeTime::Hours(23));
+        assert_eq!(relative_time(at(9, 8, 0), now), RelativeTime::Yesterday);
+        assert_eq!(relative_time(at(3, 8, 0), now), RelativeTime::Days(7));
+        assert_eq!(
+            relative_time(at(2, 8, 0), now),
+            RelativeTime::Date(NaiveDate::from_ymd_opt(2024, 5, 2).unwrap())
+        );
+        assert_eq!(
+            relative_time(at(11, 8, 0), now),
+            RelativeTime::Date(NaiveDate::from_ymd_opt(2024, 5, 11).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_format_relative() {
+        assert_eq!(format_relative(RelativeTime::JustNow, "en"), "just now");
+        assert_eq!(format_relative(RelativeTime::Minutes(1), "en"), "1 minute ago");
+        assert_eq!(format_relative(RelativeTime::Minutes(5), "en"), "5 minutes ago");
+        assert_eq!(format_relative(RelativeTime::Days(3), "ko"), "3일 전");
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        assert_eq!(format_relative(RelativeTime::Date(date), "ja"), "2024-01-02");
+    }
+
+    #[test]
+    fn test_t_count() {
+        assert_eq!(plural_category("en", 1), "one");
+        assert_eq!(plural_category("en-US", 0), "other");
+        assert_eq!(plural_category("ko", 1), "other");
+        assert_eq!(plural_category("zh-Hans", 1), "other");
+
+        assert_eq!(t_count_in("counts.screenshots", 1, "en"), "1 screenshot");
+        assert_eq!(t_count_in("counts.screenshots", 3, "en"), "3 screenshots");
+        assert_eq!(t_count_in("counts.screenshots", 1, "ko"), "스크린샷 1개");
+        assert_eq!(t_count_in("counts.screenshots", 3, "ja"), "スクリーンショット3枚");
     }
 
     #[test]
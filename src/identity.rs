@@ -0,0 +1,129 @@
+//! Content-based identity for screenshots
+//!
+//! Paths change when files are renamed or moved; the content hash doesn't, so pins and
+//! duplicate detection can follow a screenshot across renames. Hashes are cached by path,
+//! modification time and size, so unchanged files are only read once.
+//!
+//! The hash is FNV-1a rather than a cryptographic one: it only has to tell screenshots apart,
+//! not resist tampering.
+
+use anyhow::{Context, Result};
+use log::debug;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+
+/// Hash of a file's bytes (64-bit FNV-1a, stable across builds so it can be persisted)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContentHash(pub u64);
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// What a cached hash was computed from; any change means the file must be read again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    modified: SystemTime,
+    size: u64,
+}
+
+static CACHE: LazyLock<Mutex<HashMap<PathBuf, (Stamp, ContentHash)>>> =
+    LazyLock::new(Default::default);
+
+fn stamp(path: &Path) -> Result<Stamp> {
+    let metadata = std::fs::metadata(path).with_context(|| format!("Failed to stat {:?}", path))?;
+    Ok(Stamp {
+        modified: metadata.modified()?,
+        size: metadata.len(),
+    })
+}
+
+/// Content hash of a screenshot, reading the file only if it changed since the last call
+pub fn screenshot_hash(path: &Path) -> Result<ContentHash> {
+    let stamp = stamp(path)?;
+    if let Some(&(cached, hash)) = CACHE.lock().get(path) {
+        if cached == stamp {
+            return Ok(hash);
+        }
+    }
+
+    let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let hash = hash_reader(file).with_context(|| format!("Failed to read {:?}", path))?;
+    debug!("Hashed {:?}: {}", path, hash);
+    CACHE.lock().insert(path.to_path_buf(), (stamp, hash));
+    Ok(hash)
+}
+
+/// The cached hash, if the file hasn't changed since it was computed; never reads the file
+pub fn cached_hash(path: &Path) -> Option<ContentHash> {
+    let stamp = stamp(path).ok()?;
+    CACHE
+        .lock()
+        .get(path)
+        .filter(|(cached, _)| *cached == stamp)
+        .map(|&(_, hash)| hash)
+}
+
+/// FNV-1a over everything `reader` yields
+fn hash_reader(mut reader: impl Read) -> std::io::Result<ContentHash> {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            return Ok(ContentHash(hash));
+        }
+        for &byte in &buf[..read] {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_reader() {
+        // Reference values for FNV-1a 64
+        assert_eq!(hash_reader(&b""[..]).unwrap(), ContentHash(0xcbf2_9ce4_8422_2325));
+        assert_eq!(hash_reader(&b"a"[..]).unwrap(), ContentHash(0xaf63_dc4c_8601_ec8c));
+        assert_eq!(ContentHash(0xab).to_string(), "00000000000000ab");
+    }
+
+    #[test]
+    fn test_screenshot_hash_cache() {
+        let dir = std::env::temp_dir().join(format!("sukusho-identity-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("first.png");
+        let renamed = dir.join("renamed.png");
+        std::fs::write(&first, b"pixels").unwrap();
+
+        assert_eq!(cached_hash(&first), None);
+        let hash = screenshot_hash(&first).unwrap();
+        assert_eq!(cached_hash(&first), Some(hash));
+
+        // Same bytes under another name are the same screenshot
+        std::fs::rename(&first, &renamed).unwrap();
+        assert_eq!(screenshot_hash(&renamed).unwrap(), hash);
+
+        // A rewrite changes the size, so the stale entry isn't used
+        std::fs::write(&renamed, b"other pixels").unwrap();
+        assert_ne!(screenshot_hash(&renamed).unwrap(), hash);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
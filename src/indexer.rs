@@ -391,6 +391,12 @@ impl IndexerState {
         let delay_ms = self.config.cpu_mode.delay_ms();
 
         for (chunk_idx, chunk) in files.chunks(batch_size).enumerate() {
+            // Finish the current batch but don't start another while quitting
+            if crate::session::is_shutting_down() {
+                info!("Indexing stopped for shutdown after {} files", *indexed_count);
+                break;
+            }
+
             let file_path_strings: Vec<String> = chunk
                 .iter()
                 .filter_map(|p| p.to_str().map(|s| s.to_string()))
@@ -0,0 +1,187 @@
+//! Checks for very large images before they are copied or uploaded
+//!
+//! Multi-monitor grabs can be 8K wide, which makes copying slow and can exceed what upload
+//! services accept. Depending on `Settings::large_image_action`, such images are passed on
+//! with a warning, downscaled first, or passed on silently.
+
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+
+use crate::settings::{LargeImageAction, Settings};
+use crate::tray::NotificationKind;
+
+/// Limits from settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LargeImagePolicy {
+    /// Longest side in pixels
+    pub max_dimension: u32,
+    /// Encoded size in bytes
+    pub max_bytes: u64,
+    pub action: LargeImageAction,
+}
+
+impl LargeImagePolicy {
+    /// Whether an image of this size exceeds a limit; `bytes` is unknown for unsaved images
+    pub fn is_large(&self, (width, height): (u32, u32), bytes: Option<u64>) -> bool {
+        width.max(height) > self.max_dimension || bytes.is_some_and(|b| b > self.max_bytes)
+    }
+
+    /// Size that brings the image within both limits, keeping the aspect ratio
+    ///
+    /// Encoded size roughly follows the pixel count, so the byte limit scales both sides by
+    /// its square root.
+    pub fn fit(&self, (width, height): (u32, u32), bytes: Option<u64>) -> (u32, u32) {
+        let by_dimension = self.max_dimension as f64 / width.max(height).max(1) as f64;
+        let by_bytes = bytes
+            .filter(|&b| b > 0)
+            .map_or(1.0, |b| (self.max_bytes as f64 / b as f64).sqrt());
+        let scale = by_dimension.min(by_bytes).min(1.0);
+        (
+            ((width as f64 * scale) as u32).max(1),
+            ((height as f64 * scale) as u32).max(1),
+        )
+    }
+}
+
+/// Check an image about to be copied; returns a downscaled copy if that is configured
+pub fn check_image(image: &RgbaImage, bytes: Option<u64>) -> Option<RgbaImage> {
+    let (width, height) = review(image.dimensions(), bytes)?;
+    Some(crate::thumbnail::resize_rgba(image.clone(), width, height))
+}
+
+/// File to upload for `path`: the file itself, or a downscaled copy if that is configured
+pub fn prepare_upload(path: &Path) -> Result<UploadFile> {
+    let bytes = std::fs::metadata(path).ok().map(|m| m.len());
+    // Not an image we can read: the upload itself decides what to do with it
+    let fitted = image::image_dimensions(path)
+        .ok()
+        .and_then(|dimensions| review(dimensions, bytes));
+    let Some((width, height)) = fitted else {
+        return Ok(UploadFile {
+            path: path.to_path_buf(),
+            temporary: false,
+        });
+    };
+
+    let image = image::open(path)
+        .with_context(|| format!("Failed to decode {:?}", path))?
+        .to_rgba8();
+    let resized = crate::thumbnail::resize_rgba(image, width, height);
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "upload".to_string());
+    let temp = std::env::temp_dir().join(format!("sukusho-{}-{}.png", std::process::id(), name));
+    resized
+        .save(&temp)
+        .with_context(|| format!("Failed to write downscaled copy {:?}", temp))?;
+    Ok(UploadFile {
+        path: temp,
+        temporary: true,
+    })
+}
+
+/// Apply the configured action to a large image; returns the size to downscale to, if any
+fn review(dimensions: (u32, u32), bytes: Option<u64>) -> Option<(u32, u32)> {
+    let settings = crate::settings_store::get();
+    let policy = settings.large_image_policy();
+    if !policy.is_large(dimensions, bytes) {
+        return None;
+    }
+
+    let size = describe(dimensions, bytes);
+    match policy.action {
+        LargeImageAction::Proceed => {
+            info!("Passing on large image ({})", size);
+            None
+        }
+        LargeImageAction::Warn => {
+            warn!("Large image ({})", size);
+            notify(&settings, NotificationKind::Warning, "notifications.large_image.warning", &size);
+            None
+        }
+        LargeImageAction::Downscale => {
+            let fitted = policy.fit(dimensions, bytes);
+            info!("Downscaling large image ({}) to {}x{}", size, fitted.0, fitted.1);
+            notify(
+                &settings,
+                NotificationKind::Info,
+                "notifications.large_image.downscaled",
+                &describe(fitted, None),
+            );
+            Some(fitted)
+        }
+    }
+}
+
+/// A file to upload; a downscaled temporary copy is deleted when dropped
+pub struct UploadFile {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl UploadFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for UploadFile {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// "7680×2160, 12.3 MiB", or just the dimensions
+fn describe((width, height): (u32, u32), bytes: Option<u64>) -> String {
+    match bytes {
+        Some(bytes) => format!(
+            "{}×{}, {}",
+            width,
+            height,
+            crate::app::format_file_size(bytes)
+        ),
+        None => format!("{}×{}", width, height),
+    }
+}
+
+fn notify(settings: &Settings, kind: NotificationKind, key: &str, size: &str) {
+    crate::tray::notify(settings, kind, "notifications.title", key, &[("size", size)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: LargeImagePolicy = LargeImagePolicy {
+        max_dimension: 7680,
+        max_bytes: 20 * 1024 * 1024,
+        action: LargeImageAction::Downscale,
+    };
+
+    #[test]
+    fn test_is_large() {
+        assert!(!POLICY.is_large((7680, 2160), None));
+        assert!(POLICY.is_large((7681, 2160), None));
+        assert!(POLICY.is_large((1920, 1080), Some(30 * 1024 * 1024)));
+        assert!(!POLICY.is_large((1920, 1080), Some(1024)));
+    }
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(POLICY.fit((15360, 4320), None), (7680, 2160));
+        assert_eq!(POLICY.fit((1920, 1080), None), (1920, 1080));
+        // Four times the byte limit halves both sides
+        assert_eq!(POLICY.fit((2000, 1000), Some(80 * 1024 * 1024)), (1000, 500));
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe((7680, 2160), None), "7680×2160");
+        assert_eq!(describe((7680, 2160), Some(2048)), "7680×2160, 2 KiB");
+    }
+}
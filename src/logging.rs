@@ -0,0 +1,166 @@
+//! Log sink: stderr plus a size-capped, rotating file
+//!
+//! Files live in `%LOCALAPPDATA%\sukusho\logs\` as `sukusho.log`, `sukusho.1.log`, ... so
+//! users can send them in without ever seeing a console.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Size at which the current file is rotated
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept besides the current one
+const KEEP_LOG_FILES: usize = 4;
+
+const LOG_NAME: &str = "sukusho";
+
+/// Folder holding the log files
+pub fn log_dir() -> Option<PathBuf> {
+    directories::BaseDirs::new().map(|dirs| dirs.data_local_dir().join("sukusho").join("logs"))
+}
+
+/// Initialize `log` with `level` (`RUST_LOG` wins if set), writing to stderr and the log file
+pub fn init(level: &str) {
+    let file = log_dir().and_then(|dir| match RotatingFile::open(&dir, MAX_LOG_BYTES, KEEP_LOG_FILES) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Failed to open log file in {:?}: {}", dir, e);
+            None
+        }
+    });
+
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level))
+        .target(env_logger::Target::Pipe(Box::new(Tee { file })))
+        .init();
+
+    if let Some(dir) = log_dir() {
+        log::info!("Logging at {} to {:?}", level, dir);
+    }
+}
+
+/// Writes everything to stderr and, if it could be opened, the log file
+struct Tee {
+    file: Option<RotatingFile>,
+}
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _ = io::stderr().write_all(buf);
+        if let Some(file) = &mut self.file {
+            // A full disk shouldn't take logging to stderr down with it
+            let _ = file.write_all(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _ = io::stderr().flush();
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `name.log`, rotated to `name.1.log` ... `name.<keep>.log` once it reaches `max_bytes`
+struct RotatingFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: &Path, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = Self::open_current(dir)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            keep,
+            file,
+            written,
+        })
+    }
+
+    fn open_current(dir: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(file_name(0)))
+    }
+
+    /// Shift every file up by one, dropping the oldest, and start a new current file
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let _ = fs::remove_file(self.dir.join(file_name(self.keep)));
+        for index in (0..self.keep).rev() {
+            let from = self.dir.join(file_name(index));
+            if from.exists() {
+                fs::rename(from, self.dir.join(file_name(index + 1)))?;
+            }
+        }
+        self.file = Self::open_current(&self.dir)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Records arrive whole, so rotating before one keeps it in a single file
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// `sukusho.log` for the current file, `sukusho.<index>.log` for rotated ones
+fn file_name(index: usize) -> String {
+    match index {
+        0 => format!("{}.log", LOG_NAME),
+        _ => format!("{}.{}.log", LOG_NAME, index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = std::env::temp_dir().join(format!("sukusho-logs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut file = RotatingFile::open(&dir, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |index| fs::read_to_string(dir.join(file_name(index))).unwrap();
+        assert_eq!(read(0), "fourth\n");
+        assert_eq!(read(1), "third\n");
+        assert_eq!(read(2), "second\n");
+        // Only `keep` rotated files are kept
+        assert!(!dir.join(file_name(3)).exists());
+
+        // Reopening appends to the current file
+        drop(file);
+        let mut file = RotatingFile::open(&dir, 100, 2).unwrap();
+        file.write_all(b"fifth\n").unwrap();
+        assert_eq!(read(0), "fourth\nfifth\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
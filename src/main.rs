@@ -8,20 +8,49 @@ extern crate rust_i18n;
 // Initialize i18n with fallback to English
 i18n!("locales", fallback = "en");
 
+mod accent;
+mod annotate;
 mod app;
+mod autostart;
+mod capture;
+mod cli;
 mod clipboard;
+mod clipboard_monitor;
+mod contact_sheet;
 mod convert;
+mod crop;
+mod dedup;
+mod delete;
+mod doctor;
 mod drag_drop;
+mod foreground;
 mod hotkey;
 mod i18n_helpers;
+mod identity;
 mod indexer;
+mod large_image;
+mod logging;
+mod ocr;
 mod organizer;
+mod pins;
+mod platform;
+mod recent;
+mod redact;
+mod rename;
+mod retention;
+mod search;
+mod session;
 mod settings;
+mod settings_store;
+mod settings_watcher;
 mod thumbnail;
 mod tray;
+mod tray_preview;
 mod ui;
 mod update_checker;
+mod upload;
 mod watcher;
+mod watermark;
 
 use anyhow::Result;
 use crossbeam_channel::{unbounded, Receiver, Sender};
@@ -32,11 +61,13 @@ use single_instance::SingleInstance;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::app::Sukusho;
-use crate::hotkey::init_global_hotkey;
-use crate::settings::Settings;
+use crate::app::{ScreenshotInfo, SettingsSection, Sukusho};
+use crate::capture::CaptureMode;
+use crate::hotkey::{init_global_hotkey, update_action_hotkey, HotkeyAction};
+use crate::settings::{OpenAction, Settings, SortOrder};
+use crate::settings_watcher::SettingsWatcher;
 use crate::tray::TrayManager;
-use crate::watcher::ScreenshotWatcher;
+use crate::watcher::{ScreenshotWatcher, WatcherHandle};
 
 /// Allocate a console window for debugging output (Windows only)
 #[cfg(windows)]
@@ -64,16 +95,63 @@ pub enum AppMessage {
     NewScreenshot(PathBuf, bool),
     /// Screenshot removed
     ScreenshotRemoved(PathBuf),
+    /// Files dragged out of the app were dropped on a target
+    DragCompleted {
+        files: Vec<PathBuf>,
+        effect: crate::platform::DropEffect,
+    },
+    /// The watcher finished announcing existing files at startup
+    InitialScanComplete { count: usize },
+    /// Deleting screenshots failed (error message)
+    DeleteFailed(String),
     /// Toggle window visibility (from tray click)
     ToggleWindow,
     /// Show main window (not settings) from tray icon click
     ShowMainWindow,
-    /// Open settings
-    OpenSettings,
+    /// Open settings, at a section if given
+    OpenSettings(Option<SettingsSection>),
+    /// Start a screen capture (from a capture hotkey)
+    Capture(CaptureMode),
+    /// Capture the foreground window to the clipboard (from its hotkey)
+    CaptureWindowToClipboard,
+    /// Screen capture saved to the given file
+    CaptureSaved(PathBuf),
+    /// Screen capture copied to the clipboard without saving a file
+    CaptureCopied,
+    /// Screen capture failed (error message)
+    CaptureFailed(String),
+    /// Show a screenshot in the in-app preview
+    ShowPreview(PathBuf),
+    /// Run OCR on a screenshot and copy the text to the clipboard
+    ExtractText(PathBuf),
+    /// OCR text copied to clipboard (character count, 0 if no text was found)
+    TextExtracted(usize),
+    /// OCR failed (localized error message)
+    TextExtractionFailed(String),
+    /// Scan all screenshots for near-duplicates (from the tray menu)
+    FindDuplicates,
+    /// Duplicate scan finished (groups of similar screenshots, newest first)
+    DuplicatesFound(Vec<Vec<PathBuf>>),
+    /// A gallery thumbnail from `ThumbnailCache` finished generating
+    ThumbnailReady,
+    /// Pin or unpin a screenshot (from the tray menu)
+    TogglePin(PathBuf),
+    /// Upload a screenshot with the configured uploader
+    Upload(PathBuf),
+    /// Upload finished; URL copied to clipboard
+    UploadCompleted(String),
+    /// Upload failed (localized error message)
+    UploadFailed(String),
+    /// Toggle launch at login (from the tray menu)
+    ToggleAutostart,
+    /// Toggle keeping the window above other apps (from the tray menu)
+    ToggleAlwaysOnTop,
     /// Change screenshot directory
     ChangeDirectory(PathBuf),
-    /// Request latest screenshot path (for tray drag)
-    RequestLatestScreenshot,
+    /// Rescan the screenshot folder for changes the watcher missed (from the tray menu)
+    Rescan,
+    /// Rescan finished (the folder scanned, every screenshot found in it, whether to notify)
+    RescanCompleted { directory: PathBuf, found: Vec<PathBuf>, report: bool },
     /// Organization started with total file count
     OrganizeStarted(usize),
     /// Organization progress update (current, total, current_file)
@@ -104,23 +182,124 @@ pub enum AppMessage {
     SearchQuery(String),
     /// Search results returned
     SearchResults(Vec<PathBuf>),
+    /// Settings changed outside the settings UI: the file was edited, or `settings_store::update`
+    SettingsReloaded(Settings),
+    /// Settings exported to the given file
+    SettingsExported(PathBuf),
+    /// Settings imported from a file and saved
+    SettingsImported(Settings),
+    /// Settings export or import failed (error message)
+    SettingsTransferFailed(String),
+    /// Display language changed at runtime (language code)
+    LanguageChanged(String),
+    /// Windows switched between light and dark
+    SystemThemeChanged,
+    /// Display resolution or scaling changed; the tray icon may need redrawing at a new size
+    DisplayScaleChanged,
+    /// Try creating the tray icon again (attempt number, from 1)
+    CreateTray(u32),
+    /// Explorer (re)started and the taskbar can take tray icons again
+    TaskbarCreated,
+    /// Checking for updates failed
+    UpdateCheckFailed,
+    /// A hotkey couldn't be registered, usually because another app has it (hotkey)
+    HotkeyConflict(String),
     /// Files copied to clipboard (count)
     CopiedToClipboard(usize),
     /// Quit application
     Quit,
 }
 
-/// Shared latest screenshot path for tray icon drag
-pub static LATEST_SCREENSHOT: parking_lot::Mutex<Option<PathBuf>> = parking_lot::Mutex::new(None);
+/// Get the latest screenshot path
+pub fn get_latest_screenshot() -> Option<PathBuf> {
+    recent::latest().map(|s| s.path)
+}
 
-/// Set the latest screenshot path
-pub fn set_latest_screenshot(path: Option<PathBuf>) {
-    *LATEST_SCREENSHOT.lock() = path;
+/// Known screenshots in the given order, for consumers outside the gallery view
+///
+/// Served from the in-memory recent list, so the folder is never re-read.
+pub fn list_screenshots(sort: SortOrder, limit: Option<usize>) -> Vec<ScreenshotInfo> {
+    recent::list(sort, limit)
 }
 
-/// Get the latest screenshot path
-pub fn get_latest_screenshot() -> Option<PathBuf> {
-    LATEST_SCREENSHOT.lock().clone()
+/// Open a screenshot the way `Settings::open_action` says
+pub fn open_screenshot(path: &std::path::Path, action: OpenAction, message_tx: &Sender<AppMessage>) {
+    info!("Opening {:?} ({:?})", path, action);
+    match action {
+        OpenAction::Internal => {
+            platform::current().show_window();
+            let _ = message_tx.send(AppMessage::ShowPreview(path.to_path_buf()));
+        }
+        OpenAction::DefaultApp => {
+            if let Err(e) = open::that(path) {
+                warn!("Failed to open {:?}: {}", path, e);
+            }
+        }
+        OpenAction::Reveal => {
+            if !platform::current().reveal(path) {
+                // Without a file manager that can select, the folder is the next best thing
+                if let Some(dir) = path.parent() {
+                    if let Err(e) = open::that(dir) {
+                        warn!("Failed to open {:?}: {}", dir, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Conditions the latest screenshot must meet in `get_latest_screenshot_filtered`
+#[derive(Debug, Clone, Default)]
+pub struct LatestOpts {
+    /// File must not have been modified for this long (i.e. it has settled)
+    pub min_age: Option<std::time::Duration>,
+    /// Allowed extensions, lowercase (None = any)
+    pub extensions: Option<Vec<String>>,
+    /// Minimum file size in bytes
+    pub min_size: u64,
+}
+
+impl LatestOpts {
+    /// Skip files that are likely still being written
+    pub fn settled() -> Self {
+        Self {
+            min_age: Some(std::time::Duration::from_millis(500)),
+            extensions: None,
+            min_size: 1,
+        }
+    }
+
+    fn accepts(
+        &self,
+        path: &std::path::Path,
+        len: u64,
+        modified: std::time::SystemTime,
+        now: std::time::SystemTime,
+    ) -> bool {
+        let extension_ok = self.extensions.as_ref().is_none_or(|allowed| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| allowed.iter().any(|a| ext.eq_ignore_ascii_case(a)))
+        });
+        // A modification time in the future counts as not settled
+        let age_ok = self.min_age.is_none_or(|min_age| {
+            now.duration_since(modified).is_ok_and(|age| age >= min_age)
+        });
+        extension_ok && age_ok && len >= self.min_size
+    }
+}
+
+/// Get the latest screenshot path if it meets the given conditions
+pub fn get_latest_screenshot_filtered(opts: &LatestOpts) -> Option<PathBuf> {
+    let path = get_latest_screenshot()?;
+    let metadata = std::fs::metadata(&path).ok()?;
+    let modified = metadata.modified().ok()?;
+    if opts.accepts(&path, metadata.len(), modified, std::time::SystemTime::now()) {
+        Some(path)
+    } else {
+        log::debug!("Latest screenshot {:?} rejected by {:?}", path, opts);
+        None
+    }
 }
 
 /// Global application state shared across threads
@@ -129,6 +308,8 @@ pub struct AppState {
     pub message_tx: Sender<AppMessage>,
     pub message_rx: Receiver<AppMessage>,
     pub tray_manager: Arc<Mutex<Option<TrayManager>>>,
+    /// Running screenshot directory watcher, replaced on directory change
+    pub watcher: Arc<Mutex<Option<WatcherHandle>>>,
     pub hide_window_on_start: bool,
 }
 
@@ -147,34 +328,86 @@ fn main() -> Result<()> {
         }
     }
 
-    // Initialize logging - write to file in console mode for easier debugging
-    let log_level = if console_mode { "debug" } else { "info" };
-    
+    // Initialize logging to stderr and the rotating log file; console mode always logs everything.
+    // Settings are read again below, once their own log messages can be recorded.
+    let log_level = if console_mode {
+        "debug".to_string()
+    } else {
+        Settings::load().map(|s| s.log_level).unwrap_or_else(|_| Settings::default().log_level)
+    };
+    logging::init(&log_level);
+
     if console_mode {
-        // Log to file for easier reading
-        let log_file = std::fs::File::create("sukusho_debug.log").expect("Failed to create log file");
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
-            .target(env_logger::Target::Pipe(Box::new(log_file)))
-            .init();
         println!("=== Sukusho Debug Console ===");
-        println!("Logging to: sukusho_debug.log");
+        if let Some(dir) = logging::log_dir() {
+            println!("Logging to: {}", dir.display());
+        }
         println!("Logging level: {}", log_level);
-    } else {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
     }
 
+    // One-shot actions like `--capture region` run and exit without a tray icon
+    let command = match cli::parse(&args[1..]) {
+        Ok(command) => command,
+        Err(e) => {
+            cli::attach_parent_console();
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
     info!("Starting Sukusho...");
+    platform::init();
 
-    // Single instance check - prevent multiple copies from running
+    // Single instance check (a named mutex) - prevent multiple copies from running
     let instance = SingleInstance::new("sukusho-screenshot-manager").unwrap();
+    let show_requested = command == Some(cli::Command::Show);
+    if let Some(command) = command.filter(|c| *c != cli::Command::Show) {
+        cli::attach_parent_console();
+        let other_instance = !instance.is_single();
+        if other_instance && command != cli::Command::Doctor {
+            // Let the running instance do it, so no second tray icon appears
+            return cli::forward(&command);
+        }
+        let settings = Settings::load().unwrap_or_default();
+        i18n_helpers::init_language(&settings);
+        return cli::run(&command, &settings, other_instance);
+    }
     if !instance.is_single() {
-        warn!("Another instance of Sukusho is already running");
+        // A second tray icon and watcher would fight over the folder; bring up the first one
+        info!("Another instance of Sukusho is already running - asking it to show its window");
+        if let Err(e) = cli::forward(&cli::Command::Show) {
+            warn!("Failed to reach the running instance: {}", e);
+        }
         return Ok(());
     }
     info!("Single instance check passed");
 
     // Load settings
-    let settings = Settings::load().unwrap_or_default();
+    let mut settings = Settings::load().unwrap_or_default();
+
+    // The Run key is the source of truth: it can be removed by other tools while we're not running
+    let autostart = autostart::is_autostart_enabled();
+    if settings.autostart != autostart {
+        info!("Syncing autostart setting with registry: {}", autostart);
+        settings.autostart = autostart;
+        let _ = settings.save();
+    }
+    if autostart {
+        // Refresh the stored path in case the executable moved
+        if let Err(e) = autostart::set_autostart(true) {
+            warn!("Failed to refresh startup registration: {}", e);
+        }
+    }
+
+    pins::load();
+    accent::apply(&settings.accent_color);
+    tray::set_tray_drag_enabled(settings.enable_tray_drag);
+    tray::set_always_on_top(settings.always_on_top);
+    tray::set_exclude_own_windows(settings.exclude_own_windows);
+    thumbnail::set_decode_limits(
+        settings.max_decode_pixels,
+        std::time::Duration::from_millis(settings.thumbnail_open_timeout_ms),
+    );
 
     // Log settings file location
     if let Some(config_path) = Settings::config_path() {
@@ -187,7 +420,7 @@ fn main() -> Result<()> {
     let screenshot_dir = settings.screenshot_directory.clone();
     let window_width = settings.window_width;
     let window_height = settings.window_height;
-    let hide_window_on_start = settings.hide_window_on_start;
+    let hide_window_on_start = settings.hide_window_on_start && !show_requested;
 
     info!("Loaded window size from settings: {}x{} (will be used directly as GPUI logical pixels)", window_width, window_height);
 
@@ -212,31 +445,77 @@ fn main() -> Result<()> {
 
     // Create tray icon before starting gpui
     let tray_message_tx = message_tx.clone();
-    let tray_manager = TrayManager::new(tray_message_tx)?;
+    // Without a taskbar yet (Explorer starting or restarting) this fails; keep running and retry
+    let tray_manager = match TrayManager::new(tray_message_tx, Arc::clone(&settings)) {
+        Ok(tray_manager) => Some(tray_manager),
+        Err(e) => {
+            warn!("Failed to create tray icon, retrying: {:#}", e);
+            tray::schedule_tray_retry(message_tx.clone(), 1);
+            None
+        }
+    };
+
+    // Listen for logoff/shutdown and display changes; also owns quitting
+    session::spawn(Arc::clone(&settings), message_tx.clone());
+    settings_store::init(Arc::clone(&settings), Some(message_tx.clone()));
 
-    // Initialize global hotkey with custom setting
+    // Accept commands from `sukusho --capture ...` and friends
+    cli::serve(Arc::clone(&settings), message_tx.clone());
+    update_checker::start_periodic_checks();
+
+    // Initialize global hotkeys with custom settings
     let hotkey_message_tx = message_tx.clone();
-    let (hotkey_str, hotkey_enabled) = {
+    let (hotkey_str, hotkey_enabled, capture_hotkeys) = {
         let s = settings.lock();
-        (s.hotkey.clone(), s.hotkey_enabled)
+        (
+            s.hotkey.clone(),
+            s.hotkey_enabled,
+            [
+                (HotkeyAction::CaptureRegion, s.capture_hotkey.clone()),
+                (HotkeyAction::CaptureFullscreen, s.capture_fullscreen_hotkey.clone()),
+                (HotkeyAction::CaptureWindow, s.capture_window_hotkey.clone()),
+                (
+                    HotkeyAction::CaptureWindowToClipboard,
+                    s.capture_window_clipboard_hotkey.clone(),
+                ),
+            ],
+        )
     };
-    if hotkey_enabled {
-        if !init_global_hotkey(hotkey_message_tx, &hotkey_str) {
-            warn!("Failed to initialize global hotkey");
+    if !init_global_hotkey(hotkey_message_tx, hotkey_enabled.then_some(hotkey_str.as_str())) {
+        warn!("Failed to initialize global hotkey");
+        if hotkey_enabled {
+            let _ = message_tx.send(AppMessage::HotkeyConflict(hotkey_str.clone()));
+        }
+    }
+    // Capture hotkeys only need the manager, not the show/hide hotkey
+    if hotkey::is_initialized() {
+        for (action, hotkey) in &capture_hotkeys {
+            if !update_action_hotkey(*action, hotkey) {
+                warn!("Failed to register {:?} hotkey", action);
+                let _ = message_tx.send(AppMessage::HotkeyConflict(hotkey.clone()));
+            }
         }
-    } else {
-        info!("Global hotkey disabled in settings");
     }
 
+    // Follow the active app so screenshots can be attributed to it
+    foreground::start_tracking();
+
     // Start file watcher in background thread
     let watcher_tx = message_tx.clone();
     let watcher_dir = screenshot_dir.clone();
     let watcher_settings = Arc::clone(&settings);
-    std::thread::spawn(move || {
-        if let Err(e) = ScreenshotWatcher::new(watcher_dir, watcher_tx, watcher_settings).run() {
-            error!("File watcher error: {}", e);
-        }
-    });
+    let watcher = ScreenshotWatcher::new(watcher_dir, watcher_tx, watcher_settings).spawn();
+
+    // Watch the settings file for external edits
+    if let Some(config_path) = Settings::config_path() {
+        let settings_tx = message_tx.clone();
+        let watched_settings = Arc::clone(&settings);
+        std::thread::spawn(move || {
+            if let Err(e) = SettingsWatcher::new(config_path, settings_tx, watched_settings).run() {
+                error!("Settings watcher error: {}", e);
+            }
+        });
+    }
 
     // Run the GPUI application
     let app = Application::new();
@@ -250,7 +529,8 @@ fn main() -> Result<()> {
             settings: Arc::clone(&settings),
             message_tx,
             message_rx,
-            tray_manager: Arc::new(Mutex::new(Some(tray_manager))),
+            tray_manager: Arc::new(Mutex::new(tray_manager)),
+            watcher: Arc::new(Mutex::new(Some(watcher))),
             hide_window_on_start,
         });
 
@@ -306,3 +586,32 @@ fn main() -> Result<()> {
     info!("Sukusho shutting down...");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_latest_opts_accepts() {
+        let now = SystemTime::now();
+        let old = now - Duration::from_secs(2);
+        let path = Path::new("shot.PNG");
+
+        assert!(LatestOpts::default().accepts(path, 0, now, now));
+
+        let settled = LatestOpts::settled();
+        assert!(settled.accepts(path, 100, old, now));
+        assert!(!settled.accepts(path, 100, now, now));
+        assert!(!settled.accepts(path, 0, old, now));
+
+        let png_only = LatestOpts {
+            extensions: Some(vec!["png".to_string()]),
+            ..Default::default()
+        };
+        assert!(png_only.accepts(path, 0, now, now));
+        assert!(!png_only.accepts(Path::new("shot.tmp"), 0, now, now));
+        assert!(!png_only.accepts(Path::new("shot"), 0, now, now));
+    }
+}
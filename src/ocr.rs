@@ -0,0 +1,117 @@
+//! Text extraction from screenshots using the Windows OCR engine (Windows.Media.Ocr)
+
+use anyhow::Result;
+use std::fmt;
+use std::path::Path;
+
+/// No OCR recognizer is installed for the requested language
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageNotInstalled {
+    pub language: String,
+}
+
+impl fmt::Display for LanguageNotInstalled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OCR language pack not installed: {}", self.language)
+    }
+}
+
+impl std::error::Error for LanguageNotInstalled {}
+
+/// Whether an installed recognizer tag covers the UI language tag
+/// (e.g. "en" matches "en-US", "zh-Hans" matches "zh-Hans-CN")
+#[cfg_attr(not(windows), allow(dead_code))]
+fn language_matches(ui_tag: &str, available_tag: &str) -> bool {
+    let ui = ui_tag.to_ascii_lowercase();
+    let available = available_tag.to_ascii_lowercase();
+    ui == available
+        || available.starts_with(&format!("{}-", ui))
+        || ui.starts_with(&format!("{}-", available))
+}
+
+/// Pick the installed recognizer language for the UI language
+#[cfg_attr(not(windows), allow(dead_code))]
+fn pick_language<'a>(ui_tag: &str, available: &'a [String]) -> Option<&'a str> {
+    available
+        .iter()
+        .find(|tag| language_matches(ui_tag, tag))
+        .map(|tag| tag.as_str())
+}
+
+/// Extract text from an image file, one line of output per recognized line.
+/// The recognizer language follows the current UI locale.
+#[cfg(windows)]
+pub fn extract_text(path: &Path) -> Result<String> {
+    use anyhow::bail;
+    use log::info;
+    use windows::Globalization::Language;
+    use windows::Graphics::Imaging::BitmapDecoder;
+    use windows::Media::Ocr::OcrEngine;
+    use windows::Storage::{FileAccessMode, StorageFile};
+    use windows::core::HSTRING;
+
+    // WinRT calls need an apartment on this worker thread; already-initialized is fine.
+    // Declared first so every WinRT object is released before COM is uninitialized.
+    let _com = crate::platform::ComGuard::multithreaded();
+
+    let ui_tag = rust_i18n::locale().to_string();
+    let available: Vec<String> = OcrEngine::AvailableRecognizerLanguages()?
+        .into_iter()
+        .filter_map(|language| language.LanguageTag().ok())
+        .map(|tag| tag.to_string())
+        .collect();
+    let Some(tag) = pick_language(&ui_tag, &available) else {
+        return Err(LanguageNotInstalled { language: ui_tag }.into());
+    };
+
+    let language = Language::CreateLanguage(&HSTRING::from(tag))?;
+    let engine = OcrEngine::TryCreateFromLanguage(&language)?;
+
+    let absolute = std::fs::canonicalize(path)?;
+    // canonicalize adds a \\?\ prefix that StorageFile rejects
+    let absolute = absolute.to_string_lossy();
+    let absolute = absolute.trim_start_matches(r"\\?\");
+    let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(absolute))?.get()?;
+    let stream = file.OpenAsync(FileAccessMode::Read)?.get()?;
+    let decoder = BitmapDecoder::CreateAsync(&stream)?.get()?;
+
+    let max = OcrEngine::MaxImageDimension()?;
+    let (width, height) = (decoder.PixelWidth()?, decoder.PixelHeight()?);
+    if width > max || height > max {
+        bail!("Image is too large for OCR ({}x{}, max {})", width, height, max);
+    }
+
+    let bitmap = decoder.GetSoftwareBitmapAsync()?.get()?;
+    let result = engine.RecognizeAsync(&bitmap)?.get()?;
+
+    let lines: Vec<String> = result
+        .Lines()?
+        .into_iter()
+        .filter_map(|line| line.Text().ok())
+        .map(|text| text.to_string())
+        .collect();
+
+    info!("OCR ({}) recognized {} lines in {:?}", tag, lines.len(), path);
+    Ok(lines.join("\n"))
+}
+
+#[cfg(not(windows))]
+pub fn extract_text(_path: &Path) -> Result<String> {
+    anyhow::bail!("OCR is only supported on Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_language() {
+        let available = vec!["en-US".to_string(), "ko".to_string(), "zh-Hans-CN".to_string()];
+        assert_eq!(pick_language("en", &available), Some("en-US"));
+        assert_eq!(pick_language("ko", &available), Some("ko"));
+        assert_eq!(pick_language("zh-Hans", &available), Some("zh-Hans-CN"));
+        assert_eq!(pick_language("ja", &available), None);
+        // Different script variants do not match
+        assert_eq!(pick_language("zh-Hant", &available), None);
+    }
+}
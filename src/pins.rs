@@ -0,0 +1,283 @@
+//! Pinned screenshots, persisted as JSON next to the settings file
+//!
+//! Pinned files are skipped by automatic cleanup. Each pin also records the file's content
+//! hash, so a pinned screenshot that is renamed or moved keeps its pin.
+
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app::ScreenshotInfo;
+use crate::identity::{self, ContentHash};
+use crate::settings::Settings;
+
+/// A pinned screenshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Pin {
+    path: PathBuf,
+    /// Missing if the file couldn't be read when it was pinned
+    #[serde(default)]
+    hash: Option<ContentHash>,
+}
+
+impl Pin {
+    fn new(path: PathBuf) -> Self {
+        let hash = identity::screenshot_hash(&path)
+            .map_err(|e| debug!("Pinning without a content hash: {:#}", e))
+            .ok();
+        Self { path, hash }
+    }
+
+    /// The file is gone, but could still be found by its content
+    fn is_dangling(&self) -> bool {
+        self.hash.is_some() && !self.path.exists()
+    }
+}
+
+/// Entry in the pins file; older versions stored bare paths
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredPin {
+    Pin(Pin),
+    Path(PathBuf),
+}
+
+/// In-memory pin list, loaded once at startup
+static PINS: Mutex<Vec<Pin>> = Mutex::new(Vec::new());
+
+/// Location of the pins file
+fn pins_path() -> Option<PathBuf> {
+    Settings::config_path().and_then(|p| p.parent().map(|d| d.join("pins.json")))
+}
+
+/// Pins are stored as absolute paths so they survive working-directory changes
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Point dangling pins at `candidates` with the same content; returns how many moved
+fn relocate(pins: &mut [Pin], candidates: &[PathBuf]) -> usize {
+    let mut moved = 0;
+    for i in 0..pins.len() {
+        if !pins[i].is_dangling() {
+            continue;
+        }
+        // A copy that is pinned already keeps its own pin
+        let found = candidates.iter().find(|c| {
+            !pins.iter().any(|p| &p.path == *c) && identity::screenshot_hash(c).ok() == pins[i].hash
+        });
+        if let Some(found) = found.cloned() {
+            info!("Pinned screenshot moved: {:?} -> {:?}", pins[i].path, found);
+            pins[i].path = found;
+            moved += 1;
+        }
+    }
+    moved
+}
+
+/// Files next to the dangling pins, where renamed screenshots usually end up
+fn dangling_siblings(pins: &[Pin]) -> Vec<PathBuf> {
+    let mut dirs: Vec<&Path> = pins
+        .iter()
+        .filter(|p| p.is_dangling())
+        .filter_map(|p| p.path.parent())
+        .collect();
+    dirs.dedup();
+    dirs.into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Drop entries whose files no longer exist
+fn prune_missing(pins: &mut Vec<Pin>) -> usize {
+    let before = pins.len();
+    pins.retain(|p| p.path.exists());
+    before - pins.len()
+}
+
+fn read_from(path: &Path) -> Result<Vec<Pin>> {
+    let content = fs::read_to_string(path)?;
+    let stored: Vec<StoredPin> = serde_json::from_str(&content)?;
+    Ok(stored
+        .into_iter()
+        .map(|entry| match entry {
+            StoredPin::Pin(pin) => pin,
+            StoredPin::Path(path) => Pin::new(path),
+        })
+        .collect())
+}
+
+fn write_to(path: &Path, pins: &[Pin]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(pins)?;
+    fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Persist the current pin list
+fn save(pins: &[Pin]) -> Result<()> {
+    let path = pins_path().ok_or_else(|| anyhow::anyhow!("Could not determine config path"))?;
+    write_to(&path, pins)
+}
+
+/// Load pins from disk, following files renamed and pruning files deleted while we weren't
+/// looking
+pub fn load() {
+    let Some(path) = pins_path() else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+
+    let mut pins = match read_from(&path) {
+        Ok(pins) => pins,
+        Err(e) => {
+            warn!("Failed to load pins from {:?}: {}", path, e);
+            return;
+        }
+    };
+
+    let moved = relocate(&mut pins, &dangling_siblings(&pins));
+    let pruned = prune_missing(&mut pins);
+    if pruned > 0 {
+        info!("Pruned {} stale pins", pruned);
+    }
+    if moved > 0 || pruned > 0 {
+        if let Err(e) = write_to(&path, &pins) {
+            warn!("Failed to save pruned pins: {}", e);
+        }
+    }
+
+    info!("Loaded {} pinned screenshots", pins.len());
+    *PINS.lock() = pins;
+}
+
+/// Pin a screenshot
+pub fn pin(path: &Path) -> Result<()> {
+    let path = absolute(path);
+    if is_pinned(&path) {
+        return Ok(());
+    }
+    // Hash outside the lock; reading a large file can take a moment
+    let pin = Pin::new(path);
+    let mut pins = PINS.lock();
+    pins.push(pin);
+    save(&pins)
+}
+
+/// Unpin a screenshot
+pub fn unpin(path: &Path) -> Result<()> {
+    let path = absolute(path);
+    let mut pins = PINS.lock();
+    let before = pins.len();
+    pins.retain(|p| p.path != path);
+    if pins.len() != before {
+        save(&pins)?;
+    }
+    Ok(())
+}
+
+/// Whether a screenshot is pinned
+pub fn is_pinned(path: &Path) -> bool {
+    let path = absolute(path);
+    PINS.lock().iter().any(|p| p.path == path)
+}
+
+/// All pinned screenshots
+pub fn pinned() -> Vec<PathBuf> {
+    PINS.lock().iter().map(|p| p.path.clone()).collect()
+}
+
+/// Move a pin to a newly arrived screenshot if it is a pinned file under a new name
+///
+/// Hashing happens in the background, and only while a pinned file is missing.
+pub fn follow_rename(screenshot: &ScreenshotInfo) {
+    if !PINS.lock().iter().any(Pin::is_dangling) {
+        return;
+    }
+    let known = screenshot.content_hash.is_some();
+    let path = absolute(&screenshot.path);
+    std::thread::spawn(move || {
+        // Hash before taking the lock, so relocating only hits the cache
+        if !known && identity::screenshot_hash(&path).is_err() {
+            return;
+        }
+        let mut pins = PINS.lock();
+        if relocate(&mut pins, &[path]) > 0 {
+            if let Err(e) = save(&pins) {
+                warn!("Failed to save pins: {}", e);
+            }
+        }
+    });
+}
+
+/// Pin the files, or unpin them if they are all pinned already
+pub fn toggle(paths: &[PathBuf]) -> Result<()> {
+    if paths.iter().all(|p| is_pinned(p)) {
+        paths.iter().try_for_each(|p| unpin(p))?;
+        info!("Unpinned {} screenshots", paths.len());
+    } else {
+        paths.iter().try_for_each(|p| pin(p))?;
+        info!("Pinned {} screenshots", paths.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_and_prune() {
+        let dir = std::env::temp_dir().join(format!("sukusho-pins-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let kept = dir.join("kept.png");
+        fs::write(&kept, b"png").unwrap();
+        let missing = dir.join("missing.png");
+
+        let file = dir.join("pins.json");
+        write_to(&file, &[Pin::new(kept.clone()), Pin::new(missing)]).unwrap();
+
+        let mut pins = read_from(&file).unwrap();
+        assert_eq!(prune_missing(&mut pins), 1);
+        assert_eq!(pins, vec![Pin::new(kept.clone())]);
+
+        // Files from before content hashes still load, and gain one
+        fs::write(&file, serde_json::to_string(&[&kept]).unwrap()).unwrap();
+        let pins = read_from(&file).unwrap();
+        assert_eq!(pins[0].path, kept);
+        assert!(pins[0].hash.is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_relocate() {
+        let dir = std::env::temp_dir().join(format!("sukusho-pins-moved-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let before = dir.join("before.png");
+        let after = dir.join("after.png");
+        let other = dir.join("other.png");
+        fs::write(&before, b"pinned").unwrap();
+        fs::write(&other, b"something else").unwrap();
+
+        let mut pins = vec![Pin::new(before)];
+        fs::rename(&pins[0].path, &after).unwrap();
+        assert!(pins[0].is_dangling());
+
+        assert_eq!(relocate(&mut pins, &[other.clone()]), 0);
+        assert_eq!(relocate(&mut pins, &dangling_siblings(&pins)), 1);
+        assert_eq!(pins[0].path, after);
+        assert!(!pins[0].is_dangling());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
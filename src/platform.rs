@@ -0,0 +1,200 @@
+//! OS services used by the cross-platform parts of the app
+//!
+//! The Windows implementations live in their own modules (`clipboard`, `drag_drop`,
+//! `tray`, `thumbnail`); this is the one place that picks between them and the stubs.
+
+use crossbeam_channel::Sender;
+use image::RgbaImage;
+use log::info;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::AppMessage;
+
+/// What the drop target did with dragged files
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropEffect {
+    Copy,
+    Move,
+    Link,
+}
+
+/// Clipboard, drag and window services that differ per OS
+pub trait Platform: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Put files on the clipboard so they paste into a file manager
+    fn copy_files(&self, files: &[PathBuf]) -> bool;
+
+    /// Drag files out of the app; blocks until the drop and returns its effect, or `None` if
+    /// nothing was dropped
+    fn start_drag(&self, files: &[PathBuf]) -> Option<DropEffect>;
+
+    /// Show and focus the main window
+    fn show_window(&self);
+
+    /// Hide the main window if it is focused, show it otherwise; returns true if shown
+    fn toggle_window(&self) -> bool;
+
+    /// Thumbnail from the OS thumbnail cache, if it has one
+    fn thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage>;
+
+    /// Show a file selected in the file manager
+    fn reveal(&self, path: &Path) -> bool;
+}
+
+/// Shell clipboard, OLE drag and drop, and Win32 window management
+#[cfg(windows)]
+pub struct WindowsPlatform;
+
+#[cfg(windows)]
+impl Platform for WindowsPlatform {
+    fn name(&self) -> &'static str {
+        "Windows"
+    }
+
+    fn copy_files(&self, files: &[PathBuf]) -> bool {
+        crate::clipboard::copy_files_to_clipboard(files)
+    }
+
+    fn start_drag(&self, files: &[PathBuf]) -> Option<DropEffect> {
+        crate::drag_drop::start_drag(files)
+    }
+
+    fn show_window(&self) {
+        crate::tray::show_window();
+    }
+
+    fn toggle_window(&self) -> bool {
+        crate::tray::toggle_window()
+    }
+
+    fn thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage> {
+        crate::thumbnail::shell_thumbnail(path, size)
+            .map_err(|e| log::debug!("{}", e))
+            .ok()
+    }
+
+    fn reveal(&self, path: &Path) -> bool {
+        crate::tray::reveal_in_explorer(path)
+    }
+}
+
+/// Used where no native implementation exists yet; everything reports that it did nothing
+#[cfg_attr(windows, allow(dead_code))]
+pub struct StubPlatform;
+
+impl Platform for StubPlatform {
+    fn name(&self) -> &'static str {
+        "stub"
+    }
+
+    fn copy_files(&self, _files: &[PathBuf]) -> bool {
+        false
+    }
+
+    fn start_drag(&self, _files: &[PathBuf]) -> Option<DropEffect> {
+        None
+    }
+
+    fn show_window(&self) {}
+
+    fn toggle_window(&self) -> bool {
+        // The window can't be hidden here, so it always counts as shown
+        self.show_window();
+        true
+    }
+
+    fn thumbnail(&self, _path: &Path, _size: u32) -> Option<RgbaImage> {
+        None
+    }
+
+    fn reveal(&self, _path: &Path) -> bool {
+        false
+    }
+}
+
+/// Keeps COM initialized on this thread for the guard's lifetime, undoing only a
+/// `CoInitializeEx` that succeeded
+#[cfg(windows)]
+pub struct ComGuard(bool);
+
+#[cfg(windows)]
+impl ComGuard {
+    /// Single-threaded apartment, for shell dialogs
+    pub fn apartment_threaded() -> Self {
+        Self::new(windows::Win32::System::Com::COINIT_APARTMENTTHREADED)
+    }
+
+    /// Multithreaded apartment, for WinRT calls on worker threads
+    pub fn multithreaded() -> Self {
+        Self::new(windows::Win32::System::Com::COINIT_MULTITHREADED)
+    }
+
+    fn new(model: windows::Win32::System::Com::COINIT) -> Self {
+        use windows::Win32::System::Com::CoInitializeEx;
+
+        Self(unsafe { CoInitializeEx(None, model) }.is_ok())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        if self.0 {
+            unsafe { windows::Win32::System::Com::CoUninitialize() };
+        }
+    }
+}
+
+static PLATFORM: OnceLock<Box<dyn Platform>> = OnceLock::new();
+
+/// Pick the implementation for this OS; call once at startup
+pub fn init() {
+    info!("Platform services: {}", current().name());
+}
+
+/// Drag `files` out of the app and announce a successful drop with `AppMessage::DragCompleted`;
+/// returns whether they were dropped
+pub fn drag_files(files: &[PathBuf], message_tx: &Sender<AppMessage>) -> bool {
+    let Some(effect) = current().start_drag(files) else {
+        return false;
+    };
+    let _ = message_tx.send(AppMessage::DragCompleted {
+        files: files.to_vec(),
+        effect,
+    });
+    true
+}
+
+/// The implementation picked by `init` (or on first use)
+pub fn current() -> &'static dyn Platform {
+    PLATFORM.get_or_init(select).as_ref()
+}
+
+#[cfg(windows)]
+fn select() -> Box<dyn Platform> {
+    Box::new(WindowsPlatform)
+}
+
+#[cfg(not(windows))]
+fn select() -> Box<dyn Platform> {
+    Box::new(StubPlatform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stub_platform() {
+        let stub = StubPlatform;
+        assert!(!stub.copy_files(&[PathBuf::from("shot.png")]));
+        assert!(stub.start_drag(&[]).is_none());
+        assert!(stub.toggle_window());
+        assert!(stub.thumbnail(Path::new("shot.png"), 64).is_none());
+        assert!(!stub.reveal(Path::new("shot.png")));
+    }
+}
@@ -0,0 +1,217 @@
+//! Most recent screenshots, kept in memory
+//!
+//! The UI feeds this as screenshots arrive and disappear, so the tray, notifications and
+//! drag can ask for the latest ones without touching the file system.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::app::ScreenshotInfo;
+use crate::settings::SortOrder;
+
+/// Capacity used until the settings are applied
+const DEFAULT_CAPACITY: usize = 50;
+
+/// The app-wide buffer
+static RECENT: Mutex<RecentScreenshots> = Mutex::new(RecentScreenshots::new(DEFAULT_CAPACITY));
+
+/// Newest screenshots first, never more than `capacity`
+#[derive(Debug)]
+pub struct RecentScreenshots {
+    items: VecDeque<ScreenshotInfo>,
+    capacity: usize,
+}
+
+impl RecentScreenshots {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Change the capacity, dropping the oldest entries if it shrank
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.items.truncate(capacity);
+    }
+
+    /// Add a screenshot in modification order, replacing an entry for the same path
+    ///
+    /// Screenshots older than everything in a full buffer are not kept.
+    pub fn push(&mut self, info: ScreenshotInfo) {
+        self.remove(&info.path);
+        let pos = self
+            .items
+            .iter()
+            .position(|s| s.modified < info.modified)
+            .unwrap_or(self.items.len());
+        if pos >= self.capacity {
+            return;
+        }
+        self.items.insert(pos, info);
+        self.items.truncate(self.capacity);
+    }
+
+    /// Forget a screenshot; returns whether it was in the buffer
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let before = self.items.len();
+        self.items.retain(|s| s.path != path);
+        self.items.len() != before
+    }
+
+    pub fn latest(&self) -> Option<&ScreenshotInfo> {
+        self.items.front()
+    }
+
+    /// Screenshots newest first
+    pub fn iter(&self) -> impl Iterator<Item = &ScreenshotInfo> {
+        self.items.iter()
+    }
+
+    /// The first `limit` screenshots in `order`; only those are sorted and cloned
+    pub fn sorted(&self, order: SortOrder, limit: Option<usize>) -> Vec<ScreenshotInfo> {
+        let mut sorted: Vec<&ScreenshotInfo> = self.iter().collect();
+        if let Some(limit) = limit.filter(|&limit| limit < sorted.len()) {
+            if limit == 0 {
+                return Vec::new();
+            }
+            sorted.select_nth_unstable_by(limit - 1, |a, b| a.cmp_by(b, order));
+            sorted.truncate(limit);
+        }
+        sorted.sort_by(|a, b| a.cmp_by(b, order));
+        sorted.into_iter().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+/// Set the capacity of the app-wide buffer
+pub fn set_capacity(capacity: usize) {
+    RECENT.lock().set_capacity(capacity);
+}
+
+/// Record a new or changed screenshot
+pub fn push(info: ScreenshotInfo) {
+    RECENT.lock().push(info);
+}
+
+/// Forget a removed screenshot
+pub fn remove(path: &Path) -> bool {
+    RECENT.lock().remove(path)
+}
+
+/// Forget everything, e.g. when the screenshot folder changes
+pub fn clear() {
+    RECENT.lock().clear();
+}
+
+/// The newest screenshot
+pub fn latest() -> Option<ScreenshotInfo> {
+    RECENT.lock().latest().cloned()
+}
+
+/// The first `limit` screenshots in `order`, or all of them
+pub fn list(order: SortOrder, limit: Option<usize>) -> Vec<ScreenshotInfo> {
+    RECENT.lock().sorted(order, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    fn info(name: &str, age_secs: u64, now: SystemTime) -> ScreenshotInfo {
+        ScreenshotInfo {
+            path: PathBuf::from(name),
+            filename: name.to_string(),
+            modified: now - Duration::from_secs(age_secs),
+            file_size: 1,
+            extension: "PNG".to_string(),
+            source_app: None,
+            content_hash: None,
+        }
+    }
+
+    fn names(recent: &RecentScreenshots) -> Vec<&str> {
+        recent.iter().map(|s| s.filename.as_str()).collect()
+    }
+
+    #[test]
+    fn test_push_keeps_newest() {
+        let now = SystemTime::now();
+        let mut recent = RecentScreenshots::new(3);
+        recent.push(info("b.png", 20, now));
+        recent.push(info("a.png", 30, now));
+        recent.push(info("d.png", 0, now));
+        recent.push(info("c.png", 10, now));
+        assert_eq!(names(&recent), ["d.png", "c.png", "b.png"]);
+
+        // Older than everything in a full buffer
+        recent.push(info("old.png", 60, now));
+        assert_eq!(names(&recent), ["d.png", "c.png", "b.png"]);
+
+        // Same path again moves it instead of duplicating it
+        recent.push(info("b.png", 5, now));
+        assert_eq!(names(&recent), ["d.png", "b.png", "c.png"]);
+        assert_eq!(recent.latest().map(|s| s.filename.as_str()), Some("d.png"));
+    }
+
+    #[test]
+    fn test_remove_and_capacity() {
+        let now = SystemTime::now();
+        let mut recent = RecentScreenshots::new(3);
+        for (name, age) in [("a.png", 2), ("b.png", 1), ("c.png", 0)] {
+            recent.push(info(name, age, now));
+        }
+
+        assert!(recent.remove(Path::new("c.png")));
+        assert!(!recent.remove(Path::new("c.png")));
+        assert_eq!(names(&recent), ["b.png", "a.png"]);
+
+        recent.push(info("z.png", 3, now));
+        let sorted = recent.sorted(SortOrder::NameAsc, Some(2));
+        assert_eq!(sorted.iter().map(|s| s.filename.as_str()).collect::<Vec<_>>(), ["a.png", "b.png"]);
+
+        recent.set_capacity(1);
+        assert_eq!(names(&recent), ["b.png"]);
+        assert_eq!(recent.items.len(), 1);
+    }
+
+    #[test]
+    fn test_sorted_screenshots() {
+        let now = SystemTime::now();
+        let mut recent = RecentScreenshots::new(10);
+        for (name, age) in [("b.png", 10), ("C.png", 0), ("a.png", 5)] {
+            recent.push(info(name, age, now));
+        }
+        let sorted = |order, limit| {
+            recent
+                .sorted(order, limit)
+                .into_iter()
+                .map(|s| s.filename)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(SortOrder::NewestFirst, None), ["C.png", "a.png", "b.png"]);
+        assert_eq!(sorted(SortOrder::OldestFirst, Some(2)), ["b.png", "a.png"]);
+        assert_eq!(sorted(SortOrder::NameAsc, None), ["a.png", "b.png", "C.png"]);
+        assert_eq!(sorted(SortOrder::NameDesc, None), ["C.png", "b.png", "a.png"]);
+        assert!(sorted(SortOrder::NameAsc, Some(0)).is_empty());
+
+        // Names differing only in case still get a stable order
+        let mut twins = RecentScreenshots::new(10);
+        twins.push(info("a.png", 10, now));
+        twins.push(info("A.png", 0, now));
+        let names: Vec<_> = twins
+            .sorted(SortOrder::NameAsc, None)
+            .into_iter()
+            .map(|s| s.filename)
+            .collect();
+        assert_eq!(names, ["A.png", "a.png"]);
+    }
+}
@@ -0,0 +1,190 @@
+//! Blur and pixelate parts of a screenshot before sharing it
+//!
+//! Edits are written to a new file next to the original, which stays untouched.
+//! Note: There is no editing overlay yet; this is the API it will call.
+
+#![allow(dead_code)]
+
+use anyhow::{bail, Context, Result};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, RgbaImage};
+use log::info;
+use std::path::{Path, PathBuf};
+
+/// A rectangle in image pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageRect {
+    /// The part of the rectangle inside a `width` x `height` image, if any
+    pub fn clamped(&self, width: u32, height: u32) -> Option<Self> {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        let rect = Self {
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
+        };
+        (rect.width > 0 && rect.height > 0).then_some(rect)
+    }
+}
+
+/// Gaussian-blur a region and save the result as a new file
+pub fn blur_region(src: &Path, rect: ImageRect, radius: f32) -> Result<PathBuf> {
+    edit(src, "redacted", |img| blur(img, rect, radius))
+}
+
+/// Pixelate a region into `block`-sized squares and save the result as a new file
+pub fn pixelate_region(src: &Path, rect: ImageRect, block: u32) -> Result<PathBuf> {
+    edit(src, "redacted", |img| pixelate(img, rect, block))
+}
+
+/// Decode `src`, apply `apply` and save next to it with `suffix` added to the name
+pub fn edit(
+    src: &Path,
+    suffix: &str,
+    apply: impl FnOnce(&mut RgbaImage) -> Result<()>,
+) -> Result<PathBuf> {
+    let mut img = image::open(src)
+        .with_context(|| format!("Failed to decode {:?}", src))?
+        .to_rgba8();
+    apply(&mut img)?;
+
+    let output = edited_path(src, suffix);
+    save_like(&img, &output)?;
+    info!("Saved edited copy of {:?} as {:?}", src, output);
+    Ok(output)
+}
+
+/// `name_<suffix>.ext` next to `src`, numbered if that name is taken
+pub fn edited_path(src: &Path, suffix: &str) -> PathBuf {
+    let stem = src
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = src.extension().map(|e| e.to_string_lossy().into_owned());
+    let with_name = |name: String| match &extension {
+        Some(ext) => src.with_file_name(format!("{}.{}", name, ext)),
+        None => src.with_file_name(name),
+    };
+
+    let mut path = with_name(format!("{}_{}", stem, suffix));
+    let mut n = 2;
+    while path.exists() {
+        path = with_name(format!("{}_{}_{}", stem, suffix, n));
+        n += 1;
+    }
+    path
+}
+
+/// Save in the format implied by the extension; JPEG has no alpha channel
+pub fn save_like(img: &RgbaImage, path: &Path) -> Result<()> {
+    let is_jpeg = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("jpeg"));
+    let result = if is_jpeg {
+        DynamicImage::ImageRgba8(img.clone()).to_rgb8().save(path)
+    } else {
+        img.save(path)
+    };
+    result.with_context(|| format!("Failed to save {:?}", path))
+}
+
+fn blur(img: &mut RgbaImage, rect: ImageRect, radius: f32) -> Result<()> {
+    if radius <= 0.0 {
+        bail!("Blur radius must be positive");
+    }
+    let rect = region(img, rect)?;
+    let view = imageops::crop_imm(&*img, rect.x, rect.y, rect.width, rect.height).to_image();
+    let blurred = imageops::blur(&view, radius);
+    imageops::replace(img, &blurred, i64::from(rect.x), i64::from(rect.y));
+    Ok(())
+}
+
+/// Downscale the region, then scale it back up with nearest-neighbour
+fn pixelate(img: &mut RgbaImage, rect: ImageRect, block: u32) -> Result<()> {
+    if block == 0 {
+        bail!("Pixelation block size must be positive");
+    }
+    let rect = region(img, rect)?;
+    let view = imageops::crop_imm(&*img, rect.x, rect.y, rect.width, rect.height).to_image();
+    let small = imageops::resize(
+        &view,
+        rect.width.div_ceil(block),
+        rect.height.div_ceil(block),
+        FilterType::Triangle,
+    );
+    let pixelated = imageops::resize(&small, rect.width, rect.height, FilterType::Nearest);
+    imageops::replace(img, &pixelated, i64::from(rect.x), i64::from(rect.y));
+    Ok(())
+}
+
+/// Clamp `rect` to the image, failing if it lies entirely outside
+fn region(img: &RgbaImage, rect: ImageRect) -> Result<ImageRect> {
+    match rect.clamped(img.width(), img.height()) {
+        Some(rect) => Ok(rect),
+        None => bail!(
+            "Region {:?} is outside the {}x{} image",
+            rect,
+            img.width(),
+            img.height()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn checkerboard() -> RgbaImage {
+        RgbaImage::from_fn(8, 8, |x, y| {
+            let v = if (x + y) % 2 == 0 { 255 } else { 0 };
+            Rgba([v, v, v, 255])
+        })
+    }
+
+    #[test]
+    fn test_clamped() {
+        let rect = ImageRect { x: 6, y: 2, width: 10, height: 3 };
+        assert_eq!(
+            rect.clamped(8, 8),
+            Some(ImageRect { x: 6, y: 2, width: 2, height: 3 })
+        );
+        assert_eq!(ImageRect { x: 8, y: 0, width: 4, height: 4 }.clamped(8, 8), None);
+    }
+
+    #[test]
+    fn test_pixelate_only_touches_region() {
+        let original = checkerboard();
+        let mut img = original.clone();
+        pixelate(&mut img, ImageRect { x: 0, y: 0, width: 4, height: 4 }, 4).unwrap();
+
+        // The region becomes one flat block, the rest is unchanged
+        let first = *img.get_pixel(0, 0);
+        assert!((0..4).all(|y| (0..4).all(|x| *img.get_pixel(x, y) == first)));
+        assert_eq!(img.get_pixel(5, 5), original.get_pixel(5, 5));
+
+        assert!(pixelate(&mut img, ImageRect { x: 9, y: 9, width: 1, height: 1 }, 4).is_err());
+    }
+
+    #[test]
+    fn test_edited_path() {
+        let dir = std::env::temp_dir().join(format!("sukusho-redact-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("shot.png");
+
+        assert_eq!(edited_path(&src, "redacted"), dir.join("shot_redacted.png"));
+        std::fs::write(dir.join("shot_redacted.png"), b"png").unwrap();
+        assert_eq!(edited_path(&src, "redacted"), dir.join("shot_redacted_2.png"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
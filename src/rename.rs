@@ -0,0 +1,229 @@
+//! Rename new screenshots from a filename template
+//!
+//! Supported tokens:
+//! - `{date}` / `{date:%Y-%m-%d}` - capture date (strftime format)
+//! - `{time}` / `{time:%H-%M-%S}` - capture time (strftime format)
+//! - `{counter}` / `{counter:03}` - number that increments until the name is free
+//! - `{app}` - application the screenshot was taken from (see `foreground::source_app`)
+
+use anyhow::{bail, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default date format for `{date}`
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Default time format for `{time}`
+const DEFAULT_TIME_FORMAT: &str = "%H-%M-%S";
+
+/// Safety limit when searching for a free name
+const MAX_COUNTER: u32 = 10000;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Date(String),
+    Time(String),
+    Counter(usize),
+    App,
+}
+
+/// A parsed filename template
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilenameTemplate {
+    tokens: Vec<Token>,
+}
+
+impl FilenameTemplate {
+    /// Parse a template such as `"Screenshot {date:%Y-%m-%d} {counter:03}"`
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut tokens = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => bail!("Unclosed '{{' in filename template"),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                    }
+                    tokens.push(Self::parse_token(&spec)?);
+                }
+                '}' => bail!("Unmatched '}}' in filename template"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(literal));
+        }
+
+        if tokens.is_empty() {
+            bail!("Filename template is empty");
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Parse the inside of a `{name:arg}` token
+    fn parse_token(spec: &str) -> Result<Token> {
+        let (name, arg) = match spec.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg)),
+            None => (spec.trim(), None),
+        };
+
+        match name {
+            "date" => Ok(Token::Date(Self::strftime_arg(arg, DEFAULT_DATE_FORMAT)?)),
+            "time" => Ok(Token::Time(Self::strftime_arg(arg, DEFAULT_TIME_FORMAT)?)),
+            "counter" => {
+                let width = match arg {
+                    Some(w) => w
+                        .parse::<usize>()
+                        .map_err(|_| anyhow::anyhow!("Invalid counter width: {}", w))?,
+                    None => 0,
+                };
+                Ok(Token::Counter(width))
+            }
+            "app" => Ok(Token::App),
+            other => bail!("Unknown filename template token: {{{}}}", other),
+        }
+    }
+
+    /// Validate a strftime argument up front so rendering can't fail later
+    fn strftime_arg(arg: Option<&str>, default: &str) -> Result<String> {
+        let format = arg.unwrap_or(default);
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            bail!("Invalid date/time format: {}", format);
+        }
+        Ok(format.to_string())
+    }
+
+    /// Whether the template contains a `{counter}` token
+    pub fn has_counter(&self) -> bool {
+        self.tokens.iter().any(|t| matches!(t, Token::Counter(_)))
+    }
+
+    /// Render the template into a file stem (without extension)
+    pub fn render(&self, time: DateTime<Local>, counter: u32, app: &str) -> String {
+        let mut out = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(s) => out.push_str(s),
+                Token::Date(f) | Token::Time(f) => out.push_str(&time.format(f).to_string()),
+                Token::Counter(width) => out.push_str(&format!("{:0width$}", counter, width = *width)),
+                Token::App => out.push_str(app),
+            }
+        }
+        sanitize_file_stem(&out)
+    }
+}
+
+/// Replace characters Windows does not allow in file names
+fn sanitize_file_stem(stem: &str) -> String {
+    let cleaned: String = stem
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Trailing dots and spaces are stripped by Windows
+    let trimmed = cleaned.trim_end_matches(['.', ' ']);
+    if trimmed.is_empty() {
+        "screenshot".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Rename a file according to the template, keeping its extension.
+///
+/// # Returns
+/// * `Ok(Some(new_path))` - File was renamed
+/// * `Ok(None)` - File already has the rendered name
+/// * `Err(_)` - Error occurred
+pub fn rename_file(file_path: &Path, template: &FilenameTemplate, app: &str) -> Result<Option<PathBuf>> {
+    let dir = file_path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Invalid file path"))?;
+    let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+    let modified = fs::metadata(file_path)?.modified()?;
+    let time: DateTime<Local> = modified.into();
+
+    let target = (1..=MAX_COUNTER)
+        .map(|counter| {
+            let base = template.render(time, counter, app);
+            // No counter token: disambiguate collisions with a suffix
+            let stem = if template.has_counter() || counter == 1 {
+                base
+            } else {
+                format!("{} ({})", base, counter)
+            };
+            if ext.is_empty() {
+                dir.join(stem)
+            } else {
+                dir.join(format!("{}.{}", stem, ext))
+            }
+        })
+        .find(|candidate| candidate == file_path || !candidate.exists())
+        .ok_or_else(|| anyhow::anyhow!("Too many duplicate files"))?;
+
+    if target == file_path {
+        return Ok(None);
+    }
+
+    fs::rename(file_path, &target)?;
+    info!("Renamed: {:?} -> {:?}", file_path, target);
+    Ok(Some(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_time() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, 15, 9, 5, 30).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_render() {
+        let template = FilenameTemplate::parse("Screenshot {date:%Y-%m-%d} {counter:03}").unwrap();
+        assert!(template.has_counter());
+        assert_eq!(template.render(sample_time(), 7, "app"), "Screenshot 2024-01-15 007");
+
+        let template = FilenameTemplate::parse("{app}_{date}_{time}").unwrap();
+        assert!(!template.has_counter());
+        assert_eq!(
+            template.render(sample_time(), 1, "chrome"),
+            "chrome_2024-01-15_09-05-30"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(FilenameTemplate::parse("").is_err());
+        assert!(FilenameTemplate::parse("Shot {date").is_err());
+        assert!(FilenameTemplate::parse("Shot }").is_err());
+        assert!(FilenameTemplate::parse("{unknown}").is_err());
+        assert!(FilenameTemplate::parse("{counter:abc}").is_err());
+        assert!(FilenameTemplate::parse("{date:%Q}").is_err());
+    }
+
+    #[test]
+    fn test_render_sanitizes() {
+        let template = FilenameTemplate::parse("{time:%H:%M} {app}").unwrap();
+        assert_eq!(template.render(sample_time(), 1, "a/b"), "09_05 a_b");
+    }
+}
@@ -0,0 +1,144 @@
+//! Automatic cleanup of old screenshots (keep newest N and/or max age)
+
+use crossbeam_channel::Sender;
+use log::{error, info};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::delete;
+use crate::AppMessage;
+
+/// Retention limits from settings
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RetentionPolicy {
+    /// Keep at most this many (unpinned) screenshots
+    pub max_screenshots: Option<usize>,
+    /// Remove screenshots older than this many days
+    pub max_age_days: Option<u32>,
+}
+
+impl RetentionPolicy {
+    /// Whether any limit is configured
+    pub fn is_enabled(&self) -> bool {
+        self.max_screenshots.is_some() || self.max_age_days.is_some()
+    }
+}
+
+/// Pick the files the policy removes. Pinned files are never removed and do not
+/// count toward `max_screenshots`.
+pub fn select_expired(
+    files: &[(PathBuf, SystemTime)],
+    policy: &RetentionPolicy,
+    pinned: &HashSet<PathBuf>,
+    now: SystemTime,
+) -> Vec<PathBuf> {
+    let mut candidates: Vec<&(PathBuf, SystemTime)> =
+        files.iter().filter(|(path, _)| !pinned.contains(path)).collect();
+    // Newest first
+    candidates.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let max_age = policy
+        .max_age_days
+        .map(|days| Duration::from_secs(u64::from(days) * 24 * 60 * 60));
+
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(rank, (_, modified))| {
+            let over_count = policy.max_screenshots.is_some_and(|max| *rank >= max);
+            let too_old = max_age.is_some_and(|max_age| {
+                now.duration_since(*modified).is_ok_and(|age| age > max_age)
+            });
+            over_count || too_old
+        })
+        .map(|(_, (path, _))| path.clone())
+        .collect()
+}
+
+/// Enforce the policy in a background thread, skipping if a run is already in progress
+pub fn spawn_cleanup(
+    files: Vec<(PathBuf, SystemTime)>,
+    policy: RetentionPolicy,
+    pinned: HashSet<PathBuf>,
+    permanent: bool,
+    running: Arc<AtomicBool>,
+    message_tx: Sender<AppMessage>,
+) {
+    if !policy.is_enabled() || running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let expired = select_expired(&files, &policy, &pinned, SystemTime::now());
+        if !expired.is_empty() {
+            let mut removed = 0;
+            for path in &expired {
+                match delete::delete_screenshot(path, permanent) {
+                    Ok(()) => {
+                        removed += 1;
+                        let _ = message_tx.send(AppMessage::ScreenshotRemoved(path.clone()));
+                    }
+                    Err(e) => error!("Cleanup failed to delete {:?}: {:#}", path, e),
+                }
+            }
+            info!(
+                "Cleanup removed {} of {} expired screenshots ({} checked, policy: {:?}, to Recycle Bin: {})",
+                removed,
+                expired.len(),
+                files.len(),
+                policy,
+                !permanent
+            );
+        }
+        running.store(false, Ordering::SeqCst);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn files(now: SystemTime) -> Vec<(PathBuf, SystemTime)> {
+        // a is newest, d is oldest
+        [("a", 0), ("b", 2), ("c", 5), ("d", 40)]
+            .iter()
+            .map(|(name, days)| {
+                (PathBuf::from(name), now - Duration::from_secs(days * DAY))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_select_expired_by_count_and_age() {
+        let now = SystemTime::now();
+        let files = files(now);
+        let none = HashSet::new();
+
+        let policy = RetentionPolicy { max_screenshots: Some(2), max_age_days: None };
+        assert_eq!(
+            select_expired(&files, &policy, &none, now),
+            vec![PathBuf::from("c"), PathBuf::from("d")]
+        );
+
+        let policy = RetentionPolicy { max_screenshots: None, max_age_days: Some(30) };
+        assert_eq!(select_expired(&files, &policy, &none, now), vec![PathBuf::from("d")]);
+
+        assert!(select_expired(&files, &RetentionPolicy::default(), &none, now).is_empty());
+    }
+
+    #[test]
+    fn test_select_expired_skips_pinned() {
+        let now = SystemTime::now();
+        let files = files(now);
+        let pinned: HashSet<PathBuf> = [PathBuf::from("a"), PathBuf::from("d")].into();
+
+        // Pinned files neither get removed nor use up the count
+        let policy = RetentionPolicy { max_screenshots: Some(1), max_age_days: Some(30) };
+        assert_eq!(select_expired(&files, &policy, &pinned, now), vec![PathBuf::from("c")]);
+    }
+}
@@ -0,0 +1,195 @@
+//! Fuzzy search over screenshot filenames
+//!
+//! Each word of the query must match the filename as a case-insensitive subsequence, so
+//! `0515 1430` finds `Screenshot 2024-05-15 143022.png`. Separators in the query are ignored
+//! for the same reason. `after:YYYY-MM-DD` and `before:YYYY-MM-DD` words filter by date.
+//! Used by the gallery search box as you type; image content search runs on Enter.
+
+use chrono::{Local, NaiveDate, TimeZone};
+use std::time::SystemTime;
+
+use crate::app::ScreenshotInfo;
+
+/// Points for each matched character
+const MATCH: i64 = 16;
+/// Extra points when a match directly follows the previous one
+const CONSECUTIVE: i64 = 8;
+/// Extra points when a match starts a word, e.g. the `2` in `Screenshot 2024`
+const BOUNDARY: i64 = 10;
+/// Points lost for each skipped character between matches
+const GAP: i64 = 1;
+
+/// A parsed search box query
+#[derive(Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Lowercased words with separators removed; all must match
+    terms: Vec<Vec<char>>,
+    /// Only screenshots modified at or after this time
+    after: Option<SystemTime>,
+    /// Only screenshots modified before this time
+    before: Option<SystemTime>,
+}
+
+impl SearchQuery {
+    /// Split `query` into words and date filters; a date that doesn't parse is searched as text
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+        for word in query.split_whitespace() {
+            let lower = word.to_lowercase();
+            if let Some(time) = lower.strip_prefix("after:").and_then(start_of_day) {
+                parsed.after = Some(time);
+            } else if let Some(time) = lower.strip_prefix("before:").and_then(start_of_day) {
+                parsed.before = Some(time);
+            } else {
+                let term: Vec<char> = lower.chars().filter(|c| !is_separator(*c)).collect();
+                if !term.is_empty() {
+                    parsed.terms.push(term);
+                }
+            }
+        }
+        parsed
+    }
+
+    /// Whether the query filters nothing
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Score of `screenshot` against this query, or `None` if it doesn't match
+    fn score(&self, screenshot: &ScreenshotInfo) -> Option<i64> {
+        if self.after.is_some_and(|after| screenshot.modified < after)
+            || self.before.is_some_and(|before| screenshot.modified >= before)
+        {
+            return None;
+        }
+        let filename: Vec<char> = screenshot.filename.to_lowercase().chars().collect();
+        self.terms
+            .iter()
+            .try_fold(0, |total, term| Some(total + fuzzy_score(term, &filename)?))
+    }
+}
+
+/// Screenshots matching `query`, best match first and newest first among equals
+pub fn search_screenshots(screenshots: &[ScreenshotInfo], query: &str) -> Vec<ScreenshotInfo> {
+    let query = SearchQuery::parse(query);
+    let mut matches: Vec<(i64, &ScreenshotInfo)> = screenshots
+        .iter()
+        .filter_map(|screenshot| Some((query.score(screenshot)?, screenshot)))
+        .collect();
+    matches.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.modified.cmp(&a.1.modified)));
+    matches.into_iter().map(|(_, screenshot)| screenshot.clone()).collect()
+}
+
+/// Best score of `pattern` as a subsequence of `text` (both lowercase), or `None` if it isn't one.
+/// Every occurrence of the first character is tried as a start, matching greedily from there.
+fn fuzzy_score(pattern: &[char], text: &[char]) -> Option<i64> {
+    let first = *pattern.first()?;
+    (0..text.len())
+        .filter(|&start| text[start] == first)
+        .filter_map(|start| {
+            let mut score = MATCH + boundary_bonus(text, start);
+            let mut previous = start;
+            for &ch in &pattern[1..] {
+                let index = previous + 1 + text[previous + 1..].iter().position(|&c| c == ch)?;
+                score += MATCH + boundary_bonus(text, index);
+                score += if index == previous + 1 {
+                    CONSECUTIVE
+                } else {
+                    -GAP * (index - previous - 1) as i64
+                };
+                previous = index;
+            }
+            Some(score)
+        })
+        .max()
+}
+
+/// `BOUNDARY` if `text[index]` starts a word: after a separator or between letters and digits
+fn boundary_bonus(text: &[char], index: usize) -> i64 {
+    let Some(&before) = index.checked_sub(1).and_then(|i| text.get(i)) else {
+        return BOUNDARY;
+    };
+    let current = text[index];
+    if is_separator(before) || before.is_ascii_digit() != current.is_ascii_digit() {
+        BOUNDARY
+    } else {
+        0
+    }
+}
+
+fn is_separator(ch: char) -> bool {
+    ch.is_whitespace() || matches!(ch, '-' | '_' | '.' | ':' | '(' | ')' | '[' | ']')
+}
+
+/// Local midnight at the start of `date` (`YYYY-MM-DD`)
+fn start_of_day(date: &str) -> Option<SystemTime> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let midnight = Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?;
+    Some(midnight.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn screenshot(filename: &str, date: (i32, u32, u32)) -> ScreenshotInfo {
+        let modified = Local.with_ymd_and_hms(date.0, date.1, date.2, 12, 0, 0).unwrap();
+        ScreenshotInfo {
+            path: PathBuf::from(filename),
+            filename: filename.to_string(),
+            modified: modified.into(),
+            file_size: 0,
+            extension: "PNG".to_string(),
+            source_app: None,
+            content_hash: None,
+        }
+    }
+
+    fn names(results: Vec<ScreenshotInfo>) -> Vec<String> {
+        results.into_iter().map(|s| s.filename).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        let name = chars("screenshot 2024-05-15 143022.png");
+        assert!(fuzzy_score(&chars("0515"), &name).is_some());
+        assert!(fuzzy_score(&chars("20240515"), &name).is_some());
+        assert!(fuzzy_score(&chars("0516"), &name).is_none());
+
+        // Contiguous and word-start matches beat scattered ones
+        let contiguous = fuzzy_score(&chars("png"), &name).unwrap();
+        let scattered = fuzzy_score(&chars("sng"), &name).unwrap();
+        assert!(contiguous > scattered);
+    }
+
+    #[test]
+    fn test_search_screenshots() {
+        let screenshots = vec![
+            screenshot("Screenshot 2024-05-15 143022.png", (2024, 5, 15)),
+            screenshot("Screenshot 2024-06-01 090000.png", (2024, 6, 1)),
+            screenshot("invoice.png", (2024, 6, 2)),
+        ];
+
+        assert_eq!(
+            names(search_screenshots(&screenshots, "SCREENSHOT 2024-05")),
+            ["Screenshot 2024-05-15 143022.png"]
+        );
+        assert_eq!(names(search_screenshots(&screenshots, "invoice")), ["invoice.png"]);
+
+        // Date filters alone keep everything in range, newest first
+        assert_eq!(
+            names(search_screenshots(&screenshots, "after:2024-06-01")),
+            ["invoice.png", "Screenshot 2024-06-01 090000.png"]
+        );
+        assert_eq!(
+            names(search_screenshots(&screenshots, "shot before:2024-06-01")),
+            ["Screenshot 2024-05-15 143022.png"]
+        );
+
+        // A malformed date is just text
+        assert!(search_screenshots(&screenshots, "after:yesterday").is_empty());
+        assert!(SearchQuery::parse("  - ").is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+//! Hidden window for OS session events (logoff, shutdown, display changes),
+//! clipboard and theme changes, and Explorer restarts
+//!
+//! This is also the one place the app exits from: quitting asks the UI to shut down
+//! cleanly and falls back to exiting from here if the UI doesn't get to it in time.
+
+use crossbeam_channel::Sender;
+#[cfg(windows)]
+use log::{error, info, warn};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+#[cfg(windows)]
+use std::time::Duration;
+
+use crate::settings::Settings;
+use crate::AppMessage;
+
+/// How long the UI gets to finish its own shutdown before we exit without it
+#[cfg(windows)]
+const QUIT_GRACE: Duration = Duration::from_secs(3);
+
+/// Set once a quit starts; long-running workers check it to stop early
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Set by the UI once settings are saved and the tray icon is gone
+static SHUTDOWN_COMPLETE: AtomicBool = AtomicBool::new(false);
+
+/// Shared with the session thread
+struct SessionState {
+    settings: Arc<Mutex<Settings>>,
+    message_tx: Sender<AppMessage>,
+}
+
+static STATE: OnceLock<SessionState> = OnceLock::new();
+
+/// Handle of the session window, for posting quit requests to it
+#[cfg(windows)]
+static SESSION_HWND: Mutex<Option<isize>> = Mutex::new(None);
+
+/// Posted to the session window to start a quit
+#[cfg(windows)]
+const WM_APP_QUIT: u32 = windows::Win32::UI::WindowsAndMessaging::WM_APP + 1;
+
+/// Timer that fires when the UI hasn't finished quitting within `QUIT_GRACE`
+#[cfg(windows)]
+const QUIT_TIMER_ID: usize = 1;
+
+/// Registered "TaskbarCreated" message, broadcast when Explorer (re)creates the taskbar
+#[cfg(windows)]
+static TASKBAR_CREATED: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// Whether the app is quitting
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Tell long-running workers to stop
+pub fn begin_shutdown() {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+}
+
+/// Called by the UI when its shutdown is done
+pub fn mark_shutdown_complete() {
+    SHUTDOWN_COMPLETE.store(true, Ordering::SeqCst);
+}
+
+/// Save settings from outside the UI, for when the UI can't be relied on to do it
+#[cfg_attr(not(windows), allow(dead_code))]
+fn flush_settings() {
+    crate::settings_store::flush();
+}
+
+/// Ask the UI and workers to quit
+fn send_quit() {
+    begin_shutdown();
+    if let Some(state) = STATE.get() {
+        let _ = state.message_tx.send(AppMessage::Quit);
+    }
+}
+
+/// Start the session window on its own thread
+#[cfg(windows)]
+pub fn spawn(settings: Arc<Mutex<Settings>>, message_tx: Sender<AppMessage>) {
+    if STATE.set(SessionState { settings, message_tx }).is_err() {
+        return;
+    }
+
+    std::thread::spawn(|| {
+        if let Err(e) = run_session_window() {
+            error!("Session window error: {}", e);
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn spawn(settings: Arc<Mutex<Settings>>, message_tx: Sender<AppMessage>) {
+    let _ = STATE.set(SessionState { settings, message_tx });
+}
+
+/// Quit the app from any thread
+#[cfg(windows)]
+pub fn request_quit() {
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::PostMessageW;
+
+    match *SESSION_HWND.lock() {
+        Some(hwnd) => unsafe {
+            let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+            if PostMessageW(hwnd, WM_APP_QUIT, WPARAM(0), LPARAM(0)).is_ok() {
+                return;
+            }
+            warn!("Failed to post quit to the session window");
+        },
+        None => warn!("Session window not available, quitting without it"),
+    }
+    send_quit();
+}
+
+#[cfg(not(windows))]
+pub fn request_quit() {
+    send_quit();
+}
+
+/// Create the session window and pump its messages until the process exits
+///
+/// This is a hidden top-level window rather than a message-only (`HWND_MESSAGE`) one:
+/// message-only windows don't receive broadcasts, so they never see
+/// `WM_QUERYENDSESSION`, `WM_ENDSESSION` or `WM_DISPLAYCHANGE`.
+#[cfg(windows)]
+fn run_session_window() -> windows::core::Result<()> {
+    use windows::core::w;
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        ChangeWindowMessageFilterEx, CreateWindowExW, DispatchMessageW, GetMessageW,
+        RegisterClassW, RegisterWindowMessageW, TranslateMessage, MSG, MSGFLT_ALLOW,
+        WINDOW_STYLE, WNDCLASSW, WS_EX_TOOLWINDOW,
+    };
+
+    unsafe {
+        let class_name = w!("SukushoSessionClass");
+        let hinstance = GetModuleHandleW(None)?;
+
+        let wc = WNDCLASSW {
+            lpfnWndProc: Some(session_wndproc),
+            hInstance: hinstance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassW(&wc) == 0 {
+            return Err(windows::core::Error::from_win32());
+        }
+
+        // Never shown, and kept out of the taskbar and Alt+Tab
+        let hwnd = CreateWindowExW(
+            WS_EX_TOOLWINDOW,
+            class_name,
+            w!("Sukusho Session"),
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            hinstance,
+            None,
+        )?;
+        *SESSION_HWND.lock() = Some(hwnd.0 as isize);
+        info!("Session window created");
+
+        let taskbar_created = RegisterWindowMessageW(w!("TaskbarCreated"));
+        TASKBAR_CREATED.store(taskbar_created, Ordering::SeqCst);
+        // An elevated process would otherwise not get the broadcast from Explorer
+        if let Err(e) = ChangeWindowMessageFilterEx(hwnd, taskbar_created, MSGFLT_ALLOW, None) {
+            warn!("Failed to allow TaskbarCreated: {}", e);
+        }
+        crate::clipboard_monitor::register(hwnd);
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn session_wndproc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::LRESULT;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DefWindowProcW, KillTimer, SetTimer, WM_CLIPBOARDUPDATE, WM_DISPLAYCHANGE, WM_DPICHANGED,
+        WM_ENDSESSION, WM_QUERYENDSESSION, WM_SETTINGCHANGE, WM_TIMER,
+    };
+
+    match msg {
+        WM_QUERYENDSESSION => {
+            // Save now in case another app vetoes and the session ends later without us
+            info!("Session ending, saving settings");
+            flush_settings();
+            LRESULT(1)
+        }
+        WM_ENDSESSION => {
+            if wparam.0 != 0 {
+                // The process is terminated as soon as we return, so wait for the UI here
+                info!("Session ended, shutting down");
+                send_quit();
+                if !wait_for_shutdown(QUIT_GRACE) {
+                    warn!("UI did not shut down before session end");
+                    flush_settings();
+                }
+            }
+            LRESULT(0)
+        }
+        WM_DISPLAYCHANGE => {
+            info!("Display configuration changed");
+            crate::tray::ensure_window_on_screen();
+            if let Some(state) = STATE.get() {
+                let _ = state.message_tx.send(AppMessage::DisplayScaleChanged);
+            }
+            LRESULT(0)
+        }
+        WM_DPICHANGED => {
+            info!("Display scale changed");
+            if let Some(state) = STATE.get() {
+                let _ = state.message_tx.send(AppMessage::DisplayScaleChanged);
+            }
+            LRESULT(0)
+        }
+        WM_SETTINGCHANGE if is_theme_change(lparam) => {
+            info!("System theme changed");
+            if let Some(state) = STATE.get() {
+                let _ = state.message_tx.send(AppMessage::SystemThemeChanged);
+            }
+            LRESULT(0)
+        }
+        WM_CLIPBOARDUPDATE => {
+            if let Some(state) = STATE.get() {
+                crate::clipboard_monitor::on_clipboard_update(&state.settings);
+            }
+            LRESULT(0)
+        }
+        WM_APP_QUIT => {
+            send_quit();
+            unsafe {
+                SetTimer(hwnd, QUIT_TIMER_ID, QUIT_GRACE.as_millis() as u32, None);
+            }
+            LRESULT(0)
+        }
+        WM_TIMER if wparam.0 == QUIT_TIMER_ID => {
+            unsafe {
+                let _ = KillTimer(hwnd, QUIT_TIMER_ID);
+            }
+            if !SHUTDOWN_COMPLETE.load(Ordering::SeqCst) {
+                warn!("UI did not quit in time, exiting");
+                flush_settings();
+                std::process::exit(0);
+            }
+            LRESULT(0)
+        }
+        _ if msg != 0 && msg == TASKBAR_CREATED.load(Ordering::SeqCst) => {
+            // Explorer restarted: the tray icon is gone, or never got created
+            info!("Taskbar created");
+            if let Some(state) = STATE.get() {
+                let _ = state.message_tx.send(AppMessage::TaskbarCreated);
+            }
+            LRESULT(0)
+        }
+        _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+    }
+}
+
+/// Whether a `WM_SETTINGCHANGE` is for "ImmersiveColorSet", sent on light/dark switches
+#[cfg(windows)]
+fn is_theme_change(lparam: windows::Win32::Foundation::LPARAM) -> bool {
+    if lparam.0 == 0 {
+        return false;
+    }
+    let name = windows::core::PCWSTR(lparam.0 as *const u16);
+    unsafe { name.to_string() }.is_ok_and(|name| name == "ImmersiveColorSet")
+}
+
+/// Wait until the UI reports its shutdown complete, up to `timeout`
+#[cfg(windows)]
+fn wait_for_shutdown(timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    while !SHUTDOWN_COMPLETE.load(Ordering::SeqCst) {
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    true
+}
@@ -1,11 +1,13 @@
 //! Application settings and persistence
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// Theme mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -31,11 +33,204 @@ impl ThemeMode {
     }
 }
 
+/// Screen corner where tray notifications appear
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// What pressing ESC during a drag does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DragEscAction {
+    /// Cancel the drag, as in Explorer
+    #[default]
+    Cancel,
+    /// Cancel the drag and copy the dragged files, for when there's no obvious drop target
+    CopyToClipboard,
+}
+
+/// What a left click on the tray icon does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayClickAction {
+    #[default]
+    ToggleWindow,
+    OpenFolder,
+    CopyLatest,
+    Nothing,
+}
+
+/// Where screen captures go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaptureTarget {
+    /// A file in the screenshot folder, in `Settings::capture_format`
+    #[default]
+    File,
+    /// The clipboard only; nothing is written to disk
+    Clipboard,
+    /// Saved to the folder and copied to the clipboard
+    Both,
+}
+
+/// File format of saved captures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaptureFormat {
+    /// Lossless, compressed at `Settings::png_compression`
+    #[default]
+    Png,
+    /// Smaller, at `Settings::jpeg_quality`
+    Jpeg,
+}
+
+impl CaptureFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Jpeg => "jpg",
+        }
+    }
+}
+
+/// What a double click on the tray icon does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayDoubleClickAction {
+    #[default]
+    ShowWindow,
+    OpenLatest,
+    OpenFolder,
+}
+
+/// What happens to an image over the large-image limits before it is copied or uploaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LargeImageAction {
+    /// Go ahead, but show a notification
+    #[default]
+    Warn,
+    /// Scale it down to fit the limits first
+    Downscale,
+    /// Go ahead without a word
+    Proceed,
+}
+
+/// Look of the tray icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayIconStyle {
+    #[default]
+    Color,
+    /// Single-color silhouette matching the taskbar theme
+    Mono,
+}
+
+/// Order of the gallery and the recent-screenshot lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+    /// Case-insensitive by file name
+    NameAsc,
+    NameDesc,
+}
+
+impl SortOrder {
+    /// Compare two screenshots by file name and modification time.
+    /// Ties are broken newest first, then by name, so the order is stable.
+    pub fn compare(
+        self,
+        (a_name, a_modified): (&str, SystemTime),
+        (b_name, b_modified): (&str, SystemTime),
+    ) -> Ordering {
+        let by_name = || a_name.to_lowercase().cmp(&b_name.to_lowercase());
+        let newest = || b_modified.cmp(&a_modified);
+        match self {
+            SortOrder::NewestFirst => newest().then_with(by_name),
+            SortOrder::OldestFirst => newest().reverse().then_with(by_name),
+            SortOrder::NameAsc => by_name().then_with(newest),
+            SortOrder::NameDesc => by_name().reverse().then_with(newest),
+        }
+    }
+}
+
+/// What opening a screenshot from the tray, a notification or the gallery does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAction {
+    /// Show it in the app's own preview
+    Internal,
+    /// Open it with the default image viewer
+    #[default]
+    DefaultApp,
+    /// Select it in Explorer
+    Reveal,
+}
+
+/// Where a watermark is placed on the screenshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatermarkAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    #[default]
+    BottomRight,
+}
+
+/// Text or image stamped onto new screenshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatermarkSettings {
+    pub enabled: bool,
+    /// Text to draw; ignored when `image_path` is set
+    pub text: String,
+    /// Image (e.g. a logo) drawn instead of text
+    pub image_path: Option<PathBuf>,
+    pub anchor: WatermarkAnchor,
+    /// 0.0 (invisible) to 1.0 (opaque)
+    pub opacity: f32,
+    /// Height of the watermark as a fraction of the screenshot's shorter side
+    pub scale: f32,
+    /// Stamp the screenshot itself instead of saving a watermarked copy next to it
+    pub overwrite: bool,
+}
+
+impl Default for WatermarkSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            text: String::new(),
+            image_path: None,
+            anchor: WatermarkAnchor::BottomRight,
+            opacity: 0.6,
+            scale: 0.04,
+            overwrite: false,
+        }
+    }
+}
+
 /// Supported conversion formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConversionFormat {
     WebP,
     Jpeg,
+    Png,
+    Avif,
 }
 
 impl Default for ConversionFormat {
@@ -49,6 +244,8 @@ impl ConversionFormat {
         match self {
             ConversionFormat::WebP => "webp",
             ConversionFormat::Jpeg => "jpg",
+            ConversionFormat::Png => "png",
+            ConversionFormat::Avif => "avif",
         }
     }
 
@@ -56,12 +253,14 @@ impl ConversionFormat {
         match self {
             ConversionFormat::WebP => "WebP",
             ConversionFormat::Jpeg => "JPEG",
+            ConversionFormat::Png => "PNG",
+            ConversionFormat::Avif => "AVIF",
         }
     }
 }
 
 /// Application settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Settings {
     /// Directory to watch for screenshots
     pub screenshot_directory: PathBuf,
@@ -72,16 +271,28 @@ pub struct Settings {
     /// Thumbnail size in pixels
     pub thumbnail_size: u32,
 
+    /// Order of the gallery and the recent-screenshot lists
+    #[serde(default)]
+    pub sort_order: SortOrder,
+
     /// Auto-convert new screenshots
     pub auto_convert_webp: bool,
 
-    /// Conversion format (WebP or JPEG)
+    /// Conversion format (WebP, JPEG, PNG or AVIF)
     #[serde(default)]
     pub conversion_format: ConversionFormat,
 
-    /// Conversion quality (0-100)
+    /// Conversion quality (0-100), used for JPEG output
     pub webp_quality: u32,
 
+    /// AVIF conversion quality (1-100)
+    #[serde(default = "default_avif_quality")]
+    pub avif_quality: u32,
+
+    /// Keep the original file next to the converted one instead of replacing it
+    #[serde(default)]
+    pub keep_original_after_convert: bool,
+
     /// Window width
     pub window_width: f32,
 
@@ -96,6 +307,59 @@ pub struct Settings {
     #[serde(default = "default_hotkey")]
     pub hotkey: String,
 
+    /// Global hotkey for region capture (empty = unbound)
+    #[serde(default)]
+    pub capture_hotkey: String,
+
+    /// Global hotkey for full-screen capture (empty = unbound)
+    #[serde(default)]
+    pub capture_fullscreen_hotkey: String,
+
+    /// Global hotkey for active-window capture (empty = unbound)
+    #[serde(default)]
+    pub capture_window_hotkey: String,
+
+    /// Global hotkey that copies the foreground window to the clipboard (empty = unbound)
+    #[serde(default)]
+    pub capture_window_clipboard_hotkey: String,
+
+    /// Also save captures copied with `capture_window_clipboard_hotkey` to the screenshot folder
+    #[serde(default)]
+    pub save_clipboard_captures: bool,
+
+    /// Monitor for full-screen capture (index in enumeration order, None = all monitors)
+    #[serde(default)]
+    pub capture_monitor: Option<usize>,
+
+    /// Full-screen capture grabs the monitor under the cursor (overrides `capture_monitor`)
+    #[serde(default)]
+    pub capture_monitor_under_cursor: bool,
+
+    /// Where region, full-screen and window captures go
+    #[serde(default)]
+    pub default_capture_target: CaptureTarget,
+
+    /// File format of saved captures
+    #[serde(default)]
+    pub capture_format: CaptureFormat,
+
+    /// PNG compression level for captures (0 = fastest, 9 = smallest)
+    #[serde(default = "default_png_compression")]
+    pub png_compression: u8,
+
+    /// JPEG quality for captures (1-100)
+    #[serde(default = "default_jpeg_quality")]
+    pub jpeg_quality: u8,
+
+    /// Save images copied to the clipboard into the screenshot folder
+    #[serde(default)]
+    pub capture_clipboard_images: bool,
+
+    /// Copying a single screenshot (Ctrl+C, tray, `--copy-latest`) also puts it on the clipboard
+    /// as an image, not just as a file
+    #[serde(default)]
+    pub copy_as_image: bool,
+
     /// Screenshot organizer enabled
     #[serde(default)]
     pub organizer_enabled: bool,
@@ -104,6 +368,14 @@ pub struct Settings {
     #[serde(default = "default_organizer_format")]
     pub organizer_format: String,
 
+    /// Rename new screenshots from `filename_template`
+    #[serde(default)]
+    pub rename_enabled: bool,
+
+    /// Filename template for renamed screenshots (e.g., "Screenshot {date:%Y-%m-%d} {counter:03}")
+    #[serde(default = "default_filename_template")]
+    pub filename_template: String,
+
     /// Vector search indexing enabled
     #[serde(default)]
     pub indexing_enabled: bool,
@@ -132,13 +404,167 @@ pub struct Settings {
     #[serde(default)]
     pub theme: ThemeMode,
 
-    /// Run on Windows startup
+    /// Accent for progress bars and badges: "system" or an RGB hex string like "#0078D7"
+    #[serde(default = "default_accent_color")]
+    pub accent_color: String,
+
+    /// Keep the main window above other windows
     #[serde(default)]
-    pub run_on_startup: bool,
+    pub always_on_top: bool,
+
+    /// Pass over Sukusho's own windows when capturing or detecting the active window
+    #[serde(default = "default_exclude_own_windows")]
+    pub exclude_own_windows: bool,
+
+    /// Launch at Windows login
+    #[serde(default, alias = "run_on_startup")]
+    pub autostart: bool,
 
     /// Hide window on start (show only tray icon)
     #[serde(default)]
     pub hide_window_on_start: bool,
+
+    /// Allow dragging the latest screenshot out of the tray icon
+    #[serde(default = "default_enable_tray_drag")]
+    pub enable_tray_drag: bool,
+
+    /// What ESC does while dragging screenshots out
+    #[serde(default)]
+    pub drag_esc_action: DragEscAction,
+
+    /// Left-click action on the tray icon (dragging works regardless)
+    #[serde(default)]
+    pub tray_left_click_action: TrayClickAction,
+
+    /// Double-click action on the tray icon
+    #[serde(default)]
+    pub tray_double_click_action: TrayDoubleClickAction,
+
+    /// What opening a screenshot does
+    #[serde(default)]
+    pub open_action: OpenAction,
+
+    /// Tray icon look
+    #[serde(default)]
+    pub tray_icon_style: TrayIconStyle,
+
+    /// Show desktop notifications for completed actions
+    #[serde(default = "default_notifications_enabled")]
+    pub notifications_enabled: bool,
+
+    /// Collapse bursts of new screenshots into one "N new screenshots" notification
+    #[serde(default = "default_notification_batching")]
+    pub notification_batching: bool,
+
+    /// How long in-app notifications stay visible, in milliseconds
+    #[serde(default = "default_notification_duration_ms")]
+    pub notification_duration_ms: u64,
+
+    /// Corner of the work area where tray notifications appear
+    #[serde(default)]
+    pub notification_corner: NotificationCorner,
+
+    /// Monitor for tray notifications (index in enumeration order, None = primary)
+    #[serde(default)]
+    pub notification_monitor: Option<usize>,
+
+    /// File extensions the watcher treats as screenshots (lowercase, without dot)
+    #[serde(default = "default_watched_extensions")]
+    pub watched_extensions: Vec<String>,
+
+    /// Send deleted screenshots to the Recycle Bin instead of deleting permanently
+    #[serde(default = "default_delete_to_recycle_bin")]
+    pub delete_to_recycle_bin: bool,
+
+    /// Load only this many of the newest existing screenshots at startup; older ones
+    /// are paged in on demand (None = load everything)
+    #[serde(default = "default_initial_scan_limit")]
+    pub initial_scan_limit: Option<usize>,
+
+    /// Files smaller than this are ignored until they grow, e.g. empty placeholders (0 = no minimum)
+    #[serde(default = "default_min_file_size_bytes")]
+    pub min_file_size_bytes: u64,
+
+    /// Check that new files really are images by their first bytes, not just their extension
+    #[serde(default)]
+    pub verify_image_magic: bool,
+
+    /// How often a screenshot folder on a network share is polled for changes, in milliseconds
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+
+    /// Show screenshots in subfolders of the screenshot folder too
+    /// (always on while the organizer files them into dated folders)
+    #[serde(default = "default_watch_subdirectories")]
+    pub watch_subdirectories: bool,
+
+    /// Keep at most this many screenshots, deleting the oldest (None = unlimited)
+    #[serde(default)]
+    pub max_screenshots: Option<usize>,
+
+    /// Delete screenshots older than this many days (None = keep forever)
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+
+    /// Imgur API client ID for anonymous uploads
+    #[serde(default)]
+    pub imgur_client_id: String,
+
+    /// Self-hosted upload endpoint (empty = upload to Imgur)
+    #[serde(default)]
+    pub upload_endpoint: String,
+
+    /// Multipart form field name for the self-hosted endpoint
+    #[serde(default = "default_upload_field_name")]
+    pub upload_field_name: String,
+
+    /// Largest file "Copy as Data URI" accepts, in bytes (the URI is about a third larger)
+    #[serde(default = "default_data_uri_max_bytes")]
+    pub data_uri_max_bytes: u64,
+
+    /// Longest side in pixels above which an image counts as large
+    #[serde(default = "default_large_image_threshold")]
+    pub large_image_threshold: u32,
+
+    /// File size in bytes above which an image counts as large
+    #[serde(default = "default_large_image_max_bytes")]
+    pub large_image_max_bytes: u64,
+
+    /// Images declaring more pixels than this are never decoded for thumbnails
+    #[serde(default = "default_max_decode_pixels")]
+    pub max_decode_pixels: u64,
+
+    /// How long reading a file for a thumbnail may take before giving up, in milliseconds
+    #[serde(default = "default_thumbnail_open_timeout_ms")]
+    pub thumbnail_open_timeout_ms: u64,
+
+    /// What copying or uploading a large image does
+    #[serde(default)]
+    pub large_image_action: LargeImageAction,
+
+    /// Watermark stamped onto new screenshots after conversion
+    #[serde(default)]
+    pub watermark: WatermarkSettings,
+
+    /// Number of recent screenshots kept in memory for the tray, notifications and drag
+    #[serde(default = "default_recent_history_size")]
+    pub recent_history_size: usize,
+
+    /// Columns of the contact sheet exported from the tray
+    #[serde(default = "default_contact_sheet_columns")]
+    pub contact_sheet_columns: usize,
+
+    /// Side of each contact sheet cell in pixels
+    #[serde(default = "default_contact_sheet_cell_size")]
+    pub contact_sheet_cell_size: u32,
+
+    /// Hours between background update checks (0 = only check when asked)
+    #[serde(default)]
+    pub update_check_interval_hours: u64,
+
+    /// Log level ("error", "warn", "info", "debug" or "trace"); `RUST_LOG` overrides it
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
 }
 
 fn default_hotkey_enabled() -> bool {
@@ -153,6 +579,10 @@ fn default_organizer_format() -> String {
     "YYYY-MM-DD".to_string()
 }
 
+fn default_filename_template() -> String {
+    "Screenshot {date:%Y-%m-%d} {counter:03}".to_string()
+}
+
 fn default_cpu_mode() -> String {
     "normal".to_string()
 }
@@ -161,21 +591,147 @@ fn default_window_opacity() -> f32 {
     1.0 // Fully opaque by default
 }
 
+fn default_enable_tray_drag() -> bool {
+    true
+}
+
+fn default_initial_scan_limit() -> Option<usize> {
+    Some(200)
+}
+
+fn default_min_file_size_bytes() -> u64 {
+    1024
+}
+
+fn default_poll_interval_ms() -> u64 {
+    2000
+}
+
+fn default_watch_subdirectories() -> bool {
+    true
+}
+
+fn default_avif_quality() -> u32 {
+    70
+}
+
+fn default_png_compression() -> u8 {
+    6
+}
+
+fn default_jpeg_quality() -> u8 {
+    90
+}
+
+fn default_data_uri_max_bytes() -> u64 {
+    2 * 1024 * 1024
+}
+
+fn default_recent_history_size() -> usize {
+    50
+}
+
+fn default_contact_sheet_columns() -> usize {
+    4
+}
+
+fn default_contact_sheet_cell_size() -> u32 {
+    240
+}
+
+fn default_large_image_threshold() -> u32 {
+    7680
+}
+
+fn default_large_image_max_bytes() -> u64 {
+    // Imgur's limit for still images
+    20 * 1024 * 1024
+}
+
+fn default_max_decode_pixels() -> u64 {
+    // About 10000x10000, well above any real screenshot
+    100_000_000
+}
+
+fn default_thumbnail_open_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_accent_color() -> String {
+    crate::accent::SYSTEM.to_string()
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_exclude_own_windows() -> bool {
+    true
+}
+
+fn default_notifications_enabled() -> bool {
+    true
+}
+
+fn default_notification_batching() -> bool {
+    true
+}
+
+fn default_notification_duration_ms() -> u64 {
+    3000
+}
+
+fn default_delete_to_recycle_bin() -> bool {
+    true
+}
+
+fn default_upload_field_name() -> String {
+    "file".to_string()
+}
+
+/// `default_watched_extensions` before HEIC/HEIF were added
+const PRE_HEIF_WATCHED_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "bmp", "webp", "avif"];
+
+fn default_watched_extensions() -> Vec<String> {
+    ["png", "jpg", "jpeg", "gif", "bmp", "webp", "avif", "heic", "heif"]
+        .iter()
+        .map(|e| e.to_string())
+        .collect()
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
             screenshot_directory: Self::default_screenshot_directory(),
             grid_columns: 4,
             thumbnail_size: 150,
+            sort_order: SortOrder::NewestFirst,
             auto_convert_webp: false,
             conversion_format: ConversionFormat::WebP,
             webp_quality: 85,
+            avif_quality: default_avif_quality(),
+            keep_original_after_convert: false,
             window_width: 815.0,
             window_height: 550.0,
             hotkey_enabled: true,
             hotkey: "Ctrl+Shift+S".to_string(),
+            capture_hotkey: String::new(),
+            capture_fullscreen_hotkey: String::new(),
+            capture_window_hotkey: String::new(),
+            capture_window_clipboard_hotkey: String::new(),
+            save_clipboard_captures: false,
+            capture_monitor: None,
+            capture_monitor_under_cursor: false,
+            default_capture_target: CaptureTarget::File,
+            capture_format: CaptureFormat::Png,
+            png_compression: default_png_compression(),
+            jpeg_quality: default_jpeg_quality(),
+            capture_clipboard_images: false,
+            copy_as_image: false,
             organizer_enabled: false,
             organizer_format: "YYYY-MM-DD".to_string(),
+            rename_enabled: false,
+            filename_template: default_filename_template(),
             indexing_enabled: false,
             indexing_cpu_mode: "normal".to_string(),
             models_downloaded: false,
@@ -183,8 +739,46 @@ impl Default for Settings {
             language: None, // Auto-detect from system
             window_opacity: 1.0, // Fully opaque by default
             theme: ThemeMode::Dark, // Dark theme by default
-            run_on_startup: false, // Don't run on startup by default
+            accent_color: default_accent_color(),
+            autostart: false, // Don't run on startup by default
             hide_window_on_start: false, // Show window by default
+            always_on_top: false,
+            exclude_own_windows: true,
+            enable_tray_drag: true,
+            drag_esc_action: DragEscAction::Cancel,
+            tray_left_click_action: TrayClickAction::ToggleWindow,
+            tray_double_click_action: TrayDoubleClickAction::ShowWindow,
+            open_action: OpenAction::DefaultApp,
+            tray_icon_style: TrayIconStyle::Color,
+            notifications_enabled: true,
+            notification_batching: default_notification_batching(),
+            notification_duration_ms: default_notification_duration_ms(),
+            notification_corner: NotificationCorner::BottomRight,
+            notification_monitor: None,
+            watched_extensions: default_watched_extensions(),
+            delete_to_recycle_bin: true,
+            initial_scan_limit: default_initial_scan_limit(),
+            min_file_size_bytes: default_min_file_size_bytes(),
+            verify_image_magic: false,
+            poll_interval_ms: default_poll_interval_ms(),
+            watch_subdirectories: default_watch_subdirectories(),
+            max_screenshots: None,
+            max_age_days: None,
+            imgur_client_id: String::new(),
+            upload_endpoint: String::new(),
+            data_uri_max_bytes: default_data_uri_max_bytes(),
+            large_image_threshold: default_large_image_threshold(),
+            large_image_max_bytes: default_large_image_max_bytes(),
+            max_decode_pixels: default_max_decode_pixels(),
+            thumbnail_open_timeout_ms: default_thumbnail_open_timeout_ms(),
+            large_image_action: LargeImageAction::Warn,
+            watermark: WatermarkSettings::default(),
+            recent_history_size: default_recent_history_size(),
+            contact_sheet_columns: default_contact_sheet_columns(),
+            contact_sheet_cell_size: default_contact_sheet_cell_size(),
+            update_check_interval_hours: 0,
+            log_level: default_log_level(),
+            upload_field_name: default_upload_field_name(),
         }
     }
 }
@@ -222,13 +816,221 @@ impl Settings {
             return Ok(Self::default());
         }
 
-        let content = fs::read_to_string(&path)?;
-        let settings: Self = serde_json::from_str(&content)?;
+        Self::load_from(&path)
+    }
+
+    /// Load settings from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut settings: Self = serde_json::from_str(&content)?;
+        settings.migrate();
 
         info!("Loaded settings from {:?}", path);
         Ok(settings)
     }
 
+    /// Export settings as pretty-printed JSON to the given file
+    pub fn export_to(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+
+        info!("Exported settings to {:?}", path);
+        Ok(())
+    }
+
+    /// Import settings from a JSON file, merging over the current values
+    ///
+    /// Fields missing from the file keep their current values and unknown fields
+    /// are ignored. `self` is left untouched if the file is malformed or invalid.
+    pub fn import_from(&mut self, path: &Path) -> Result<()> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let imported: serde_json::Value =
+            serde_json::from_str(&content).context("File is not valid JSON")?;
+        let merged = self.merged_with(imported)?;
+
+        *self = merged;
+        info!("Imported settings from {:?}", path);
+        Ok(())
+    }
+
+    /// Overlay a JSON object onto these settings and validate the result
+    fn merged_with(&self, overlay: serde_json::Value) -> Result<Self> {
+        let serde_json::Value::Object(overlay) = overlay else {
+            anyhow::bail!("Settings file must contain a JSON object");
+        };
+
+        let mut base = serde_json::to_value(self)?;
+        if let serde_json::Value::Object(ref mut map) = base {
+            map.extend(overlay);
+        }
+
+        let mut merged: Self =
+            serde_json::from_value(base).context("Settings file has invalid values")?;
+        merged.migrate();
+        merged.validate()?;
+
+        // A path from another machine may not exist here
+        if !merged.screenshot_directory.exists() {
+            warn!(
+                "Imported screenshot directory {:?} does not exist, keeping {:?}",
+                merged.screenshot_directory, self.screenshot_directory
+            );
+            merged.screenshot_directory = self.screenshot_directory.clone();
+        }
+
+        Ok(merged)
+    }
+
+    /// Bring settings saved by an older version up to date
+    fn migrate(&mut self) {
+        // Saved defaults predate HEIC/HEIF support; lists the user edited are left alone
+        if self.watched_extensions == PRE_HEIF_WATCHED_EXTENSIONS {
+            info!("Adding heic and heif to the default watched extensions");
+            self.watched_extensions = default_watched_extensions();
+        }
+    }
+
+    /// Check that values are within the ranges the UI allows
+    pub fn validate(&self) -> Result<()> {
+        if self.grid_columns == 0 {
+            anyhow::bail!("grid_columns must be at least 1");
+        }
+        if !(80..=300).contains(&self.thumbnail_size) {
+            anyhow::bail!("thumbnail_size must be between 80 and 300");
+        }
+        if !(1..=100).contains(&self.webp_quality) {
+            anyhow::bail!("webp_quality must be between 1 and 100");
+        }
+        if !(1..=100).contains(&self.avif_quality) {
+            anyhow::bail!("avif_quality must be between 1 and 100");
+        }
+        if self.png_compression > 9 {
+            anyhow::bail!("png_compression must be between 0 and 9");
+        }
+        if !(1..=100).contains(&self.jpeg_quality) {
+            anyhow::bail!("jpeg_quality must be between 1 and 100");
+        }
+        if !(0.3..=1.0).contains(&self.window_opacity) {
+            anyhow::bail!("window_opacity must be between 0.3 and 1.0");
+        }
+        if self.watched_extensions.is_empty() {
+            anyhow::bail!("watched_extensions must not be empty");
+        }
+        if self.initial_scan_limit == Some(0) {
+            anyhow::bail!("initial_scan_limit must be at least 1");
+        }
+        if self.poll_interval_ms < 100 {
+            anyhow::bail!("poll_interval_ms must be at least 100");
+        }
+        if self.max_screenshots == Some(0) {
+            anyhow::bail!("max_screenshots must be at least 1");
+        }
+        if self.max_age_days == Some(0) {
+            anyhow::bail!("max_age_days must be at least 1");
+        }
+        if self.data_uri_max_bytes == 0 {
+            anyhow::bail!("data_uri_max_bytes must be at least 1");
+        }
+        if self.large_image_threshold == 0 {
+            anyhow::bail!("large_image_threshold must be at least 1");
+        }
+        if self.large_image_max_bytes == 0 {
+            anyhow::bail!("large_image_max_bytes must be at least 1");
+        }
+        if self.max_decode_pixels == 0 {
+            anyhow::bail!("max_decode_pixels must be at least 1");
+        }
+        if self.thumbnail_open_timeout_ms < 100 {
+            anyhow::bail!("thumbnail_open_timeout_ms must be at least 100");
+        }
+        if !(0.0..=1.0).contains(&self.watermark.opacity) {
+            anyhow::bail!("watermark opacity must be between 0 and 1");
+        }
+        if !(self.watermark.scale > 0.0 && self.watermark.scale <= 1.0) {
+            anyhow::bail!("watermark scale must be greater than 0 and at most 1");
+        }
+        if self.watermark.enabled
+            && self.watermark.image_path.is_none()
+            && self.watermark.text.trim().is_empty()
+        {
+            anyhow::bail!("watermark needs a text or an image_path when enabled");
+        }
+        if self.recent_history_size == 0 {
+            anyhow::bail!("recent_history_size must be at least 1");
+        }
+        if self.update_check_interval_hours > 24 * 365 {
+            anyhow::bail!("update_check_interval_hours must be at most a year (8760)");
+        }
+        if !(1..=12).contains(&self.contact_sheet_columns) {
+            anyhow::bail!("contact_sheet_columns must be between 1 and 12");
+        }
+        if !(64..=1024).contains(&self.contact_sheet_cell_size) {
+            anyhow::bail!("contact_sheet_cell_size must be between 64 and 1024");
+        }
+        if !crate::accent::is_valid_setting(&self.accent_color) {
+            anyhow::bail!("accent_color must be \"system\" or an RGB hex color like #0078D7");
+        }
+        if self.log_level.parse::<log::LevelFilter>().is_err() {
+            anyhow::bail!("log_level must be one of off, error, warn, info, debug or trace");
+        }
+        let endpoint = self.upload_endpoint.trim();
+        if !endpoint.is_empty()
+            && !(endpoint.starts_with("https://") || endpoint.starts_with("http://"))
+        {
+            anyhow::bail!("upload_endpoint must be an http(s) URL");
+        }
+        crate::rename::FilenameTemplate::parse(&self.filename_template)
+            .context("filename_template is invalid")?;
+        Ok(())
+    }
+
+    /// Whether the watcher looks into subfolders of the screenshot folder
+    pub fn watches_subdirectories(&self) -> bool {
+        self.watch_subdirectories || self.organizer_enabled
+    }
+
+    /// Target format for newly detected screenshots, or None when conversion is off
+    pub fn convert_to(&self) -> Option<ConversionFormat> {
+        self.auto_convert_webp.then_some(self.conversion_format)
+    }
+
+    /// Encoder quality for `format`; AVIF has its own setting
+    pub fn quality_for(&self, format: ConversionFormat) -> u32 {
+        match format {
+            ConversionFormat::Avif => self.avif_quality,
+            _ => self.webp_quality,
+        }
+    }
+
+    /// Which monitor(s) full-screen capture grabs
+    pub fn fullscreen_target(&self) -> crate::capture::FullscreenTarget {
+        use crate::capture::FullscreenTarget;
+
+        match (self.capture_monitor_under_cursor, self.capture_monitor) {
+            (true, _) => FullscreenTarget::UnderCursor,
+            (false, Some(index)) => FullscreenTarget::Monitor(index),
+            (false, None) => FullscreenTarget::AllMonitors,
+        }
+    }
+
+    /// Cleanup limits for the retention task
+    pub fn retention_policy(&self) -> crate::retention::RetentionPolicy {
+        crate::retention::RetentionPolicy {
+            max_screenshots: self.max_screenshots,
+            max_age_days: self.max_age_days,
+        }
+    }
+
+    /// Limits for copying and uploading large images
+    pub fn large_image_policy(&self) -> crate::large_image::LargeImagePolicy {
+        crate::large_image::LargeImagePolicy {
+            max_dimension: self.large_image_threshold,
+            max_bytes: self.large_image_max_bytes,
+            action: self.large_image_action,
+        }
+    }
+
     /// Save settings to disk
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path()
@@ -268,12 +1070,66 @@ mod tests {
     fn test_conversion_format_extension() {
         assert_eq!(ConversionFormat::WebP.extension(), "webp");
         assert_eq!(ConversionFormat::Jpeg.extension(), "jpg");
+        assert_eq!(ConversionFormat::Avif.extension(), "avif");
     }
 
     #[test]
     fn test_conversion_format_display_name() {
         assert_eq!(ConversionFormat::WebP.display_name(), "WebP");
         assert_eq!(ConversionFormat::Jpeg.display_name(), "JPEG");
+        assert_eq!(ConversionFormat::Png.display_name(), "PNG");
+        assert_eq!(ConversionFormat::Avif.display_name(), "AVIF");
+    }
+
+    #[test]
+    fn test_tray_click_action_serde() {
+        let json = serde_json::to_string(&TrayClickAction::CopyLatest).unwrap();
+        assert_eq!(json, "\"copy-latest\"");
+
+        let action: TrayClickAction = serde_json::from_str("\"open-folder\"").unwrap();
+        assert_eq!(action, TrayClickAction::OpenFolder);
+
+        let action: TrayDoubleClickAction = serde_json::from_str("\"open-latest\"").unwrap();
+        assert_eq!(action, TrayDoubleClickAction::OpenLatest);
+
+        let action: OpenAction = serde_json::from_str("\"default_app\"").unwrap();
+        assert_eq!(action, OpenAction::DefaultApp);
+    }
+
+    #[test]
+    fn test_notification_corner_serde() {
+        let json = serde_json::to_string(&NotificationCorner::TopLeft).unwrap();
+        assert_eq!(json, "\"top-left\"");
+
+        let settings = Settings::default()
+            .merged_with(serde_json::json!({ "notification_corner": "bottom-left" }))
+            .unwrap();
+        assert_eq!(settings.notification_corner, NotificationCorner::BottomLeft);
+        assert_eq!(settings.notification_monitor, None);
+    }
+
+    #[test]
+    fn test_convert_to() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.convert_to(), None);
+
+        settings.auto_convert_webp = true;
+        settings.conversion_format = ConversionFormat::Png;
+        assert_eq!(settings.convert_to(), Some(ConversionFormat::Png));
+    }
+
+    #[test]
+    fn test_fullscreen_target() {
+        use crate::capture::FullscreenTarget;
+
+        let mut settings = Settings::default();
+        assert_eq!(settings.fullscreen_target(), FullscreenTarget::AllMonitors);
+
+        settings.capture_monitor = Some(1);
+        assert_eq!(settings.fullscreen_target(), FullscreenTarget::Monitor(1));
+
+        settings.capture_monitor_under_cursor = true;
+        assert_eq!(settings.fullscreen_target(), FullscreenTarget::UnderCursor);
     }
 
     #[test]
@@ -283,15 +1139,56 @@ mod tests {
         // Check default values
         assert_eq!(settings.grid_columns, 4);
         assert_eq!(settings.thumbnail_size, 150);
+        assert_eq!(settings.sort_order, SortOrder::NewestFirst);
         assert_eq!(settings.auto_convert_webp, false);
         assert_eq!(settings.conversion_format, ConversionFormat::WebP);
         assert_eq!(settings.webp_quality, 85);
+        assert_eq!(settings.avif_quality, 70);
         assert_eq!(settings.window_width, 815.0);
         assert_eq!(settings.window_height, 550.0);
         assert_eq!(settings.hotkey_enabled, true);
         assert_eq!(settings.hotkey, "Ctrl+Shift+S");
         assert_eq!(settings.organizer_enabled, false);
         assert_eq!(settings.organizer_format, "YYYY-MM-DD");
+        assert_eq!(settings.delete_to_recycle_bin, true);
+        assert_eq!(settings.enable_tray_drag, true);
+        assert_eq!(settings.drag_esc_action, DragEscAction::Cancel);
+        assert_eq!(settings.always_on_top, false);
+        assert_eq!(settings.exclude_own_windows, true);
+        assert_eq!(settings.capture_clipboard_images, false);
+        assert_eq!(settings.copy_as_image, false);
+        assert_eq!(settings.default_capture_target, CaptureTarget::File);
+        assert_eq!(settings.capture_format, CaptureFormat::Png);
+        assert_eq!(settings.png_compression, 6);
+        assert_eq!(settings.jpeg_quality, 90);
+        assert_eq!(settings.data_uri_max_bytes, 2 * 1024 * 1024);
+        assert_eq!(settings.large_image_threshold, 7680);
+        assert_eq!(settings.large_image_max_bytes, 20 * 1024 * 1024);
+        assert_eq!(settings.max_decode_pixels, 100_000_000);
+        assert_eq!(settings.thumbnail_open_timeout_ms, 5000);
+        assert_eq!(settings.large_image_action, LargeImageAction::Warn);
+        assert_eq!(settings.watermark.enabled, false);
+        assert_eq!(settings.watermark.anchor, WatermarkAnchor::BottomRight);
+        assert_eq!(settings.recent_history_size, 50);
+        assert_eq!(settings.contact_sheet_columns, 4);
+        assert_eq!(settings.contact_sheet_cell_size, 240);
+        assert_eq!(settings.update_check_interval_hours, 0);
+        assert_eq!(settings.log_level, "info");
+        assert_eq!(settings.capture_hotkey, "");
+        assert_eq!(settings.capture_window_clipboard_hotkey, "");
+        assert_eq!(settings.save_clipboard_captures, false);
+        assert_eq!(settings.tray_left_click_action, TrayClickAction::ToggleWindow);
+        assert_eq!(settings.tray_double_click_action, TrayDoubleClickAction::ShowWindow);
+        assert_eq!(settings.open_action, OpenAction::DefaultApp);
+        assert_eq!(settings.tray_icon_style, TrayIconStyle::Color);
+        assert_eq!(settings.accent_color, "system");
+        assert_eq!(settings.notifications_enabled, true);
+        assert_eq!(settings.notification_batching, true);
+        assert_eq!(settings.initial_scan_limit, Some(200));
+        assert_eq!(settings.min_file_size_bytes, 1024);
+        assert_eq!(settings.verify_image_magic, false);
+        assert_eq!(settings.poll_interval_ms, 2000);
+        assert_eq!(settings.watch_subdirectories, true);
     }
 
     #[test]
@@ -355,6 +1252,115 @@ mod tests {
         assert_eq!(deserialized, ConversionFormat::Jpeg);
     }
 
+    #[test]
+    fn test_settings_validate() {
+        assert!(Settings::default().validate().is_ok());
+
+        let mut settings = Settings::default();
+        settings.thumbnail_size = 20;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.webp_quality = 0;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.avif_quality = 101;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.png_compression = 10;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.jpeg_quality = 0;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.watched_extensions.clear();
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.filename_template = "Shot {nope}".to_string();
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.max_screenshots = Some(0);
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.poll_interval_ms = 10;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.max_decode_pixels = 0;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.contact_sheet_columns = 0;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.watermark.enabled = true;
+        assert!(settings.validate().is_err());
+        settings.watermark.text = "© me".to_string();
+        assert!(settings.validate().is_ok());
+        settings.watermark.opacity = 1.5;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.thumbnail_open_timeout_ms = 0;
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.upload_endpoint = "ftp://example.com".to_string();
+        assert!(settings.validate().is_err());
+
+        let mut settings = Settings::default();
+        settings.log_level = "loud".to_string();
+        assert!(settings.validate().is_err());
+        settings.log_level = "Debug".to_string();
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_settings_merge_partial() {
+        let settings = Settings::default();
+        let overlay = serde_json::json!({
+            "grid_columns": 6,
+            "unknown_field": "ignored"
+        });
+
+        let merged = settings.merged_with(overlay).unwrap();
+        assert_eq!(merged.grid_columns, 6);
+        assert_eq!(merged.thumbnail_size, settings.thumbnail_size);
+        assert_eq!(merged.hotkey, settings.hotkey);
+    }
+
+    #[test]
+    fn test_settings_migrate_watched_extensions() {
+        let overlay = serde_json::json!({ "watched_extensions": PRE_HEIF_WATCHED_EXTENSIONS });
+        let merged = Settings::default().merged_with(overlay).unwrap();
+        assert_eq!(merged.watched_extensions, default_watched_extensions());
+
+        let overlay = serde_json::json!({ "watched_extensions": ["png"] });
+        let merged = Settings::default().merged_with(overlay).unwrap();
+        assert_eq!(merged.watched_extensions, vec!["png".to_string()]);
+    }
+
+    #[test]
+    fn test_settings_merge_rejects_invalid() {
+        let settings = Settings::default();
+
+        assert!(settings.merged_with(serde_json::json!([1, 2, 3])).is_err());
+        assert!(settings
+            .merged_with(serde_json::json!({ "grid_columns": "six" }))
+            .is_err());
+        assert!(settings
+            .merged_with(serde_json::json!({ "webp_quality": 500 }))
+            .is_err());
+    }
+
     #[test]
     fn test_quality_bounds() {
         let settings = Settings::default();
@@ -363,4 +1369,17 @@ mod tests {
         assert!(settings.webp_quality >= 1);
         assert!(settings.webp_quality <= 100);
     }
+
+    #[test]
+    fn test_watches_subdirectories() {
+        let mut settings = Settings::default();
+        assert!(settings.watches_subdirectories());
+
+        settings.watch_subdirectories = false;
+        assert!(!settings.watches_subdirectories());
+
+        // The organizer's dated folders are always watched
+        settings.organizer_enabled = true;
+        assert!(settings.watches_subdirectories());
+    }
 }
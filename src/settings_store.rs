@@ -0,0 +1,137 @@
+//! Process-wide access to the live settings
+//!
+//! Modules read the current values with `get` or `read` instead of having the shared
+//! settings handed down to them. `update` validates a change, applies it, tells the UI
+//! with `AppMessage::SettingsReloaded` and writes the file shortly after, so a burst of
+//! changes (e.g. dragging a slider) is saved once. `flush` writes it right away on exit.
+//!
+//! The store wraps the same `Arc<Mutex<Settings>>` as `AppState`, so code that still
+//! locks it directly sees the same values.
+
+use anyhow::Result;
+use crossbeam_channel::Sender;
+use log::{debug, error};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::settings::Settings;
+use crate::AppMessage;
+
+/// How long after the last change the file is written
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+struct SettingsStore {
+    settings: Arc<Mutex<Settings>>,
+    /// Where changes are announced; `None` in one-shot command line runs
+    message_tx: Option<Sender<AppMessage>>,
+}
+
+static STORE: OnceLock<SettingsStore> = OnceLock::new();
+
+/// Bumped on every change; a pending save only writes if no newer change followed it
+static SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Use these settings for the whole process; later calls are ignored
+pub fn init(settings: Arc<Mutex<Settings>>, message_tx: Option<Sender<AppMessage>>) {
+    let _ = STORE.set(SettingsStore {
+        settings,
+        message_tx,
+    });
+}
+
+/// Copy of the current settings (the defaults before `init`)
+pub fn get() -> Settings {
+    read(Settings::clone)
+}
+
+/// Read from the current settings without copying all of them
+pub fn read<R>(f: impl FnOnce(&Settings) -> R) -> R {
+    match STORE.get() {
+        Some(store) => f(&store.settings.lock()),
+        None => f(&Settings::default()),
+    }
+}
+
+/// Change the settings; nothing is applied if the result doesn't validate.
+/// Returns whether anything changed.
+pub fn update(f: impl FnOnce(&mut Settings)) -> Result<bool> {
+    let store = STORE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Settings store not initialized"))?;
+
+    let updated = {
+        let mut current = store.settings.lock();
+        match apply_update(&current, f)? {
+            Some(updated) => {
+                *current = updated.clone();
+                updated
+            }
+            None => return Ok(false),
+        }
+    };
+
+    if let Some(tx) = &store.message_tx {
+        let _ = tx.send(AppMessage::SettingsReloaded(updated));
+    }
+    schedule_save(store);
+    Ok(true)
+}
+
+/// The settings after `f`, or `None` if it changed nothing
+fn apply_update(current: &Settings, f: impl FnOnce(&mut Settings)) -> Result<Option<Settings>> {
+    let mut updated = current.clone();
+    f(&mut updated);
+    if updated == *current {
+        return Ok(None);
+    }
+    updated.validate()?;
+    Ok(Some(updated))
+}
+
+/// Write the settings now, dropping any save still waiting out `SAVE_DELAY`.
+/// Call before exiting, since the pending save runs on a detached thread.
+pub fn flush() {
+    let Some(store) = STORE.get() else {
+        return;
+    };
+    SAVE_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Err(e) = store.settings.lock().save() {
+        error!("Failed to save settings: {}", e);
+    }
+}
+
+/// Write the settings once no further change has arrived for `SAVE_DELAY`
+fn schedule_save(store: &'static SettingsStore) {
+    let generation = SAVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    std::thread::spawn(move || {
+        std::thread::sleep(SAVE_DELAY);
+        if SAVE_GENERATION.load(Ordering::SeqCst) != generation {
+            debug!("Settings changed again, deferring save");
+            return;
+        }
+        if let Err(e) = store.settings.lock().save() {
+            error!("Failed to save settings: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_update() {
+        let current = Settings::default();
+
+        let updated = apply_update(&current, |s| s.always_on_top = true).unwrap();
+        assert!(updated.is_some_and(|s| s.always_on_top));
+
+        // Setting a value to what it already is changes nothing
+        let unchanged = apply_update(&current, |s| s.always_on_top = current.always_on_top).unwrap();
+        assert!(unchanged.is_none());
+
+        assert!(apply_update(&current, |s| s.thumbnail_size = 10).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! Hot-reload of the settings file when it is edited outside the app
+
+use anyhow::Result;
+use crossbeam_channel::Sender;
+use log::{debug, error, info, warn};
+use notify::RecursiveMode;
+use notify_debouncer_full::{new_debouncer, DebounceEventResult};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::settings::Settings;
+use crate::AppMessage;
+
+pub struct SettingsWatcher {
+    path: PathBuf,
+    message_tx: Sender<AppMessage>,
+    settings: Arc<Mutex<Settings>>,
+}
+
+impl SettingsWatcher {
+    pub fn new(
+        path: PathBuf,
+        message_tx: Sender<AppMessage>,
+        settings: Arc<Mutex<Settings>>,
+    ) -> Self {
+        Self {
+            path,
+            message_tx,
+            settings,
+        }
+    }
+
+    /// Run the watcher (blocking)
+    pub fn run(self) -> Result<()> {
+        // Watch the parent directory: editors often replace the file instead of writing in place
+        let dir = self
+            .path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Settings path has no parent directory"))?
+            .to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        info!("Starting settings watcher for: {:?}", self.path);
+
+        let path = self.path.clone();
+        let tx = self.message_tx.clone();
+        let settings = Arc::clone(&self.settings);
+        let mut debouncer = new_debouncer(
+            Duration::from_millis(500),
+            None,
+            move |result: DebounceEventResult| match result {
+                Ok(events) => {
+                    if events.iter().any(|e| e.paths.iter().any(|p| p == &path)) {
+                        Self::reload(&path, &tx, &settings);
+                    }
+                }
+                Err(errors) => {
+                    for e in errors {
+                        error!("Settings watcher error: {:?}", e);
+                    }
+                }
+            },
+        )?;
+
+        debouncer.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        info!("Settings watcher started successfully");
+
+        // Keep the debouncer alive until the app quits
+        while !crate::session::is_shutting_down() {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        info!("Settings watcher stopped");
+        Ok(())
+    }
+
+    /// Reload, validate and apply the settings file
+    fn reload(path: &Path, tx: &Sender<AppMessage>, settings: &Arc<Mutex<Settings>>) {
+        if !path.exists() {
+            debug!("Settings file removed, keeping current settings");
+            return;
+        }
+
+        let mut reloaded = match Settings::load_from(path) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Ignoring settings reload, failed to parse {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        if let Err(e) = reloaded.validate() {
+            warn!("Ignoring settings reload, invalid value: {}", e);
+            return;
+        }
+
+        let new_directory = {
+            let mut current = settings.lock();
+
+            // Our own saves also land here - nothing to do if the file matches memory
+            if *current == reloaded {
+                return;
+            }
+
+            // Directory changes go through the UI so the watcher thread is restarted
+            let new_directory = (reloaded.screenshot_directory != current.screenshot_directory)
+                .then(|| reloaded.screenshot_directory.clone());
+            reloaded.screenshot_directory = current.screenshot_directory.clone();
+
+            *current = reloaded.clone();
+            new_directory
+        };
+
+        info!("Settings reloaded from {:?}", path);
+        let _ = tx.send(AppMessage::SettingsReloaded(reloaded));
+        if let Some(dir) = new_directory {
+            let _ = tx.send(AppMessage::ChangeDirectory(dir));
+        }
+    }
+}
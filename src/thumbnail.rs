@@ -8,9 +8,13 @@
 use image::{DynamicImage, RgbaImage};
 use log::{debug, warn};
 use parking_lot::Mutex;
+use rust_i18n::t;
 use std::collections::HashMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(windows)]
 use windows::{
@@ -28,198 +32,466 @@ use windows::{
 /// Default thumbnail size
 pub const THUMBNAIL_SIZE: u32 = 150;
 
+/// Physical pixels needed to fill `logical` pixels at a display scale factor (1.0 = 96 DPI)
+pub fn physical_size(logical: u32, scale: f32) -> u32 {
+    let scale = if scale.is_finite() && scale > 0.0 { scale } else { 1.0 };
+    ((logical as f32 * scale).round() as u32).max(1)
+}
+
+/// Largest image (width x height) decoded for a thumbnail (mirrors `Settings::max_decode_pixels`)
+static MAX_DECODE_PIXELS: AtomicU64 = AtomicU64::new(100_000_000);
+
+/// How long reading a file for a thumbnail may take, in milliseconds
+/// (mirrors `Settings::thumbnail_open_timeout_ms`)
+static OPEN_TIMEOUT_MS: AtomicU64 = AtomicU64::new(5000);
+
+/// Set the guards applied before decoding an image ourselves
+pub fn set_decode_limits(max_pixels: u64, open_timeout: Duration) {
+    MAX_DECODE_PIXELS.store(max_pixels, Ordering::Relaxed);
+    OPEN_TIMEOUT_MS.store(open_timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Read and decode a whole image within the limits from `set_decode_limits`
+pub fn open_limited(path: &Path) -> Result<DynamicImage, ThumbnailError> {
+    let timeout = Duration::from_millis(OPEN_TIMEOUT_MS.load(Ordering::Relaxed));
+    let max_pixels = MAX_DECODE_PIXELS.load(Ordering::Relaxed);
+    read_with_timeout(path, timeout).and_then(|bytes| decode_limited(path, &bytes, max_pixels))
+}
+
+/// Formats only the Windows HEIF codec can decode
+const HEIF_EXTENSIONS: &[&str] = &["heic", "heif"];
+
+/// Whether the missing-codec hint has been logged
+static HEIF_HINT_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the file is HEIC/HEIF (which `image` can't decode)
+pub fn is_heif(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| HEIF_EXTENSIONS.iter().any(|h| ext.eq_ignore_ascii_case(h)))
+}
+
+/// Whether Windows has a HEIF decoder (the "HEIF Image Extensions" Store package)
+#[cfg(windows)]
+pub fn heif_codec_installed() -> bool {
+    use std::sync::OnceLock;
+    use windows::Graphics::Imaging::BitmapDecoder;
+
+    static INSTALLED: OnceLock<bool> = OnceLock::new();
+    *INSTALLED.get_or_init(|| {
+        let Ok(decoders) = BitmapDecoder::GetDecoderInformationEnumerator() else {
+            return false;
+        };
+        decoders.into_iter().any(|info| {
+            info.FileExtensions().is_ok_and(|extensions| {
+                extensions
+                    .into_iter()
+                    .any(|ext| ext.to_string().eq_ignore_ascii_case(".heic"))
+            })
+        })
+    })
+}
+
+#[cfg(not(windows))]
+pub fn heif_codec_installed() -> bool {
+    false
+}
+
+/// Log once that HEIC files need the Windows codec
+pub fn log_heif_hint() {
+    if !HEIF_HINT_LOGGED.swap(true, Ordering::Relaxed) && !heif_codec_installed() {
+        warn!(
+            "HEIC/HEIF thumbnails need the \"HEIF Image Extensions\" from the Microsoft Store, which is not installed"
+        );
+    }
+}
+
+/// Why a thumbnail could not be produced
+#[derive(Debug)]
+pub enum ThumbnailError {
+    /// The file does not exist
+    NotFound(PathBuf),
+    /// The file is not an image format we can decode
+    UnsupportedFormat(PathBuf),
+    /// The file could not be read or decoded
+    Decode(PathBuf, image::ImageError),
+    /// The image header declares more pixels than `Settings::max_decode_pixels`
+    TooLarge(PathBuf, u32, u32),
+    /// Reading the file took longer than `Settings::thumbnail_open_timeout_ms`
+    TimedOut(PathBuf),
+    /// The Windows Shell has no thumbnail for the file
+    #[cfg(windows)]
+    Shell(PathBuf, windows::core::Error),
+    /// The request was cancelled, usually because the file was removed
+    Cancelled(PathBuf),
+}
+
+impl fmt::Display for ThumbnailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThumbnailError::NotFound(path) => write!(f, "File not found: {:?}", path),
+            ThumbnailError::UnsupportedFormat(path) => {
+                write!(f, "Unsupported image format: {:?}", path)
+            }
+            ThumbnailError::Decode(path, e) => write!(f, "Failed to decode {:?}: {}", path, e),
+            ThumbnailError::TooLarge(path, width, height) => {
+                write!(f, "Image too large to decode ({}x{}): {:?}", width, height, path)
+            }
+            ThumbnailError::TimedOut(path) => write!(f, "Timed out reading {:?}", path),
+            #[cfg(windows)]
+            ThumbnailError::Shell(path, e) => {
+                write!(f, "Shell thumbnail unavailable for {:?}: {}", path, e)
+            }
+            ThumbnailError::Cancelled(path) => write!(f, "Thumbnail cancelled: {:?}", path),
+        }
+    }
+}
+
+impl std::error::Error for ThumbnailError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThumbnailError::Decode(_, e) => Some(e),
+            #[cfg(windows)]
+            ThumbnailError::Shell(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// What to show instead of a thumbnail when the file itself is the problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    /// Not an image format we can decode
+    Unsupported,
+    /// Recognized, but truncated or damaged
+    Corrupt,
+    /// Too many pixels to decode safely
+    TooLarge,
+}
+
+impl Placeholder {
+    /// Localized label drawn in place of the thumbnail
+    pub fn label(self) -> String {
+        match self {
+            Placeholder::Unsupported => t!("gallery.placeholder.unsupported"),
+            Placeholder::Corrupt => t!("gallery.placeholder.corrupt"),
+            Placeholder::TooLarge => t!("gallery.placeholder.too_large"),
+        }
+        .to_string()
+    }
+}
+
+impl ThumbnailError {
+    /// The placeholder for errors caused by the file itself; `None` for transient failures
+    /// (missing file, timeout, cancellation) that may succeed on a later attempt
+    pub fn placeholder(&self) -> Option<Placeholder> {
+        match self {
+            ThumbnailError::UnsupportedFormat(_) => Some(Placeholder::Unsupported),
+            ThumbnailError::Decode(..) => Some(Placeholder::Corrupt),
+            ThumbnailError::TooLarge(..) => Some(Placeholder::TooLarge),
+            _ => None,
+        }
+    }
+
+    /// Whether the file itself is the problem (show a broken-file placeholder)
+    pub fn is_broken_file(&self) -> bool {
+        self.placeholder().is_some()
+    }
+
+    fn from_image_error(path: &Path, e: image::ImageError) -> Self {
+        match e {
+            image::ImageError::Unsupported(_) => ThumbnailError::UnsupportedFormat(path.to_path_buf()),
+            image::ImageError::IoError(ref io) if io.kind() == std::io::ErrorKind::NotFound => {
+                ThumbnailError::NotFound(path.to_path_buf())
+            }
+            e => ThumbnailError::Decode(path.to_path_buf(), e),
+        }
+    }
+}
+
+/// Snapshot of thumbnail cache counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that had to generate a thumbnail
+    pub misses: u64,
+    /// Thumbnails provided by the OS thumbnail cache
+    pub shell_hits: u64,
+    /// Thumbnails decoded and resized by us
+    pub manual_fallbacks: u64,
+    /// Entries dropped because the cache was full
+    pub evictions: u64,
+}
+
+/// Cancels a thumbnail request; `ThumbnailCache::invalidate` trips it for its path
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Fail with `Cancelled` if the token was tripped
+    fn check(&self, path: &Path) -> Result<(), ThumbnailError> {
+        if self.is_cancelled() {
+            debug!("Thumbnail for {:?} cancelled", path);
+            return Err(ThumbnailError::Cancelled(path.to_path_buf()));
+        }
+        Ok(())
+    }
+}
+
+/// A cached thumbnail and values derived from it
+struct CacheEntry {
+    image: Arc<RgbaImage>,
+    /// Computed on first request by `dominant_color`
+    dominant_color: Option<[u8; 3]>,
+}
+
 /// Thumbnail cache to avoid regenerating thumbnails
 pub struct ThumbnailCache {
-    /// Path -> RGBA image data
-    cache: Mutex<HashMap<PathBuf, Arc<RgbaImage>>>,
+    /// (Path, physical size) -> RGBA image data, so thumbnails for different scales don't mix
+    cache: Mutex<HashMap<(PathBuf, u32), CacheEntry>>,
+    /// Tokens of thumbnails being generated, by path
+    in_flight: Mutex<HashMap<PathBuf, Vec<CancelToken>>>,
     /// Maximum cache size
     max_size: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    shell_hits: AtomicU64,
+    manual_fallbacks: AtomicU64,
+    evictions: AtomicU64,
 }
 
 impl ThumbnailCache {
     pub fn new(max_size: usize) -> Self {
         Self {
             cache: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
             max_size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            shell_hits: AtomicU64::new(0),
+            manual_fallbacks: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Current hit/miss counters
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            shell_hits: self.shell_hits.load(Ordering::Relaxed),
+            manual_fallbacks: self.manual_fallbacks.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
         }
     }
 
     /// Get a cached thumbnail or generate a new one
-    pub fn get_or_create(&self, path: &Path, size: u32) -> Option<Arc<RgbaImage>> {
+    pub fn get_or_create(&self, path: &Path, size: u32) -> Result<Arc<RgbaImage>, ThumbnailError> {
+        self.get_or_create_cancellable(path, size, &CancelToken::default())
+    }
+
+    /// `get_or_create` for a slot of `logical` pixels on a display with the given scale factor,
+    /// e.g. 300px for a 150px slot at 200%
+    pub fn get_or_create_scaled(
+        &self,
+        path: &Path,
+        logical: u32,
+        scale: f32,
+    ) -> Result<Arc<RgbaImage>, ThumbnailError> {
+        self.get_or_create(path, physical_size(logical, scale))
+    }
+
+    /// Generate a thumbnail on a background thread and hand it to `on_done`.
+    /// The returned token cancels it, as does invalidating the path.
+    pub fn request(
+        self: &Arc<Self>,
+        path: PathBuf,
+        size: u32,
+        on_done: impl FnOnce(Result<Arc<RgbaImage>, ThumbnailError>) + Send + 'static,
+    ) -> CancelToken {
+        let token = CancelToken::default();
+        let cache = Arc::clone(self);
+        let request_token = token.clone();
+        std::thread::spawn(move || {
+            on_done(cache.get_or_create_cancellable(&path, size, &request_token));
+        });
+        token
+    }
+
+    /// `get_or_create` that gives up before the expensive steps once `token` is tripped
+    pub fn get_or_create_cancellable(
+        &self,
+        path: &Path,
+        size: u32,
+        token: &CancelToken,
+    ) -> Result<Arc<RgbaImage>, ThumbnailError> {
+        let key = (path.to_path_buf(), size);
+
         // Check cache first
         {
             let cache = self.cache.lock();
-            if let Some(img) = cache.get(path) {
-                return Some(Arc::clone(img));
+            if let Some(entry) = cache.get(&key) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Arc::clone(&entry.image));
             }
         }
+        self.misses.fetch_add(1, Ordering::Relaxed);
 
         // Generate thumbnail
-        let img = self.generate_thumbnail(path, size)?;
-        let img = Arc::new(img);
+        self.track(path, token);
+        let generated = self.generate_thumbnail(path, size, token);
+        self.untrack(path, token);
+        let img = Arc::new(generated?);
 
         // Store in cache
         {
             let mut cache = self.cache.lock();
+            // Checked under the lock, so a concurrent `invalidate` can't be undone
+            token.check(path)?;
 
             // Evict oldest entries if cache is full
             if cache.len() >= self.max_size {
                 // Simple eviction: remove first entry
                 if let Some(key) = cache.keys().next().cloned() {
                     cache.remove(&key);
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
                 }
             }
 
-            cache.insert(path.to_path_buf(), Arc::clone(&img));
+            cache.insert(
+                key,
+                CacheEntry {
+                    image: Arc::clone(&img),
+                    dominant_color: None,
+                },
+            );
         }
 
-        Some(img)
+        Ok(img)
     }
 
-    /// Remove a path from the cache
-    pub fn invalidate(&self, path: &Path) {
-        let mut cache = self.cache.lock();
-        cache.remove(path);
+    /// Like `get_or_create`, for callers that don't care why a thumbnail is missing
+    pub fn get(&self, path: &Path, size: u32) -> Option<Arc<RgbaImage>> {
+        self.get_or_create(path, size)
+            .map_err(|e| debug!("No thumbnail: {}", e))
+            .ok()
     }
 
-    /// Clear all cached thumbnails
-    pub fn clear(&self) {
-        let mut cache = self.cache.lock();
-        cache.clear();
+    /// Like `get_or_create_scaled`, for callers that don't care why a thumbnail is missing
+    pub fn get_scaled(&self, path: &Path, logical: u32, scale: f32) -> Option<Arc<RgbaImage>> {
+        self.get(path, physical_size(logical, scale))
     }
 
-    /// Generate a thumbnail for the given path
-    fn generate_thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage> {
-        // Try Windows Shell API first (fastest, uses system cache)
-        #[cfg(windows)]
-        if let Some(img) = self.get_windows_thumbnail(path, size) {
-            return Some(img);
+    /// Most common color of a screenshot, computed once from its thumbnail
+    pub fn dominant_color(&self, path: &Path) -> Option<[u8; 3]> {
+        let key = (path.to_path_buf(), THUMBNAIL_SIZE);
+        if let Some(color) = self.cache.lock().get(&key).and_then(|e| e.dominant_color) {
+            return Some(color);
         }
 
-        // Fall back to manual thumbnail generation
-        self.generate_manual_thumbnail(path, size)
+        let image = self.get(path, THUMBNAIL_SIZE)?;
+        let color = compute_dominant_color(&image)?;
+        if let Some(entry) = self.cache.lock().get_mut(&key) {
+            entry.dominant_color = Some(color);
+        }
+        Some(color)
     }
 
-    /// Get thumbnail using Windows Shell API
-    #[cfg(windows)]
-    fn get_windows_thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage> {
-        use std::ffi::OsStr;
-        use std::os::windows::ffi::OsStrExt;
-
-        unsafe {
-            // Convert path to wide string
-            let wide_path: Vec<u16> = OsStr::new(path)
-                .encode_wide()
-                .chain(std::iter::once(0))
-                .collect();
-
-            // Create shell item
-            let shell_item: IShellItemImageFactory =
-                match SHCreateItemFromParsingName(PCWSTR(wide_path.as_ptr()), None) {
-                    Ok(item) => item,
-                    Err(e) => {
-                        debug!("Failed to create shell item for {:?}: {}", path, e);
-                        return None;
-                    }
-                };
-
-            // Get thumbnail
-            let hbitmap: HBITMAP = match shell_item.GetImage(
-                SIZE {
-                    cx: size as i32,
-                    cy: size as i32,
-                },
-                SIIGBF_THUMBNAILONLY,
-            ) {
-                Ok(bmp) => bmp,
-                Err(e) => {
-                    debug!("Failed to get thumbnail for {:?}: {}", path, e);
-                    return None;
-                }
-            };
-
-            // Convert HBITMAP to RgbaImage
-            let result = self.hbitmap_to_rgba(hbitmap, size);
-
-            // Clean up
-            let _ = DeleteObject(hbitmap);
-
-            result
+    /// Remove a path from the cache, cancelling thumbnails still being generated for it
+    pub fn invalidate(&self, path: &Path) {
+        if let Some(tokens) = self.in_flight.lock().remove(path) {
+            tokens.iter().for_each(CancelToken::cancel);
         }
+        let mut cache = self.cache.lock();
+        cache.retain(|(cached, _), _| cached != path);
     }
 
-    /// Convert Windows HBITMAP to RgbaImage
-    #[cfg(windows)]
-    unsafe fn hbitmap_to_rgba(&self, hbitmap: HBITMAP, size: u32) -> Option<RgbaImage> {
-        let hdc = unsafe { CreateCompatibleDC(None) };
-        if hdc.is_invalid() {
-            return None;
-        }
+    fn track(&self, path: &Path, token: &CancelToken) {
+        self.in_flight
+            .lock()
+            .entry(path.to_path_buf())
+            .or_default()
+            .push(token.clone());
+    }
 
-        let _old = unsafe { SelectObject(hdc, hbitmap) };
-
-        let mut bi = BITMAPINFO {
-            bmiHeader: BITMAPINFOHEADER {
-                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
-                biWidth: size as i32,
-                biHeight: -(size as i32), // Negative for top-down
-                biPlanes: 1,
-                biBitCount: 32,
-                biCompression: BI_RGB.0,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+    fn untrack(&self, path: &Path, token: &CancelToken) {
+        let mut in_flight = self.in_flight.lock();
+        if let Some(tokens) = in_flight.get_mut(path) {
+            tokens.retain(|t| !Arc::ptr_eq(&t.0, &token.0));
+            if tokens.is_empty() {
+                in_flight.remove(path);
+            }
+        }
+    }
 
-        let mut buffer = vec![0u8; (size * size * 4) as usize];
-
-        let result = unsafe {
-            GetDIBits(
-                hdc,
-                hbitmap,
-                0,
-                size,
-                Some(buffer.as_mut_ptr() as *mut _),
-                &mut bi,
-                DIB_RGB_COLORS,
-            )
-        };
+    /// Clear all cached thumbnails
+    pub fn clear(&self) {
+        let mut cache = self.cache.lock();
+        cache.clear();
+    }
 
-        let _ = unsafe { DeleteDC(hdc) };
+    /// Generate a thumbnail for the given path
+    fn generate_thumbnail(
+        &self,
+        path: &Path,
+        size: u32,
+        token: &CancelToken,
+    ) -> Result<RgbaImage, ThumbnailError> {
+        if !path.exists() {
+            return Err(ThumbnailError::NotFound(path.to_path_buf()));
+        }
+        token.check(path)?;
 
-        if result == 0 {
-            return None;
+        // Try the OS thumbnail cache first
+        if let Some(img) = crate::platform::current().thumbnail(path, size) {
+            self.shell_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(img);
         }
 
-        // Convert BGRA to RGBA
-        for chunk in buffer.chunks_exact_mut(4) {
-            chunk.swap(0, 2); // Swap B and R
+        // `image` can't decode HEIF, so there is nothing to fall back to
+        if is_heif(path) {
+            log_heif_hint();
+            return Err(ThumbnailError::UnsupportedFormat(path.to_path_buf()));
         }
 
-        RgbaImage::from_raw(size, size, buffer)
+        // Fall back to manual thumbnail generation
+        token.check(path)?;
+        self.manual_fallbacks.fetch_add(1, Ordering::Relaxed);
+        self.generate_manual_thumbnail(path, size)
     }
 
     /// Manual thumbnail generation using image crate
-    fn generate_manual_thumbnail(&self, path: &Path, size: u32) -> Option<RgbaImage> {
+    fn generate_manual_thumbnail(&self, path: &Path, size: u32) -> Result<RgbaImage, ThumbnailError> {
         debug!("Generating manual thumbnail for {:?}", path);
 
-        // Load image
-        let img = match image::open(path) {
-            Ok(img) => img,
-            Err(e) => {
-                warn!("Failed to open image {:?}: {}", path, e);
-                return None;
+        let img = open_limited(path).map_err(|e| {
+            // Unsupported formats get a placeholder; only real failures are worth a warning
+            if matches!(
+                e,
+                ThumbnailError::Decode(..) | ThumbnailError::TooLarge(..) | ThumbnailError::TimedOut(_)
+            ) {
+                warn!("{}", e);
+            } else {
+                debug!("{}", e);
             }
-        };
+            e
+        })?;
 
         // Use fast_image_resize for better performance
-        Some(self.resize_with_fast_image_resize(&img, size))
+        Ok(self.resize_with_fast_image_resize(&img, size))
     }
 
     /// Resize image using fast_image_resize crate
     fn resize_with_fast_image_resize(&self, img: &DynamicImage, target_size: u32) -> RgbaImage {
-        use fast_image_resize::{images::Image, ResizeAlg, ResizeOptions, Resizer};
-
         let rgba = img.to_rgba8();
         let (width, height) = rgba.dimensions();
 
@@ -232,34 +504,113 @@ impl ThumbnailCache {
             ((width as f32 * ratio) as u32, target_size)
         };
 
-        let new_width = new_width.max(1);
-        let new_height = new_height.max(1);
+        resize_rgba(rgba, new_width.max(1), new_height.max(1))
+    }
+}
 
-        // Create source image
-        let src_image = Image::from_vec_u8(
-            width,
-            height,
-            rgba.into_raw(),
-            fast_image_resize::PixelType::U8x4,
-        )
-        .expect("Failed to create source image");
+/// Read a whole file on a helper thread, giving up after `timeout`.
+/// A read stuck on an unresponsive network share is left to finish on its own.
+fn read_with_timeout(path: &Path, timeout: Duration) -> Result<Vec<u8>, ThumbnailError> {
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    let owned = path.to_path_buf();
+    std::thread::spawn(move || {
+        let _ = tx.send(std::fs::read(&owned));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(bytes)) => Ok(bytes),
+        Ok(Err(e)) => Err(ThumbnailError::from_image_error(path, image::ImageError::IoError(e))),
+        Err(_) => Err(ThumbnailError::TimedOut(path.to_path_buf())),
+    }
+}
 
-        // Create destination image
-        let mut dst_image = Image::new(new_width, new_height, fast_image_resize::PixelType::U8x4);
+/// Decode an image, checking the size its header declares before allocating any pixels
+fn decode_limited(path: &Path, bytes: &[u8], max_pixels: u64) -> Result<DynamicImage, ThumbnailError> {
+    // The extension is the fallback if the content doesn't reveal the format, like `image::open`
+    let reader = || {
+        let mut reader = image::io::Reader::new(std::io::Cursor::new(bytes));
+        if let Ok(format) = image::ImageFormat::from_path(path) {
+            reader.set_format(format);
+        }
+        reader
+            .with_guessed_format()
+            .map_err(|e| ThumbnailError::from_image_error(path, image::ImageError::IoError(e)))
+    };
 
-        // Resize
-        let mut resizer = Resizer::new();
-        let options = ResizeOptions::new().resize_alg(ResizeAlg::Convolution(
-            fast_image_resize::FilterType::Lanczos3,
-        ));
+    if reader()?.format().is_none() {
+        return Err(ThumbnailError::UnsupportedFormat(path.to_path_buf()));
+    }
+    let (width, height) = reader()?
+        .into_dimensions()
+        .map_err(|e| ThumbnailError::from_image_error(path, e))?;
+    if u64::from(width) * u64::from(height) > max_pixels {
+        return Err(ThumbnailError::TooLarge(path.to_path_buf(), width, height));
+    }
+    reader()?
+        .decode()
+        .map_err(|e| ThumbnailError::from_image_error(path, e))
+}
+
+/// Resize to exactly `width` x `height` with a Lanczos filter
+pub fn resize_rgba(rgba: RgbaImage, width: u32, height: u32) -> RgbaImage {
+    use fast_image_resize::{images::Image, ResizeAlg, ResizeOptions, Resizer};
+
+    let (src_width, src_height) = rgba.dimensions();
+
+    // Create source image
+    let src_image = Image::from_vec_u8(
+        src_width,
+        src_height,
+        rgba.into_raw(),
+        fast_image_resize::PixelType::U8x4,
+    )
+    .expect("Failed to create source image");
+
+    // Create destination image
+    let mut dst_image = Image::new(width, height, fast_image_resize::PixelType::U8x4);
+
+    // Resize
+    let mut resizer = Resizer::new();
+    let options = ResizeOptions::new().resize_alg(ResizeAlg::Convolution(
+        fast_image_resize::FilterType::Lanczos3,
+    ));
 
-        resizer
-            .resize(&src_image, &mut dst_image, &options)
-            .expect("Failed to resize image");
+    resizer
+        .resize(&src_image, &mut dst_image, &options)
+        .expect("Failed to resize image");
 
-        RgbaImage::from_raw(new_width, new_height, dst_image.into_vec())
-            .unwrap_or_else(|| RgbaImage::new(new_width, new_height))
+    RgbaImage::from_raw(width, height, dst_image.into_vec())
+        .unwrap_or_else(|| RgbaImage::new(width, height))
+}
+
+/// Pixels sampled per image for `dominant_color`
+const COLOR_SAMPLES: usize = 512;
+
+/// Most populated bucket of a 4-bit-per-channel histogram over a sample of opaque
+/// pixels, returned as the mean color of that bucket
+fn compute_dominant_color(img: &RgbaImage) -> Option<[u8; 3]> {
+    let pixels: Vec<&image::Rgba<u8>> = img.pixels().collect();
+    let step = (pixels.len() / COLOR_SAMPLES).max(1);
+
+    // Bucket -> (count, sum of r, g, b)
+    let mut buckets: HashMap<u16, (u32, [u32; 3])> = HashMap::new();
+    for pixel in pixels.iter().step_by(step) {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let key = (u16::from(r >> 4) << 8) | (u16::from(g >> 4) << 4) | u16::from(b >> 4);
+        let (count, sum) = buckets.entry(key).or_default();
+        *count += 1;
+        sum[0] += u32::from(r);
+        sum[1] += u32::from(g);
+        sum[2] += u32::from(b);
     }
+
+    // Ties go to the lowest bucket so results are deterministic
+    let (_, (count, sum)) = buckets
+        .into_iter()
+        .max_by(|a, b| a.1 .0.cmp(&b.1 .0).then(b.0.cmp(&a.0)))?;
+    Some(sum.map(|c| (c / count) as u8))
 }
 
 impl Default for ThumbnailCache {
@@ -267,3 +618,223 @@ impl Default for ThumbnailCache {
         Self::new(500)
     }
 }
+
+/// Get a thumbnail from the Windows Shell (fastest, uses the system thumbnail cache)
+#[cfg(windows)]
+pub fn shell_thumbnail(path: &Path, size: u32) -> Result<RgbaImage, ThumbnailError> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+
+    unsafe {
+        // Convert path to wide string
+        let wide_path: Vec<u16> = OsStr::new(path)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+
+        // Create shell item
+        let shell_item: IShellItemImageFactory =
+            match SHCreateItemFromParsingName(PCWSTR(wide_path.as_ptr()), None) {
+                Ok(item) => item,
+                Err(e) => return Err(ThumbnailError::Shell(path.to_path_buf(), e)),
+            };
+
+        // Get thumbnail
+        let hbitmap: HBITMAP = match shell_item.GetImage(
+            SIZE {
+                cx: size as i32,
+                cy: size as i32,
+            },
+            SIIGBF_THUMBNAILONLY,
+        ) {
+            Ok(bmp) => bmp,
+            Err(e) => return Err(ThumbnailError::Shell(path.to_path_buf(), e)),
+        };
+
+        // Convert HBITMAP to RgbaImage
+        let result = hbitmap_to_rgba(hbitmap, size);
+
+        // Clean up
+        let _ = DeleteObject(hbitmap);
+
+        result.ok_or_else(|| {
+            ThumbnailError::Shell(path.to_path_buf(), windows::core::Error::from_win32())
+        })
+    }
+}
+
+/// Convert Windows HBITMAP to RgbaImage
+#[cfg(windows)]
+unsafe fn hbitmap_to_rgba(hbitmap: HBITMAP, size: u32) -> Option<RgbaImage> {
+    let hdc = unsafe { CreateCompatibleDC(None) };
+    if hdc.is_invalid() {
+        return None;
+    }
+
+    let _old = unsafe { SelectObject(hdc, hbitmap) };
+
+    let mut bi = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: size as i32,
+            biHeight: -(size as i32), // Negative for top-down
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut buffer = vec![0u8; (size * size * 4) as usize];
+
+    let result = unsafe {
+        GetDIBits(
+            hdc,
+            hbitmap,
+            0,
+            size,
+            Some(buffer.as_mut_ptr() as *mut _),
+            &mut bi,
+            DIB_RGB_COLORS,
+        )
+    };
+
+    let _ = unsafe { DeleteDC(hdc) };
+
+    if result == 0 {
+        return None;
+    }
+
+    // Convert BGRA to RGBA
+    for chunk in buffer.chunks_exact_mut(4) {
+        chunk.swap(0, 2); // Swap B and R
+    }
+
+    RgbaImage::from_raw(size, size, buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_or_create_errors() {
+        let cache = ThumbnailCache::new(4);
+        let dir = std::env::temp_dir();
+
+        let missing = dir.join("sukusho-thumb-missing.png");
+        assert!(matches!(
+            cache.get_or_create(&missing, THUMBNAIL_SIZE),
+            Err(ThumbnailError::NotFound(_))
+        ));
+
+        let garbage = dir.join(format!("sukusho-thumb-{}.png", std::process::id()));
+        std::fs::write(&garbage, b"not a png").unwrap();
+        let err = cache.get_or_create(&garbage, THUMBNAIL_SIZE).unwrap_err();
+        assert!(err.is_broken_file());
+        assert!(cache.get(&garbage, THUMBNAIL_SIZE).is_none());
+        std::fs::remove_file(&garbage).unwrap();
+
+        assert_eq!(cache.stats().hits, 0);
+        assert_eq!(cache.stats().misses, 3);
+    }
+
+    #[test]
+    fn test_invalidate_cancels_in_flight() {
+        let cache = ThumbnailCache::new(4);
+        let path = std::env::temp_dir().join(format!("sukusho-thumb-cancel-{}.png", std::process::id()));
+        std::fs::write(&path, b"not a png").unwrap();
+
+        let token = CancelToken::default();
+        cache.track(&path, &token);
+        cache.invalidate(&path);
+        assert!(token.is_cancelled());
+        assert!(cache.in_flight.lock().is_empty());
+
+        // A cancelled request stops before decoding
+        assert!(matches!(
+            cache.get_or_create_cancellable(&path, THUMBNAIL_SIZE, &token),
+            Err(ThumbnailError::Cancelled(_))
+        ));
+        assert_eq!(cache.stats().manual_fallbacks, 0);
+        assert!(cache.in_flight.lock().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_physical_size() {
+        assert_eq!(physical_size(150, 1.0), 150);
+        assert_eq!(physical_size(150, 1.5), 225);
+        assert_eq!(physical_size(150, 2.0), 300);
+        assert_eq!(physical_size(150, 0.0), 150);
+        assert_eq!(physical_size(150, f32::NAN), 150);
+        assert_eq!(physical_size(0, 2.0), 1);
+    }
+
+    #[test]
+    fn test_cache_key_includes_size() {
+        let cache = ThumbnailCache::new(4);
+        let path = PathBuf::from("shot.png");
+        let entry = |size| CacheEntry {
+            image: Arc::new(RgbaImage::new(size, size)),
+            dominant_color: None,
+        };
+        cache.cache.lock().insert((path.clone(), 150), entry(150));
+        cache.cache.lock().insert((path.clone(), 300), entry(300));
+
+        assert_eq!(cache.get_or_create(&path, 150).unwrap().width(), 150);
+        assert_eq!(cache.get_or_create_scaled(&path, 150, 2.0).unwrap().width(), 300);
+
+        // Invalidating drops every scale
+        cache.invalidate(&path);
+        assert!(cache.cache.lock().is_empty());
+    }
+
+    #[test]
+    fn test_decode_limited() {
+        let mut png = Vec::new();
+        RgbaImage::new(40, 30)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let path = Path::new("shot.png");
+
+        let img = decode_limited(path, &png, 40 * 30).unwrap();
+        assert_eq!((img.width(), img.height()), (40, 30));
+        assert!(matches!(
+            decode_limited(path, &png, 40 * 30 - 1),
+            Err(ThumbnailError::TooLarge(_, 40, 30))
+        ));
+
+        let truncated = decode_limited(path, &png[..png.len() / 2], u64::MAX).unwrap_err();
+        assert_eq!(truncated.placeholder(), Some(Placeholder::Corrupt));
+
+        let unknown = decode_limited(Path::new("shot.xyz"), b"not an image", u64::MAX).unwrap_err();
+        assert_eq!(unknown.placeholder(), Some(Placeholder::Unsupported));
+        assert_eq!(ThumbnailError::TimedOut(path.to_path_buf()).placeholder(), None);
+    }
+
+    #[test]
+    fn test_dominant_color() {
+        // Three quarters white, one quarter red
+        let img = RgbaImage::from_fn(8, 8, |x, _| {
+            if x < 2 {
+                image::Rgba([200, 10, 10, 255])
+            } else {
+                image::Rgba([250, 250, 250, 255])
+            }
+        });
+        assert_eq!(compute_dominant_color(&img), Some([250, 250, 250]));
+
+        let transparent = RgbaImage::new(4, 4);
+        assert_eq!(compute_dominant_color(&transparent), None);
+    }
+
+    #[test]
+    fn test_is_heif() {
+        assert!(is_heif(Path::new("IMG_0001.HEIC")));
+        assert!(is_heif(Path::new("photo.heif")));
+        assert!(!is_heif(Path::new("shot.png")));
+        assert!(!is_heif(Path::new("heic")));
+    }
+}
@@ -5,12 +5,16 @@ use crossbeam_channel::Sender;
 use log::{debug, info};
 use rust_i18n::t;
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem},
     Icon, TrayIcon, TrayIconBuilder, TrayIconEvent,
 };
 
+use crate::settings::{
+    NotificationCorner, Settings, TrayClickAction, TrayDoubleClickAction, TrayIconStyle,
+};
 use crate::AppMessage;
 
 #[cfg(windows)]
@@ -29,15 +33,169 @@ static TRAY_DRAG_START: Mutex<Option<(f64, f64)>> = Mutex::new(None);
 /// Drag threshold in pixels
 const DRAG_THRESHOLD: f64 = 5.0;
 
+/// Bumped on every tray click; a pending single-click action only runs if it is still current
+static CLICK_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Whether dragging from the tray icon starts a file drag (mirrors `Settings::enable_tray_drag`)
+static TRAY_DRAG_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable dragging screenshots out of the tray icon
+pub fn set_tray_drag_enabled(enabled: bool) {
+    TRAY_DRAG_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether our own windows are passed over by window capture and foreground tracking
+/// (mirrors `Settings::exclude_own_windows`)
+static EXCLUDE_OWN_WINDOWS: AtomicBool = AtomicBool::new(true);
+
+/// Skip or allow our own windows when picking the active window
+pub fn set_exclude_own_windows(enabled: bool) {
+    EXCLUDE_OWN_WINDOWS.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether the main window stays above other windows (mirrors `Settings::always_on_top`)
+static ALWAYS_ON_TOP: AtomicBool = AtomicBool::new(false);
+
+/// Handle of the main window
+///
+/// `HWND` wraps a raw pointer and isn't `Send`, so the handle is stored as an integer and
+/// converted back in one place. The main window is created once by GPUI and only ever
+/// hidden, never destroyed, so a stored handle stays valid until the process exits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct WindowHandle(isize);
+
+impl WindowHandle {
+    #[cfg(windows)]
+    pub fn hwnd(&self) -> HWND {
+        HWND(self.0 as *mut std::ffi::c_void)
+    }
+}
+
 /// Shared state for window handle
-pub static WINDOW_HWND: Mutex<Option<isize>> = Mutex::new(None);
+static WINDOW_HWND: Mutex<Option<WindowHandle>> = Mutex::new(None);
 
 /// Track window visibility
 static WINDOW_VISIBLE: AtomicBool = AtomicBool::new(true);
 
-/// Set the window handle for tray operations
+/// Guards the one-time registration of the notification window class
+#[cfg(windows)]
+static NOTIFICATION_CLASS: std::sync::Once = std::sync::Once::new();
+
+/// Set the window handle for tray operations; `hwnd` must be the main window created by GPUI
 pub fn set_window_hwnd(hwnd: isize) {
-    *WINDOW_HWND.lock() = Some(hwnd);
+    let handle = WindowHandle(hwnd);
+    *WINDOW_HWND.lock() = Some(handle);
+    install_window_subclass(handle);
+    apply_always_on_top(handle);
+}
+
+/// The main window handle, once the window has been created
+pub fn window_handle() -> Option<WindowHandle> {
+    *WINDOW_HWND.lock()
+}
+
+/// Keep the main window above other apps, or return it to the normal z-order
+pub fn set_always_on_top(enabled: bool) {
+    ALWAYS_ON_TOP.store(enabled, Ordering::SeqCst);
+    if let Some(handle) = window_handle() {
+        apply_always_on_top(handle);
+    }
+}
+
+/// Apply the topmost state to the main window only; the notification window manages its own
+#[cfg(windows)]
+fn apply_always_on_top(handle: WindowHandle) {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SetWindowPos, HWND_NOTOPMOST, HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+    };
+
+    let enabled = ALWAYS_ON_TOP.load(Ordering::SeqCst);
+    let insert_after = if enabled { HWND_TOPMOST } else { HWND_NOTOPMOST };
+    unsafe {
+        if let Err(e) = SetWindowPos(
+            handle.hwnd(),
+            insert_after,
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        ) {
+            log::warn!("Failed to update always-on-top state: {}", e);
+        } else {
+            debug!("Always on top: {}", enabled);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn apply_always_on_top(_handle: WindowHandle) {
+    // Not implemented for non-Windows
+}
+
+/// Subclass ID for our hook on the main window proc
+#[cfg(windows)]
+const WINDOW_SUBCLASS_ID: usize = 1;
+
+/// Hook the main window so visibility changes made outside `show_window`/`hide_window`
+/// (minimize, Alt+F4) keep `WINDOW_VISIBLE` accurate
+#[cfg(windows)]
+fn install_window_subclass(handle: WindowHandle) {
+    use windows::Win32::UI::Shell::SetWindowSubclass;
+
+    unsafe {
+        if !SetWindowSubclass(handle.hwnd(), Some(window_subclass_proc), WINDOW_SUBCLASS_ID, 0).as_bool() {
+            log::warn!("Failed to subclass main window; tray toggle may misjudge visibility");
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn install_window_subclass(_handle: WindowHandle) {}
+
+#[cfg(windows)]
+unsafe extern "system" fn window_subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    _data: usize,
+) -> LRESULT {
+    use windows::Win32::UI::Shell::DefSubclassProc;
+
+    match msg {
+        WM_SHOWWINDOW => {
+            WINDOW_VISIBLE.store(wparam.0 != 0, Ordering::SeqCst);
+        }
+        WM_SIZE => match wparam.0 as u32 {
+            SIZE_MINIMIZED => {
+                debug!("Window minimized");
+                WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+            }
+            SIZE_RESTORED | SIZE_MAXIMIZED => {
+                let visible = unsafe { IsWindowVisible(hwnd) }.as_bool();
+                WINDOW_VISIBLE.store(visible, Ordering::SeqCst);
+            }
+            _ => {}
+        },
+        WM_DPICHANGED => {
+            // GPUI's window proc applies the suggested rectangle from lParam and rescales
+            // its rendering, so the message has to reach it rather than stop here
+            debug!("Main window DPI changed to {}", wparam.0 & 0xFFFF);
+        }
+        WM_CLOSE => {
+            // We're a tray app: closing the window hides it instead of exiting
+            info!("Window close requested, hiding to tray");
+            let _ = unsafe { ShowWindow(hwnd, SW_HIDE) };
+            WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+            return LRESULT(0);
+        }
+        _ => {}
+    }
+
+    unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) }
 }
 
 /// Check if our window is currently the foreground (focused) window
@@ -45,14 +203,7 @@ pub fn set_window_hwnd(hwnd: isize) {
 pub fn is_window_focused() -> bool {
     use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
 
-    if let Some(hwnd) = *WINDOW_HWND.lock() {
-        unsafe {
-            let foreground = GetForegroundWindow();
-            foreground.0 as isize == hwnd
-        }
-    } else {
-        false
-    }
+    window_handle().is_some_and(|handle| unsafe { GetForegroundWindow() } == handle.hwnd())
 }
 
 #[cfg(not(windows))]
@@ -60,6 +211,29 @@ pub fn is_window_focused() -> bool {
     false
 }
 
+/// Whether a window belongs to Sukusho: the main window, or any other window of this
+/// process such as the notification popup or the tray preview
+#[cfg(windows)]
+pub fn is_own_window(hwnd: HWND) -> bool {
+    use windows::Win32::System::Threading::GetCurrentProcessId;
+
+    if hwnd.is_invalid() {
+        return false;
+    }
+    if window_handle().is_some_and(|handle| handle.hwnd() == hwnd) {
+        return true;
+    }
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+    pid != 0 && pid == unsafe { GetCurrentProcessId() }
+}
+
+/// Whether the active-window lookups should pass over `hwnd` and use the window behind it
+#[cfg(windows)]
+pub fn skip_own_window(hwnd: HWND) -> bool {
+    EXCLUDE_OWN_WINDOWS.load(Ordering::SeqCst) && is_own_window(hwnd)
+}
+
 /// Check if window is visible
 pub fn is_window_visible() -> bool {
     WINDOW_VISIBLE.load(Ordering::SeqCst)
@@ -68,13 +242,11 @@ pub fn is_window_visible() -> bool {
 /// Hide the window
 #[cfg(windows)]
 pub fn hide_window() {
-    use windows::Win32::Foundation::HWND;
     use windows::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
 
-    if let Some(hwnd) = *WINDOW_HWND.lock() {
+    if let Some(handle) = window_handle() {
         unsafe {
-            let hwnd = HWND(hwnd as *mut std::ffi::c_void);
-            let _ = ShowWindow(hwnd, SW_HIDE);
+            let _ = ShowWindow(handle.hwnd(), SW_HIDE);
             WINDOW_VISIBLE.store(false, Ordering::SeqCst);
             info!("Window hidden");
         }
@@ -86,37 +258,71 @@ pub fn hide_window() {
     // Not implemented for non-Windows
 }
 
+/// Center a window of the given size in a work area, shrinking it to fit if needed
+///
+/// Work area and result are `(left, top, width, height)`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn fit_in_work_area(window_size: (i32, i32), work: (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
+    let (left, top, work_width, work_height) = work;
+    let width = window_size.0.min(work_width).max(1);
+    let height = window_size.1.min(work_height).max(1);
+
+    // Centered, then clamped so the titlebar stays reachable
+    let x = (left + (work_width - width) / 2).clamp(left, left + work_width - width);
+    let y = (top + (work_height - height) / 2).clamp(top, top + work_height - height);
+    (x, y, width, height)
+}
+
+/// Monitor under the mouse cursor, with its bounds and work area.
+/// Coordinates follow the calling thread's DPI awareness.
+#[cfg(windows)]
+pub fn cursor_monitor() -> Option<(HMONITOR, MONITORINFO)> {
+    use windows::Win32::Foundation::POINT;
+
+    unsafe {
+        let mut cursor_pos = POINT::default();
+        GetCursorPos(&mut cursor_pos).ok()?;
+
+        let monitor = MonitorFromPoint(cursor_pos, MONITOR_DEFAULTTONEAREST);
+        let mut monitor_info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        GetMonitorInfoW(monitor, &mut monitor_info)
+            .as_bool()
+            .then_some((monitor, monitor_info))
+    }
+}
+
 /// Move window to the monitor where the cursor is located
 #[cfg(windows)]
 fn move_window_to_cursor_monitor() {
-    use windows::Win32::Foundation::{HWND, POINT, RECT};
-    use windows::Win32::Graphics::Gdi::{
-        GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST,
-    };
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{MonitorFromWindow, MONITOR_DEFAULTTONEAREST};
     use windows::Win32::UI::WindowsAndMessaging::{
-        GetCursorPos, GetWindowRect, SetWindowPos, HWND_TOP, SWP_NOSIZE, SWP_NOZORDER,
+        GetWindowRect, SetWindowPos, HWND_TOP, SWP_NOACTIVATE, SWP_NOSIZE, SWP_NOZORDER,
     };
 
-    if let Some(hwnd) = *WINDOW_HWND.lock() {
+    if let Some(handle) = window_handle() {
+        let Some((monitor, monitor_info)) = cursor_monitor() else {
+            return;
+        };
         unsafe {
-            let hwnd = HWND(hwnd as *mut std::ffi::c_void);
-
-            // Get cursor position
-            let mut cursor_pos = POINT::default();
-            if GetCursorPos(&mut cursor_pos).is_err() {
-                return;
-            }
-
-            // Get monitor at cursor position
-            let monitor = MonitorFromPoint(cursor_pos, MONITOR_DEFAULTTONEAREST);
-
-            // Get monitor info
-            let mut monitor_info = MONITORINFO {
-                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
-                ..Default::default()
-            };
-            if !GetMonitorInfoW(monitor, &mut monitor_info).as_bool() {
-                return;
+            let hwnd = handle.hwnd();
+
+            // Hop onto the target monitor first; if its scaling differs, WM_DPICHANGED
+            // resizes the window there, and the size read below is already rescaled
+            if MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) != monitor {
+                let work = monitor_info.rcWork;
+                let _ = SetWindowPos(
+                    hwnd,
+                    HWND_TOP,
+                    work.left,
+                    work.top,
+                    0,
+                    0,
+                    SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+                );
             }
 
             // Get current window rect
@@ -128,13 +334,23 @@ fn move_window_to_cursor_monitor() {
             let window_width = window_rect.right - window_rect.left;
             let window_height = window_rect.bottom - window_rect.top;
 
-            // Calculate centered position on the monitor
+            // Calculate centered position on the monitor, shrinking oversized windows
             let monitor_work = monitor_info.rcWork;
-            let monitor_width = monitor_work.right - monitor_work.left;
-            let monitor_height = monitor_work.bottom - monitor_work.top;
+            let (new_x, new_y, new_width, new_height) = fit_in_work_area(
+                (window_width, window_height),
+                (
+                    monitor_work.left,
+                    monitor_work.top,
+                    monitor_work.right - monitor_work.left,
+                    monitor_work.bottom - monitor_work.top,
+                ),
+            );
 
-            let new_x = monitor_work.left + (monitor_width - window_width) / 2;
-            let new_y = monitor_work.top + (monitor_height - window_height) / 2;
+            // Only resize when the window doesn't fit
+            let mut flags = SWP_NOZORDER;
+            if (new_width, new_height) == (window_width, window_height) {
+                flags |= SWP_NOSIZE;
+            }
 
             // Move window to new position
             let _ = SetWindowPos(
@@ -142,22 +358,84 @@ fn move_window_to_cursor_monitor() {
                 HWND_TOP,
                 new_x,
                 new_y,
-                0,
-                0,
-                SWP_NOSIZE | SWP_NOZORDER,
+                new_width,
+                new_height,
+                flags,
             );
             debug!(
-                "Moved window to monitor at cursor position ({}, {})",
-                new_x, new_y
+                "Moved window to monitor at cursor position ({}, {}) size {}x{}",
+                new_x, new_y, new_width, new_height
             );
         }
     }
 }
 
+/// Pull the window back onto its nearest monitor if it is no longer fully inside the
+/// work area, e.g. after a monitor was disconnected or the resolution changed
+#[cfg(windows)]
+pub fn ensure_window_on_screen() {
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+
+    let Some(handle) = window_handle() else {
+        return;
+    };
+
+    unsafe {
+        let hwnd = handle.hwnd();
+        // A minimized window sits at -32000, -32000; Windows restores it to its normal place
+        if IsIconic(hwnd).as_bool() {
+            return;
+        }
+
+        let mut window_rect = RECT::default();
+        if GetWindowRect(hwnd, &mut window_rect).is_err() {
+            return;
+        }
+
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut monitor_info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut monitor_info).as_bool() {
+            return;
+        }
+
+        let work = monitor_info.rcWork;
+        if window_rect.left >= work.left
+            && window_rect.top >= work.top
+            && window_rect.right <= work.right
+            && window_rect.bottom <= work.bottom
+        {
+            return;
+        }
+
+        let window_width = window_rect.right - window_rect.left;
+        let window_height = window_rect.bottom - window_rect.top;
+        let (x, y, width, height) = fit_in_work_area(
+            (window_width, window_height),
+            (work.left, work.top, work.right - work.left, work.bottom - work.top),
+        );
+
+        let mut flags = SWP_NOZORDER | SWP_NOACTIVATE;
+        if (width, height) == (window_width, window_height) {
+            flags |= SWP_NOSIZE;
+        }
+        let _ = SetWindowPos(hwnd, HWND_TOP, x, y, width, height, flags);
+        info!("Moved window back on screen at ({}, {}) size {}x{}", x, y, width, height);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn ensure_window_on_screen() {
+    // Not implemented for non-Windows
+}
+
 /// Show and activate the window using Windows API
 #[cfg(windows)]
 pub fn show_window() {
-    use windows::Win32::Foundation::HWND;
     use windows::Win32::UI::WindowsAndMessaging::{
         SetForegroundWindow, ShowWindow, SW_RESTORE, SW_SHOW,
     };
@@ -165,23 +443,19 @@ pub fn show_window() {
     // First move window to cursor's monitor
     move_window_to_cursor_monitor();
 
-    if let Some(hwnd) = *WINDOW_HWND.lock() {
+    if let Some(handle) = window_handle() {
         unsafe {
-            let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+            let hwnd = handle.hwnd();
             let _ = ShowWindow(hwnd, SW_RESTORE);
             let _ = ShowWindow(hwnd, SW_SHOW);
             let _ = SetForegroundWindow(hwnd);
             WINDOW_VISIBLE.store(true, Ordering::SeqCst);
             info!("Window shown and focused");
         }
+        apply_always_on_top(handle);
     }
 }
 
-#[cfg(not(windows))]
-pub fn show_window() {
-    // Not implemented for non-Windows
-}
-
 /// Toggle window visibility - hide if focused, show if not
 /// Returns true if window was shown, false if hidden
 #[cfg(windows)]
@@ -197,17 +471,140 @@ pub fn toggle_window() -> bool {
     }
 }
 
-#[cfg(not(windows))]
-pub fn toggle_window() -> bool {
-    show_window();
-    true
+/// What a notification is about; picks its accent color and glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+    Update,
+}
+
+impl NotificationKind {
+    /// Accent as 0xRRGGBB; plain information follows the app accent
+    pub fn color(self) -> u32 {
+        match self {
+            NotificationKind::Info => crate::accent::rgb(),
+            NotificationKind::Success => 0x107C10,
+            NotificationKind::Warning => 0xFFB900,
+            NotificationKind::Error => 0xE81123,
+            NotificationKind::Update => 0x0078D7,
+        }
+    }
+
+    /// Symbol drawn inside the badge in the notification's top-left corner
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn glyph(self) -> char {
+        match self {
+            NotificationKind::Info => 'i',
+            NotificationKind::Success => '\u{2713}', // check mark
+            NotificationKind::Warning => '!',
+            NotificationKind::Error => '\u{2715}', // multiplication x
+            NotificationKind::Update => '\u{2193}', // downwards arrow
+        }
+    }
+}
+
+/// Show a localized tray notification, unless notifications are turned off in settings
+///
+/// `args` fill the `%{name}` placeholders of the message.
+pub fn notify(
+    settings: &Settings,
+    kind: NotificationKind,
+    title_key: &str,
+    message_key: &str,
+    args: &[(&str, &str)],
+) {
+    if !settings.notifications_enabled {
+        debug!("Notifications disabled, skipping {}", message_key);
+        return;
+    }
+
+    let title = t!(title_key).to_string();
+    let message = interpolate(&t!(message_key), args);
+    show_tray_notification(
+        kind,
+        &title,
+        &message,
+        settings.notification_corner,
+        settings.notification_monitor,
+    );
+}
+
+/// Announce a screenshot that arrived while the window is hidden
+///
+/// Arrivals are collected for `ARRIVAL_BATCH_WINDOW` first, so a burst (e.g. pasting ten
+/// files into the folder) can be announced as one notification.
+pub fn notify_new_screenshot(settings: &Arc<Mutex<Settings>>, path: std::path::PathBuf) {
+    if !settings.lock().notifications_enabled {
+        return;
+    }
+    let first = {
+        let mut pending = PENDING_ARRIVALS.lock();
+        pending.push(path);
+        pending.len() == 1
+    };
+    if !first {
+        return;
+    }
+
+    let settings = Arc::clone(settings);
+    std::thread::spawn(move || {
+        std::thread::sleep(ARRIVAL_BATCH_WINDOW);
+        let paths = std::mem::take(&mut *PENDING_ARRIVALS.lock());
+        let settings = settings.lock().clone();
+        for (key, arg, value) in arrival_notifications(&paths, settings.notification_batching) {
+            notify(&settings, NotificationKind::Info, "notifications.title", key, &[(arg, &value)]);
+        }
+    });
+}
+
+/// How long new screenshots are collected before they are announced
+const ARRIVAL_BATCH_WINDOW: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Screenshots waiting for `notify_new_screenshot` to announce them
+static PENDING_ARRIVALS: Mutex<Vec<std::path::PathBuf>> = Mutex::new(Vec::new());
+
+/// Message key and its argument for each notification announcing `paths`
+///
+/// With batching, more than one arrival becomes a single summary.
+fn arrival_notifications(
+    paths: &[std::path::PathBuf],
+    batching: bool,
+) -> Vec<(&'static str, &'static str, String)> {
+    if batching && paths.len() > 1 {
+        return vec![("notifications.new_screenshot.other", "count", paths.len().to_string())];
+    }
+    paths
+        .iter()
+        .map(|path| {
+            let file = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            ("notifications.new_screenshot.one", "file", file)
+        })
+        .collect()
+}
+
+/// Replace `%{name}` placeholders, leaving unknown ones as they are
+pub fn interpolate(template: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("%{{{}}}", name), value)
+    })
 }
 
 /// Show a custom notification window near the system tray
 #[cfg(windows)]
-#[allow(dead_code)]
-pub fn show_tray_notification(title: &str, message: &str) {
-    use windows::core::{w, PCWSTR};
+pub fn show_tray_notification(
+    kind: NotificationKind,
+    title: &str,
+    message: &str,
+    corner: NotificationCorner,
+    monitor: Option<usize>,
+) {
+    use windows::core::w;
     use windows::Win32::System::LibraryLoader::GetModuleHandleW;
     use windows::Win32::Foundation::COLORREF;
     use windows::Win32::UI::WindowsAndMessaging::{
@@ -222,47 +619,45 @@ pub fn show_tray_notification(title: &str, message: &str) {
 
         unsafe {
             let class_name = w!("SukushoNotificationClass");
-
-            // Register window class
             let hinstance = GetModuleHandleW(None).unwrap();
 
-            let wc = WNDCLASSW {
-                lpfnWndProc: Some(notification_wndproc),
-                hInstance: hinstance.into(),
-                lpszClassName: class_name,
-                hCursor: LoadCursorW(None, IDC_ARROW).unwrap(),
-                hbrBackground: CreateSolidBrush(COLORREF(0x00000000)), // Transparent black
-                ..Default::default()
-            };
+            // Register window class once per process
+            NOTIFICATION_CLASS.call_once(|| {
+                let wc = WNDCLASSW {
+                    lpfnWndProc: Some(notification_wndproc),
+                    hInstance: hinstance.into(),
+                    lpszClassName: class_name,
+                    hCursor: LoadCursorW(None, IDC_ARROW).unwrap(),
+                    hbrBackground: CreateSolidBrush(COLORREF(0x00000000)), // Transparent black
+                    ..Default::default()
+                };
+
+                if RegisterClassW(&wc) == 0 {
+                    log::warn!(
+                        "Failed to register notification window class: {}",
+                        windows::core::Error::from_win32()
+                    );
+                }
+            });
 
-            RegisterClassW(&wc);
-
-            // Get taskbar position to position notification
-            let mut taskbar_rect = RECT::default();
-            if let Ok(taskbar) = FindWindowW(w!("Shell_TrayWnd"), PCWSTR::null()) {
-                let _ = GetWindowRect(taskbar, &mut taskbar_rect);
-            }
+            // The work area excludes the taskbar on whichever edge it is docked
+            let work = notification_work_area(monitor);
 
             // Get DPI for proper scaling
             let dpi = GetDpiForSystem();
             let scale = dpi as f32 / 96.0; // 96 is the standard DPI
 
-            // Get screen dimensions
-            let screen_width = GetSystemMetrics(SM_CXSCREEN);
-            let screen_height = GetSystemMetrics(SM_CYSCREEN);
-
             // Notification dimensions (scaled for DPI)
             let notif_width = (400.0 * scale) as i32;
             let notif_height = (140.0 * scale) as i32;
             let margin = (15.0 * scale) as i32;
 
-            // Position in bottom-right corner, above taskbar
-            let x = screen_width - notif_width - margin;
-            let y = if taskbar_rect.bottom > taskbar_rect.top {
-                taskbar_rect.top - notif_height - margin
-            } else {
-                screen_height - notif_height - margin
-            };
+            let (x, y) = notification_origin(
+                corner,
+                (work.left, work.top, work.right - work.left, work.bottom - work.top),
+                (notif_width, notif_height),
+                margin,
+            );
 
             // Create layered window
             let hwnd = CreateWindowExW(
@@ -278,7 +673,17 @@ pub fn show_tray_notification(title: &str, message: &str) {
                 None,
                 hinstance,
                 None,
-            ).unwrap();
+            );
+            let hwnd = match hwnd {
+                Ok(hwnd) => hwnd,
+                Err(e) => {
+                    log::warn!("Failed to create notification window ({}), using balloon tip", e);
+                    if let Err(e) = show_balloon_notification(kind, &title, &message) {
+                        log::warn!("Failed to show balloon notification: {}", e);
+                    }
+                    return;
+                }
+            };
 
             // Set layered window attributes for transparency
             let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 230, LWA_ALPHA);
@@ -287,11 +692,15 @@ pub fn show_tray_notification(title: &str, message: &str) {
             let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
             let _ = AnimateWindow(hwnd, 200, AW_BLEND);
 
-            // Store title, message, and scale in window data
+            // Store title, message, scale and kind in window data
             let title_wide: Vec<u16> = title.encode_utf16().chain(std::iter::once(0)).collect();
             let message_wide: Vec<u16> = message.encode_utf16().chain(std::iter::once(0)).collect();
 
-            SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(Box::new((title_wide, message_wide, scale))) as isize);
+            SetWindowLongPtrW(
+                hwnd,
+                GWLP_USERDATA,
+                Box::into_raw(Box::new((title_wide, message_wide, scale, kind))) as isize,
+            );
 
             // Invalidate to trigger paint
             let _ = InvalidateRect(hwnd, None, true);
@@ -320,7 +729,9 @@ unsafe extern "system" fn notification_wndproc(
 ) -> LRESULT { unsafe {
     use windows::core::w;
     use windows::Win32::Foundation::COLORREF;
-    use windows::Win32::Graphics::Gdi::{BeginPaint, EndPaint, PAINTSTRUCT};
+    use windows::Win32::Graphics::Gdi::{
+        BeginPaint, Ellipse, EndPaint, GetStockObject, PAINTSTRUCT, NULL_PEN,
+    };
     use windows::Win32::UI::WindowsAndMessaging::KillTimer;
 
     match msg {
@@ -328,11 +739,11 @@ unsafe extern "system" fn notification_wndproc(
             let mut ps = PAINTSTRUCT::default();
             let hdc = BeginPaint(hwnd, &mut ps);
 
-            // Get stored title, message, and scale
+            // Get stored title, message, scale and kind
             let user_data = GetWindowLongPtrW(hwnd, GWLP_USERDATA);
             if user_data != 0 {
-                let data_ptr = user_data as *const (Vec<u16>, Vec<u16>, f32);
-                let (title, message, scale) = &*data_ptr;
+                let data_ptr = user_data as *const (Vec<u16>, Vec<u16>, f32, NotificationKind);
+                let (title, message, scale, kind) = &*data_ptr;
 
                 // Set up drawing
                 let mut rect = RECT::default();
@@ -348,12 +759,53 @@ unsafe extern "system" fn notification_wndproc(
                 let _ = FrameRect(hdc, &rect, border_brush);
                 let _ = DeleteObject(border_brush);
 
+                // Accent bar along the top edge
+                let mut accent_rect = rect.clone();
+                accent_rect.bottom = accent_rect.top + (4.0 * scale).round() as i32;
+                let accent_brush =
+                    CreateSolidBrush(COLORREF(crate::accent::colorref(kind.color())));
+                let _ = FillRect(hdc, &accent_rect, accent_brush);
+
+                // Badge with the kind's glyph, left of the title
+                let padding = (20.0 * scale) as i32;
+                let badge_size = (24.0 * scale) as i32;
+                let badge = RECT {
+                    left: rect.left + padding,
+                    top: rect.top + padding + (3.0 * scale) as i32,
+                    right: rect.left + padding + badge_size,
+                    bottom: rect.top + padding + (3.0 * scale) as i32 + badge_size,
+                };
+                let old_brush = SelectObject(hdc, accent_brush);
+                let old_pen = SelectObject(hdc, GetStockObject(NULL_PEN));
+                let _ = Ellipse(hdc, badge.left, badge.top, badge.right + 1, badge.bottom + 1);
+                let _ = SelectObject(hdc, old_pen);
+                let _ = SelectObject(hdc, old_brush);
+                let _ = DeleteObject(accent_brush);
+
+                let glyph_font = CreateFontW(
+                    (16.0 * scale) as i32, 0, 0, 0, 700, 0, 0, 0, // DPI-scaled, FW_BOLD
+                    1, // DEFAULT_CHARSET
+                    0, // OUT_DEFAULT_PRECIS
+                    0, // CLIP_DEFAULT_PRECIS
+                    5, // CLEARTYPE_QUALITY
+                    0, // DEFAULT_PITCH | FF_DONTCARE
+                    w!("Segoe UI Symbol"),
+                );
+                let old_font = SelectObject(hdc, glyph_font);
+                let _ = SetBkMode(hdc, TRANSPARENT);
+                let _ = SetTextColor(hdc, COLORREF(0x00FFFFFF));
+                let mut glyph_rect = badge;
+                let mut glyph = [0u16; 2];
+                let glyph = kind.glyph().encode_utf16(&mut glyph);
+                let _ = DrawTextW(hdc, glyph, &mut glyph_rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE);
+                let _ = SelectObject(hdc, old_font);
+                let _ = DeleteObject(glyph_font);
+
                 // Set text properties
                 let _ = SetBkMode(hdc, TRANSPARENT);
                 let _ = SetTextColor(hdc, COLORREF(0x00FFFFFF)); // White text
 
                 // Calculate scaled dimensions
-                let padding = (20.0 * scale) as i32;
                 let title_height = (35.0 * scale) as i32;
                 let message_top = (60.0 * scale) as i32;
                 let message_bottom_margin = (15.0 * scale) as i32;
@@ -361,7 +813,7 @@ unsafe extern "system" fn notification_wndproc(
                 // Draw title (bold, DPI-scaled font)
                 let mut title_rect = rect.clone();
                 title_rect.top += padding;
-                title_rect.left += padding;
+                title_rect.left += padding + badge_size + (10.0 * scale) as i32;
                 title_rect.right -= padding;
                 title_rect.bottom = title_rect.top + title_height;
 
@@ -410,7 +862,7 @@ unsafe extern "system" fn notification_wndproc(
             // Clean up user data
             let user_data = GetWindowLongPtrW(hwnd, GWLP_USERDATA);
             if user_data != 0 {
-                let data_ptr = user_data as *mut (Vec<u16>, Vec<u16>, f32);
+                let data_ptr = user_data as *mut (Vec<u16>, Vec<u16>, f32, NotificationKind);
                 drop(Box::from_raw(data_ptr));
             }
             LRESULT(0)
@@ -433,76 +885,804 @@ unsafe extern "system" fn notification_wndproc(
     }
 }}
 
+/// Top-left position for a notification of `size` in the given corner of a work area
+/// `(left, top, width, height)`, inset by `margin`
+#[cfg_attr(not(windows), allow(dead_code))]
+fn notification_origin(
+    corner: NotificationCorner,
+    work: (i32, i32, i32, i32),
+    size: (i32, i32),
+    margin: i32,
+) -> (i32, i32) {
+    let (left, top, width, height) = work;
+    let x_left = left + margin;
+    let x_right = left + width - size.0 - margin;
+    let y_top = top + margin;
+    let y_bottom = top + height - size.1 - margin;
+
+    match corner {
+        NotificationCorner::TopLeft => (x_left, y_top),
+        NotificationCorner::TopRight => (x_right, y_top),
+        NotificationCorner::BottomLeft => (x_left, y_bottom),
+        NotificationCorner::BottomRight => (x_right, y_bottom),
+    }
+}
+
+/// Work area of the chosen monitor (enumeration order), falling back to the primary one
+#[cfg(windows)]
+fn notification_work_area(monitor: Option<usize>) -> RECT {
+    use windows::Win32::Foundation::{BOOL, POINT, TRUE};
+
+    unsafe extern "system" fn collect(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        unsafe {
+            let monitors = &mut *(data.0 as *mut Vec<HMONITOR>);
+            monitors.push(monitor);
+        }
+        TRUE
+    }
+
+    unsafe {
+        let mut monitors: Vec<HMONITOR> = Vec::new();
+        let _ = EnumDisplayMonitors(
+            None,
+            None,
+            Some(collect),
+            LPARAM(&mut monitors as *mut _ as isize),
+        );
+
+        let handle = monitor
+            .and_then(|index| monitors.get(index).copied())
+            .unwrap_or_else(|| MonitorFromPoint(POINT::default(), MONITOR_DEFAULTTOPRIMARY));
+
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if GetMonitorInfoW(handle, &mut info).as_bool() {
+            info.rcWork
+        } else {
+            // Last resort: the primary screen without taskbar adjustment
+            RECT {
+                left: 0,
+                top: 0,
+                right: GetSystemMetrics(SM_CXSCREEN),
+                bottom: GetSystemMetrics(SM_CYSCREEN),
+            }
+        }
+    }
+}
+
+/// The tray icon's own window and icon ID, which tray-icon doesn't expose; balloon tips are
+/// shown on this icon rather than on a second one
+#[cfg(windows)]
+static TRAY_ICON_ID: Mutex<Option<(WindowHandle, u32)>> = Mutex::new(None);
+
+/// tray-icon's hidden windows (one per icon) owned by the calling thread
+#[cfg(windows)]
+fn tray_icon_windows() -> Vec<WindowHandle> {
+    use windows::Win32::Foundation::{BOOL, TRUE};
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+
+    unsafe extern "system" fn collect(hwnd: HWND, data: LPARAM) -> BOOL {
+        unsafe {
+            let windows = &mut *(data.0 as *mut Vec<WindowHandle>);
+            let mut class = [0u16; 32];
+            let len = GetClassNameW(hwnd, &mut class).max(0) as usize;
+            if String::from_utf16_lossy(&class[..len]) == "tray_icon_app" {
+                windows.push(WindowHandle(hwnd.0 as isize));
+            }
+        }
+        TRUE
+    }
+
+    let mut windows = Vec::new();
+    unsafe {
+        let _ = EnumThreadWindows(
+            GetCurrentThreadId(),
+            Some(collect),
+            LPARAM(&mut windows as *mut _ as isize),
+        );
+    }
+    windows
+}
+
+/// Find the window and icon ID of `tray_icon`, just built on this thread; `existing` is
+/// `tray_icon_windows()` from before it was built
+#[cfg(windows)]
+fn remember_tray_icon(tray_icon: &TrayIcon, existing: &[WindowHandle]) {
+    use windows::Win32::UI::Shell::{Shell_NotifyIconGetRect, NOTIFYICONIDENTIFIER};
+
+    let Some(window) = tray_icon_windows().into_iter().find(|w| !existing.contains(w)) else {
+        log::warn!("Tray icon window not found; balloon tips are unavailable");
+        return;
+    };
+    // tray-icon numbers the icon right after its public ID; check nearby IDs in case that changes
+    let first = tray_icon.id().as_ref().parse::<u32>().unwrap_or(0);
+    let id = (first..first + 8).find(|&id| {
+        let identifier = NOTIFYICONIDENTIFIER {
+            cbSize: std::mem::size_of::<NOTIFYICONIDENTIFIER>() as u32,
+            hWnd: window.hwnd(),
+            uID: id,
+            ..Default::default()
+        };
+        unsafe { Shell_NotifyIconGetRect(&identifier).is_ok() }
+    });
+    match id {
+        Some(id) => *TRAY_ICON_ID.lock() = Some((window, id)),
+        None => log::warn!("Tray icon ID not found; balloon tips are unavailable"),
+    }
+}
+
+/// Copy a string into a fixed-size, null-terminated UTF-16 buffer, truncating if needed
+#[cfg_attr(not(windows), allow(dead_code))]
+fn fill_wide(buf: &mut [u16], s: &str) {
+    let len = buf.len().saturating_sub(1);
+    let mut written = 0;
+    for (slot, unit) in buf.iter_mut().zip(s.encode_utf16().take(len)) {
+        *slot = unit;
+        written += 1;
+    }
+    if let Some(terminator) = buf.get_mut(written) {
+        *terminator = 0;
+    }
+}
+
+/// Fallback for `show_tray_notification`: a native balloon tip from the tray icon
+#[cfg(windows)]
+fn show_balloon_notification(kind: NotificationKind, title: &str, message: &str) -> Result<()> {
+    use windows::Win32::UI::Shell::{
+        Shell_NotifyIconW, NIF_INFO, NIIF_ERROR, NIIF_INFO, NIIF_WARNING, NIM_MODIFY,
+        NOTIFYICONDATAW,
+    };
+
+    let (window, id) = TRAY_ICON_ID
+        .lock()
+        .ok_or_else(|| anyhow::anyhow!("Tray icon not available"))?;
+
+    let mut data = NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: window.hwnd(),
+        uID: id,
+        uFlags: NIF_INFO,
+        dwInfoFlags: match kind {
+            NotificationKind::Warning => NIIF_WARNING,
+            NotificationKind::Error => NIIF_ERROR,
+            _ => NIIF_INFO,
+        },
+        ..Default::default()
+    };
+    fill_wide(&mut data.szInfoTitle, title);
+    fill_wide(&mut data.szInfo, message);
+
+    if !unsafe { Shell_NotifyIconW(NIM_MODIFY, &data) }.as_bool() {
+        anyhow::bail!("Shell_NotifyIconW failed");
+    }
+    Ok(())
+}
+
 #[cfg(not(windows))]
-pub fn show_tray_notification(_title: &str, _message: &str) {
+pub fn show_tray_notification(
+    _kind: NotificationKind,
+    _title: &str,
+    _message: &str,
+    _corner: NotificationCorner,
+    _monitor: Option<usize>,
+) {
     // Not implemented for non-Windows
 }
 
+/// A plain tray menu entry. `TrayManager::new` adds one menu item per registered action,
+/// in order, and routes clicks on it to `handler`.
+#[derive(Clone, Copy)]
+pub struct TrayAction {
+    /// Menu item id
+    pub id: &'static str,
+    /// Locale key of the label
+    pub label_key: &'static str,
+    /// Runs on the menu event thread when the item is clicked
+    pub handler: fn(&Sender<AppMessage>, &Arc<Mutex<Settings>>),
+    /// Whether the action can be used with these settings; if not, its item is grayed out
+    pub available: fn(&Settings) -> bool,
+}
+
+fn always_available(_: &Settings) -> bool {
+    true
+}
+
+/// Send `message` for the latest settled screenshot, if there is one
+fn send_for_latest(
+    message_tx: &Sender<AppMessage>,
+    message: fn(std::path::PathBuf) -> AppMessage,
+) {
+    match crate::get_latest_screenshot_filtered(&crate::LatestOpts::settled()) {
+        Some(latest_path) => {
+            let _ = message_tx.send(message(latest_path));
+        }
+        None => debug!("No screenshots available for tray action"),
+    }
+}
+
+/// The actions listed between the settings entries and the toggles, in menu order
+pub fn tray_actions() -> Vec<TrayAction> {
+    vec![
+        TrayAction {
+            id: "copy-text-latest",
+            label_key: "tray.menu.copy_text_latest",
+            handler: |tx, _| send_for_latest(tx, AppMessage::ExtractText),
+            available: always_available,
+        },
+        TrayAction {
+            id: "copy-recent",
+            label_key: "tray.menu.copy_recent",
+            handler: |_, settings| copy_recent(settings),
+            available: always_available,
+        },
+        TrayAction {
+            id: "save-clipboard-image",
+            label_key: "tray.menu.save_clipboard_image",
+            handler: |_, settings| save_clipboard_image(settings),
+            available: always_available,
+        },
+        TrayAction {
+            id: "export-contact-sheet",
+            label_key: "tray.menu.export_contact_sheet",
+            handler: |_, settings| {
+                // Decoding a few dozen screenshots takes a while; keep the menu responsive
+                let settings = Arc::clone(settings);
+                std::thread::spawn(move || export_contact_sheet(&settings));
+            },
+            available: always_available,
+        },
+        TrayAction {
+            id: "upload-latest",
+            label_key: "tray.menu.upload_latest",
+            handler: |tx, _| send_for_latest(tx, AppMessage::Upload),
+            available: crate::upload::is_configured,
+        },
+        TrayAction {
+            id: "toggle-pin-latest",
+            label_key: "tray.menu.toggle_pin_latest",
+            handler: |tx, _| send_for_latest(tx, AppMessage::TogglePin),
+            available: always_available,
+        },
+        TrayAction {
+            id: "find-duplicates",
+            label_key: "tray.menu.find_duplicates",
+            handler: |tx, _| {
+                crate::platform::current().show_window();
+                let _ = tx.send(AppMessage::FindDuplicates);
+            },
+            available: always_available,
+        },
+        TrayAction {
+            id: "rescan",
+            label_key: "tray.menu.rescan",
+            handler: |tx, _| {
+                info!("Rescan requested from tray menu");
+                let _ = tx.send(AppMessage::Rescan);
+            },
+            available: always_available,
+        },
+        TrayAction {
+            id: "diagnostics",
+            label_key: "tray.menu.diagnostics",
+            handler: |_, settings| {
+                // The version check goes over the network
+                let settings = settings.lock().clone();
+                std::thread::spawn(move || crate::doctor::run_from_tray(&settings));
+            },
+            available: always_available,
+        },
+    ]
+}
+
 pub struct TrayManager {
     _tray_icon: TrayIcon,
+    settings_item: MenuItem,
+    check_updates_item: MenuItem,
+    /// Items built from `tray_actions`
+    action_items: Vec<(TrayAction, MenuItem)>,
+    always_on_top_item: CheckMenuItem,
+    autostart_item: CheckMenuItem,
+    quit_item: MenuItem,
+    /// Style of the current icon, whether it was drawn for a light taskbar, and its size
+    icon_look: (TrayIconStyle, bool, u32),
+    /// Tooltip line describing the latest screenshot
+    latest_tooltip: Option<String>,
+}
+
+/// How long to wait for a second click before treating a click as single
+#[cfg(windows)]
+fn double_click_time() -> std::time::Duration {
+    use windows::Win32::UI::Input::KeyboardAndMouse::GetDoubleClickTime;
+
+    std::time::Duration::from_millis(u64::from(unsafe { GetDoubleClickTime() }))
+}
+
+#[cfg(not(windows))]
+fn double_click_time() -> std::time::Duration {
+    std::time::Duration::from_millis(500)
+}
+
+/// Run the configured left-click action
+fn handle_left_click(
+    action: TrayClickAction,
+    settings: &Arc<Mutex<Settings>>,
+    message_tx: &Sender<AppMessage>,
+) {
+    match action {
+        TrayClickAction::ToggleWindow => {
+            let was_shown = crate::platform::current().toggle_window();
+            // If window was shown, send message to reset to main view
+            if was_shown {
+                let _ = message_tx.send(AppMessage::ShowMainWindow);
+            }
+        }
+        TrayClickAction::OpenFolder => open_screenshot_folder(settings),
+        TrayClickAction::CopyLatest => copy_latest(settings),
+        TrayClickAction::Nothing => {
+            debug!("Tray left click ignored (no action configured)");
+        }
+    }
+}
+
+/// Copy the latest screenshot like Ctrl+C on a single screenshot would
+///
+/// The window may be hidden, so the result is shown as a tray notification. Copying runs on
+/// its own thread since copying as an image decodes the screenshot.
+pub fn copy_latest(settings: &Arc<Mutex<Settings>>) {
+    let Some(latest_path) = crate::get_latest_screenshot_filtered(&crate::LatestOpts::settled())
+    else {
+        debug!("No screenshots available to copy");
+        return;
+    };
+    let settings = Arc::clone(settings);
+    std::thread::spawn(move || {
+        let as_image = settings.lock().copy_as_image;
+        if crate::clipboard::copy_screenshots(&[latest_path], as_image) {
+            notify(
+                &settings.lock(),
+                NotificationKind::Success,
+                "notifications.title",
+                "notifications.copied_to_clipboard.one",
+                &[],
+            );
+        } else {
+            log::warn!("Failed to copy latest screenshot");
+        }
+    });
+}
+
+/// Copy every screenshot in the recent list as files, e.g. to paste them into a report
+pub fn copy_recent(settings: &Mutex<Settings>) {
+    let sort_order = settings.lock().sort_order;
+    let files: Vec<_> = crate::list_screenshots(sort_order, None)
+        .into_iter()
+        .map(|s| s.path)
+        .filter(|path| path.exists())
+        .collect();
+    if files.is_empty() {
+        debug!("No recent screenshots to copy");
+        return;
+    }
+    let count = files.len();
+    if crate::platform::current().copy_files(&files) {
+        info!("Copied {} recent screenshots", count);
+        notify(
+            &settings.lock(),
+            NotificationKind::Success,
+            "notifications.title",
+            &crate::i18n_helpers::count_key("notifications.copied_to_clipboard", count),
+            &[("count", &count.to_string())],
+        );
+    } else {
+        log::warn!("Failed to copy {} recent screenshots", count);
+    }
+}
+
+/// Export the recent screenshots as one contact sheet image and show it in Explorer
+pub fn export_contact_sheet(settings: &Mutex<Settings>) {
+    let (sort_order, directory, columns, cell_size) = {
+        let s = settings.lock();
+        (
+            s.sort_order,
+            s.screenshot_directory.clone(),
+            s.contact_sheet_columns,
+            s.contact_sheet_cell_size,
+        )
+    };
+    let files: Vec<_> = crate::list_screenshots(sort_order, None)
+        .into_iter()
+        .map(|s| s.path)
+        .filter(|path| path.exists())
+        .collect();
+    if files.is_empty() {
+        debug!("No recent screenshots for a contact sheet");
+        return;
+    }
+    match crate::contact_sheet::export_contact_sheet(&files, &directory, columns, cell_size) {
+        Ok(path) => {
+            let file = path.file_name().unwrap_or_default().to_string_lossy();
+            notify(
+                &settings.lock(),
+                NotificationKind::Success,
+                "notifications.title",
+                "notifications.contact_sheet.saved",
+                &[("file", &file)],
+            );
+            crate::platform::current().reveal(&path);
+        }
+        Err(e) => {
+            log::error!("Failed to export contact sheet: {}", e);
+            notify(
+                &settings.lock(),
+                NotificationKind::Error,
+                "notifications.title",
+                "notifications.contact_sheet.failed",
+                &[("error", &e.to_string())],
+            );
+        }
+    }
+}
+
+/// Save the image on the clipboard as a PNG screenshot, e.g. one copied from a browser
+pub fn save_clipboard_image(settings: &Mutex<Settings>) {
+    let Some(image) = crate::clipboard::paste_image_from_clipboard() else {
+        notify(
+            &settings.lock(),
+            NotificationKind::Warning,
+            "notifications.title",
+            "notifications.clipboard.no_image",
+            &[],
+        );
+        return;
+    };
+    let directory = settings.lock().screenshot_directory.clone();
+    match crate::capture::save_capture(&image.to_rgba8(), &directory) {
+        Ok(path) => {
+            let file = path.file_name().unwrap_or_default().to_string_lossy();
+            notify(
+                &settings.lock(),
+                NotificationKind::Success,
+                "notifications.title",
+                "notifications.clipboard.saved",
+                &[("file", &file)],
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to save clipboard image: {}", e);
+            notify(
+                &settings.lock(),
+                NotificationKind::Error,
+                "notifications.title",
+                "notifications.clipboard.save_failed",
+                &[("error", &e.to_string())],
+            );
+        }
+    }
+}
+
+/// Side of the generated tray icon, in pixels, when the system can't tell us
+const ICON_SIZE: u32 = 32;
+
+/// Small icon size (`SM_CXSMICON`) at the DPI of the monitor the taskbar is on
+///
+/// Drawing at exactly this size keeps the icon crisp; anything else is scaled by the shell.
+#[cfg(windows)]
+fn icon_size() -> u32 {
+    use windows::core::w;
+    use windows::Win32::UI::HiDpi::{GetDpiForSystem, GetDpiForWindow, GetSystemMetricsForDpi};
+
+    let dpi = unsafe {
+        match FindWindowW(w!("Shell_TrayWnd"), None).map(|taskbar| GetDpiForWindow(taskbar)) {
+            Ok(dpi) if dpi != 0 => dpi,
+            _ => GetDpiForSystem(),
+        }
+    };
+    match unsafe { GetSystemMetricsForDpi(SM_CXSMICON, dpi) } {
+        size if size > 0 => size as u32,
+        _ => ICON_SIZE,
+    }
+}
+
+#[cfg(not(windows))]
+fn icon_size() -> u32 {
+    ICON_SIZE
+}
+
+/// Tray icon pixels for a look and size, drawn on first use; variants should draw on a copy
+fn cached_camera_icon(style: TrayIconStyle, light_taskbar: bool, size: u32) -> &'static [u8] {
+    // A handful of entries at most: one per style and DPI the session has seen
+    static CACHE: Mutex<Vec<((TrayIconStyle, bool, u32), &'static [u8])>> =
+        Mutex::new(Vec::new());
+
+    // The colored icon ignores the taskbar theme
+    let key = (style, style == TrayIconStyle::Mono && light_taskbar, size);
+    let mut cache = CACHE.lock();
+    if let Some((_, rgba)) = cache.iter().find(|(k, _)| *k == key) {
+        return rgba;
+    }
+    let rgba: &'static [u8] = Box::leak(camera_icon_rgba(size, key.0, key.1).into_boxed_slice());
+    cache.push((key, rgba));
+    rgba
+}
+
+/// RGBA pixels of the generated camera icon
+///
+/// The mono style is a single-color silhouette with the lens cut out: black for a light
+/// taskbar, white for a dark one, like the built-in tray icons.
+fn camera_icon_rgba(size: u32, style: TrayIconStyle, light_taskbar: bool) -> Vec<u8> {
+    let mono = if light_taskbar { [0, 0, 0, 255] } else { [255, 255, 255, 255] };
+    let mut rgba = vec![0u8; (size * size * 4) as usize];
+
+    for y in 0..size {
+        for x in 0..size {
+            let idx = ((y * size + x) * 4) as usize;
+            let fx = x as f32 / size as f32;
+            let fy = y as f32 / size as f32;
+
+            let in_body = fx > 0.1 && fx < 0.9 && fy > 0.25 && fy < 0.85;
+            let cx = 0.5;
+            let cy = 0.55;
+            let r = 0.22;
+            let dist = ((fx - cx).powi(2) + (fy - cy).powi(2)).sqrt();
+            let in_lens = dist < r;
+            let in_lens_inner = dist < r * 0.6;
+            let in_flash = fx > 0.6 && fx < 0.8 && fy > 0.12 && fy < 0.28;
+
+            let pixel = match style {
+                TrayIconStyle::Color if in_lens_inner => [100, 180, 255, 255],
+                TrayIconStyle::Color if in_lens => [40, 40, 50, 255],
+                TrayIconStyle::Color if in_body || in_flash => [60, 60, 70, 255],
+                TrayIconStyle::Mono if in_lens_inner => mono,
+                TrayIconStyle::Mono if in_lens => [0, 0, 0, 0],
+                TrayIconStyle::Mono if in_body || in_flash => mono,
+                _ => [0, 0, 0, 0],
+            };
+            rgba[idx..idx + 4].copy_from_slice(&pixel);
+        }
+    }
+    rgba
+}
+
+/// Whether the taskbar uses the light theme (`SystemUsesLightTheme`)
+#[cfg(windows)]
+fn taskbar_is_light() -> bool {
+    use windows::core::w;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut data: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!(r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize"),
+            w!("SystemUsesLightTheme"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut std::ffi::c_void),
+            Some(&mut size),
+        )
+    };
+    // Missing on Windows versions before the light taskbar existed, which were dark
+    result.is_ok() && data != 0
+}
+
+#[cfg(not(windows))]
+fn taskbar_is_light() -> bool {
+    false
+}
+
+/// Retries after the tray icon failed to create; after that only `TaskbarCreated` retries
+const TRAY_RETRY_ATTEMPTS: u32 = 8;
+
+/// Wait before the first retry, doubled for each one after it
+const TRAY_RETRY_BASE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Longest wait between retries
+const TRAY_RETRY_MAX: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Wait before retry `attempt` (from 1), or None once retries are used up
+fn tray_retry_delay(attempt: u32) -> Option<std::time::Duration> {
+    if attempt == 0 || attempt > TRAY_RETRY_ATTEMPTS {
+        return None;
+    }
+    Some(
+        TRAY_RETRY_BASE
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(TRAY_RETRY_MAX),
+    )
+}
+
+/// Ask the UI to create the tray icon again after a backoff; false once retries are used up
+///
+/// The icon belongs to the UI thread, which runs its message loop, so it is created there.
+pub fn schedule_tray_retry(message_tx: Sender<AppMessage>, attempt: u32) -> bool {
+    let Some(delay) = tray_retry_delay(attempt) else {
+        return false;
+    };
+    info!("Retrying tray icon creation in {:?} (attempt {})", delay, attempt);
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let _ = message_tx.send(AppMessage::CreateTray(attempt));
+    });
+    true
+}
+
+/// Longest file name shown in the tooltip; Windows cuts tooltips off at 127 characters
+const TOOLTIP_MAX_NAME: usize = 48;
+
+/// "shot.png — 1920×1080 — 342 KiB", without the dimensions if they are unknown
+fn latest_tooltip_line(filename: &str, dimensions: Option<(u32, u32)>, file_size: u64) -> String {
+    let name = if filename.chars().count() > TOOLTIP_MAX_NAME {
+        let head: String = filename.chars().take(TOOLTIP_MAX_NAME - 1).collect();
+        format!("{}…", head)
+    } else {
+        filename.to_string()
+    };
+    let size = crate::app::format_file_size(file_size);
+    match dimensions {
+        Some((width, height)) => format!("{} — {}×{} — {}", name, width, height, size),
+        None => format!("{} — {}", name, size),
+    }
+}
+
+/// Run the configured double-click action
+fn handle_double_click(
+    action: TrayDoubleClickAction,
+    settings: &Mutex<Settings>,
+    message_tx: &Sender<AppMessage>,
+) {
+    match action {
+        TrayDoubleClickAction::ShowWindow => {
+            crate::platform::current().show_window();
+            let _ = message_tx.send(AppMessage::ToggleWindow);
+        }
+        TrayDoubleClickAction::OpenLatest => {
+            let Some(latest_path) =
+                crate::get_latest_screenshot_filtered(&crate::LatestOpts::settled())
+            else {
+                debug!("No screenshots available to open");
+                return;
+            };
+            info!("Opening latest screenshot from tray: {:?}", latest_path);
+            let action = settings.lock().open_action;
+            crate::open_screenshot(&latest_path, action, message_tx);
+        }
+        TrayDoubleClickAction::OpenFolder => open_screenshot_folder(settings),
+    }
+}
+
+fn open_screenshot_folder(settings: &Mutex<Settings>) {
+    let dir = settings.lock().screenshot_directory.clone();
+    info!("Opening screenshot folder from tray: {:?}", dir);
+    if let Err(e) = open::that(&dir) {
+        log::warn!("Failed to open screenshot folder {:?}: {}", dir, e);
+    }
+}
+
+/// Open an Explorer window with `path` selected
+#[cfg(windows)]
+pub fn reveal_in_explorer(path: &std::path::Path) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // Explorer parses its own command line: `/select,` must be glued to the quoted path
+    let mut arg = std::ffi::OsString::from("/select,\"");
+    arg.push(path);
+    arg.push("\"");
+    match std::process::Command::new("explorer")
+        .raw_arg(arg)
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+    {
+        Ok(_) => true,
+        Err(e) => {
+            log::warn!("Failed to reveal {:?}: {}", path, e);
+            false
+        }
+    }
 }
 
 impl TrayManager {
-    pub fn new(message_tx: Sender<AppMessage>) -> Result<Self> {
+    pub fn new(message_tx: Sender<AppMessage>, settings: Arc<Mutex<Settings>>) -> Result<Self> {
         info!("Creating tray icon...");
 
+        let (autostart, always_on_top, icon_style) = {
+            let s = settings.lock();
+            (s.autostart, s.always_on_top, s.tray_icon_style)
+        };
+
         let menu = Menu::new();
         let settings_item = MenuItem::new(&t!("tray.menu.settings"), true, None);
         let check_updates_item = MenuItem::new(&t!("tray.menu.check_for_updates"), true, None);
+        let action_items: Vec<(TrayAction, MenuItem)> = {
+            let s = settings.lock();
+            tray_actions()
+                .into_iter()
+                .map(|action| {
+                    let available = (action.available)(&s);
+                    let item = MenuItem::with_id(action.id, t!(action.label_key), available, None);
+                    (action, item)
+                })
+                .collect()
+        };
+        let always_on_top_item =
+            CheckMenuItem::new(&t!("tray.menu.always_on_top"), true, always_on_top, None);
+        let autostart_item = CheckMenuItem::new(&t!("tray.menu.start_at_login"), true, autostart, None);
         let quit_item = MenuItem::new(&t!("tray.menu.quit"), true, None);
 
         menu.append_items(&[
             &settings_item,
             &check_updates_item,
             &PredefinedMenuItem::separator(),
+        ])?;
+        for (_, item) in &action_items {
+            menu.append(item)?;
+        }
+        menu.append_items(&[
+            &PredefinedMenuItem::separator(),
+            &always_on_top_item,
+            &autostart_item,
+            &PredefinedMenuItem::separator(),
             &quit_item,
         ])?;
 
-        let icon = Self::generate_camera_icon()?;
+        let icon_look = Self::icon_look(icon_style);
+        let icon = Self::generate_camera_icon(icon_look)?;
 
+        #[cfg(windows)]
+        let existing_windows = tray_icon_windows();
         let tray_icon = TrayIconBuilder::new()
             .with_menu(Box::new(menu))
             .with_tooltip(&t!("tray.tooltip"))
             .with_icon(icon)
             .with_menu_on_left_click(false)
             .build()?;
+        #[cfg(windows)]
+        remember_tray_icon(&tray_icon, &existing_windows);
 
         let menu_tx = message_tx.clone();
         let settings_id = settings_item.id().clone();
         let check_updates_id = check_updates_item.id().clone();
+        let actions: Vec<TrayAction> = action_items.iter().map(|(action, _)| *action).collect();
+        let always_on_top_id = always_on_top_item.id().clone();
+        let autostart_id = autostart_item.id().clone();
         let quit_id = quit_item.id().clone();
+        let menu_settings = Arc::clone(&settings);
 
         std::thread::spawn(move || {
             let menu_receiver = MenuEvent::receiver();
             loop {
                 if let Ok(event) = menu_receiver.recv() {
                     if event.id == settings_id {
-                        show_window();
-                        let _ = menu_tx.send(AppMessage::OpenSettings);
+                        crate::platform::current().show_window();
+                        let _ = menu_tx.send(AppMessage::OpenSettings(None));
                     } else if event.id == check_updates_id {
                         info!("Check for updates requested from tray menu");
-                        std::thread::spawn(|| {
-                            use crate::update_checker;
-                            info!("{}", rust_i18n::t!("notifications.update.checking"));
-
-                            match update_checker::check_for_updates() {
-                                Ok(has_update) => {
-                                    if has_update {
-                                        info!("{}", rust_i18n::t!("notifications.update.available"));
-                                        update_checker::open_releases_page();
-                                    } else {
-                                        info!("{}", rust_i18n::t!("notifications.update.up_to_date"));
-                                    }
-                                }
-                                Err(e) => {
-                                    log::warn!("Failed to check for updates: {}", e);
-                                    log::warn!("{}", rust_i18n::t!("notifications.update.check_failed"));
-                                }
-                            }
-                        });
+                        crate::update_checker::check_in_background(menu_tx.clone());
+                    } else if let Some(action) = actions.iter().find(|a| event.id == a.id) {
+                        // The item may have been clicked just before the settings changed
+                        let available = (action.available)(&menu_settings.lock());
+                        if available {
+                            (action.handler)(&menu_tx, &menu_settings);
+                        } else {
+                            debug!("Tray action {} is not available", action.id);
+                        }
+                    } else if event.id == always_on_top_id {
+                        // The UI owns the setting and resyncs the check mark
+                        let _ = menu_tx.send(AppMessage::ToggleAlwaysOnTop);
+                    } else if event.id == autostart_id {
+                        // The UI owns the setting and resyncs the check mark
+                        let _ = menu_tx.send(AppMessage::ToggleAutostart);
                     } else if event.id == quit_id {
                         info!("Quit requested from tray menu");
-                        std::process::exit(0);
+                        crate::session::request_quit();
                     }
                 }
             }
@@ -520,6 +1700,7 @@ impl TrayManager {
                             position,
                             ..
                         } => {
+                            crate::tray_preview::hide();
                             *TRAY_DRAG_START.lock() = Some((position.x, position.y));
                             TRAY_MOUSE_DOWN.store(true, Ordering::SeqCst);
                         }
@@ -531,15 +1712,29 @@ impl TrayManager {
                             if TRAY_MOUSE_DOWN.load(Ordering::SeqCst) {
                                 TRAY_MOUSE_DOWN.store(false, Ordering::SeqCst);
                                 *TRAY_DRAG_START.lock() = None;
-                                let was_shown = toggle_window();
-                                // If window was shown, send message to reset to main view
-                                if was_shown {
-                                    let _ = click_tx.send(AppMessage::ShowMainWindow);
+                                let action = settings.lock().tray_left_click_action;
+                                if action == TrayClickAction::Nothing {
+                                    continue;
                                 }
+                                // Wait out the double-click time so a double click can cancel this
+                                let generation = CLICK_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+                                let settings = Arc::clone(&settings);
+                                let click_tx = click_tx.clone();
+                                std::thread::spawn(move || {
+                                    std::thread::sleep(double_click_time());
+                                    if CLICK_GENERATION.load(Ordering::SeqCst) == generation {
+                                        handle_left_click(action, &settings, &click_tx);
+                                    } else {
+                                        debug!("Tray click superseded by a double click");
+                                    }
+                                });
                             }
                         }
                         TrayIconEvent::Move { position, .. } => {
-                            if TRAY_MOUSE_DOWN.load(Ordering::SeqCst) {
+                            // With dragging disabled, movement while pressed still ends as a click
+                            if TRAY_MOUSE_DOWN.load(Ordering::SeqCst)
+                                && TRAY_DRAG_ENABLED.load(Ordering::SeqCst)
+                            {
                                 if let Some((start_x, start_y)) = *TRAY_DRAG_START.lock() {
                                     let dx = position.x - start_x;
                                     let dy = position.y - start_y;
@@ -549,9 +1744,9 @@ impl TrayManager {
                                         TRAY_MOUSE_DOWN.store(false, Ordering::SeqCst);
                                         *TRAY_DRAG_START.lock() = None;
 
-                                        if let Some(latest_path) = crate::get_latest_screenshot() {
+                                        if let Some(latest_path) = crate::get_latest_screenshot_filtered(&crate::LatestOpts::settled()) {
                                             info!("Starting tray drag with: {:?}", latest_path);
-                                            crate::drag_drop::start_drag(&[latest_path]);
+                                            crate::platform::drag_files(&[latest_path], &click_tx);
                                         } else {
                                             debug!("No screenshots available for tray drag");
                                         }
@@ -559,14 +1754,22 @@ impl TrayManager {
                                 }
                             }
                         }
+                        TrayIconEvent::Enter { position, .. } => {
+                            if !TRAY_MOUSE_DOWN.load(Ordering::SeqCst) {
+                                crate::tray_preview::show((position.x as i32, position.y as i32));
+                            }
+                        }
                         TrayIconEvent::Leave { .. } => {
+                            crate::tray_preview::hide();
                             if TRAY_MOUSE_DOWN.load(Ordering::SeqCst) {
                                 TRAY_MOUSE_DOWN.store(false, Ordering::SeqCst);
                                 *TRAY_DRAG_START.lock() = None;
 
-                                if let Some(latest_path) = crate::get_latest_screenshot() {
+                                if !TRAY_DRAG_ENABLED.load(Ordering::SeqCst) {
+                                    debug!("Tray drag disabled, ignoring press that left the icon");
+                                } else if let Some(latest_path) = crate::get_latest_screenshot_filtered(&crate::LatestOpts::settled()) {
                                     info!("Starting tray drag (leave) with: {:?}", latest_path);
-                                    crate::drag_drop::start_drag(&[latest_path]);
+                                    crate::platform::drag_files(&[latest_path], &click_tx);
                                 }
                             }
                         }
@@ -576,8 +1779,10 @@ impl TrayManager {
                         } => {
                             TRAY_MOUSE_DOWN.store(false, Ordering::SeqCst);
                             *TRAY_DRAG_START.lock() = None;
-                            show_window();
-                            let _ = click_tx.send(AppMessage::ToggleWindow);
+                            // Cancel the single click still waiting from the first press
+                            CLICK_GENERATION.fetch_add(1, Ordering::SeqCst);
+                            let action = settings.lock().tray_double_click_action;
+                            handle_double_click(action, &settings, &click_tx);
                         }
                         _ => {}
                     }
@@ -588,60 +1793,248 @@ impl TrayManager {
         info!("Tray icon created successfully");
         Ok(Self {
             _tray_icon: tray_icon,
+            settings_item,
+            check_updates_item,
+            action_items,
+            always_on_top_item,
+            autostart_item,
+            quit_item,
+            icon_look,
+            latest_tooltip: None,
         })
     }
 
-    fn generate_camera_icon() -> Result<Icon> {
-        let size = 32u32;
-        let mut rgba = vec![0u8; (size * size * 4) as usize];
-
-        for y in 0..size {
-            for x in 0..size {
-                let idx = ((y * size + x) * 4) as usize;
-                let fx = x as f32 / size as f32;
-                let fy = y as f32 / size as f32;
-
-                let in_body = fx > 0.1 && fx < 0.9 && fy > 0.25 && fy < 0.85;
-                let cx = 0.5;
-                let cy = 0.55;
-                let r = 0.22;
-                let dist = ((fx - cx).powi(2) + (fy - cy).powi(2)).sqrt();
-                let in_lens = dist < r;
-                let in_lens_inner = dist < r * 0.6;
-                let in_flash = fx > 0.6 && fx < 0.8 && fy > 0.12 && fy < 0.28;
-
-                if in_lens_inner {
-                    rgba[idx] = 100;
-                    rgba[idx + 1] = 180;
-                    rgba[idx + 2] = 255;
-                    rgba[idx + 3] = 255;
-                } else if in_lens {
-                    rgba[idx] = 40;
-                    rgba[idx + 1] = 40;
-                    rgba[idx + 2] = 50;
-                    rgba[idx + 3] = 255;
-                } else if in_body || in_flash {
-                    rgba[idx] = 60;
-                    rgba[idx + 1] = 60;
-                    rgba[idx + 2] = 70;
-                    rgba[idx + 3] = 255;
-                } else {
-                    rgba[idx] = 0;
-                    rgba[idx + 1] = 0;
-                    rgba[idx + 2] = 0;
-                    rgba[idx + 3] = 0;
-                }
-            }
-        }
+    /// How the icon for `style` should be drawn right now
+    fn icon_look(style: TrayIconStyle) -> (TrayIconStyle, bool, u32) {
+        (style, style == TrayIconStyle::Mono && taskbar_is_light(), icon_size())
+    }
 
+    fn generate_camera_icon(
+        (style, light_taskbar, size): (TrayIconStyle, bool, u32),
+    ) -> Result<Icon> {
+        let rgba = cached_camera_icon(style, light_taskbar, size).to_vec();
         Icon::from_rgba(rgba, size, size)
             .map_err(|e| anyhow::anyhow!("Failed to create generated icon: {}", e))
     }
 
+    /// Switch the icon style, also picking up taskbar theme and DPI changes
+    pub fn set_icon_style(&mut self, style: TrayIconStyle) {
+        let look = Self::icon_look(style);
+        if self.icon_look == look {
+            return;
+        }
+        match Self::generate_camera_icon(look) {
+            Ok(icon) => {
+                if let Err(e) = self._tray_icon.set_icon(Some(icon)) {
+                    log::warn!("Failed to update tray icon: {}", e);
+                    return;
+                }
+                debug!("Tray icon changed to {:?}", look);
+                self.icon_look = look;
+            }
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+
     /// Update tray icon tooltip
     pub fn update_tooltip(&mut self, text: &str) {
         if let Err(e) = self._tray_icon.set_tooltip(Some(text)) {
             log::warn!("Failed to update tray tooltip: {}", e);
         }
     }
+
+    /// Summarize the latest screenshot in the tooltip, below the app name
+    pub fn set_latest_screenshot(&mut self, latest: Option<&crate::app::ScreenshotInfo>) {
+        self.latest_tooltip = latest.map(|info| {
+            // Only the header is read, so this stays cheap even for large files
+            let dimensions = image::image_dimensions(&info.path).ok();
+            latest_tooltip_line(&info.filename, dimensions, info.file_size)
+        });
+        let text = self.tooltip_text();
+        self.update_tooltip(&text);
+    }
+
+    fn tooltip_text(&self) -> String {
+        match &self.latest_tooltip {
+            Some(line) => format!("{}\n{}", t!("tray.tooltip"), line),
+            None => t!("tray.tooltip").to_string(),
+        }
+    }
+
+    /// Update the "Always on top" check mark
+    pub fn set_always_on_top_checked(&mut self, checked: bool) {
+        self.always_on_top_item.set_checked(checked);
+    }
+
+    /// Update the "Start at login" check mark
+    pub fn set_autostart_checked(&mut self, checked: bool) {
+        self.autostart_item.set_checked(checked);
+    }
+
+    /// Gray out the actions these settings don't support
+    pub fn update_action_availability(&mut self, settings: &Settings) {
+        for (action, item) in &self.action_items {
+            item.set_enabled((action.available)(settings));
+        }
+    }
+
+    /// Re-resolve menu labels and tooltip for the current locale
+    /// Call after `rust_i18n::set_locale` so the tray follows runtime language changes
+    pub fn refresh_labels(&mut self) {
+        self.settings_item.set_text(t!("tray.menu.settings"));
+        self.check_updates_item.set_text(t!("tray.menu.check_for_updates"));
+        for (action, item) in &self.action_items {
+            item.set_text(t!(action.label_key));
+        }
+        self.always_on_top_item.set_text(t!("tray.menu.always_on_top"));
+        self.autostart_item.set_text(t!("tray.menu.start_at_login"));
+        self.quit_item.set_text(t!("tray.menu.quit"));
+        let text = self.tooltip_text();
+        self.update_tooltip(&text);
+        debug!("Tray labels refreshed for locale: {}", rust_i18n::locale().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_tray_actions() {
+        let actions = tray_actions();
+        let ids: HashSet<_> = actions.iter().map(|a| a.id).collect();
+        assert_eq!(ids.len(), actions.len());
+
+        // Upload needs a destination
+        let upload = actions.iter().find(|a| a.id == "upload-latest").unwrap();
+        let mut settings = Settings::default();
+        assert!(!(upload.available)(&settings));
+        settings.imgur_client_id = "abc".to_string();
+        assert!((upload.available)(&settings));
+    }
+
+    #[test]
+    fn test_latest_tooltip_line() {
+        assert_eq!(
+            latest_tooltip_line("shot.png", Some((1920, 1080)), 342 * 1024),
+            "shot.png — 1920×1080 — 342 KiB"
+        );
+        assert_eq!(latest_tooltip_line("shot.heic", None, 100), "shot.heic — 100 B");
+
+        let long = "x".repeat(100);
+        let line = latest_tooltip_line(&long, Some((1, 1)), 1);
+        assert!(line.starts_with(&format!("{}…", "x".repeat(TOOLTIP_MAX_NAME - 1))));
+    }
+
+    #[test]
+    fn test_arrival_notifications() {
+        let paths = [PathBuf::from("a.png"), PathBuf::from("b.png"), PathBuf::from("c.png")];
+
+        assert_eq!(
+            arrival_notifications(&paths, true),
+            [("notifications.new_screenshot.other", "count", "3".to_string())]
+        );
+        assert_eq!(
+            arrival_notifications(&paths[..1], true),
+            [("notifications.new_screenshot.one", "file", "a.png".to_string())]
+        );
+
+        let individual = arrival_notifications(&paths, false);
+        assert_eq!(individual.len(), 3);
+        assert_eq!(individual[2].2, "c.png");
+    }
+
+    #[test]
+    fn test_tray_retry_delay() {
+        use std::time::Duration;
+
+        assert_eq!(tray_retry_delay(0), None);
+        assert_eq!(tray_retry_delay(1), Some(Duration::from_secs(1)));
+        assert_eq!(tray_retry_delay(3), Some(Duration::from_secs(4)));
+        assert_eq!(tray_retry_delay(TRAY_RETRY_ATTEMPTS), Some(TRAY_RETRY_MAX));
+        assert_eq!(tray_retry_delay(TRAY_RETRY_ATTEMPTS + 1), None);
+    }
+
+    #[test]
+    fn test_mono_icon() {
+        for (light, color) in [(true, [0, 0, 0, 255]), (false, [255, 255, 255, 255])] {
+            let rgba = camera_icon_rgba(32, TrayIconStyle::Mono, light);
+            assert!(rgba.chunks(4).all(|p| p == color || p[3] == 0));
+            assert!(rgba.chunks(4).any(|p| p == color));
+        }
+        // The colored icon ignores the taskbar theme
+        assert_eq!(
+            camera_icon_rgba(32, TrayIconStyle::Color, true),
+            camera_icon_rgba(32, TrayIconStyle::Color, false)
+        );
+    }
+
+    #[test]
+    fn test_cached_camera_icon() {
+        let first = cached_camera_icon(TrayIconStyle::Mono, true, 32);
+        assert!(std::ptr::eq(first, cached_camera_icon(TrayIconStyle::Mono, true, 32)));
+        assert_eq!(first, camera_icon_rgba(32, TrayIconStyle::Mono, true).as_slice());
+        assert_ne!(first, cached_camera_icon(TrayIconStyle::Mono, false, 32));
+
+        // Each size is drawn at that size, and color shares one entry for both themes
+        assert_eq!(cached_camera_icon(TrayIconStyle::Mono, true, 20).len(), 20 * 20 * 4);
+        assert!(std::ptr::eq(
+            cached_camera_icon(TrayIconStyle::Color, true, 24),
+            cached_camera_icon(TrayIconStyle::Color, false, 24)
+        ));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(
+            interpolate("Changed to %{language} (%{language})", &[("language", "English")]),
+            "Changed to English (English)"
+        );
+        assert_eq!(interpolate("Hi %{name}", &[]), "Hi %{name}");
+    }
+
+    #[test]
+    fn test_fill_wide() {
+        let mut buf = [0xFFFFu16; 4];
+        fill_wide(&mut buf, "ab");
+        assert_eq!(buf, [b'a' as u16, b'b' as u16, 0, 0xFFFF]);
+
+        // Truncated to leave room for the terminator
+        fill_wide(&mut buf, "abcdef");
+        assert_eq!(buf, [b'a' as u16, b'b' as u16, b'c' as u16, 0]);
+    }
+
+    #[test]
+    fn test_notification_kind_colors() {
+        assert_eq!(NotificationKind::Error.color(), 0xE81123);
+        assert_eq!(crate::accent::colorref(NotificationKind::Error.color()), 0x2311E8);
+        assert_ne!(NotificationKind::Success.glyph(), NotificationKind::Error.glyph());
+    }
+
+    #[test]
+    fn test_notification_origin() {
+        // Work area with a left-docked 60px taskbar
+        let work = (60, 0, 1860, 1080);
+        let size = (400, 140);
+        assert_eq!(notification_origin(NotificationCorner::TopLeft, work, size, 15), (75, 15));
+        assert_eq!(notification_origin(NotificationCorner::TopRight, work, size, 15), (1505, 15));
+        assert_eq!(notification_origin(NotificationCorner::BottomLeft, work, size, 15), (75, 925));
+        assert_eq!(
+            notification_origin(NotificationCorner::BottomRight, work, size, 15),
+            (1505, 925)
+        );
+    }
+
+    #[test]
+    fn test_fit_in_work_area() {
+        // Fits: centered, size unchanged
+        assert_eq!(fit_in_work_area((800, 600), (0, 0, 1920, 1040)), (560, 220, 800, 600));
+        // Secondary monitor to the left
+        assert_eq!(fit_in_work_area((800, 600), (-1280, 0, 1280, 984)), (-1040, 192, 800, 600));
+        // Larger than the work area: shrunk and pinned to the top-left corner
+        assert_eq!(fit_in_work_area((1600, 1000), (0, 0, 1366, 728)), (0, 0, 1366, 728));
+        assert_eq!(fit_in_work_area((1600, 500), (100, 40, 1366, 728)), (100, 154, 1366, 500));
+    }
 }
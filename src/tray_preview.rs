@@ -0,0 +1,320 @@
+//! Thumbnail of the latest screenshot shown while hovering the tray icon
+//!
+//! Uses the same kind of layered popup as the tray notifications, on its own thread.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(windows)]
+use crate::thumbnail::ThumbnailCache;
+#[cfg(windows)]
+use std::sync::LazyLock;
+#[cfg(windows)]
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM};
+
+/// Longest side of the preview image at 100% scaling
+#[cfg(windows)]
+const PREVIEW_SIZE: u32 = 220;
+
+/// Padding around the image and gap to the cursor at 100% scaling
+#[cfg(windows)]
+const PREVIEW_PADDING: f32 = 8.0;
+#[cfg(windows)]
+const CURSOR_GAP: f32 = 16.0;
+
+/// Whether the cursor is still over the tray icon; a preview created after it left closes itself
+static WANTED: AtomicBool = AtomicBool::new(false);
+
+/// The open preview window, if any
+static PREVIEW_HWND: Mutex<Option<isize>> = Mutex::new(None);
+
+/// Only the latest few screenshots are ever previewed
+#[cfg(windows)]
+static THUMBNAILS: LazyLock<ThumbnailCache> = LazyLock::new(|| ThumbnailCache::new(4));
+
+#[cfg(windows)]
+static PREVIEW_CLASS: std::sync::Once = std::sync::Once::new();
+
+/// Image data kept with the window for painting
+#[cfg(windows)]
+struct PreviewData {
+    /// Top-down BGRA rows
+    pixels: Vec<u8>,
+    width: i32,
+    height: i32,
+    padding: i32,
+}
+
+/// Show the latest screenshot near `cursor` (physical pixels); does nothing without one
+#[cfg(windows)]
+pub fn show(cursor: (i32, i32)) {
+    use windows::Win32::UI::HiDpi::GetDpiForSystem;
+
+    if WANTED.swap(true, Ordering::SeqCst) || PREVIEW_HWND.lock().is_some() {
+        return;
+    }
+    let Some(latest) = crate::get_latest_screenshot_filtered(&crate::LatestOpts::settled()) else {
+        log::debug!("No screenshot to preview");
+        return;
+    };
+
+    std::thread::spawn(move || {
+        let scale = unsafe { GetDpiForSystem() } as f32 / 96.0;
+        let Some(thumbnail) = THUMBNAILS.get_scaled(&latest, PREVIEW_SIZE, scale) else {
+            return;
+        };
+        let pixels = thumbnail
+            .pixels()
+            .flat_map(|p| [p[2], p[1], p[0], p[3]])
+            .collect();
+        let data = PreviewData {
+            pixels,
+            width: thumbnail.width() as i32,
+            height: thumbnail.height() as i32,
+            padding: (PREVIEW_PADDING * scale) as i32,
+        };
+        run_window(data, cursor, (CURSOR_GAP * scale) as i32);
+    });
+}
+
+#[cfg(not(windows))]
+pub fn show(_cursor: (i32, i32)) {
+    WANTED.store(true, Ordering::SeqCst);
+}
+
+/// Close the preview, if one is open or about to open
+#[cfg(windows)]
+pub fn hide() {
+    use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_CLOSE};
+
+    WANTED.store(false, Ordering::SeqCst);
+    if let Some(hwnd) = PREVIEW_HWND.lock().take() {
+        unsafe {
+            let _ = PostMessageW(
+                HWND(hwnd as *mut std::ffi::c_void),
+                WM_CLOSE,
+                WPARAM(0),
+                LPARAM(0),
+            );
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn hide() {
+    WANTED.store(false, Ordering::SeqCst);
+    PREVIEW_HWND.lock().take();
+}
+
+/// Create the popup and pump its messages until it is closed
+#[cfg(windows)]
+fn run_window(data: PreviewData, cursor: (i32, i32), gap: i32) {
+    use windows::core::w;
+    use windows::Win32::Foundation::{COLORREF, POINT};
+    use windows::Win32::Graphics::Gdi::{
+        CreateSolidBrush, GetMonitorInfoW, MonitorFromPoint, MONITORINFO,
+        MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    unsafe {
+        let class_name = w!("SukushoTrayPreviewClass");
+        let Ok(hinstance) = GetModuleHandleW(None) else {
+            return;
+        };
+
+        PREVIEW_CLASS.call_once(|| {
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(preview_wndproc),
+                hInstance: hinstance.into(),
+                lpszClassName: class_name,
+                hbrBackground: CreateSolidBrush(COLORREF(0x00262626)),
+                ..Default::default()
+            };
+            if RegisterClassW(&wc) == 0 {
+                log::warn!(
+                    "Failed to register tray preview window class: {}",
+                    windows::core::Error::from_win32()
+                );
+            }
+        });
+
+        let mut monitor_info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        let monitor = MonitorFromPoint(POINT { x: cursor.0, y: cursor.1 }, MONITOR_DEFAULTTONEAREST);
+        if !GetMonitorInfoW(monitor, &mut monitor_info).as_bool() {
+            return;
+        }
+        let work = monitor_info.rcWork;
+
+        let size = (data.width + data.padding * 2, data.height + data.padding * 2);
+        let (x, y) = preview_origin(
+            cursor,
+            size,
+            (work.left, work.top, work.right - work.left, work.bottom - work.top),
+            gap,
+        );
+
+        let hwnd = match CreateWindowExW(
+            WS_EX_LAYERED | WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+            class_name,
+            w!("Sukusho Preview"),
+            WS_POPUP,
+            x,
+            y,
+            size.0,
+            size.1,
+            None,
+            None,
+            hinstance,
+            None,
+        ) {
+            Ok(hwnd) => hwnd,
+            Err(e) => {
+                log::warn!("Failed to create tray preview window: {}", e);
+                return;
+            }
+        };
+        SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(Box::new(data)) as isize);
+
+        {
+            // The cursor may have left while the thumbnail loaded, or come back and
+            // started another preview
+            let mut preview = PREVIEW_HWND.lock();
+            if !WANTED.load(Ordering::SeqCst) || preview.is_some() {
+                let _ = DestroyWindow(hwnd);
+                return;
+            }
+            *preview = Some(hwnd.0 as isize);
+        }
+
+        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 240, LWA_ALPHA);
+        let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn preview_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    use windows::Win32::Foundation::COLORREF;
+    use windows::Win32::Graphics::Gdi::{
+        BeginPaint, CreateSolidBrush, DeleteObject, EndPaint, FrameRect, SetDIBitsToDevice,
+        BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, PAINTSTRUCT,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    unsafe {
+        match msg {
+            WM_PAINT => {
+                let mut ps = PAINTSTRUCT::default();
+                let hdc = BeginPaint(hwnd, &mut ps);
+
+                let user_data = GetWindowLongPtrW(hwnd, GWLP_USERDATA);
+                if user_data != 0 {
+                    let data = &*(user_data as *const PreviewData);
+                    let info = BITMAPINFO {
+                        bmiHeader: BITMAPINFOHEADER {
+                            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                            biWidth: data.width,
+                            // Negative height: rows are top-down
+                            biHeight: -data.height,
+                            biPlanes: 1,
+                            biBitCount: 32,
+                            biCompression: BI_RGB.0,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    };
+                    SetDIBitsToDevice(
+                        hdc,
+                        data.padding,
+                        data.padding,
+                        data.width as u32,
+                        data.height as u32,
+                        0,
+                        0,
+                        0,
+                        data.height as u32,
+                        data.pixels.as_ptr().cast(),
+                        &info,
+                        DIB_RGB_COLORS,
+                    );
+
+                    let mut rect = RECT::default();
+                    let _ = GetClientRect(hwnd, &mut rect);
+                    let border_brush = CreateSolidBrush(COLORREF(0x00404040));
+                    let _ = FrameRect(hdc, &rect, border_brush);
+                    let _ = DeleteObject(border_brush);
+                }
+
+                let _ = EndPaint(hwnd, &ps);
+                LRESULT(0)
+            }
+            WM_CLOSE => {
+                let _ = DestroyWindow(hwnd);
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                let user_data = GetWindowLongPtrW(hwnd, GWLP_USERDATA);
+                if user_data != 0 {
+                    SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0);
+                    drop(Box::from_raw(user_data as *mut PreviewData));
+                }
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+}
+
+/// Top-left corner for a preview of `size` near `cursor`, inside the work area
+/// `(left, top, width, height)`
+///
+/// The preview sits above the cursor, or below it when the taskbar is at the top.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn preview_origin(
+    cursor: (i32, i32),
+    size: (i32, i32),
+    work: (i32, i32, i32, i32),
+    gap: i32,
+) -> (i32, i32) {
+    let (left, top, width, height) = work;
+    let x = (cursor.0 - size.0 / 2).clamp(left, (left + width - size.0).max(left));
+    let above = cursor.1 - gap - size.1;
+    let y = if above >= top { above } else { cursor.1 + gap };
+    (x, y.clamp(top, (top + height - size.1).max(top)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_origin() {
+        let work = (0, 0, 1920, 1040);
+
+        // Bottom taskbar: above the cursor, centered on it
+        assert_eq!(preview_origin((1000, 1060), (200, 100), work, 10), (900, 940));
+
+        // Near the right edge: pulled back inside
+        assert_eq!(preview_origin((1910, 1060), (200, 100), work, 10), (1720, 940));
+
+        // Top taskbar: below the cursor
+        let work = (0, 40, 1920, 1040);
+        assert_eq!(preview_origin((1000, 20), (200, 100), work, 10), (900, 40));
+    }
+}
@@ -6,15 +6,16 @@ use gpui::*;
 use rust_i18n::t;
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::ActiveTheme;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex as StdMutex};
+use std::sync::{Arc, LazyLock, Mutex as StdMutex};
 use std::time::{Instant, SystemTime};
 
 use crate::app::{format_file_size, GalleryAction, ScreenshotInfo, Sukusho};
 use crate::drag_drop;
-use crate::thumbnail::ThumbnailCache;
+use crate::thumbnail::{Placeholder, ThumbnailCache, ThumbnailError};
+use crate::AppMessage;
 
 /// Flag to track if a gallery item was clicked (to prevent background deselection)
 static ITEM_CLICKED: AtomicBool = AtomicBool::new(false);
@@ -25,6 +26,120 @@ static LAST_CLICK: StdMutex<Option<(Instant, PathBuf)>> = StdMutex::new(None);
 /// Double-click time threshold in milliseconds
 const DOUBLE_CLICK_TIME_MS: u128 = 500;
 
+/// Thumbnails from `ThumbnailCache` for items gpui can't decode itself (e.g. HEIC through the
+/// Windows codec), keyed like the cache by path and physical size
+static CACHED_THUMBNAILS: LazyLock<StdMutex<HashMap<(PathBuf, u32), CachedThumbnail>>> =
+    LazyLock::new(Default::default);
+
+#[derive(Clone)]
+enum CachedThumbnail {
+    /// Still being generated in the background
+    Loading,
+    Ready(Arc<Image>),
+    /// The file itself can't be shown
+    Broken(Placeholder),
+    /// Nothing to show right now (e.g. the file is still being written)
+    Unavailable,
+}
+
+/// Look up the `ThumbnailCache` thumbnail for `path`, generating it in the background the first
+/// time; `ThumbnailReady` asks for a redraw once it's done
+fn cached_thumbnail(
+    path: &Path,
+    size: u32,
+    cache: &Arc<ThumbnailCache>,
+    tx: &crossbeam_channel::Sender<AppMessage>,
+) -> CachedThumbnail {
+    let key = (path.to_path_buf(), size);
+    let mut thumbnails = CACHED_THUMBNAILS.lock().unwrap();
+    if let Some(thumbnail) = thumbnails.get(&key) {
+        return thumbnail.clone();
+    }
+    thumbnails.insert(key.clone(), CachedThumbnail::Loading);
+    drop(thumbnails);
+
+    let tx = tx.clone();
+    cache.request(path.to_path_buf(), size, move |result| {
+        let thumbnail = match result {
+            Ok(rgba) => {
+                let mut png = Vec::new();
+                match rgba.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png) {
+                    Ok(()) => {
+                        CachedThumbnail::Ready(Arc::new(Image::from_bytes(ImageFormat::Png, png)))
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to encode thumbnail for {:?}: {}", key.0, e);
+                        CachedThumbnail::Unavailable
+                    }
+                }
+            }
+            // Removed from the gallery; `forget_thumbnails` already dropped the entry
+            Err(ThumbnailError::Cancelled(_)) => return,
+            Err(e) if e.is_broken_file() => {
+                log::debug!("Showing a placeholder for {:?}: {}", key.0, e);
+                CachedThumbnail::Broken(e.placeholder().unwrap_or(Placeholder::Corrupt))
+            }
+            Err(e) => {
+                log::debug!("No thumbnail for {:?}: {}", key.0, e);
+                CachedThumbnail::Unavailable
+            }
+        };
+        CACHED_THUMBNAILS.lock().unwrap().insert(key, thumbnail);
+        let _ = tx.send(AppMessage::ThumbnailReady);
+    });
+    CachedThumbnail::Loading
+}
+
+/// Drop the gallery's thumbnails for `path`, e.g. after it was removed or replaced
+pub fn forget_thumbnails(path: &Path) {
+    CACHED_THUMBNAILS.lock().unwrap().retain(|(cached, _), _| cached != path);
+}
+
+/// Drop every gallery thumbnail, e.g. after switching folders
+pub fn clear_thumbnails() {
+    CACHED_THUMBNAILS.lock().unwrap().clear();
+}
+
+/// What to draw for a thumbnail that isn't ready or can't be shown
+fn thumbnail_placeholder(
+    thumbnail: &CachedThumbnail,
+    extension: SharedString,
+    color: Hsla,
+) -> AnyElement {
+    let reason = match thumbnail {
+        CachedThumbnail::Loading => Some(t!("gallery.placeholder.loading").to_string()),
+        CachedThumbnail::Broken(placeholder) => Some(placeholder.label()),
+        CachedThumbnail::Ready(_) | CachedThumbnail::Unavailable => None,
+    };
+    div()
+        .size_full()
+        .flex()
+        .flex_col()
+        .items_center()
+        .justify_center()
+        .text_sm()
+        .text_color(color)
+        .child(extension)
+        .children(reason.map(|reason| div().text_xs().child(reason)))
+        .into_any_element()
+}
+
+/// Draw `thumbnail`, or its placeholder until it's ready
+fn cached_thumbnail_element(
+    thumbnail: CachedThumbnail,
+    extension: SharedString,
+    color: Hsla,
+) -> AnyElement {
+    match thumbnail {
+        CachedThumbnail::Ready(image) => img(image)
+            .max_w_full()
+            .max_h_full()
+            .object_fit(ObjectFit::Contain)
+            .into_any_element(),
+        thumbnail => thumbnail_placeholder(&thumbnail, extension, color),
+    }
+}
+
 /// Date group category
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum DateGroup {
@@ -103,11 +218,18 @@ fn group_by_date(screenshots: &[ScreenshotInfo]) -> Vec<(DateGroup, Vec<&Screens
 struct GalleryItemData {
     path: PathBuf,
     is_selected: bool,
+    is_pinned: bool,
+    is_shared: bool,
     selected_paths: Vec<PathBuf>,
     size: u32,
     index: usize,
     file_size: u64,
+    modified: SystemTime,
     extension: String,
+    source_app: Option<String>,
+    /// Physical pixel size requested from `ThumbnailCache`
+    thumbnail_px: u32,
+    thumbnail_cache: Arc<ThumbnailCache>,
 }
 
 /// Build a gallery grid component with date grouping
@@ -115,15 +237,21 @@ pub fn gallery(
     screenshots: Vec<ScreenshotInfo>,
     filtered_paths: Option<Vec<PathBuf>>,
     selected: HashSet<PathBuf>,
-    _thumbnail_cache: Arc<ThumbnailCache>,
+    pinned: HashSet<PathBuf>,
+    shared: HashSet<PathBuf>,
+    thumbnail_cache: Arc<ThumbnailCache>,
     _columns: u32,
     thumbnail_size: u32,
+    scale_factor: f32,
     has_more: bool,
+    loading: bool,
     cx: &mut Context<Sukusho>,
 ) -> impl IntoElement {
     let spacing = 8.0;
+    let thumbnail_px = crate::thumbnail::physical_size(thumbnail_size, scale_factor);
 
     // Filter screenshots if search is active
+    let searching = filtered_paths.is_some();
     let visible_screenshots = if let Some(filter) = filtered_paths {
         let filter_set: HashSet<_> = filter.into_iter().collect();
         screenshots
@@ -143,7 +271,13 @@ pub fn gallery(
             .child(
                 div()
                     .text_color(cx.theme().muted_foreground)
-                    .child(t!("app.empty_state").to_string()),
+                    .child(if loading {
+                        t!("app.loading_state").to_string()
+                    } else if searching {
+                        t!("app.search.no_results").to_string()
+                    } else {
+                        t!("app.empty_state").to_string()
+                    }),
             )
             .into_any_element();
     }
@@ -192,11 +326,17 @@ pub fn gallery(
             let data = GalleryItemData {
                 path: info.path.clone(),
                 is_selected,
+                is_pinned: pinned.contains(&info.path),
+                is_shared: shared.contains(&info.path),
                 selected_paths,
                 size: thumbnail_size,
                 index: global_index,
                 file_size: info.file_size,
+                modified: info.modified,
                 extension: info.extension.clone(),
+                source_app: info.source_app.clone(),
+                thumbnail_px,
+                thumbnail_cache: Arc::clone(&thumbnail_cache),
             };
             group_items.push(gallery_item(data, cx).into_any_element());
             global_index += 1;
@@ -302,7 +442,43 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
     let hover_border = cx.theme().primary;
     let hover_bg = cx.theme().muted;
 
-    let file_badge = format!("{} | {}", data.extension, format_file_size(data.file_size));
+    let age = crate::i18n_helpers::format_relative_time(data.modified);
+    let file_badge = match &data.source_app {
+        Some(app) => format!("{} | {} | {} | {}", app, age, data.extension, format_file_size(data.file_size)),
+        None => format!("{} | {} | {}", age, data.extension, format_file_size(data.file_size)),
+    };
+
+    // gpui can't decode HEIC/HEIF at all, so those go straight to `ThumbnailCache` (the Windows
+    // codec); other formats only fall back to it when gpui fails
+    let extension = SharedString::from(data.extension.to_uppercase());
+    let placeholder_color = cx.theme().muted_foreground;
+    let message_tx = cx.global::<crate::AppState>().message_tx.clone();
+    let thumbnail_cache = data.thumbnail_cache;
+    let thumbnail_px = data.thumbnail_px;
+    let image = if crate::thumbnail::is_heif(&path) {
+        let thumbnail = cached_thumbnail(&path, thumbnail_px, &thumbnail_cache, &message_tx);
+        cached_thumbnail_element(thumbnail, extension, placeholder_color)
+    } else {
+        let fallback_path = path.clone();
+        let loading_extension = extension.clone();
+        img(path.clone())
+            .max_w_full()
+            .max_h_full()
+            .object_fit(ObjectFit::Contain)
+            .with_loading(move || {
+                thumbnail_placeholder(
+                    &CachedThumbnail::Loading,
+                    loading_extension.clone(),
+                    placeholder_color,
+                )
+            })
+            .with_fallback(move || {
+                let thumbnail =
+                    cached_thumbnail(&fallback_path, thumbnail_px, &thumbnail_cache, &message_tx);
+                cached_thumbnail_element(thumbnail, extension.clone(), placeholder_color)
+            })
+            .into_any_element()
+    };
 
     // Badge colors - semi-transparent black with white text for good contrast
     let badge_bg = gpui::hsla(0.0, 0.0, 0.0, 0.75);
@@ -349,12 +525,7 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
                         .flex()
                         .items_center()
                         .justify_center()
-                        .child(
-                            img(path.clone())
-                                .max_w_full()
-                                .max_h_full()
-                                .object_fit(ObjectFit::Contain),
-                        ),
+                        .child(image),
                 )
                 // Selection checkbox - always visible (circular design)
                 .child(
@@ -405,6 +576,29 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
                             }),
                         ),
                 )
+                // Pinned (excluded from automatic cleanup) and shared (dropped into another
+                // app since launch) markers
+                .when(data.is_pinned || data.is_shared, |this| {
+                    let marker = |icon: &'static str| {
+                        div()
+                            .px(px(6.0))
+                            .py(px(2.0))
+                            .rounded(px(6.0))
+                            .bg(badge_bg)
+                            .text_xs()
+                            .child(icon)
+                    };
+                    this.child(
+                        div()
+                            .absolute()
+                            .top(px(6.0))
+                            .right(px(6.0))
+                            .flex()
+                            .gap(px(4.0))
+                            .when(data.is_shared, |row| row.child(marker("📤")))
+                            .when(data.is_pinned, |row| row.child(marker("📌"))),
+                    )
+                })
                 .child(
                     // File format and size badge - enhanced styling
                     div()
@@ -496,7 +690,8 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
                             "DragDetect returned true, starting native OLE drag with {} files",
                             drag_paths.len()
                         );
-                        drag_drop::start_drag(&drag_paths);
+                        let message_tx = cx.global::<crate::AppState>().message_tx.clone();
+                        crate::platform::drag_files(&drag_paths, &message_tx);
                     } else {
                         // User just clicked without dragging - treat as selection
                         log::debug!("DragDetect returned false, treating as click");
@@ -519,25 +714,46 @@ fn gallery_item(data: GalleryItemData, cx: &mut Context<Sukusho>) -> impl IntoEl
         )
 }
 
+/// App commands appended below the shell context menu items
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub enum ShellMenuCommand {
+    CopyText,
+    CopyDataUri,
+    Upload,
+    TogglePin,
+}
+
+/// Menu command IDs for our own items, outside the shell's ID range
+#[cfg(windows)]
+const COPY_TEXT_COMMAND: usize = 0x8000;
+#[cfg(windows)]
+const UPLOAD_COMMAND: usize = 0x8001;
+#[cfg(windows)]
+const TOGGLE_PIN_COMMAND: usize = 0x8002;
+#[cfg(windows)]
+const COPY_DATA_URI_COMMAND: usize = 0x8003;
+
 /// Show Windows shell context menu for multiple files
+/// Returns the app command if one of our own items was chosen
 #[cfg(windows)]
-pub fn show_shell_context_menu(paths: &[PathBuf]) {
-    use crate::tray::WINDOW_HWND;
+pub fn show_shell_context_menu(paths: &[PathBuf]) -> Option<ShellMenuCommand> {
     use log::{debug, error, info};
     use std::ffi::OsStr;
     use std::os::windows::ffi::OsStrExt;
     use windows::core::PCWSTR;
-    use windows::Win32::Foundation::{HWND, POINT};
+    use windows::Win32::Foundation::POINT;
     use windows::Win32::UI::Shell::{
         BHID_SFUIObject, IContextMenu, IShellItem, SHCreateItemFromParsingName, CMINVOKECOMMANDINFO,
     };
     use windows::Win32::UI::WindowsAndMessaging::{
-        CreatePopupMenu, DestroyMenu, GetCursorPos, PostMessageW, SetForegroundWindow,
-        TrackPopupMenu, TPM_LEFTALIGN, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_NULL,
+        AppendMenuW, CreatePopupMenu, DestroyMenu, GetCursorPos, PostMessageW,
+        SetForegroundWindow, TrackPopupMenu, MF_SEPARATOR, MF_STRING, TPM_LEFTALIGN,
+        TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_NULL,
     };
 
     if paths.is_empty() {
-        return;
+        return None;
     }
 
     info!("Opening context menu for {} files", paths.len());
@@ -546,15 +762,15 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
     let valid_paths: Vec<_> = paths.iter().filter(|p| p.exists()).collect();
     if valid_paths.is_empty() {
         error!("No valid paths for context menu");
-        return;
+        return None;
     }
 
     // Get window handle
-    let hwnd = match *WINDOW_HWND.lock() {
-        Some(h) => HWND(h as *mut std::ffi::c_void),
+    let hwnd = match crate::tray::window_handle() {
+        Some(handle) => handle.hwnd(),
         None => {
             error!("No window handle available for context menu");
-            return;
+            return None;
         }
     };
 
@@ -580,7 +796,7 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
 
         if shell_items.is_empty() {
             error!("No shell items created");
-            return;
+            return None;
         }
 
         info!("Created {} shell items for context menu", shell_items.len());
@@ -592,7 +808,7 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
             Ok(cm) => cm,
             Err(e) => {
                 error!("Failed to get context menu: {:?}", e);
-                return;
+                return None;
             }
         };
 
@@ -603,7 +819,7 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
             Ok(m) => m,
             Err(e) => {
                 error!("Failed to create popup menu: {:?}", e);
-                return;
+                return None;
             }
         };
 
@@ -617,7 +833,19 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
         ) {
             error!("Failed to query context menu: {:?}", e);
             let _ = DestroyMenu(hmenu);
-            return;
+            return None;
+        }
+
+        // Our own entry below the shell items
+        let _ = AppendMenuW(hmenu, MF_SEPARATOR, 0, PCWSTR::null());
+        for (id, key) in [
+            (COPY_TEXT_COMMAND, "gallery.context_menu.copy_text"),
+            (COPY_DATA_URI_COMMAND, "gallery.context_menu.copy_data_uri"),
+            (UPLOAD_COMMAND, "gallery.context_menu.upload"),
+            (TOGGLE_PIN_COMMAND, "gallery.context_menu.toggle_pin"),
+        ] {
+            let label: Vec<u16> = t!(key).encode_utf16().chain(std::iter::once(0)).collect();
+            let _ = AppendMenuW(hmenu, MF_STRING, id, PCWSTR(label.as_ptr()));
         }
 
         // Get cursor position
@@ -639,6 +867,18 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
         // Post WM_NULL to clear menu state
         let _ = PostMessageW(hwnd, WM_NULL, None, None);
 
+        let app_command = match cmd.0 as usize {
+            COPY_TEXT_COMMAND => Some(ShellMenuCommand::CopyText),
+            COPY_DATA_URI_COMMAND => Some(ShellMenuCommand::CopyDataUri),
+            UPLOAD_COMMAND => Some(ShellMenuCommand::Upload),
+            TOGGLE_PIN_COMMAND => Some(ShellMenuCommand::TogglePin),
+            _ => None,
+        };
+        if app_command.is_some() {
+            let _ = DestroyMenu(hmenu);
+            return app_command;
+        }
+
         if cmd.0 != 0 {
             let mut invoke_info = CMINVOKECOMMANDINFO {
                 cbSize: std::mem::size_of::<CMINVOKECOMMANDINFO>() as u32,
@@ -656,9 +896,11 @@ pub fn show_shell_context_menu(paths: &[PathBuf]) {
 
         let _ = DestroyMenu(hmenu);
     }
+    None
 }
 
 #[cfg(not(windows))]
-pub fn show_shell_context_menu(_paths: &[PathBuf]) {
+pub fn show_shell_context_menu(_paths: &[PathBuf]) -> Option<ShellMenuCommand> {
     // Not implemented for non-Windows
+    None
 }
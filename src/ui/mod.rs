@@ -3,7 +3,7 @@
 mod gallery;
 pub mod toast;
 
-pub use gallery::gallery;
+pub use gallery::{clear_thumbnails, forget_thumbnails, gallery};
 #[cfg(windows)]
-pub use gallery::show_shell_context_menu;
+pub use gallery::{show_shell_context_menu, ShellMenuCommand};
 pub use toast::ToastManager;
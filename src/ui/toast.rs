@@ -12,12 +12,12 @@ pub struct Toast {
 }
 
 impl Toast {
-    pub fn new(id: usize, message: String) -> Self {
+    pub fn new(id: usize, message: String, duration: Duration) -> Self {
         Self {
             id,
             message,
             created_at: Instant::now(),
-            duration: Duration::from_secs(3),
+            duration,
         }
     }
 
@@ -29,18 +29,25 @@ impl Toast {
 pub struct ToastManager {
     toasts: Vec<Toast>,
     next_id: usize,
+    duration: Duration,
 }
 
 impl ToastManager {
-    pub fn new() -> Self {
+    pub fn new(duration: Duration) -> Self {
         Self {
             toasts: Vec::new(),
             next_id: 0,
+            duration,
         }
     }
 
+    /// Change how long newly shown toasts stay visible
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
     pub fn show(&mut self, message: String) {
-        let toast = Toast::new(self.next_id, message);
+        let toast = Toast::new(self.next_id, message, self.duration);
         self.next_id += 1;
         self.toasts.push(toast);
     }
@@ -1,52 +1,263 @@
 //! Update checker using GitHub Releases API
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use crossbeam_channel::Sender;
 use log::{debug, info, warn};
+use parking_lot::Mutex;
 use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use crate::AppMessage;
 
 const GITHUB_API_URL: &str = "https://api.github.com/repos/ssut/sukusho/releases/latest";
 const RELEASES_PAGE_URL: &str = "https://github.com/ssut/sukusho/releases";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Attempts made by `check_for_updates_with_retry`
+const RETRY_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubles for each one after
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+
+/// Delay before the first periodic check, so startup isn't slowed down by it
+const PERIODIC_FIRST_DELAY: Duration = Duration::from_secs(60);
+
+/// Versions periodic checks have already announced since startup
+static NOTIFIED_VERSIONS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
 #[derive(Debug, Deserialize)]
 struct GitHubRelease {
     tag_name: String,
-    #[allow(dead_code)]
     html_url: String,
+    #[serde(default)]
+    prerelease: bool,
+}
+
+/// The latest release compared with the running version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateInfo {
+    /// Release version without the `v` prefix
+    pub latest_version: String,
+    /// Release page on GitHub
+    #[allow(dead_code)]
+    pub html_url: String,
+    /// Whether the release is newer than the running version (never for pre-releases)
+    pub has_update: bool,
+}
+
+/// GitHub answered with a non-success status
+#[derive(Debug)]
+struct HttpStatusError(reqwest::StatusCode);
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to fetch release information ({})", self.0)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// Like `check_for_updates`, retrying transient failures with exponential backoff
+pub fn check_for_updates_with_retry() -> Result<bool> {
+    with_retry(RETRY_ATTEMPTS, check_for_updates, std::thread::sleep)
 }
 
 /// Check for updates from GitHub Releases
 /// Returns true if a new version is available
 pub fn check_for_updates() -> Result<bool> {
-    info!("Checking for updates...");
-    debug!("Current version: {}", CURRENT_VERSION);
+    Ok(latest_release()?.has_update)
+}
 
-    // Make request to GitHub API
+/// The latest release on GitHub compared with the running version
+pub fn latest_release() -> Result<UpdateInfo> {
     let client = reqwest::blocking::Client::builder()
         .user_agent(format!("sukusho/{}", CURRENT_VERSION))
         .timeout(std::time::Duration::from_secs(10))
         .build()?;
 
-    let response = client.get(GITHUB_API_URL).send()?;
+    check_with_url(GITHUB_API_URL, &client)
+}
+
+/// Fetch the latest release from `url` (GitHub's "latest release" JSON) with `client`
+pub fn check_with_url(url: &str, client: &reqwest::blocking::Client) -> Result<UpdateInfo> {
+    info!("Checking for updates...");
+    debug!("Current version: {}", CURRENT_VERSION);
+
+    let response = client.get(url).send()?;
 
     if !response.status().is_success() {
         warn!("GitHub API returned status: {}", response.status());
-        anyhow::bail!("Failed to fetch release information");
+        return Err(HttpStatusError(response.status()).into());
+    }
+
+    let update = parse_release(&response.text()?)?;
+    if update.has_update {
+        info!("New version available: {} -> {}", CURRENT_VERSION, update.latest_version);
+    } else {
+        info!("Already on the latest version");
+    }
+
+    Ok(update)
+}
+
+/// Run `attempt` up to `attempts` times, sleeping between tries while errors are retryable
+fn with_retry<T>(
+    attempts: u32,
+    mut attempt: impl FnMut() -> Result<T>,
+    mut sleep: impl FnMut(Duration),
+) -> Result<T> {
+    let mut tries = 1;
+    loop {
+        match attempt() {
+            Err(e) if tries < attempts && is_retryable(&e) => {
+                let delay = backoff_delay(tries - 1, jitter());
+                debug!(
+                    "Update check attempt {} failed ({}), retrying in {:?}",
+                    tries, e, delay
+                );
+                sleep(delay);
+                tries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Network errors, timeouts, rate limits and server errors may go away on their own
+fn is_retryable(error: &anyhow::Error) -> bool {
+    use reqwest::StatusCode;
+
+    if let Some(HttpStatusError(status)) = error.downcast_ref::<HttpStatusError>() {
+        return status.is_server_error()
+            || *status == StatusCode::TOO_MANY_REQUESTS
+            || *status == StatusCode::REQUEST_TIMEOUT;
     }
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_timeout() || e.is_connect() || e.is_request())
+}
+
+/// Delay before retry number `retry` (0-based); `jitter` in 0..1 picks a point in the
+/// upper half of the exponential step so simultaneous clients spread out
+fn backoff_delay(retry: u32, jitter: f64) -> Duration {
+    let step = RETRY_BASE_DELAY * 2u32.pow(retry);
+    step.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+}
 
-    let release: GitHubRelease = response.json()?;
+/// A number in 0..1 that differs between calls and processes
+fn jitter() -> f64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parse a GitHub release and compare it with the running version
+pub fn parse_release(json: &str) -> Result<UpdateInfo> {
+    let release: GitHubRelease =
+        serde_json::from_str(json).context("Invalid release information")?;
     debug!("Latest release: {}", release.tag_name);
 
     let latest_version = release.tag_name.trim_start_matches('v');
-    let has_update = is_newer_version(CURRENT_VERSION, latest_version);
+    let Some((core, pre)) = split_version(latest_version) else {
+        anyhow::bail!("Release tag is not a version: {:?}", release.tag_name);
+    };
+    let is_prerelease = release.prerelease || pre.is_some();
 
-    if has_update {
-        info!("New version available: {} -> {}", CURRENT_VERSION, latest_version);
-    } else {
-        info!("Already on the latest version");
-    }
+    Ok(UpdateInfo {
+        latest_version: latest_version.to_string(),
+        html_url: release.html_url,
+        has_update: !is_prerelease && is_newer_version(CURRENT_VERSION, core),
+    })
+}
+
+/// Split `1.2.3-beta.1` into `("1.2.3", Some("beta.1"))`, or `None` if it isn't a version
+fn split_version(version: &str) -> Option<(&str, Option<&str>)> {
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+    let parts: Vec<&str> = core.split('.').collect();
+    let valid = (1..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    valid.then_some((core, pre))
+}
+
+/// Check for updates on a background thread, opening the releases page if there is one
+///
+/// A failed check is reported to the UI, which links it to the updates section of settings.
+pub fn check_in_background(message_tx: Sender<AppMessage>) {
+    std::thread::spawn(move || {
+        info!("{}", rust_i18n::t!("notifications.update.checking"));
+
+        match check_for_updates_with_retry() {
+            Ok(true) => {
+                info!("{}", rust_i18n::t!("notifications.update.available"));
+                open_releases_page();
+            }
+            Ok(false) => info!("{}", rust_i18n::t!("notifications.update.up_to_date")),
+            Err(e) => {
+                warn!("Failed to check for updates: {}", e);
+                let _ = message_tx.send(AppMessage::UpdateCheckFailed);
+            }
+        }
+    });
+}
+
+/// Check every `Settings::update_check_interval_hours` on a background thread (0 = never).
+///
+/// Unlike a manual check, an available update is announced with a notification rather than
+/// by opening the browser, and only once per version until the app restarts.
+pub fn start_periodic_checks() {
+    std::thread::spawn(|| {
+        let mut next_check = std::time::Instant::now() + PERIODIC_FIRST_DELAY;
+        while !crate::session::is_shutting_down() {
+            std::thread::sleep(Duration::from_millis(500));
+            let hours = crate::settings_store::read(|s| s.update_check_interval_hours);
+            if hours == 0 || std::time::Instant::now() < next_check {
+                continue;
+            }
+            next_check = std::time::Instant::now() + Duration::from_secs(hours * 60 * 60);
+
+            match with_retry(RETRY_ATTEMPTS, latest_release, std::thread::sleep) {
+                Ok(update) if update.has_update => {
+                    if first_notice(&update.latest_version) {
+                        crate::tray::notify(
+                            &crate::settings_store::get(),
+                            crate::tray::NotificationKind::Update,
+                            "notifications.title",
+                            "notifications.update.new_version",
+                            &[("version", &update.latest_version)],
+                        );
+                    } else {
+                        debug!("Already announced version {} this session", update.latest_version);
+                    }
+                }
+                Ok(_) => {}
+                // Nobody asked for this check, so a failure isn't worth interrupting for
+                Err(e) => warn!("Periodic update check failed: {}", e),
+            }
+        }
+    });
+}
 
-    Ok(has_update)
+/// Whether `version` hasn't been announced since startup; remembers it if not
+fn first_notice(version: &str) -> bool {
+    NOTIFIED_VERSIONS
+        .lock()
+        .get_or_insert_with(HashSet::new)
+        .insert(version.to_string())
 }
 
 /// Open the releases page in the default browser
@@ -89,6 +300,13 @@ fn is_newer_version(current: &str, latest: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_first_notice() {
+        assert!(first_notice("99.0.0-test"));
+        assert!(!first_notice("99.0.0-test"));
+        assert!(first_notice("99.0.1-test"));
+    }
+
     #[test]
     fn test_version_comparison() {
         assert!(is_newer_version("0.1.0", "0.2.0"));
@@ -99,4 +317,139 @@ mod tests {
         assert!(!is_newer_version("1.0.0", "0.9.9"));
         assert!(!is_newer_version("0.1.0", "0.1.0"));
     }
+
+    fn release_json(tag: &str, prerelease: bool) -> String {
+        format!(
+            r#"{{"tag_name": "{}", "html_url": "https://example.com/{}", "prerelease": {}}}"#,
+            tag, tag, prerelease
+        )
+    }
+
+    #[test]
+    fn test_parse_release() {
+        let update = parse_release(&release_json("v999.0.0", false)).unwrap();
+        assert_eq!(update.latest_version, "999.0.0");
+        assert_eq!(update.html_url, "https://example.com/v999.0.0");
+        assert!(update.has_update);
+
+        // Without the `v` prefix, and older than anything we ship
+        assert!(!parse_release(&release_json("0.0.1", false)).unwrap().has_update);
+
+        // Missing `prerelease` defaults to a normal release
+        let json = r#"{"tag_name": "v999.1", "html_url": "https://example.com"}"#;
+        assert!(parse_release(json).unwrap().has_update);
+    }
+
+    #[test]
+    fn test_parse_release_skips_prereleases() {
+        assert!(!parse_release(&release_json("v999.0.0", true)).unwrap().has_update);
+
+        let update = parse_release(&release_json("v999.0.0-beta.1", false)).unwrap();
+        assert_eq!(update.latest_version, "999.0.0-beta.1");
+        assert!(!update.has_update);
+    }
+
+    #[test]
+    fn test_parse_release_rejects_malformed() {
+        assert!(parse_release(&release_json("nightly", false)).is_err());
+        assert!(parse_release(&release_json("v1..2", false)).is_err());
+        assert!(parse_release(&release_json("v1.2.3.4", false)).is_err());
+        assert!(parse_release("not json").is_err());
+        assert!(parse_release(r#"{"tag_name": "v1.0.0"}"#).is_err());
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0, 1.0), RETRY_BASE_DELAY);
+        assert_eq!(backoff_delay(0, 0.0), RETRY_BASE_DELAY / 2);
+        assert_eq!(backoff_delay(2, 1.0), RETRY_BASE_DELAY * 4);
+        assert!((0..100).all(|_| (0.0..1.0).contains(&jitter())));
+    }
+
+    #[test]
+    fn test_with_retry() {
+        let server_error = || anyhow::Error::new(HttpStatusError(reqwest::StatusCode::BAD_GATEWAY));
+
+        // Transient failures are retried until one succeeds
+        let (mut calls, mut sleeps) = (0, Vec::new());
+        let result = with_retry(
+            3,
+            || {
+                calls += 1;
+                if calls < 3 { Err(server_error()) } else { Ok(calls) }
+            },
+            |delay| sleeps.push(delay),
+        );
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(sleeps.len(), 2);
+        assert!(sleeps[1] >= sleeps[0]);
+
+        // Gives up after the last attempt
+        let mut calls = 0;
+        let result: Result<()> = with_retry(
+            3,
+            || {
+                calls += 1;
+                Err(server_error())
+            },
+            |_| {},
+        );
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        // Not found and unauthorized fail fast
+        for status in [reqwest::StatusCode::NOT_FOUND, reqwest::StatusCode::UNAUTHORIZED] {
+            let mut calls = 0;
+            let result: Result<()> = with_retry(
+                3,
+                || {
+                    calls += 1;
+                    Err(HttpStatusError(status).into())
+                },
+                |_| panic!("should not retry {}", status),
+            );
+            assert!(result.is_err());
+            assert_eq!(calls, 1);
+        }
+
+        // So do malformed releases
+        let mut calls = 0;
+        let _ = with_retry(
+            3,
+            || {
+                calls += 1;
+                parse_release("not json")
+            },
+            |_| {},
+        );
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_check_with_url() {
+        use std::io::{Read, Write};
+
+        // Serve one canned response from a local port
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/latest", listener.local_addr().unwrap());
+        let body = release_json("v999.0.0", false);
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let client = reqwest::blocking::Client::new();
+        let update = check_with_url(&url, &client).unwrap();
+        assert_eq!(update.latest_version, "999.0.0");
+        assert!(update.has_update);
+        server.join().unwrap();
+    }
 }
@@ -0,0 +1,193 @@
+//! Screenshot upload to Imgur or a self-hosted HTTP endpoint
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use reqwest::blocking::{multipart, Client, Response};
+use reqwest::StatusCode;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::settings::Settings;
+
+const IMGUR_UPLOAD_URL: &str = "https://api.imgur.com/3/image";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The server rejected the upload with HTTP 429
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimited {
+    /// Seconds to wait, from the Retry-After header
+    pub retry_after: Option<u64>,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.retry_after {
+            Some(secs) => write!(f, "Upload rate limited, retry after {}s", secs),
+            None => write!(f, "Upload rate limited"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Upload using the destination configured in settings
+/// A custom endpoint takes precedence over Imgur
+///
+/// A large image may be replaced by a downscaled copy first (see `large_image`).
+pub fn upload(path: &Path, settings: &Settings) -> Result<String> {
+    let file = crate::large_image::prepare_upload(path)?;
+    if !settings.upload_endpoint.trim().is_empty() {
+        upload_custom(file.path(), settings.upload_endpoint.trim(), &settings.upload_field_name)
+    } else {
+        upload_imgur(file.path(), &settings.imgur_client_id)
+    }
+}
+
+/// Whether an upload destination is set up
+pub fn is_configured(settings: &Settings) -> bool {
+    !settings.upload_endpoint.trim().is_empty() || !settings.imgur_client_id.trim().is_empty()
+}
+
+/// Upload anonymously to Imgur and return the image link
+pub fn upload_imgur(path: &Path, client_id: &str) -> Result<String> {
+    let client_id = client_id.trim();
+    if client_id.is_empty() {
+        bail!("Imgur client ID is not set");
+    }
+
+    info!("Uploading {:?} to Imgur", path);
+    let form = multipart::Form::new()
+        .file("image", path)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    let response = client()?
+        .post(IMGUR_UPLOAD_URL)
+        .header("Authorization", format!("Client-ID {}", client_id))
+        .multipart(form)
+        .send()
+        .context("Network error")?;
+
+    let body = check_status(response)?;
+    let url = parse_imgur_link(&body)?;
+    info!("Uploaded to {}", url);
+    Ok(url)
+}
+
+/// Upload as a multipart form field to a self-hosted endpoint and return the URL from the response
+pub fn upload_custom(path: &Path, endpoint: &str, field_name: &str) -> Result<String> {
+    let field_name = match field_name.trim() {
+        "" => "file",
+        name => name,
+    }
+    .to_string();
+
+    info!("Uploading {:?} to {}", path, endpoint);
+    let form = multipart::Form::new()
+        .file(field_name, path)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    let response = client()?
+        .post(endpoint)
+        .multipart(form)
+        .send()
+        .context("Network error")?;
+
+    let body = check_status(response)?;
+    let url = extract_url(&body)
+        .ok_or_else(|| anyhow::anyhow!("Upload response did not contain a URL"))?;
+    info!("Uploaded to {}", url);
+    Ok(url)
+}
+
+fn client() -> Result<Client> {
+    Ok(Client::builder()
+        .user_agent(format!("sukusho/{}", CURRENT_VERSION))
+        .timeout(Duration::from_secs(60))
+        .build()?)
+}
+
+/// Turn error statuses into errors and return the response body
+fn check_status(response: Response) -> Result<String> {
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok());
+        warn!("Upload rate limited (retry after {:?})", retry_after);
+        return Err(RateLimited { retry_after }.into());
+    }
+
+    let body = response.text().context("Network error")?;
+    if !status.is_success() {
+        warn!("Upload returned status {}: {}", status, body);
+        bail!("Server returned HTTP {}", status.as_u16());
+    }
+    Ok(body)
+}
+
+/// Read `data.link` from an Imgur API response
+fn parse_imgur_link(body: &str) -> Result<String> {
+    let json: serde_json::Value =
+        serde_json::from_str(body).context("Unexpected response from Imgur")?;
+    json.pointer("/data/link")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("Imgur response did not contain a link"))
+}
+
+/// Find the uploaded URL in a plain-text or JSON response body
+fn extract_url(body: &str) -> Option<String> {
+    let body = body.trim();
+    if is_url(body) {
+        return Some(body.to_string());
+    }
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    find_url(&json)
+}
+
+/// Look for a `url` or `link` field, checking this level before nested objects
+fn find_url(value: &serde_json::Value) -> Option<String> {
+    let map = value.as_object()?;
+    for key in ["url", "link"] {
+        if let Some(url) = map.get(key).and_then(|v| v.as_str()).filter(|s| is_url(s)) {
+            return Some(url.to_string());
+        }
+    }
+    map.values().find_map(find_url)
+}
+
+fn is_url(s: &str) -> bool {
+    (s.starts_with("https://") || s.starts_with("http://")) && !s.contains(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_imgur_link() {
+        let body = r#"{"data":{"id":"abc","link":"https://i.imgur.com/abc.png"},"success":true,"status":200}"#;
+        assert_eq!(parse_imgur_link(body).unwrap(), "https://i.imgur.com/abc.png");
+        assert!(parse_imgur_link(r#"{"data":{"error":"bad"},"success":false}"#).is_err());
+        assert!(parse_imgur_link("not json").is_err());
+    }
+
+    #[test]
+    fn test_extract_url() {
+        assert_eq!(
+            extract_url("https://example.com/a.png\n").as_deref(),
+            Some("https://example.com/a.png")
+        );
+        assert_eq!(
+            extract_url(r#"{"url":"https://example.com/a.png"}"#).as_deref(),
+            Some("https://example.com/a.png")
+        );
+        assert_eq!(
+            extract_url(r#"{"status":"ok","files":{"link":"http://host/x"}}"#).as_deref(),
+            Some("http://host/x")
+        );
+        assert_eq!(extract_url(r#"{"url":"not a url"}"#), None);
+        assert_eq!(extract_url("uploaded!"), None);
+    }
+}
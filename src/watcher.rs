@@ -1,27 +1,288 @@
 //! File system watcher for screenshot directory
 
 use anyhow::Result;
-use crossbeam_channel::Sender;
+use crossbeam_channel::{bounded, Receiver, Sender};
 use log::{debug, error, info, warn};
-use notify::RecursiveMode;
-use notify_debouncer_full::{new_debouncer, DebounceEventResult};
+use notify::{PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_full::{new_debouncer_opt, DebounceEventResult, Debouncer, RecommendedCache};
 use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::convert;
+use crate::foreground;
 use crate::organizer;
+use crate::rename::{self, FilenameTemplate};
 use crate::settings::Settings;
 use crate::AppMessage;
 
-/// Image extensions we care about
-const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "avif"];
+/// Paths written by our own post-processing, so their events don't trigger another pass
+static GENERATED_PATHS: Mutex<Vec<(PathBuf, Instant)>> = Mutex::new(Vec::new());
+
+/// How long events for a freshly written path are ignored
+const GENERATED_SUPPRESS_WINDOW: Duration = Duration::from_secs(5);
+
+/// New files skipped for being under `min_file_size_bytes`, waiting to be written
+static TINY_PATHS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// A path is announced to the UI at most once within this window
+const ANNOUNCE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Recently announced paths, so the startup scan and duplicate create events
+/// don't report the same screenshot twice
+#[derive(Default)]
+struct RecentAnnouncements {
+    seen: HashMap<PathBuf, Instant>,
+    last_prune: Option<Instant>,
+}
+
+impl RecentAnnouncements {
+    /// Record the path and return whether it should be announced
+    fn check(&mut self, path: &Path, now: Instant) -> bool {
+        // Prune at most once per window to keep the startup scan linear
+        if self.last_prune.is_none_or(|at| now.duration_since(at) >= ANNOUNCE_WINDOW) {
+            self.seen.retain(|_, at| now.duration_since(*at) < ANNOUNCE_WINDOW);
+            self.last_prune = Some(now);
+        }
+
+        match self.seen.get(path) {
+            Some(at) if now.duration_since(*at) < ANNOUNCE_WINDOW => false,
+            _ => {
+                self.seen.insert(path.to_path_buf(), now);
+                true
+            }
+        }
+    }
+}
+
+/// Send `NewScreenshot` unless the path was announced within `ANNOUNCE_WINDOW`
+fn announce(
+    announced: &Mutex<RecentAnnouncements>,
+    tx: &Sender<AppMessage>,
+    path: PathBuf,
+    auto_index: bool,
+) {
+    if announced.lock().check(&path, Instant::now()) {
+        let _ = tx.send(AppMessage::NewScreenshot(path, auto_index));
+    } else {
+        debug!("Skipping duplicate announcement: {:?}", path);
+    }
+}
+
+/// Debounced watcher for the screenshot directory; dropping it stops watching
+enum Backend {
+    /// File system notifications
+    Native(Debouncer<RecommendedWatcher, RecommendedCache>),
+    /// Rescans on an interval, for network shares that don't deliver notifications
+    Polling(Debouncer<PollWatcher, RecommendedCache>),
+}
+
+/// Whether `path` is a UNC path (`\\server\share`, or `\\?\UNC\server\share`)
+fn is_unc_path(path: &Path) -> bool {
+    let path = path.to_string_lossy().replace('/', "\\");
+    if let Some(rest) = path.strip_prefix(r"\\?\") {
+        return rest.get(..4).is_some_and(|unc| unc.eq_ignore_ascii_case(r"UNC\"));
+    }
+    // `\\.\` is a device path, not a share
+    path.starts_with(r"\\") && !path.starts_with(r"\\.\")
+}
+
+/// Whether `path` is on a network share, either by UNC path or through a mapped drive
+fn is_network_path(path: &Path) -> bool {
+    is_unc_path(path) || is_remote_drive(path)
+}
+
+/// Whether `path` is on a drive letter mapped to a network share
+#[cfg(windows)]
+fn is_remote_drive(path: &Path) -> bool {
+    use std::path::{Component, Prefix};
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+    use windows::Win32::System::WindowsProgramming::DRIVE_REMOTE;
+
+    let Some(Component::Prefix(prefix)) = path.components().next() else {
+        return false;
+    };
+    let letter = match prefix.kind() {
+        Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => letter as char,
+        _ => return false,
+    };
+    let root = HSTRING::from(format!("{}:\\", letter));
+    unsafe { GetDriveTypeW(&root) == DRIVE_REMOTE }
+}
+
+#[cfg(not(windows))]
+fn is_remote_drive(_path: &Path) -> bool {
+    false
+}
+
+/// Screenshots under `directory` (and its subdirectories, if watched), newest first
+///
+/// Empty and tiny files are left out, like new files are.
+pub fn existing_files(directory: &Path, settings: &Settings) -> Vec<(PathBuf, SystemTime)> {
+    fn scan_dir(dir: &Path, extensions: &[String], recursive: bool, files: &mut Vec<PathBuf>) {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    if recursive {
+                        scan_dir(&path, extensions, recursive, files);
+                    }
+                } else if ScreenshotWatcher::is_image_file(&path, extensions) {
+                    files.push(path);
+                }
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    let recursive = settings.watches_subdirectories();
+    scan_dir(directory, &settings.watched_extensions, recursive, &mut files);
+
+    let min_size = settings.min_file_size_bytes;
+    let mut files: Vec<(PathBuf, SystemTime)> = files
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+            if metadata.as_ref().is_some_and(|m| m.len() < min_size) {
+                debug!("Skipping file under {} bytes: {:?}", min_size, path);
+                return None;
+            }
+            let modified = metadata
+                .and_then(|m| m.modified().ok())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((path, modified))
+        })
+        .collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1));
+    files
+}
+
+/// Differences between the screenshot list and a fresh scan
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Reconciled {
+    /// Found on disk but not known
+    pub added: Vec<PathBuf>,
+    /// Known but gone from disk
+    pub removed: Vec<PathBuf>,
+}
+
+/// Compare the `known` screenshots with what a scan `found`
+///
+/// Files in `deferred` are waiting to be paged in, so they are neither added nor removed.
+pub fn reconcile(known: &[PathBuf], deferred: &[PathBuf], found: &[PathBuf]) -> Reconciled {
+    let found_set: HashSet<&PathBuf> = found.iter().collect();
+    let known_set: HashSet<&PathBuf> = known.iter().chain(deferred).collect();
+    Reconciled {
+        added: found
+            .iter()
+            .filter(|path| !known_set.contains(path))
+            .cloned()
+            .collect(),
+        removed: known
+            .iter()
+            .filter(|path| !found_set.contains(path))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Existing files left out of the startup scan, newest first
+type Unscanned = Arc<Mutex<Vec<(PathBuf, SystemTime)>>>;
 
 pub struct ScreenshotWatcher {
     directory: PathBuf,
     message_tx: Sender<AppMessage>,
     settings: Arc<Mutex<Settings>>,
+    announced: Arc<Mutex<RecentAnnouncements>>,
+    unscanned: Unscanned,
+    /// While set, file system events are dropped
+    paused: Arc<AtomicBool>,
+}
+
+/// Handle to a running watcher thread
+pub struct WatcherHandle {
+    stop_tx: Sender<()>,
+    thread: JoinHandle<()>,
+    message_tx: Sender<AppMessage>,
+    announced: Arc<Mutex<RecentAnnouncements>>,
+    unscanned: Unscanned,
+    paused: Arc<AtomicBool>,
+}
+
+impl WatcherHandle {
+    /// Announce up to `count` more of the older files skipped by the startup scan.
+    /// Returns how many were announced.
+    pub fn load_more(&self, count: usize) -> usize {
+        let batch: Vec<PathBuf> = {
+            let mut unscanned = self.unscanned.lock();
+            let count = count.min(unscanned.len());
+            unscanned.drain(..count).map(|(path, _)| path).collect()
+        };
+        let loaded = batch.len();
+        for path in batch.into_iter().filter(|p| p.exists()) {
+            announce(&self.announced, &self.message_tx, path, false);
+        }
+        if loaded > 0 {
+            debug!("Paged in {} older screenshots", loaded);
+        }
+        loaded
+    }
+
+    /// Whether older files are still waiting for `load_more`
+    pub fn has_unscanned(&self) -> bool {
+        !self.unscanned.lock().is_empty()
+    }
+
+    /// Files skipped by the startup scan, with their modification times
+    pub fn unscanned(&self) -> Vec<(PathBuf, SystemTime)> {
+        self.unscanned.lock().clone()
+    }
+
+    /// Drop file system events until `resume`, e.g. while a batch job rewrites the folder.
+    /// Nothing is queued meanwhile; rescan after resuming to catch up.
+    pub fn pause(&self) {
+        if !self.paused.swap(true, Ordering::SeqCst) {
+            info!("File watcher paused");
+        }
+    }
+
+    /// Process file system events again; returns whether the watcher was paused
+    pub fn resume(&self) -> bool {
+        let was_paused = self.paused.swap(false, Ordering::SeqCst);
+        if was_paused {
+            info!("File watcher resumed");
+        }
+        was_paused
+    }
+
+    /// The pause flag, for code that pauses the watcher without holding the handle
+    pub fn pause_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.paused)
+    }
+
+    /// Signal the watcher to stop and wait for its thread to exit
+    pub fn stop(self) {
+        let _ = self.stop_tx.send(());
+        if self.thread.join().is_err() {
+            error!("File watcher thread panicked");
+        }
+    }
+
+    /// Signal the watcher to stop and let its thread exit in the background, for callers
+    /// (like the UI thread) that shouldn't wait on it
+    pub fn stop_detached(self) {
+        let _ = self.stop_tx.send(());
+        std::thread::spawn(move || {
+            if self.thread.join().is_err() {
+                error!("File watcher thread panicked");
+            }
+        });
+    }
 }
 
 impl ScreenshotWatcher {
@@ -34,11 +295,36 @@ impl ScreenshotWatcher {
             directory,
             message_tx,
             settings,
+            announced: Arc::default(),
+            unscanned: Arc::default(),
+            paused: Arc::default(),
         }
     }
 
-    /// Run the watcher (blocking)
-    pub fn run(self) -> Result<()> {
+    /// Run the watcher on a background thread
+    pub fn spawn(self) -> WatcherHandle {
+        let (stop_tx, stop_rx) = bounded(1);
+        let message_tx = self.message_tx.clone();
+        let announced = Arc::clone(&self.announced);
+        let unscanned = Arc::clone(&self.unscanned);
+        let paused = Arc::clone(&self.paused);
+        let thread = std::thread::spawn(move || {
+            if let Err(e) = self.run(stop_rx) {
+                error!("File watcher error: {}", e);
+            }
+        });
+        WatcherHandle {
+            stop_tx,
+            thread,
+            message_tx,
+            announced,
+            unscanned,
+            paused,
+        }
+    }
+
+    /// Run the watcher until a stop signal arrives (blocking)
+    fn run(self, stop_rx: Receiver<()>) -> Result<()> {
         info!("Starting file watcher for: {:?}", self.directory);
 
         // Ensure directory exists
@@ -51,70 +337,121 @@ impl ScreenshotWatcher {
         }
 
         // Scan existing files first (includes subdirectories for organized files)
-        self.scan_existing_files()?;
+        if !self.scan_existing_files(&stop_rx)? {
+            info!("File watcher stopped during initial scan");
+            return Ok(());
+        }
+
+        // Network shares often don't deliver change notifications, so poll those
+        let backend = if is_network_path(&self.directory) {
+            info!("Screenshot directory is on a network share, polling for changes");
+            Backend::Polling(self.start_debouncer(self.poll_config())?)
+        } else {
+            match self.start_debouncer(notify::Config::default()) {
+                Ok(debouncer) => Backend::Native(debouncer),
+                Err(e) => {
+                    warn!("Can't watch {:?} natively ({}), polling instead", self.directory, e);
+                    Backend::Polling(self.start_debouncer(self.poll_config())?)
+                }
+            }
+        };
 
-        // Create debounced watcher
+        info!(
+            "File watcher started successfully ({})",
+            match backend {
+                Backend::Native(_) => "native",
+                Backend::Polling(_) => "polling",
+            }
+        );
+
+        // Keep the debouncer alive until asked to stop (or the handle is dropped)
+        let _ = stop_rx.recv();
+        drop(backend);
+        info!("File watcher stopped: {:?}", self.directory);
+        Ok(())
+    }
+
+    /// Create a debounced watcher of type `T` and start watching the directory
+    fn start_debouncer<T: Watcher>(
+        &self,
+        config: notify::Config,
+    ) -> Result<Debouncer<T, RecommendedCache>> {
         let tx = self.message_tx.clone();
         let base_dir = self.directory.clone();
         let settings = Arc::clone(&self.settings);
-        let mut debouncer = new_debouncer(
+        let announced = Arc::clone(&self.announced);
+        let paused = Arc::clone(&self.paused);
+        let mut debouncer = new_debouncer_opt::<_, T, _>(
             Duration::from_millis(200),
             None,
             move |result: DebounceEventResult| {
-                Self::handle_debounced_events(result, &tx, &base_dir, &settings);
+                Self::handle_debounced_events(
+                    result,
+                    &tx,
+                    &base_dir,
+                    &settings,
+                    &announced,
+                    &paused,
+                );
             },
+            RecommendedCache::new(),
+            config,
         )?;
 
-        // Watch the directory recursively to detect deletions in subdirectories
-        debouncer.watch(&self.directory, RecursiveMode::Recursive)?;
-
-        info!("File watcher started successfully");
+        // Subdirectories are watched for the organizer's folders and deletions in them
+        let mode = if self.settings.lock().watches_subdirectories() {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        debouncer.watch(&self.directory, mode)?;
+        Ok(debouncer)
+    }
 
-        // Keep the thread alive
-        loop {
-            std::thread::sleep(Duration::from_secs(60));
-        }
+    /// `PollWatcher` config using `Settings::poll_interval_ms`
+    fn poll_config(&self) -> notify::Config {
+        let interval = Duration::from_millis(self.settings.lock().poll_interval_ms);
+        notify::Config::default().with_poll_interval(interval)
     }
 
-    /// Scan existing files in the directory (recursive to include organized subdirectories)
-    fn scan_existing_files(&self) -> Result<()> {
+    /// Scan existing files in the directory (and organized subdirectories, if watched)
+    ///
+    /// Returns false if a stop signal arrived before the scan finished.
+    fn scan_existing_files(&self, stop_rx: &Receiver<()>) -> Result<bool> {
         info!("Scanning existing screenshots...");
         let mut count = 0;
-        let mut files = Vec::new();
-
-        // Recursive scan function
-        fn scan_dir(dir: &Path, files: &mut Vec<PathBuf>) {
-            if let Ok(entries) = std::fs::read_dir(dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.is_dir() {
-                        // Recurse into subdirectories
-                        scan_dir(&path, files);
-                    } else if ScreenshotWatcher::is_image_file(&path) {
-                        files.push(path);
-                    }
-                }
-            }
+        // Not held across the scan, which can take a while on large folders
+        let settings = self.settings.lock().clone();
+        let limit = settings.initial_scan_limit;
+        let mut files = existing_files(&self.directory, &settings);
+
+        // Older files wait for `WatcherHandle::load_more`
+        if let Some(limit) = limit.filter(|&limit| files.len() > limit) {
+            let rest = files.split_off(limit);
+            info!("Deferring {} older screenshots until requested", rest.len());
+            *self.unscanned.lock() = rest;
         }
 
-        scan_dir(&self.directory, &mut files);
-
-        // Sort by modified time (newest first)
-        files.sort_by(|a, b| {
-            let a_time = std::fs::metadata(a).and_then(|m| m.modified()).ok();
-            let b_time = std::fs::metadata(b).and_then(|m| m.modified()).ok();
-            b_time.cmp(&a_time)
+        // Announce in gallery order so it fills in the way it will end up
+        files.sort_by(|(a, a_modified), (b, b_modified)| {
+            let a_name = a.file_name().unwrap_or_default().to_string_lossy();
+            let b_name = b.file_name().unwrap_or_default().to_string_lossy();
+            settings.sort_order.compare((&a_name, *a_modified), (&b_name, *b_modified))
         });
 
-        for path in files {
+        for (path, _) in files {
+            if !stop_rx.is_empty() {
+                return Ok(false);
+            }
             debug!("Found existing screenshot: {:?}", path);
             // Don't auto-index during initial scan (false)
-            let _ = self.message_tx.send(AppMessage::NewScreenshot(path, false));
+            announce(&self.announced, &self.message_tx, path, false);
             count += 1;
         }
 
         info!("Found {} existing screenshots", count);
-        Ok(())
+        let _ = self.message_tx.send(AppMessage::InitialScanComplete { count });
+        Ok(true)
     }
 
     /// Handle debounced file system events
@@ -123,11 +460,18 @@ impl ScreenshotWatcher {
         tx: &Sender<AppMessage>,
         base_dir: &Path,
         settings: &Arc<Mutex<Settings>>,
+        announced: &Arc<Mutex<RecentAnnouncements>>,
+        paused: &AtomicBool,
     ) {
+        // Paused events are dropped, not queued; whoever paused rescans after resuming
+        if paused.load(Ordering::SeqCst) {
+            debug!("File watcher paused, dropping events");
+            return;
+        }
         match result {
             Ok(events) => {
                 for event in events {
-                    Self::process_event(&event, tx, base_dir, settings);
+                    Self::process_event(&event, tx, base_dir, settings, announced);
                 }
             }
             Err(errors) => {
@@ -144,15 +488,19 @@ impl ScreenshotWatcher {
         tx: &Sender<AppMessage>,
         base_dir: &Path,
         settings: &Arc<Mutex<Settings>>,
+        announced: &Arc<Mutex<RecentAnnouncements>>,
     ) {
         use notify::EventKind;
 
+        // Read per event so extension changes from a settings reload apply immediately
+        let extensions = settings.lock().watched_extensions.clone();
+
         for path in &event.paths {
             // For Remove events, file no longer exists so we only check extension
             // For other events, we check if it's actually a file
             let dominated_event = match &event.kind {
-                EventKind::Remove(_) => Self::has_image_extension(path),
-                _ => Self::is_image_file(path),
+                EventKind::Remove(_) => Self::has_image_extension(path, &extensions),
+                _ => Self::is_image_file(path, &extensions),
             };
 
             if !dominated_event {
@@ -161,99 +509,348 @@ impl ScreenshotWatcher {
 
             match &event.kind {
                 EventKind::Create(_) => {
-                    info!("New screenshot detected: {:?}", path);
-
-                    // Check if organizer and/or auto-convert is enabled
-                    let (organizer_enabled, organizer_format, auto_convert, conversion_format, quality) = {
-                        let s = settings.lock();
-                        (
-                            s.organizer_enabled,
-                            s.organizer_format.clone(),
-                            s.auto_convert_webp,
-                            s.conversion_format,
-                            s.webp_quality,
-                        )
-                    };
-
-                    // Process in background thread
-                    let path_clone = path.clone();
-                    let base_dir = base_dir.to_path_buf();
-                    let tx = tx.clone();
-
-                    std::thread::spawn(move || {
-                        // Small delay to ensure file is fully written
-                        std::thread::sleep(Duration::from_millis(500));
-
-                        let mut current_path = path_clone.clone();
-
-                        // Step 1: Auto-convert if enabled (PNG -> WebP/JPEG)
-                        if auto_convert && convert::is_convertible(&current_path) {
-                            info!("Auto-converting screenshot: {:?}", current_path);
-                            match convert::convert_image(&current_path, conversion_format, quality) {
-                                Ok(new_path) => {
-                                    info!("Converted: {:?} -> {:?}", current_path, new_path);
-                                    current_path = new_path;
-                                }
-                                Err(e) => {
-                                    error!("Failed to convert screenshot: {}", e);
-                                }
-                            }
-                        }
-
-                        // Step 2: Organize if enabled (move to date-based subdirectory)
-                        if organizer_enabled {
-                            match organizer::organize_file(
-                                &current_path,
-                                &base_dir,
-                                &organizer_format,
-                            ) {
-                                Ok(Some(new_path)) => {
-                                    info!("Organized: {:?} -> {:?}", current_path, new_path);
-                                    current_path = new_path;
-                                }
-                                Ok(None) => {
-                                    // Already organized or in subdirectory
-                                }
-                                Err(e) => {
-                                    error!("Failed to organize screenshot: {}", e);
-                                }
-                            }
-                        }
+                    // Files we produced ourselves (rename, convert or organize output) are
+                    // reported to the UI by the thread that wrote them
+                    if Self::is_generated(path) {
+                        debug!("Ignoring event for generated file: {:?}", path);
+                        continue;
+                    }
 
-                        // Send final path to UI with auto-index flag (true for new screenshots)
-                        let _ = tx.send(AppMessage::NewScreenshot(current_path, true));
-                    });
+                    info!("New screenshot detected: {:?}", path);
+                    Self::process_new_file(path, tx, base_dir, settings, announced);
                 }
                 EventKind::Remove(_) => {
                     info!("Screenshot removed: {:?}", path);
+                    Self::forget_tiny(path);
                     let _ = tx.send(AppMessage::ScreenshotRemoved(path.clone()));
                 }
                 EventKind::Modify(_) => {
                     // Modification might mean the file is fully written
                     debug!("Screenshot modified: {:?}", path);
+                    if Self::forget_tiny(path) {
+                        info!("Placeholder file was written: {:?}", path);
+                        Self::process_new_file(path, tx, base_dir, settings, announced);
+                    }
                 }
                 _ => {}
             }
         }
     }
 
+    /// Rename, convert and organize a new file on a background thread, then announce it
+    fn process_new_file(
+        path: &Path,
+        tx: &Sender<AppMessage>,
+        base_dir: &Path,
+        settings: &Arc<Mutex<Settings>>,
+        announced: &Arc<Mutex<RecentAnnouncements>>,
+    ) {
+        // Check if rename, organizer and/or auto-convert is enabled
+        let (rename_template, organizer_enabled, organizer_format, convert_to, quality, keep_original, min_size, verify_magic, watermark) = {
+            let s = settings.lock();
+            let rename_template = if s.rename_enabled {
+                match FilenameTemplate::parse(&s.filename_template) {
+                    Ok(template) => Some(template),
+                    Err(e) => {
+                        warn!("Invalid filename template: {}", e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            (
+                rename_template,
+                s.organizer_enabled,
+                s.organizer_format.clone(),
+                s.convert_to(),
+                s.quality_for(s.conversion_format),
+                s.keep_original_after_convert,
+                s.min_file_size_bytes,
+                s.verify_image_magic,
+                s.watermark.enabled.then(|| s.watermark.clone()),
+            )
+        };
+
+        // Capture the source app now, before the delay below lets focus move
+        let source_app = foreground::source_app();
+        let app_name = source_app.clone().unwrap_or_else(|| "unknown".to_string());
+
+        // Process in background thread
+        let path_clone = path.to_path_buf();
+        let base_dir = base_dir.to_path_buf();
+        let tx = tx.clone();
+        let announced = Arc::clone(announced);
+        let settings = Arc::clone(settings);
+
+        std::thread::spawn(move || {
+            // Small delay to ensure file is fully written
+            std::thread::sleep(Duration::from_millis(500));
+
+            // Placeholders are picked up again once a modify event shows they've been written
+            if Self::is_too_small(&path_clone, min_size) {
+                debug!("Skipping file under {} bytes for now: {:?}", min_size, path_clone);
+                Self::mark_tiny(&path_clone);
+                return;
+            }
+
+            // The extension only says what the file claims to be
+            if verify_magic && !Self::has_image_signature(&path_clone) {
+                debug!("Skipping file that isn't an image despite its extension: {:?}", path_clone);
+                return;
+            }
+
+            let mut current_path = path_clone.clone();
+
+            // Step 0: Rename from the filename template if enabled
+            if let Some(template) = &rename_template {
+                match rename::rename_file(&current_path, template, &app_name) {
+                    Ok(Some(new_path)) => {
+                        Self::mark_generated(&new_path);
+                        current_path = new_path;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        error!("Failed to rename screenshot: {}", e);
+                    }
+                }
+            }
+
+            // Step 1: Auto-convert if enabled (PNG/BMP -> WebP/JPEG/PNG)
+            if let Some(format) = convert_to.filter(|f| convert::needs_conversion(&current_path, *f)) {
+                info!("Auto-converting screenshot: {:?}", current_path);
+                // Mark before writing so the output's create event is never processed
+                Self::mark_generated(&current_path.with_extension(format.extension()));
+                match convert::convert_image(&current_path, format, quality, keep_original) {
+                    Ok(new_path) => {
+                        info!("Converted: {:?} -> {:?}", current_path, new_path);
+                        convert::notify_completed(&settings.lock(), format, &new_path);
+                        if keep_original {
+                            if let Some(app) = &source_app {
+                                foreground::record_source(&current_path, app);
+                            }
+                            announce(&announced, &tx, current_path.clone(), true);
+                        }
+                        current_path = new_path;
+                    }
+                    Err(e) => {
+                        error!("Failed to convert screenshot: {}", e);
+                    }
+                }
+            }
+
+            // Step 1.5: Watermark if enabled, on a copy unless it may overwrite
+            if let Some(watermark) = &watermark {
+                if !watermark.overwrite {
+                    Self::mark_generated(&crate::redact::edited_path(&current_path, "watermarked"));
+                }
+                match crate::watermark::watermark_file(&current_path, watermark) {
+                    Ok(new_path) if new_path != current_path => {
+                        info!("Watermarked: {:?} -> {:?}", current_path, new_path);
+                        if let Some(app) = &source_app {
+                            foreground::record_source(&current_path, app);
+                        }
+                        announce(&announced, &tx, current_path.clone(), true);
+                        current_path = new_path;
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        error!("Failed to watermark screenshot: {}", e);
+                    }
+                }
+            }
+
+            // Step 2: Organize if enabled (move to date-based subdirectory)
+            if organizer_enabled {
+                match organizer::organize_file(
+                    &current_path,
+                    &base_dir,
+                    &organizer_format,
+                ) {
+                    Ok(Some(new_path)) => {
+                        info!("Organized: {:?} -> {:?}", current_path, new_path);
+                        Self::mark_generated(&new_path);
+                        current_path = new_path;
+                    }
+                    Ok(None) => {
+                        // Already organized or in subdirectory
+                    }
+                    Err(e) => {
+                        error!("Failed to organize screenshot: {}", e);
+                    }
+                }
+            }
+
+            if let Some(app) = &source_app {
+                foreground::record_source(&current_path, app);
+            }
+
+            // Send final path to UI with auto-index flag (true for new screenshots)
+            announce(&announced, &tx, current_path, true);
+        });
+    }
+
+    /// Remember a path produced by post-processing so its events are ignored for a short window
+    fn mark_generated(path: &Path) {
+        let mut generated = GENERATED_PATHS.lock();
+        generated.retain(|(_, at)| at.elapsed() < GENERATED_SUPPRESS_WINDOW);
+        generated.push((path.to_path_buf(), Instant::now()));
+    }
+
+    /// Whether a file exists and is smaller than `min_size` bytes (0 = no minimum)
+    fn is_too_small(path: &Path, min_size: u64) -> bool {
+        min_size > 0 && std::fs::metadata(path).is_ok_and(|m| m.len() < min_size)
+    }
+
+    /// Remember a file skipped for being too small, so a later write announces it
+    fn mark_tiny(path: &Path) {
+        let mut tiny = TINY_PATHS.lock();
+        if !tiny.iter().any(|p| p == path) {
+            tiny.push(path.to_path_buf());
+        }
+    }
+
+    /// Stop tracking a skipped file; returns whether it was tracked
+    fn forget_tiny(path: &Path) -> bool {
+        let mut tiny = TINY_PATHS.lock();
+        let before = tiny.len();
+        tiny.retain(|p| p != path);
+        tiny.len() != before
+    }
+
+    /// Whether a path was written by post-processing within the suppression window
+    fn is_generated(path: &Path) -> bool {
+        let mut generated = GENERATED_PATHS.lock();
+        generated.retain(|(_, at)| at.elapsed() < GENERATED_SUPPRESS_WINDOW);
+        generated.iter().any(|(p, _)| p == path)
+    }
+
     /// Check if a path is an image file we care about (file must exist)
-    fn is_image_file(path: &Path) -> bool {
+    fn is_image_file(path: &Path, extensions: &[String]) -> bool {
         if !path.is_file() {
             return false;
         }
-        Self::has_image_extension(path)
+        Self::has_image_extension(path, extensions)
     }
 
-    /// Check if a path has an image extension (doesn't check if file exists)
+    /// Whether the file starts with a PNG, JPEG, GIF, BMP, WebP, AVIF or HEIF signature
+    fn has_image_signature(path: &Path) -> bool {
+        use std::io::Read;
+
+        let mut header = Vec::with_capacity(16);
+        match std::fs::File::open(path).and_then(|file| file.take(16).read_to_end(&mut header)) {
+            Ok(_) => Self::is_image_signature(&header),
+            Err(e) => {
+                debug!("Failed to read {:?}: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// Whether `header`, the first bytes of a file, matches a known image format
+    fn is_image_signature(header: &[u8]) -> bool {
+        const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+        const JPEG: &[u8] = b"\xff\xd8\xff";
+        // ISO base media (`ftyp`) brands for AVIF and HEIC/HEIF
+        const FTYP_BRANDS: [&[u8]; 10] = [
+            b"avif", b"avis", b"mif1", b"msf1", b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis",
+        ];
+
+        header.starts_with(PNG)
+            || header.starts_with(JPEG)
+            || header.starts_with(b"GIF87a")
+            || header.starts_with(b"GIF89a")
+            || header.starts_with(b"BM")
+            || (header.starts_with(b"RIFF") && header.get(8..12) == Some(&b"WEBP"[..]))
+            || (header.get(4..8) == Some(&b"ftyp"[..])
+                && header.get(8..12).is_some_and(|brand| FTYP_BRANDS.contains(&brand)))
+    }
+
+    /// Check if a path has a watched extension (doesn't check if file exists)
     /// Used for Remove events where the file no longer exists
-    fn has_image_extension(path: &Path) -> bool {
+    fn has_image_extension(path: &Path, extensions: &[String]) -> bool {
         path.extension()
             .and_then(|ext| ext.to_str())
-            .is_some_and(|ext| {
-                IMAGE_EXTENSIONS
-                    .iter()
-                    .any(|&e| e.eq_ignore_ascii_case(ext))
-            })
+            .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile() {
+        let paths = |names: &[&str]| -> Vec<PathBuf> { names.iter().map(PathBuf::from).collect() };
+        let known = paths(&["a.png", "b.png", "c.png"]);
+        let deferred = paths(&["old.png"]);
+        let found = paths(&["new.png", "a.png", "c.png", "old.png"]);
+
+        assert_eq!(
+            reconcile(&known, &deferred, &found),
+            Reconciled {
+                added: paths(&["new.png"]),
+                removed: paths(&["b.png"]),
+            }
+        );
+        assert_eq!(reconcile(&known, &[], &known), Reconciled::default());
+    }
+
+    #[test]
+    fn test_is_unc_path() {
+        assert!(is_unc_path(Path::new(r"\\server\share\Screenshots")));
+        assert!(is_unc_path(Path::new("//server/share")));
+        assert!(is_unc_path(Path::new(r"\\?\UNC\server\share")));
+        assert!(!is_unc_path(Path::new(r"\\?\C:\Screenshots")));
+        assert!(!is_unc_path(Path::new(r"\\.\pipe\name")));
+        assert!(!is_unc_path(Path::new(r"C:\Users\me\Pictures")));
+        assert!(!is_unc_path(Path::new("/home/me/Pictures")));
+    }
+
+    #[test]
+    fn test_is_image_signature() {
+        let sniff = ScreenshotWatcher::is_image_signature;
+        assert!(sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(sniff(b"\xff\xd8\xff\xe0\0\x10JFIF"));
+        assert!(sniff(b"GIF89a\x01\0"));
+        assert!(sniff(b"BM\x36\0\0\0"));
+        assert!(sniff(b"RIFF\x24\0\0\0WEBPVP8 "));
+        assert!(sniff(b"\0\0\0\x1cftypavif\0\0\0\0"));
+        // Start of the ftyp box of an iPhone HEIC photo
+        assert!(sniff(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic"));
+        assert!(sniff(b"\0\0\0\x18ftyphevc\0\0\0\0"));
+
+        assert!(!sniff(b"RIFF\x24\0\0\0WAVEfmt "));
+        assert!(!sniff(b"\0\0\0\x1cftypisom"));
+        assert!(!sniff(b"just some text"));
+        assert!(!sniff(b""));
+    }
+
+    #[test]
+    fn test_recent_announcements() {
+        let mut announced = RecentAnnouncements::default();
+        let start = Instant::now();
+        let a = Path::new("a.png");
+
+        assert!(announced.check(a, start));
+        assert!(!announced.check(a, start + Duration::from_millis(300)));
+        assert!(announced.check(Path::new("b.png"), start + Duration::from_millis(300)));
+        assert!(announced.check(a, start + ANNOUNCE_WINDOW));
+    }
+
+    #[test]
+    fn test_tiny_files() {
+        let path = std::env::temp_dir().join(format!("sukusho-tiny-{}.png", std::process::id()));
+        std::fs::write(&path, [0u8; 16]).unwrap();
+        assert!(ScreenshotWatcher::is_too_small(&path, 1024));
+        assert!(!ScreenshotWatcher::is_too_small(&path, 16));
+        assert!(!ScreenshotWatcher::is_too_small(&path, 0));
+        std::fs::remove_file(&path).unwrap();
+        assert!(!ScreenshotWatcher::is_too_small(&path, 1024));
+
+        ScreenshotWatcher::mark_tiny(&path);
+        ScreenshotWatcher::mark_tiny(&path);
+        assert!(ScreenshotWatcher::forget_tiny(&path));
+        assert!(!ScreenshotWatcher::forget_tiny(&path));
     }
 }
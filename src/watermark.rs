@@ -0,0 +1,165 @@
+//! Stamp a text or image watermark onto screenshots
+//!
+//! Applied to new screenshots after conversion when `Settings::watermark` is enabled.
+//! Unless `overwrite` is set, the watermarked image is a copy and the original stays as it was.
+
+use ab_glyph::{Font, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use image::{Pixel, Rgba, RgbaImage};
+use log::info;
+use std::path::{Path, PathBuf};
+
+use crate::annotate;
+use crate::redact;
+use crate::settings::{WatermarkAnchor, WatermarkSettings};
+
+/// Gap between the watermark and the screenshot edge, as a fraction of the shorter side
+const MARGIN: f32 = 0.02;
+
+/// Text is drawn white over a dark shadow so it reads on any background
+const TEXT_COLOR: [u8; 3] = [255, 255, 255];
+const SHADOW_COLOR: [u8; 3] = [0, 0, 0];
+
+/// Watermark `src` per `config`; returns the path of the stamped image
+pub fn watermark_file(src: &Path, config: &WatermarkSettings) -> Result<PathBuf> {
+    if !config.overwrite {
+        return redact::edit(src, "watermarked", |img| apply_watermark(img, config));
+    }
+
+    let mut img = image::open(src)
+        .with_context(|| format!("Failed to decode {:?}", src))?
+        .to_rgba8();
+    apply_watermark(&mut img, config)?;
+    redact::save_like(&img, src)?;
+    info!("Watermarked {:?}", src);
+    Ok(src.to_path_buf())
+}
+
+/// Draw the configured text or image onto `img`
+pub fn apply_watermark(img: &mut RgbaImage, config: &WatermarkSettings) -> Result<()> {
+    let shorter = img.width().min(img.height()) as f32;
+    let height = (shorter * config.scale).round().max(1.0);
+    let margin = (shorter * MARGIN).round() as i32;
+    let alpha = (config.opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    match &config.image_path {
+        Some(path) => {
+            let mark = image::open(path)
+                .with_context(|| format!("Failed to open watermark image {:?}", path))?
+                .to_rgba8();
+            let width = (mark.width() as f32 * height / mark.height().max(1) as f32).round();
+            let mark = crate::thumbnail::resize_rgba(mark, width.max(1.0) as u32, height as u32);
+            let origin = anchor_origin(config.anchor, img.dimensions(), mark.dimensions(), margin);
+            overlay(img, &mark, origin, alpha);
+        }
+        None => {
+            let font = annotate::system_font()?;
+            let size = text_size(&font, &config.text, height);
+            let origin = anchor_origin(config.anchor, img.dimensions(), size, margin);
+            let offset = (height / 16.0).ceil().max(1.0) as i32;
+            let [r, g, b] = SHADOW_COLOR;
+            annotate::draw_text(
+                img,
+                &config.text,
+                (origin.0 + offset, origin.1 + offset),
+                height,
+                Rgba([r, g, b, alpha / 2]),
+                &font,
+            );
+            let [r, g, b] = TEXT_COLOR;
+            annotate::draw_text(img, &config.text, origin, height, Rgba([r, g, b, alpha]), &font);
+        }
+    }
+    Ok(())
+}
+
+/// Top-left corner of a `mark`-sized box placed at `anchor` inside `canvas`, `margin` from its edges
+fn anchor_origin(
+    anchor: WatermarkAnchor,
+    canvas: (u32, u32),
+    mark: (u32, u32),
+    margin: i32,
+) -> (i32, i32) {
+    let place = |canvas: u32, mark: u32, step: u8| {
+        let (canvas, mark) = (canvas as i32, mark as i32);
+        match step {
+            0 => margin,
+            1 => (canvas - mark) / 2,
+            _ => canvas - mark - margin,
+        }
+    };
+    let (column, row) = match anchor {
+        WatermarkAnchor::TopLeft => (0, 0),
+        WatermarkAnchor::TopCenter => (1, 0),
+        WatermarkAnchor::TopRight => (2, 0),
+        WatermarkAnchor::CenterLeft => (0, 1),
+        WatermarkAnchor::Center => (1, 1),
+        WatermarkAnchor::CenterRight => (2, 1),
+        WatermarkAnchor::BottomLeft => (0, 2),
+        WatermarkAnchor::BottomCenter => (1, 2),
+        WatermarkAnchor::BottomRight => (2, 2),
+    };
+    (place(canvas.0, mark.0, column), place(canvas.1, mark.1, row))
+}
+
+/// Width and height of `text` drawn with `annotate::draw_text` at line height `size`
+fn text_size(font: &impl Font, text: &str, size: f32) -> (u32, u32) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let line_height = scaled.height() + scaled.line_gap();
+    let width = text
+        .lines()
+        .map(|line| {
+            let mut previous = None;
+            line.chars().fold(0.0, |width, ch| {
+                let id = scaled.glyph_id(ch);
+                let kern = previous.map_or(0.0, |p| scaled.kern(p, id));
+                previous = Some(id);
+                width + kern + scaled.h_advance(id)
+            })
+        })
+        .fold(0.0f32, f32::max);
+    let lines = text.lines().count().max(1) as f32;
+    let height = scaled.height() + line_height * (lines - 1.0);
+    (width.ceil() as u32, height.ceil() as u32)
+}
+
+/// Blend `mark` onto `img` at `origin`, scaling its alpha by `alpha` / 255
+fn overlay(img: &mut RgbaImage, mark: &RgbaImage, origin: (i32, i32), alpha: u8) {
+    for (x, y, pixel) in mark.enumerate_pixels() {
+        let (tx, ty) = (origin.0 + x as i32, origin.1 + y as i32);
+        if tx < 0 || ty < 0 || tx >= img.width() as i32 || ty >= img.height() as i32 {
+            continue;
+        }
+        let mut pixel = *pixel;
+        pixel[3] = (u16::from(pixel[3]) * u16::from(alpha) / 255) as u8;
+        img.get_pixel_mut(tx as u32, ty as u32).blend(&pixel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor_origin() {
+        let canvas = (200, 100);
+        let mark = (40, 20);
+        assert_eq!(anchor_origin(WatermarkAnchor::TopLeft, canvas, mark, 4), (4, 4));
+        assert_eq!(anchor_origin(WatermarkAnchor::Center, canvas, mark, 4), (80, 40));
+        assert_eq!(anchor_origin(WatermarkAnchor::BottomRight, canvas, mark, 4), (156, 76));
+        assert_eq!(anchor_origin(WatermarkAnchor::TopCenter, canvas, mark, 4), (80, 4));
+        assert_eq!(anchor_origin(WatermarkAnchor::CenterLeft, canvas, mark, 4), (4, 40));
+    }
+
+    #[test]
+    fn test_overlay_opacity() {
+        let mut img = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+        let mark = RgbaImage::from_pixel(2, 2, Rgba([255, 255, 255, 255]));
+        overlay(&mut img, &mark, (3, 3), 128);
+
+        // Only the overlapping corner is touched, at about half strength
+        let corner = img.get_pixel(3, 3);
+        assert!((120..=136).contains(&corner[0]));
+        assert_eq!(*img.get_pixel(2, 2), Rgba([0, 0, 0, 255]));
+    }
+}